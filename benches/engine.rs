@@ -0,0 +1,43 @@
+//! Compares how fast the full engine and the fast engine play basic strategy, run with
+//! `cargo bench`.
+
+use blackjack::config::GameConfig;
+use blackjack::fast::simulate_fast;
+use blackjack::input::BasicStrategy;
+use blackjack::simulation::{simulate, NoProgress, SimulationResult};
+use std::time::Instant;
+
+/// Times a simulation and prints its throughput, returning the hands played per second.
+fn bench(name: &str, simulation: impl FnOnce() -> SimulationResult) -> f64 {
+    let start = Instant::now();
+    let result = simulation();
+    let seconds = start.elapsed().as_secs_f64();
+    let hands = result.hands as f64 / seconds;
+    println!(
+        "{:<8}{:>12} rounds in {:>6.2}s {:>14.0} hands/s   house edge {:+.3}%",
+        name,
+        result.rounds,
+        seconds,
+        hands,
+        result.house_edge() * 100.0
+    );
+    hands
+}
+
+fn main() {
+    let conf = GameConfig::default();
+    let engine = bench("engine", || {
+        simulate(
+            conf.clone(),
+            &mut BasicStrategy,
+            1_000_000,
+            Some(1),
+            &mut NoProgress,
+        )
+        .unwrap()
+    });
+    let fast = bench("fast", || {
+        simulate_fast(&conf, 50_000_000, Some(1), &mut NoProgress).unwrap()
+    });
+    println!("The fast engine is {:.0} times as fast", fast / engine);
+}
@@ -0,0 +1,41 @@
+/* The C interface of the blackjack engine, linked from the library's cdylib. */
+#ifndef BLACKJACK_H
+#define BLACKJACK_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A game at the table, owned by the library. */
+typedef struct Game Game;
+
+/* Sits down at a freshly shuffled shoe, NULL if the settings can't be played.
+ * Amounts are in cents. */
+Game *blackjack_game_new(uint32_t decks, float penetration, bool hit_soft_17, int64_t buy_in_cents);
+
+void blackjack_game_free(Game *game);
+
+/* Plays an action: "bet", "deal", "even-money", "insure", "hit", "stand", "double", "split",
+ * "surrender", "buy", "rescue" or "tip". The argument is an amount in cents, the hand index for
+ * "even-money" and non-zero to rescue. Returns {"events": [...]} or {"error": "..."}. */
+char *blackjack_game_act(Game *game, const char *action, int64_t amount);
+
+/* Calls off a round the shoe ran out of cards for and reshuffles, returns {"events": [...]}. */
+char *blackjack_game_void_round(Game *game);
+
+/* The table as JSON: the state, the dealer's cards, the hands, the bankroll, the moves and the count. */
+char *blackjack_game_state(const Game *game);
+
+/* The functions returning JSON return {"error": "..."} for a NULL game or if the library failed. */
+
+/* Releases a string returned by the functions above. */
+void blackjack_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,87 @@
+use crate::money::Money;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The variance of a blackjack hand in squared bets, splits and doubles push it above 1.
+const HAND_VARIANCE: f64 = 1.3;
+
+/// A linear model of the player's edge, `base` off the top of the shoe and rising by
+/// `per_true_count` for every point of true count. Both are fractions, e.g. -0.005 for -0.5%.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeModel {
+    pub base: f64,
+    pub per_true_count: f64,
+}
+
+impl EdgeModel {
+    /// A typical six deck S17 game with basic strategy, each true count is worth about 0.5%.
+    pub const DEFAULT: EdgeModel = EdgeModel {
+        base: -0.005,
+        per_true_count: 0.005,
+    };
+
+    pub fn edge(&self, true_count: f32) -> f64 {
+        self.base + self.per_true_count * true_count as f64
+    }
+
+    /// The Kelly fraction of the bankroll to bet, 0 when the player has no edge.
+    pub fn kelly_fraction(&self, true_count: f32) -> f64 {
+        (self.edge(true_count) / HAND_VARIANCE).clamp(0.0, 1.0)
+    }
+
+    /// The Kelly bet for the bankroll, rounded down to a multiple of `bet_unit`.
+    pub fn kelly_bet(&self, true_count: f32, bankroll: Money, bet_unit: Money) -> Money {
+        bankroll
+            .fraction(self.kelly_fraction(true_count))
+            .round_down_to(bet_unit)
+    }
+}
+
+impl Display for EdgeModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%,{}%", self.base * 100.0, self.per_true_count * 100.0)
+    }
+}
+
+/// Parses "<base>%,<per true count>%", e.g. "-0.5%,0.5%", the percent signs are optional.
+impl FromStr for EdgeModel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (base, per_true_count) = s
+            .split_once(',')
+            .ok_or_else(|| format!("expected an edge model like -0.5%,0.5%, got '{}'", s))?;
+        let percent = |p: &str| {
+            p.trim()
+                .trim_end_matches('%')
+                .parse::<f64>()
+                .map(|p| p / 100.0)
+                .map_err(|e| format!("invalid percentage '{}': {}", p, e))
+        };
+
+        Ok(EdgeModel {
+            base: percent(base)?,
+            per_true_count: percent(per_true_count)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::advisor::EdgeModel;
+    use crate::money::Money;
+
+    #[test]
+    fn test_kelly_bet() {
+        let model = "-0.5%,0.5%".parse::<EdgeModel>().unwrap();
+        assert_eq!(model, EdgeModel::DEFAULT);
+
+        // No edge, no bet
+        assert_eq!(model.kelly_fraction(0.0), 0.0);
+        assert_eq!(model.kelly_fraction(-3.0), 0.0);
+
+        // 1.5% edge at a true count of 4
+        let bet = model.kelly_bet(4.0, Money::from_units(10000), Money::from_units(5));
+        assert_eq!(bet, Money::from_units(115));
+    }
+}
@@ -0,0 +1,1940 @@
+use blackjack::chart::{generate_chart, ChartRow, StrategyChart};
+use blackjack::config::GameConfig;
+use blackjack::counting::{betting_correlation, CountingMethod};
+use blackjack::custom_chart::{ChartFormat, CustomChart};
+use blackjack::dealer::{dealer_table, simulate_dealer_table, DealerTable, DEALER_OUTCOMES};
+use blackjack::deviations::{HiLoBot, INSURANCE_INDEX};
+use blackjack::error::Error;
+use blackjack::fast::simulate_fast;
+use blackjack::indices::{index_chart, IndexBot, IndexPlay, MIN_INDEX};
+use blackjack::input::{BasicStrategy, PlayerInput, Strategy};
+use blackjack::insurance::{breakeven_density, simulate_insurance, InsuranceOffers};
+use blackjack::money::Money;
+use blackjack::optimizer::{evolve, Evolution};
+use blackjack::outcomes::{simulate_outcomes, StartingHand};
+use blackjack::rules::Rules;
+use blackjack::simulation::{
+    compare_counts, compare_strategies, risk_of_ruin, simulate, simulate_resumable,
+    simulate_to_precision, simulate_traced, tournament as simulate_tournament, Checkpoint,
+    NoProgress, Progress, SimulationResult,
+};
+use blackjack::solver::{solve, Composition};
+use blackjack::spread::{optimal_ramp, SpreadLimits, SpreadObjective};
+use blackjack::sweep::{penetration_sweep, sweep, SweepRule};
+use blackjack::team::{simulate_team, Team, TeamPart};
+use blackjack::training::{self, train as learn};
+use blackjack::types::{self, Card, Hand, Move, Suit};
+use blackjack::wonging::{simulate_wonging, Wonging};
+use serde_json::{json, Value};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{stderr, BufWriter, IsTerminal, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// How the summary of a simulation is printed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SummaryFormat {
+    Text,
+    /// A single JSON document with the settings and the results, for other tools.
+    Json,
+}
+
+impl Display for SummaryFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SummaryFormat::Text => "text",
+                SummaryFormat::Json => "json",
+            }
+        )
+    }
+}
+
+impl FromStr for SummaryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(SummaryFormat::Text),
+            "json" => Ok(SummaryFormat::Json),
+            _ => Err(format!("expected 'text' or 'json', got '{}'", s)),
+        }
+    }
+}
+
+/// How a simulation is seeded and summarized.
+pub struct Report {
+    pub seed: u64,
+    pub format: SummaryFormat,
+    /// Whether the text summary shows how often each true count came up and the EV at it.
+    pub true_counts: bool,
+    /// Leaves out the progress bar.
+    pub quiet: bool,
+    /// The bankroll the certainty equivalent of the result is worked out for.
+    pub bankroll: Option<Money>,
+}
+
+impl Report {
+    /// Prints what is about to be simulated, as text only.
+    fn progress(&self, message: &str) {
+        if self.format == SummaryFormat::Text {
+            println!("{} with seed {}...", message, self.seed);
+        }
+    }
+
+    /// Shows the progress through the `unit`s of the simulation on stderr, unless told to be quiet
+    /// or stderr isn't a terminal.
+    fn progress_bar(&self, unit: &'static str) -> Box<dyn Progress> {
+        if self.quiet || !stderr().is_terminal() {
+            Box::new(NoProgress)
+        } else {
+            Box::new(ProgressBar::new(unit))
+        }
+    }
+
+    /// The settings of the simulation for a JSON summary.
+    fn settings(&self, command: &str, conf: &GameConfig) -> Value {
+        json!({
+            "command": command,
+            "seed": self.seed,
+            "rules": conf.rules,
+            "bet_system": conf.bet_system.map(|system| system.to_string()),
+            "bet_ramp": conf.bet_ramp.as_ref().map(|ramp| ramp.to_string()),
+            "base_bet": conf.base_bet,
+            "table_min": conf.table_min,
+            "counting": conf.counting.to_string(),
+            "ace_side_count": conf.ace_side_count,
+            "deck_estimation": conf.deck_estimation.to_string(),
+            "chart": conf.chart.as_ref().map(|chart| chart.path.display().to_string()),
+        })
+    }
+
+    /// The aggregate statistics of a simulation for a JSON summary.
+    fn summary(&self, result: &SimulationResult) -> Value {
+        json!({
+            "rounds": result.rounds,
+            "hands": result.hands,
+            "initial_bets": result.initial_bets,
+            "net": result.net,
+            "house_edge": result.house_edge(),
+            "ev_per_round": result.mean(),
+            "variance": result.variance(),
+            "std_dev": result.std_dev(),
+            "n0": result.n0(),
+            "house_edge_margin": result.house_edge_margin(),
+            "ev_margin": result.mean_margin(),
+            "desirability_index": result.desirability_index(),
+            "score": result.score(),
+            "certainty_equivalent": self
+                .bankroll
+                .map(|bankroll| result.certainty_equivalent(bankroll)),
+            "breakdown": result.breakdown,
+            "true_counts": result
+                .true_counts
+                .iter()
+                .map(|(true_count, count)| {
+                    let count = json!({
+                        "rounds": count.rounds,
+                        "initial_bets": count.initial_bets,
+                        "ev": count.player_edge(),
+                    });
+                    (true_count.to_string(), count)
+                })
+                .collect::<serde_json::Map<String, Value>>(),
+        })
+    }
+
+    /// Prints the advantage play metrics of the result, comparable with the literature.
+    fn print_metrics(&self, result: &SimulationResult) {
+        println!("DI:             {:.2}", result.desirability_index());
+        match result.score() {
+            Some(score) => println!("SCORE:          {:.2}", score),
+            None => println!("SCORE:          no edge"),
+        }
+        if let Some(bankroll) = self.bankroll {
+            println!(
+                "CE:             {:+.4} per round with a bankroll of {}",
+                result.certainty_equivalent(bankroll),
+                bankroll
+            );
+        }
+    }
+
+    fn print_true_counts(&self, result: &SimulationResult) {
+        if self.true_counts {
+            print_true_counts(result);
+        }
+    }
+
+    fn print_json(&self, mut settings: Value, results: Value) {
+        if let (Value::Object(settings), Value::Object(results)) = (&mut settings, results) {
+            settings.extend(results);
+        }
+        println!("{}", settings);
+    }
+}
+
+/// The least time between redraws of the progress bar.
+const PROGRESS_REDRAW: Duration = Duration::from_millis(100);
+
+/// The width of the progress bar in characters.
+const PROGRESS_WIDTH: usize = 30;
+
+/// A progress bar on a single line of stderr, with the throughput and the time left.
+struct ProgressBar {
+    unit: &'static str,
+    start: Instant,
+    drawn: Option<Instant>,
+}
+
+impl ProgressBar {
+    fn new(unit: &'static str) -> Self {
+        Self {
+            unit,
+            start: Instant::now(),
+            drawn: None,
+        }
+    }
+}
+
+impl Progress for ProgressBar {
+    fn update(&mut self, done: u64, total: u64) {
+        let now = Instant::now();
+        let finished = done >= total;
+        if !finished
+            && self
+                .drawn
+                .is_some_and(|drawn| now - drawn < PROGRESS_REDRAW)
+        {
+            return;
+        }
+        self.drawn = Some(now);
+        let share = done as f64 / total.max(1) as f64;
+        let elapsed = (now - self.start).as_secs_f64();
+        let rate = done as f64 / elapsed.max(f64::EPSILON);
+        let left = if finished {
+            elapsed
+        } else {
+            (total - done) as f64 / rate.max(f64::EPSILON)
+        };
+        let filled = (share * PROGRESS_WIDTH as f64) as usize;
+        eprint!(
+            "\r[{}{}] {:>5.1}% {}/{} {}, {:.0} {}/s, {} {}",
+            "#".repeat(filled),
+            " ".repeat(PROGRESS_WIDTH - filled),
+            share * 100.0,
+            done,
+            total,
+            self.unit,
+            rate,
+            self.unit,
+            if finished { "took" } else { "ETA" },
+            duration(left)
+        );
+        if finished {
+            eprintln!();
+        }
+        let _ = stderr().flush();
+    }
+}
+
+/// Formats seconds as h:mm:ss, or m:ss below an hour.
+fn duration(seconds: f64) -> String {
+    let seconds = seconds.min(u32::MAX as f64) as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// The widest bar of the true count histogram.
+const HISTOGRAM_WIDTH: f64 = 50.0;
+
+/// Prints how often the rounds were bet at each true count as a histogram, with the player's
+/// expected result per initial bet at the count.
+fn print_true_counts(result: &SimulationResult) {
+    println!("True counts at the bet:");
+    println!("  {:>4} {:>8} {:>8}", "TC", "Rounds", "EV");
+    let most = result
+        .true_counts
+        .values()
+        .map(|count| count.rounds)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    for (true_count, count) in &result.true_counts {
+        let share = count.rounds as f64 / result.rounds.max(1) as f64;
+        let bar = (count.rounds as f64 / most as f64 * HISTOGRAM_WIDTH).ceil() as usize;
+        println!(
+            "  {:>+4} {:>7.3}% {:>+7.2}% {}",
+            true_count,
+            share * 100.0,
+            count.player_edge() * 100.0,
+            "#".repeat(bar)
+        );
+    }
+}
+
+/// Estimates the house edge of the rules by simulating basic strategy and prints where it comes
+/// from.
+pub fn house_edge(
+    conf: GameConfig,
+    rounds: u64,
+    resume: Option<&Path>,
+    trace: Option<&Path>,
+    precision: Option<f64>,
+    fast: bool,
+    report: &Report,
+) -> Result<(), Error> {
+    let (result, settings) = match (resume, trace, precision) {
+        (None, Some(path), _) => {
+            report.progress(&format!(
+                "Simulating {} rounds of basic strategy, traced to {}",
+                rounds,
+                path.display()
+            ));
+            let settings = report.settings("house-edge", &conf);
+            let cannot_write = |e: std::io::Error| {
+                Error::Config(format!("cannot write {}: {}", path.display(), e))
+            };
+            let mut out = BufWriter::new(File::create(path).map_err(cannot_write)?);
+            let result = simulate_traced(
+                conf,
+                &mut BasicStrategy,
+                rounds,
+                Some(report.seed),
+                &mut out,
+                report.progress_bar("rounds").as_mut(),
+            )?;
+            out.flush().map_err(cannot_write)?;
+            (result, settings)
+        }
+        (Some(path), _, _) => {
+            let mut checkpoint = if path.exists() {
+                let checkpoint = Checkpoint::load(path)?;
+                if !checkpoint.matches(&conf, rounds) {
+                    return Err(Error::Config(format!(
+                        "{} is of a simulation with other settings",
+                        path.display()
+                    )));
+                }
+                checkpoint
+            } else {
+                Checkpoint::new(&conf, report.seed, rounds)
+            };
+            let report = &Report {
+                seed: checkpoint.seed,
+                ..*report
+            };
+            report.progress(&format!(
+                "Simulating {} rounds of basic strategy, {} done before",
+                rounds, checkpoint.result.rounds
+            ));
+            let settings = report.settings("house-edge", &conf);
+            let result = simulate_resumable(
+                conf,
+                &mut BasicStrategy,
+                &mut checkpoint,
+                &mut |checkpoint| checkpoint.save(path),
+                report.progress_bar("rounds").as_mut(),
+            )?;
+            (result, settings)
+        }
+        (None, None, Some(precision)) => {
+            report.progress(&format!(
+                "Simulating basic strategy until the house edge is known to ±{}%",
+                precision * 100.0
+            ));
+            let settings = report.settings("house-edge", &conf);
+            let result = simulate_to_precision(
+                conf,
+                &mut BasicStrategy,
+                precision,
+                Some(report.seed),
+                report.progress_bar("rounds").as_mut(),
+            )?;
+            (result, settings)
+        }
+        (None, None, None) if fast => {
+            report.progress(&format!(
+                "Simulating {} rounds of basic strategy on the fast engine",
+                rounds
+            ));
+            let settings = report.settings("house-edge", &conf);
+            let result = simulate_fast(
+                &conf,
+                rounds,
+                Some(report.seed),
+                report.progress_bar("rounds").as_mut(),
+            )?;
+            (result, settings)
+        }
+        (None, None, None) => {
+            report.progress(&format!("Simulating {} rounds of basic strategy", rounds));
+            let settings = report.settings("house-edge", &conf);
+            let result = simulate(
+                conf,
+                &mut BasicStrategy,
+                rounds,
+                Some(report.seed),
+                report.progress_bar("rounds").as_mut(),
+            )?;
+            (result, settings)
+        }
+    };
+    match report.format {
+        SummaryFormat::Text => {
+            print_house_edge(&result, report);
+            report.print_true_counts(&result);
+        }
+        SummaryFormat::Json => report.print_json(
+            settings,
+            json!({ "strategy": Strategy::Basic, "summary": report.summary(&result) }),
+        ),
+    }
+    Ok(())
+}
+
+fn print_house_edge(result: &SimulationResult, report: &Report) {
+    println!("============ HOUSE EDGE ============");
+    println!("Rounds played:  {}", result.rounds);
+    println!("Hands played:   {}", result.hands);
+    println!(
+        "House edge:     {:+.3}%{}",
+        result.house_edge() * 100.0,
+        margin(
+            result.house_edge_margin().map(|margin| margin * 100.0),
+            3,
+            "%"
+        )
+    );
+    print_spread(result);
+    report.print_metrics(result);
+    println!("Player result per initial bet, by hand:");
+    let breakdown = &result.breakdown;
+    let parts = [
+        ("Base game", breakdown.base),
+        ("Blackjacks", breakdown.blackjacks),
+        ("Doubles", breakdown.doubles),
+        ("Splits", breakdown.splits),
+        ("Surrenders", breakdown.surrenders),
+    ];
+    for (name, amount) in parts {
+        print_part(result, name, amount);
+    }
+}
+
+/// Prints how much the results of a round spread around the expected result.
+fn print_spread(result: &SimulationResult) {
+    println!(
+        "EV per round:   {:+.4}{}",
+        result.mean(),
+        margin(result.mean_margin(), 4, "")
+    );
+    println!("Variance:       {:.3}", result.variance());
+    println!("Std deviation:  {:.3}", result.std_dev());
+    match result.n0() {
+        Some(n0) => println!("N0:             {:.0} rounds", n0),
+        None => println!("N0:             no edge"),
+    }
+}
+
+/// The 95% confidence interval either side of an estimate, if there are enough rounds to tell.
+fn margin(margin: Option<f64>, precision: usize, unit: &str) -> String {
+    margin.map_or(String::new(), |margin| {
+        format!(" ± {:.*}{} (95% CI)", precision, margin, unit)
+    })
+}
+
+fn print_part(result: &SimulationResult, name: &str, amount: Money) {
+    println!(
+        "  {:<12}{:+.3}%",
+        name,
+        result.per_initial_bet(amount) * 100.0
+    );
+}
+
+/// Simulates the strategies over the same shoes and prints how they compare.
+pub fn compare(
+    conf: GameConfig,
+    strategies: &[Strategy],
+    rounds: u64,
+    report: &Report,
+) -> Result<(), Error> {
+    report.progress(&format!("Simulating {} rounds of each strategy", rounds));
+    let results = compare_strategies(
+        &conf,
+        strategies,
+        rounds,
+        report.seed,
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    if report.format == SummaryFormat::Json {
+        let strategies = results
+            .iter()
+            .map(|(strategy, result)| json!({ "strategy": strategy, "summary": report.summary(result) }))
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("simulate", &conf),
+            json!({ "strategies": strategies }),
+        );
+        return Ok(());
+    }
+
+    println!("============ STRATEGIES ============");
+    print!(
+        "{:<14}{:>10}{:>10}{:>10}{:>10}{:>8}{:>10}{:>8}{:>8}",
+        "Strategy", "Hands", "EV", "95% CI", "Variance", "SD", "N0", "DI", "SCORE"
+    );
+    if report.bankroll.is_some() {
+        print!("{:>10}", "CE");
+    }
+    println!();
+    for (strategy, result) in &results {
+        let n0 = result
+            .n0()
+            .map_or("-".to_string(), |n0| format!("{:.0}", n0));
+        let score = result
+            .score()
+            .map_or("-".to_string(), |score| format!("{:.2}", score));
+        let interval = result
+            .house_edge_margin()
+            .map_or("-".to_string(), |margin| format!("±{:.3}%", margin * 100.0));
+        print!(
+            "{:<14}{:>10}{:>9.3}%{:>10}{:>10.3}{:>8.3}{:>10}{:>8.2}{:>8}",
+            strategy.to_string(),
+            result.hands,
+            result.player_edge() * 100.0,
+            interval,
+            result.variance(),
+            result.std_dev(),
+            n0,
+            result.desirability_index(),
+            score
+        );
+        if let Some(bankroll) = report.bankroll {
+            print!("{:>+10.4}", result.certainty_equivalent(bankroll));
+        }
+        println!();
+    }
+    for (strategy, result) in &results {
+        if report.true_counts {
+            println!("{}:", strategy);
+            report.print_true_counts(result);
+        }
+    }
+    Ok(())
+}
+
+/// Simulates the counter bot with each counting system over the same shoes and prints them side
+/// by side with their betting correlations, to compare their strength.
+pub fn count_systems(
+    conf: GameConfig,
+    systems: &[CountingMethod],
+    rounds: u64,
+    report: &Report,
+) -> Result<(), Error> {
+    report.progress(&format!(
+        "Simulating {} rounds of the hi-lo bot with each count",
+        rounds
+    ));
+    let results = compare_counts(
+        &conf,
+        systems,
+        rounds,
+        report.seed,
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    let correlation = |system: &CountingMethod| {
+        betting_correlation(system.system().as_ref(), conf.ace_side_count)
+    };
+    if report.format == SummaryFormat::Json {
+        let systems = results
+            .iter()
+            .map(|(system, result)| {
+                json!({
+                    "system": system.to_string(),
+                    "betting_correlation": correlation(system),
+                    "summary": report.summary(result),
+                })
+            })
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("count-systems", &conf),
+            json!({ "systems": systems }),
+        );
+        return Ok(());
+    }
+
+    println!("============ COUNTING SYSTEMS ============");
+    println!(
+        "{:<12}{:>6}{:>10}{:>10}{:>12}{:>8}{:>10}{:>8}",
+        "System", "BC", "EV", "95% CI", "Win/100", "SD", "N0", "SCORE"
+    );
+    for (system, result) in &results {
+        let n0 = result
+            .n0()
+            .map_or("-".to_string(), |n0| format!("{:.0}", n0));
+        let score = result
+            .score()
+            .map_or("-".to_string(), |score| format!("{:.2}", score));
+        let interval = result
+            .house_edge_margin()
+            .map_or("-".to_string(), |margin| format!("±{:.3}%", margin * 100.0));
+        println!(
+            "{:<12}{:>6.2}{:>9.3}%{:>10}{:>+12.2}{:>8.3}{:>10}{:>8}",
+            system.system().name(),
+            correlation(system),
+            result.player_edge() * 100.0,
+            interval,
+            result.mean() * 100.0,
+            result.std_dev(),
+            n0,
+            score
+        );
+    }
+    if conf.ace_side_count {
+        println!("The aces are side counted by the counts that leave them out");
+    }
+    for (system, result) in &results {
+        if report.true_counts {
+            println!("{}:", system.system().name());
+            report.print_true_counts(result);
+        }
+    }
+    Ok(())
+}
+
+/// Simulates basic strategy under every combination of the swept rules and prints the house
+/// edges as a matrix, with a row for each combination of the other rules and a column for each
+/// deck count.
+pub fn rule_sweep(
+    conf: GameConfig,
+    swept: &[SweepRule],
+    decks: &[u32],
+    rounds: u64,
+    report: &Report,
+) -> Result<(), Error> {
+    let decks = if swept.contains(&SweepRule::Decks) {
+        decks
+    } else {
+        &[conf.rules.deck_count]
+    };
+    if decks.is_empty() {
+        return Err(Error::Config(
+            "there are no deck counts to sweep".to_string(),
+        ));
+    }
+    report.progress(&format!(
+        "Simulating {} rounds of basic strategy for each combination of the rules",
+        rounds
+    ));
+    let points = sweep(
+        &conf,
+        swept,
+        decks,
+        rounds,
+        report.seed,
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    if report.format == SummaryFormat::Json {
+        let points = points
+            .iter()
+            .map(|point| json!({ "rules": point.rules, "summary": report.summary(&point.result) }))
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("sweep", &conf),
+            json!({ "strategy": Strategy::Basic, "swept": swept, "results": points }),
+        );
+        return Ok(());
+    }
+
+    let label = |rules: &Rules| {
+        let label = swept
+            .iter()
+            .filter(|rule| **rule != SweepRule::Decks)
+            .map(|rule| rule.label(rules))
+            .collect::<Vec<_>>();
+        if label.is_empty() {
+            "As set".to_string()
+        } else {
+            label.join(" ")
+        }
+    };
+    println!("============ RULE SWEEP ============");
+    println!("House edge of basic strategy over {} rounds:", rounds);
+    print!("{:<16}", "Rules");
+    for point in &points[..decks.len()] {
+        print!("{:>10}", SweepRule::Decks.label(&point.rules));
+    }
+    println!();
+    // The deck counts are varied last, so each row is a run of the deck counts
+    for row in points.chunks(decks.len()) {
+        print!("{:<16}", label(&row[0].rules));
+        for point in row {
+            print!("{:>+9.3}%", point.result.house_edge() * 100.0);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// The true counts the share of rounds at or above is shown for in the penetration sweep.
+const HIGH_COUNTS: [i32; 4] = [1, 2, 3, 4];
+
+/// Simulates basic strategy with the cut card at each of the penetrations and prints how the
+/// house edge and the share of rounds at high counts change.
+pub fn penetration(
+    conf: GameConfig,
+    penetrations: &[f32],
+    rounds: u64,
+    report: &Report,
+) -> Result<(), Error> {
+    if penetrations.is_empty() {
+        return Err(Error::Config(
+            "there are no penetrations to sweep".to_string(),
+        ));
+    }
+    report.progress(&format!(
+        "Simulating {} rounds of basic strategy at each penetration",
+        rounds
+    ));
+    let points = penetration_sweep(
+        &conf,
+        penetrations,
+        rounds,
+        report.seed,
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    if report.format == SummaryFormat::Json {
+        let points = points
+            .iter()
+            .map(|point| {
+                json!({
+                    "penetration": point.rules.penetration,
+                    "high_counts": HIGH_COUNTS
+                        .iter()
+                        .map(|count| (count.to_string(), json!(point.result.share_from_count(*count))))
+                        .collect::<serde_json::Map<String, Value>>(),
+                    "summary": report.summary(&point.result),
+                })
+            })
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("penetration", &conf),
+            json!({ "strategy": Strategy::Basic, "results": points }),
+        );
+        return Ok(());
+    }
+
+    println!("============ PENETRATION ============");
+    match &conf.bet_ramp {
+        Some(ramp) => println!(
+            "Basic strategy betting {} over {} rounds at each penetration:",
+            ramp, rounds
+        ),
+        None => println!(
+            "Basic strategy flat betting over {} rounds at each penetration:",
+            rounds
+        ),
+    }
+    print!(
+        "  {:>11}{:>12}{:>10}",
+        "Penetration", "House edge", "Win/100"
+    );
+    for count in HIGH_COUNTS {
+        print!("{:>9}", format!("TC>={:+}", count));
+    }
+    println!();
+    for point in &points {
+        print!(
+            "  {:>10.1}%{:>+11.3}%{:>+10.2}",
+            point.rules.penetration.unwrap_or_default() * 100.0,
+            point.result.house_edge() * 100.0,
+            point.result.mean() * 100.0
+        );
+        for count in HIGH_COUNTS {
+            print!("{:>8.2}%", point.result.share_from_count(count) * 100.0);
+        }
+        println!();
+    }
+    println!();
+    println!(
+        "Win/100 is the result of 100 rounds in units, TC>= the share of rounds bet at the count"
+    );
+    Ok(())
+}
+
+/// Simulates a team of spotters calling in a big player and prints how the team and its
+/// bankroll did.
+pub fn team_play(conf: GameConfig, team: &Team, rounds: u64, report: &Report) -> Result<(), Error> {
+    report.progress(&format!(
+        "Simulating {} rounds at each of {} tables",
+        rounds, team.spotters
+    ));
+    let result = simulate_team(
+        &conf,
+        team,
+        rounds,
+        Some(report.seed),
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    let risk_of_ruin = result.risk_of_ruin(team.bankroll);
+    let part = |part: &TeamPart| {
+        json!({
+            "rounds": part.rounds,
+            "initial_bets": part.initial_bets,
+            "net": part.net,
+            "player_edge": part.player_edge(),
+        })
+    };
+    if report.format == SummaryFormat::Json {
+        report.print_json(
+            report.settings("team", &conf),
+            json!({
+                "strategy": Strategy::Basic,
+                "spotters": team.spotters,
+                "spotter_bet": team.spotter_bet,
+                "entry": team.entry,
+                "exit": team.exit,
+                "big_bet": team.big_bet,
+                "big_player_ramp": team.ramp.as_ref().map(|ramp| ramp.to_string()),
+                "bankroll": team.bankroll,
+                "rounds": result.rounds,
+                "net": result.net(),
+                "ev_per_round": result.mean(),
+                "variance": result.variance(),
+                "std_dev": result.variance().sqrt(),
+                "spotter_results": part(&result.spotters),
+                "big_player_results": part(&result.big_player),
+                "big_player_share": result.big_player_share(),
+                "final_bankroll": result.final_bankroll,
+                "lowest_bankroll": result.lowest_bankroll,
+                "max_drawdown": result.max_drawdown,
+                "risk_of_ruin": risk_of_ruin,
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ TEAM PLAY ============");
+    println!(
+        "Tables:          {}, the big player in at TC {} and out below TC {}",
+        team.spotters, team.entry, team.exit
+    );
+    println!("Rounds:          {} at each table", result.rounds);
+    println!("Team result:     {:+}", result.net());
+    println!("EV per round:    {:+.4} over all tables", result.mean());
+    println!("Std deviation:   {:.3}", result.variance().sqrt());
+    let print_part = |name: &str, part: &TeamPart| {
+        println!(
+            "  {:<15}{:+} on {} bets totalling {}, {:+.3}% per initial bet",
+            name,
+            part.net,
+            part.rounds,
+            part.initial_bets,
+            part.player_edge() * 100.0
+        )
+    };
+    print_part("Spotters", &result.spotters);
+    print_part("Big player", &result.big_player);
+    println!(
+        "Big player in:   {:.2}% of the rounds at the tables",
+        result.big_player_share() * 100.0
+    );
+    println!(
+        "Bankroll:        {} at the start, {} at the end",
+        team.bankroll, result.final_bankroll
+    );
+    println!("Lowest:          {}", result.lowest_bankroll);
+    println!("Max drawdown:    {}", result.max_drawdown);
+    println!(
+        "Risk of ruin:    {:.2}% playing on forever",
+        risk_of_ruin * 100.0
+    );
+    Ok(())
+}
+
+/// Simulates back counting with `strategy` and prints the rounds played out of those watched,
+/// next to the result of playing every round.
+pub fn wonging(
+    conf: GameConfig,
+    wonging: &Wonging,
+    mut strategy: Strategy,
+    rounds: u64,
+    report: &Report,
+) -> Result<(), Error> {
+    report.progress(&format!(
+        "Watching {} rounds, sitting in from TC {} with the {} bot",
+        rounds, wonging.entry, strategy
+    ));
+    let result = simulate_wonging(
+        &conf,
+        wonging,
+        &mut strategy,
+        rounds,
+        Some(report.seed),
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    if report.format == SummaryFormat::Json {
+        report.print_json(
+            report.settings("wong", &conf),
+            json!({
+                "strategy": strategy,
+                "entry": wonging.entry,
+                "exit": wonging.exit,
+                "bet": wonging.bet,
+                "ramp": wonging.ramp.as_ref().map(|ramp| ramp.to_string()),
+                "observed": result.observed,
+                "played": result.played.rounds,
+                "played_share": result.played_share(),
+                "initial_bets": result.played.initial_bets,
+                "net": result.played.net,
+                "player_edge": result.played.player_edge(),
+                "ev_per_round": result.mean(),
+                "std_dev": result.variance().sqrt(),
+                "win_per_100_observed": result.win_per_100_observed(),
+                "every_round_edge": result.table.player_edge(),
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ WONGING ============");
+    println!(
+        "Entry:           in at TC {}, out below TC {} or at the shuffle",
+        wonging.entry, wonging.exit
+    );
+    println!(
+        "Rounds:          {} played of {} watched ({:.2}%)",
+        result.played.rounds,
+        result.observed,
+        result.played_share() * 100.0
+    );
+    println!(
+        "Result:          {:+} on bets totalling {}",
+        result.played.net, result.played.initial_bets
+    );
+    println!(
+        "Player edge:     {:+.3}% per initial bet, {:+.3}% playing every round",
+        result.played.player_edge() * 100.0,
+        result.table.player_edge() * 100.0
+    );
+    println!(
+        "EV per round:    {:+.4} of the rounds played",
+        result.mean()
+    );
+    println!("Std deviation:   {:.3}", result.variance().sqrt());
+    println!("Win/100 watched: {:+.3}", result.win_per_100_observed());
+    Ok(())
+}
+
+/// Plays the tournament and prints the bots ranked by their average final bankroll.
+pub fn tournament(
+    conf: GameConfig,
+    bots: &[Strategy],
+    sessions: u64,
+    rounds: u64,
+    bankroll: Money,
+    report: &Report,
+) -> Result<(), Error> {
+    report.progress(&format!(
+        "Playing {} sessions of {} rounds with each of {} bots",
+        sessions,
+        rounds,
+        bots.len()
+    ));
+    let standings = simulate_tournament(
+        &conf,
+        bots,
+        sessions,
+        rounds,
+        bankroll,
+        report.seed,
+        report.progress_bar("sessions").as_mut(),
+    )?;
+    if report.format == SummaryFormat::Json {
+        let standings = standings
+            .iter()
+            .map(|standing| {
+                json!({
+                    "strategy": standing.strategy,
+                    "sessions": standing.sessions,
+                    "rounds": standing.rounds,
+                    "mean_bankroll": standing.mean_bankroll(),
+                    "best": standing.best,
+                    "worst": standing.worst,
+                    "wins": standing.wins,
+                    "busts": standing.busts,
+                })
+            })
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("tournament", &conf),
+            json!({
+                "sessions": sessions,
+                "rounds": rounds,
+                "bankroll": bankroll,
+                "standings": standings,
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ TOURNAMENT ============");
+    println!(
+        "{:<6}{:<14}{:>12}{:>12}{:>12}{:>8}{:>8}",
+        "Rank", "Bot", "Bankroll", "Best", "Worst", "Wins", "Busts"
+    );
+    for (rank, standing) in standings.iter().enumerate() {
+        println!(
+            "{:<6}{:<14}{:>12}{:>12}{:>12}{:>8}{:>8}",
+            rank + 1,
+            standing.strategy.to_string(),
+            standing.mean_bankroll().to_string(),
+            standing.best.to_string(),
+            standing.worst.to_string(),
+            standing.wins,
+            standing.busts
+        );
+    }
+    println!(
+        "Bankroll is the average at the end of a session, every bot starting with {}",
+        bankroll
+    );
+    Ok(())
+}
+
+/// Learns a strategy by self-play with Q-learning and prints it as a strategy chart, how much of
+/// it agrees with basic strategy and what it is worth next to basic strategy over the same shoes,
+/// saving it to `export` as a chart the chart bot can play.
+pub fn train(
+    conf: GameConfig,
+    rounds: u64,
+    epsilon: f64,
+    eval_rounds: u64,
+    export: Option<&Path>,
+    report: &Report,
+) -> Result<(), Error> {
+    report.progress(&format!(
+        "Learning a strategy over {} rounds, exploring {:.0}% of the moves",
+        rounds,
+        epsilon * 100.0
+    ));
+    let (table, _) = learn(
+        &conf,
+        rounds,
+        epsilon,
+        Some(report.seed),
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    let chart = table.chart(&conf.rules);
+    let agreement = training::agreement(&chart, &generate_chart(&conf.rules)?);
+    let learned = CustomChart::from_chart("learned", &chart);
+    if let Some(path) = export {
+        learned.save(path)?;
+    }
+
+    report.progress(&format!(
+        "Simulating {} rounds of the learned strategy and basic strategy",
+        eval_rounds
+    ));
+    let eval_conf = GameConfig {
+        chart: Some(learned),
+        ..conf.clone()
+    };
+    let results = compare_strategies(
+        &eval_conf,
+        &[Strategy::Chart, Strategy::Basic],
+        eval_rounds,
+        report.seed,
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    if report.format == SummaryFormat::Json {
+        let strategies = results
+            .iter()
+            .map(|(strategy, result)| {
+                let name = match strategy {
+                    Strategy::Chart => "learned".to_string(),
+                    strategy => strategy.to_string(),
+                };
+                json!({ "strategy": name, "summary": report.summary(result) })
+            })
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("train", &conf),
+            json!({
+                "rounds": rounds,
+                "epsilon": epsilon,
+                "states": table.values.len(),
+                "chart": chart,
+                "agreement": agreement,
+                "strategies": strategies,
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ LEARNED STRATEGY ============");
+    print_chart_rows("Hard", &chart.hard, false);
+    print_chart_rows("Soft", &chart.soft, false);
+    print_chart_rows("Pairs", &chart.pairs, false);
+    println!();
+    println!(
+        "Learned over {} rounds, {} states seen, {:.1}% of the chart agrees with basic strategy",
+        rounds,
+        table.values.len(),
+        agreement * 100.0
+    );
+    println!("============ LEARNED VS BASIC ============");
+    println!(
+        "{:<14}{:>10}{:>10}{:>10}",
+        "Strategy", "Hands", "EV", "95% CI"
+    );
+    for (strategy, result) in &results {
+        let name = match strategy {
+            Strategy::Chart => "learned".to_string(),
+            strategy => strategy.to_string(),
+        };
+        let interval = result
+            .house_edge_margin()
+            .map_or("-".to_string(), |margin| format!("±{:.3}%", margin * 100.0));
+        println!(
+            "{:<14}{:>10}{:>9.3}%{:>10}",
+            name,
+            result.hands,
+            result.player_edge() * 100.0,
+            interval
+        );
+    }
+    if let Some(path) = export {
+        println!("Saved the learned chart to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Evolves the chart of `conf`, or basic strategy for the rules without one, with a genetic
+/// algorithm and prints how the generations did, the evolved chart with the cells that changed
+/// and what both are worth over the same fresh shoes, saving the evolved chart to `export`.
+pub fn optimize(
+    conf: GameConfig,
+    evolution: &Evolution,
+    eval_rounds: u64,
+    export: Option<&Path>,
+    report: &Report,
+) -> Result<(), Error> {
+    let start = match &conf.chart {
+        Some(chart) => chart.clone(),
+        None => CustomChart::from_chart("basic strategy", &generate_chart(&conf.rules)?),
+    };
+    report.progress(&format!(
+        "Evolving {} over {} generations of {} charts, playing {} rounds each",
+        start.name, evolution.generations, evolution.population, evolution.rounds
+    ));
+    let evolved = evolve(
+        &conf,
+        evolution,
+        &start,
+        report.seed,
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    if let Some(path) = export {
+        evolved.chart.save(path)?;
+    }
+
+    report.progress(&format!("Simulating {} rounds of both charts", eval_rounds));
+    // Fresh shoes the charts weren't rated on
+    let eval_seed = report.seed.wrapping_add(evolution.generations);
+    let mut results = vec![];
+    for chart in [&start, &evolved.chart] {
+        let conf = GameConfig {
+            chart: Some(chart.clone()),
+            ..conf.clone()
+        };
+        let result = simulate(
+            conf,
+            &mut Strategy::Chart,
+            eval_rounds,
+            Some(eval_seed),
+            &mut NoProgress,
+        )?;
+        results.push((chart, result));
+    }
+    let changed = evolved
+        .chart
+        .rows
+        .iter()
+        .map(|(hand, cells)| {
+            let before = start.rows.get(hand);
+            let changes = cells
+                .iter()
+                .enumerate()
+                .filter(|(i, cell)| before.is_some_and(|before| before[*i] != **cell))
+                .count();
+            (hand, cells, changes)
+        })
+        .collect::<Vec<_>>();
+    if report.format == SummaryFormat::Json {
+        let generations = evolved
+            .generations
+            .iter()
+            .map(|generation| json!({ "best": generation.best, "mean": generation.mean }))
+            .collect::<Vec<Value>>();
+        let rows = evolved
+            .chart
+            .rows
+            .iter()
+            .map(|(hand, cells)| {
+                let cells = cells
+                    .iter()
+                    .map(|cell| cell.to_string())
+                    .collect::<Vec<_>>();
+                json!({ "hand": hand.to_string(), "cells": cells })
+            })
+            .collect::<Vec<Value>>();
+        let strategies = results
+            .iter()
+            .map(
+                |(chart, result)| json!({ "chart": chart.name, "summary": report.summary(result) }),
+            )
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("optimize", &conf),
+            json!({
+                "population": evolution.population,
+                "generations": generations,
+                "rounds": evolution.rounds,
+                "mutation_rate": evolution.mutation_rate,
+                "elite": evolution.elite,
+                "chart": rows,
+                "changed_cells": changed.iter().map(|(_, _, changes)| changes).sum::<usize>(),
+                "strategies": strategies,
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ GENERATIONS ============");
+    println!("{:<12}{:>10}{:>10}", "Generation", "Best", "Mean");
+    for (i, generation) in evolved.generations.iter().enumerate() {
+        println!(
+            "{:<12}{:>9.3}%{:>9.3}%",
+            i + 1,
+            generation.best * 100.0,
+            generation.mean * 100.0
+        );
+    }
+    println!("============ EVOLVED CHART ============");
+    print!("{:<7}", "Hand");
+    for upcard in StrategyChart::UPCARDS {
+        print!("{:>5}", upcard);
+    }
+    println!();
+    for (hand, cells, changes) in &changed {
+        if *changes == 0 {
+            continue;
+        }
+        print!("{:<7}", hand.to_string());
+        for (i, cell) in cells.iter().enumerate() {
+            let before = start.rows.get(hand).map(|before| before[i]);
+            let mark = if before.is_some_and(|before| before != *cell) {
+                "*"
+            } else {
+                ""
+            };
+            print!("{:>5}", format!("{}{}", cell, mark));
+        }
+        println!();
+    }
+    println!(
+        "{} cells changed from {}, marked *, the other hands are played as before",
+        changed.iter().map(|(_, _, changes)| changes).sum::<usize>(),
+        start.name
+    );
+    println!("============ START VS EVOLVED ============");
+    println!("{:<24}{:>10}{:>10}{:>10}", "Chart", "Hands", "EV", "95% CI");
+    for (chart, result) in &results {
+        let interval = result
+            .house_edge_margin()
+            .map_or("-".to_string(), |margin| format!("±{:.3}%", margin * 100.0));
+        println!(
+            "{:<24}{:>10}{:>9.3}%{:>10}",
+            chart.name,
+            result.hands,
+            result.player_edge() * 100.0,
+            interval
+        );
+    }
+    if let Some(path) = export {
+        println!("Saved the evolved chart to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Works out the index numbers of the common deviations for the counting system and the rules
+/// and prints them as a chart next to the published Hi-Lo indices, then simulates the counter
+/// with them, with the published indices and with basic strategy over the same shoes to check
+/// what they are worth.
+pub fn indices(conf: GameConfig, rounds: u64, report: &Report) -> Result<(), Error> {
+    report.progress(&format!(
+        "Working out the index numbers for {}",
+        conf.counting
+    ));
+    let chart = index_chart(&conf)?;
+    report.progress(&format!(
+        "Simulating {} rounds of the counter with each set of indices",
+        rounds
+    ));
+    let mut results = vec![];
+    let mut bots: [(&str, Box<dyn PlayerInput>); 3] = [
+        (
+            "basic strategy",
+            Box::new(IndexBot {
+                plays: vec![],
+                insurance: None,
+            }),
+        ),
+        ("published Hi-Lo", Box::new(HiLoBot)),
+        ("worked out", Box::new(IndexBot::new(&chart))),
+    ];
+    for (name, bot) in bots.iter_mut() {
+        let result = simulate(
+            conf.clone(),
+            bot.as_mut(),
+            rounds,
+            Some(report.seed),
+            report.progress_bar("rounds").as_mut(),
+        )?;
+        results.push((*name, result));
+    }
+    let hand = |play: &IndexPlay| {
+        if play.pair {
+            let card = play.total / 2;
+            format!("{},{}", card, card)
+        } else {
+            play.total.to_string()
+        }
+    };
+    let upcard = |play: &IndexPlay| match play.upcard {
+        11 => "A".to_string(),
+        card => card.to_string(),
+    };
+    let index = |index: Option<f32>| match index {
+        Some(index) if index <= MIN_INDEX as f32 => "always".to_string(),
+        Some(index) => format!("{:+}", index),
+        None => "never".to_string(),
+    };
+    if report.format == SummaryFormat::Json {
+        let plays = chart
+            .plays
+            .iter()
+            .map(|play| {
+                json!({
+                    "hand": hand(play),
+                    "upcard": upcard(play),
+                    "play": play.deviation,
+                    "otherwise": play.otherwise,
+                    "index": play.index,
+                    "published": play.published,
+                })
+            })
+            .collect::<Vec<Value>>();
+        let strategies = results
+            .iter()
+            .map(|(name, result)| json!({ "indices": name, "summary": report.summary(result) }))
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("indices", &conf),
+            json!({
+                "system": chart.system,
+                "insurance": chart.insurance,
+                "plays": plays,
+                "strategies": strategies,
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ INDEX NUMBERS ============");
+    println!(
+        "{:<8}{:>8}{:>12}{:>12}{:>10}{:>12}",
+        "Hand", "Upcard", "Play", "Otherwise", "Index", "Published"
+    );
+    println!(
+        "{:<8}{:>8}{:>12}{:>12}{:>10}{:>12}",
+        "Insure",
+        "A",
+        "",
+        "",
+        index(chart.insurance),
+        format!("{:+}", INSURANCE_INDEX)
+    );
+    for play in &chart.plays {
+        println!(
+            "{:<8}{:>8}{:>12}{:>12}{:>10}{:>12}",
+            hand(play),
+            upcard(play),
+            format!("{:?}", play.deviation),
+            format!("{:?}", play.otherwise),
+            index(play.index),
+            format!("{:+}", play.published)
+        );
+    }
+    println!(
+        "The deviation is played from the {} true count of the index up, the published indices are Hi-Lo's",
+        chart.system
+    );
+    println!("============ INDICES BY SIMULATION ============");
+    println!(
+        "{:<18}{:>10}{:>10}{:>10}",
+        "Indices", "Hands", "EV", "95% CI"
+    );
+    for (name, result) in &results {
+        let interval = result
+            .house_edge_margin()
+            .map_or("-".to_string(), |margin| format!("±{:.3}%", margin * 100.0));
+        println!(
+            "{:<18}{:>10}{:>9.3}%{:>10}",
+            name,
+            result.hands,
+            result.player_edge() * 100.0,
+            interval
+        );
+    }
+    println!("Every counter bets the same, over the same shoes");
+    Ok(())
+}
+
+/// Simulates basic strategy and prints what insurance would have returned at each true count
+/// and share of tens among the unseen cards, next to what the share of tens predicts.
+pub fn insurance(conf: GameConfig, rounds: u64, report: &Report) -> Result<(), Error> {
+    report.progress(&format!(
+        "Simulating {} rounds of basic strategy, looking at every insurance offer",
+        rounds
+    ));
+    let settings = report.settings("insurance", &conf);
+    let result = simulate_insurance(
+        conf,
+        rounds,
+        Some(report.seed),
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    let offers = |offers: &InsuranceOffers| {
+        json!({
+            "offers": offers.offers,
+            "ten_density": offers.ten_density(),
+            "expected_ev": offers.expected_ev(),
+            "simulated_ev": offers.simulated_ev(),
+        })
+    };
+    if report.format == SummaryFormat::Json {
+        report.print_json(
+            settings,
+            json!({
+                "rounds": result.rounds,
+                "breakeven_density": breakeven_density(),
+                "true_counts": result
+                    .true_counts
+                    .iter()
+                    .map(|(true_count, o)| (true_count.to_string(), offers(o)))
+                    .collect::<serde_json::Map<String, Value>>(),
+                "ten_densities": result
+                    .ten_densities
+                    .iter()
+                    .map(|(percent, o)| (percent.to_string(), offers(o)))
+                    .collect::<serde_json::Map<String, Value>>(),
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ INSURANCE ============");
+    println!(
+        "Insurance pays 2:1, so it wins in the long run once {:.1}% of the unseen cards are tens.",
+        breakeven_density() * 100.0
+    );
+    println!("Expected result of insuring a unit, from the tens left and as simulated:");
+    let print_row = |label: String, offers: &InsuranceOffers| {
+        println!(
+            "  {:>6}{:>9}{:>9.2}%{:>+10.4}{:>+11.4}",
+            label,
+            offers.offers,
+            offers.ten_density() * 100.0,
+            offers.expected_ev(),
+            offers.simulated_ev()
+        );
+    };
+    println!(
+        "  {:>6}{:>9}{:>10}{:>10}{:>11}",
+        "TC", "Offers", "Tens", "Expected", "Simulated"
+    );
+    for (true_count, offers) in &result.true_counts {
+        print_row(format!("{:+}", true_count), offers);
+    }
+    println!();
+    println!(
+        "  {:>6}{:>9}{:>10}{:>10}{:>11}",
+        "Tens", "Offers", "Average", "Expected", "Simulated"
+    );
+    for (percent, offers) in &result.ten_densities {
+        print_row(format!("{}%", percent), offers);
+    }
+    Ok(())
+}
+
+/// Plays sessions of basic strategy with the bet ramp until the bankroll is lost or the target is
+/// reached and prints how often it was lost.
+pub fn ruin(
+    conf: GameConfig,
+    bankroll: Money,
+    target: Money,
+    sessions: u64,
+    max_rounds: u64,
+    analytic: bool,
+    report: &Report,
+) -> Result<(), Error> {
+    if target <= bankroll {
+        return Err(Error::Config(
+            "the target must be above the bankroll".to_string(),
+        ));
+    }
+    let spread = match &conf.bet_ramp {
+        Some(ramp) => format!("bet ramp {} of {}", ramp, conf.base_bet),
+        None => format!("flat bets of {}", conf.table_min),
+    };
+    report.progress(&format!(
+        "Simulating {} sessions from {} to {} with {}",
+        sessions, bankroll, target, spread
+    ));
+    let result = risk_of_ruin(
+        &conf,
+        &mut BasicStrategy,
+        bankroll,
+        target,
+        sessions,
+        max_rounds,
+        Some(report.seed),
+        report.progress_bar("sessions").as_mut(),
+    )?;
+    let analytic_risk = analytic.then(|| result.analytic_risk_of_ruin(bankroll, target));
+
+    if report.format == SummaryFormat::Json {
+        report.print_json(
+            report.settings("risk-of-ruin", &conf),
+            json!({
+                "strategy": Strategy::Basic,
+                "bankroll": bankroll,
+                "target": target,
+                "max_rounds": max_rounds,
+                "sessions": result.sessions,
+                "ruined": result.ruined,
+                "reached_target": result.reached_target,
+                "risk_of_ruin": result.risk_of_ruin(),
+                "analytic_risk_of_ruin": analytic_risk,
+                "summary": report.summary(&result.totals),
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ RISK OF RUIN ============");
+    println!("Sessions:          {}", result.sessions);
+    println!("Ruined:            {}", result.ruined);
+    println!("Reached target:    {}", result.reached_target);
+    let unfinished = result.sessions - result.ruined - result.reached_target;
+    if unfinished > 0 {
+        println!("Out of rounds:     {}", unfinished);
+    }
+    println!("Risk of ruin:      {:.2}%", result.risk_of_ruin() * 100.0);
+    if let Some(risk) = analytic_risk {
+        println!(
+            "Analytic estimate: {:.2}% (mean {:+.4}, variance {:.3} per round)",
+            risk * 100.0,
+            result.totals.mean(),
+            result.totals.variance()
+        );
+    }
+    report.print_metrics(&result.totals);
+    report.print_true_counts(&result.totals);
+    Ok(())
+}
+
+/// Simulates flat bets of basic strategy, then searches for the bet ramp within the limits that
+/// does best by the objective at the true counts the rounds were bet at.
+pub fn optimize_spread(
+    mut conf: GameConfig,
+    rounds: u64,
+    objective: SpreadObjective,
+    mut limits: SpreadLimits,
+    report: &Report,
+) -> Result<(), Error> {
+    if let Some(max) = conf.table_max {
+        let spread = (max.units_f64() / limits.base_bet.units_f64()).floor() as u64;
+        limits.max_spread = limits.max_spread.min(spread.max(1));
+    }
+    report.progress(&format!(
+        "Simulating {} rounds of flat bets to find the best spread up to 1-{}",
+        rounds, limits.max_spread
+    ));
+    let settings = report.settings("optimize-spread", &conf);
+    conf.bet_ramp = None;
+    let result = simulate(
+        conf,
+        &mut BasicStrategy,
+        rounds,
+        Some(report.seed),
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    let best = optimal_ramp(&result.true_counts, objective, &limits)?;
+
+    if report.format == SummaryFormat::Json {
+        report.print_json(
+            settings,
+            json!({
+                "strategy": Strategy::Basic,
+                "objective": objective,
+                "max_spread": limits.max_spread,
+                "bankroll": limits.bankroll,
+                "max_risk": limits.max_risk,
+                "best_ramp": best.ramp.to_string(),
+                "ev_per_round": best.mean,
+                "variance": best.variance,
+                "std_dev": best.variance.sqrt(),
+                "score": best.score,
+                "growth": best.growth,
+                "risk_of_ruin": best.risk_of_ruin,
+                "summary": report.summary(&result),
+            }),
+        );
+        return Ok(());
+    }
+
+    println!("============ BET SPREAD ============");
+    let ramp = if best.ramp.steps.is_empty() {
+        "flat bets".to_string()
+    } else {
+        best.ramp.to_string()
+    };
+    println!(
+        "Best ramp by {}: {} (of {} base bets)",
+        objective, ramp, limits.base_bet
+    );
+    println!("EV per round:   {:+.4}", best.mean);
+    println!("Std deviation:  {:.3}", best.variance.sqrt());
+    println!("SCORE:          {:.2}", best.score);
+    println!(
+        "Growth:         {:+.4}% of the bankroll per 100 rounds",
+        best.growth * 100.0 * 100.0
+    );
+    println!(
+        "Risk of ruin:   {:.2}% of {}",
+        best.risk_of_ruin * 100.0,
+        limits.bankroll
+    );
+    println!("Base bets by true count:");
+    let steps = &best.ramp.steps;
+    match steps.first() {
+        Some((first, _)) => println!("  below {:+}: 1", first),
+        None => println!("  any: 1"),
+    }
+    for (i, (true_count, units)) in steps.iter().enumerate() {
+        match steps.get(i + 1) {
+            Some((next, _)) => println!("  {:+} to below {:+}: {}", true_count, next, units),
+            None => println!("  {:+} and up: {}", true_count, units),
+        }
+    }
+    report.print_true_counts(&result);
+    Ok(())
+}
+
+/// Works out the best play of every hand against every upcard for the rules and prints it as a
+/// strategy chart, optionally with the expected result of each play.
+pub fn strategy_chart(rules: &Rules, ev: bool, format: SummaryFormat) -> Result<(), Error> {
+    let chart = generate_chart(rules)?;
+    if format == SummaryFormat::Json {
+        println!("{}", json!({ "rules": rules, "chart": chart }));
+        return Ok(());
+    }
+
+    println!("============ BASIC STRATEGY ============");
+    print_chart_rows("Hard", &chart.hard, ev);
+    print_chart_rows("Soft", &chart.soft, ev);
+    print_chart_rows("Pairs", &chart.pairs, ev);
+    println!();
+    println!("H hit, S stand, P split, D double (or hit), Ds double (or stand),");
+    println!("Rh, Rs and Rp surrender (or hit, stand and split)");
+    Ok(())
+}
+
+/// Prints the chart the chart bot plays, the --chart or basic strategy for the rules, in the
+/// layout of `format`, or writes it to `output`.
+pub fn export_chart(
+    conf: &GameConfig,
+    format: ChartFormat,
+    output: Option<&Path>,
+) -> Result<(), Error> {
+    let chart = match &conf.chart {
+        Some(chart) => chart.clone(),
+        None => CustomChart::from_chart("Basic strategy", &generate_chart(&conf.rules)?),
+    };
+    let text = chart.render(format);
+    match output {
+        Some(path) => {
+            std::fs::write(path, text)
+                .map_err(|e| Error::Config(format!("cannot write {}: {}", path.display(), e)))?;
+            println!("Saved the chart to {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn print_chart_rows(name: &str, rows: &[ChartRow], ev: bool) {
+    let width = if ev { 7 } else { 4 };
+    print!("{:<7}", name);
+    for upcard in StrategyChart::UPCARDS {
+        print!("{:>width$}", upcard, width = width);
+    }
+    println!();
+    for row in rows {
+        print!("{:<7}", row.label);
+        for cell in &row.cells {
+            print!("{:>width$}", cell.play.to_string(), width = width);
+        }
+        println!();
+        if ev {
+            print!("{:<7}", "");
+            for cell in &row.cells {
+                print!("{:>+width$.3}", cell.ev, width = width);
+            }
+            println!();
+        }
+    }
+}
+
+/// Simulates the strategy and prints the expected result of every starting hand against every
+/// upcard, or with a `hand` how the hands played from it ended against each upcard.
+pub fn hand_outcomes(
+    conf: GameConfig,
+    strategy: Strategy,
+    rounds: u64,
+    hand: Option<StartingHand>,
+    report: &Report,
+) -> Result<(), Error> {
+    report.progress(&format!(
+        "Simulating {} rounds of {}, recording the outcomes of every starting hand",
+        rounds, strategy
+    ));
+    let settings = report.settings("outcomes", &conf);
+    let table = simulate_outcomes(
+        conf,
+        &mut { strategy },
+        rounds,
+        Some(report.seed),
+        report.progress_bar("rounds").as_mut(),
+    )?;
+    let hands = table
+        .hands
+        .iter()
+        .filter(|(starting, _)| hand.is_none_or(|hand| hand == **starting));
+    if report.format == SummaryFormat::Json {
+        let mut rows = vec![];
+        for (starting, outcomes) in hands {
+            for (upcard, outcomes) in StrategyChart::UPCARDS.iter().zip(outcomes) {
+                let mut row = json!(outcomes);
+                if let Value::Object(row) = &mut row {
+                    row.insert("hand".to_string(), json!(starting.to_string()));
+                    row.insert("upcard".to_string(), json!(upcard));
+                    row.insert("ev".to_string(), json!(outcomes.ev()));
+                }
+                rows.push(row);
+            }
+        }
+        report.print_json(
+            settings,
+            json!({ "strategy": strategy, "rounds": table.rounds, "outcomes": rows }),
+        );
+        return Ok(());
+    }
+
+    println!("============ HAND OUTCOMES ============");
+    let Some(hand) = hand else {
+        println!("Expected result of each starting hand in initial bets:");
+        print!("{:<7}", "Hand");
+        for upcard in StrategyChart::UPCARDS {
+            print!("{:>7}", upcard);
+        }
+        println!();
+        for (starting, outcomes) in hands {
+            print!("{:<7}", starting.to_string());
+            for outcomes in outcomes {
+                print!("{:>+7.3}", outcomes.ev());
+            }
+            println!();
+        }
+        return Ok(());
+    };
+    let Some(outcomes) = table.hands.get(&hand) else {
+        println!("{} was never dealt", hand);
+        return Ok(());
+    };
+    println!(
+        "How the hands played from {} ended against each upcard:",
+        hand
+    );
+    println!(
+        "  {:>6}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>9}",
+        "Upcard", "Dealt", "BJ", "Win", "Push", "Loss", "Bust", "Surr", "Double", "Split", "EV"
+    );
+    for (upcard, outcomes) in StrategyChart::UPCARDS.iter().zip(outcomes) {
+        let share = |count| format!("{:.1}%", outcomes.share(count) * 100.0);
+        println!(
+            "  {:>6}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>+9.4}",
+            upcard,
+            outcomes.dealt,
+            share(outcomes.blackjacks),
+            share(outcomes.wins),
+            share(outcomes.pushes),
+            share(outcomes.losses),
+            share(outcomes.busts),
+            share(outcomes.surrenders),
+            share(outcomes.doubles),
+            format!(
+                "{:.1}%",
+                outcomes.splits as f64 / outcomes.dealt.max(1) as f64 * 100.0
+            ),
+            outcomes.ev()
+        );
+    }
+    println!();
+    println!("The shares are of the hands played, the splits of the hands dealt");
+    Ok(())
+}
+
+/// Prints the chance of each result of the dealer's hand behind every upcard, worked out exactly
+/// or, given a number of `hands`, by dealing them from a shuffled shoe.
+pub fn dealer_outcomes(
+    conf: &GameConfig,
+    hands: Option<u64>,
+    report: &Report,
+) -> Result<(), Error> {
+    let table = match hands {
+        Some(hands) => {
+            report.progress(&format!("Dealing {} dealer hands", hands));
+            simulate_dealer_table(
+                &conf.rules,
+                hands,
+                Some(report.seed),
+                report.progress_bar("hands").as_mut(),
+            )?
+        }
+        None => dealer_table(&conf.rules)?,
+    };
+    if report.format == SummaryFormat::Json {
+        let rows = table
+            .rows
+            .iter()
+            .map(|row| {
+                let chances = |chances: [f64; DEALER_OUTCOMES]| {
+                    DealerTable::OUTCOMES
+                        .iter()
+                        .zip(chances)
+                        .map(|(outcome, chance)| (outcome.to_lowercase(), json!(chance)))
+                        .collect::<serde_json::Map<String, Value>>()
+                };
+                json!({
+                    "upcard": row.upcard,
+                    "chances": chances(row.chances),
+                    "without_blackjack": chances(row.without_blackjack()),
+                })
+            })
+            .collect::<Vec<Value>>();
+        report.print_json(
+            report.settings("dealer-table", conf),
+            json!({ "hands": table.hands, "rows": rows }),
+        );
+        return Ok(());
+    }
+
+    println!("============ DEALER OUTCOMES ============");
+    match table.hands {
+        Some(hands) => println!(
+            "From {} hands dealt from {} deck shoes:",
+            hands, conf.rules.deck_count
+        ),
+        None => println!("Exact, drawing every card with the odds of a full shoe:"),
+    }
+    print!("{:<8}", "Upcard");
+    for outcome in DealerTable::OUTCOMES {
+        print!("{:>8}", outcome);
+    }
+    println!("{:>8}", "Bust*");
+    for row in &table.rows {
+        print!("{:<8}", row.upcard);
+        for chance in row.chances {
+            print!("{:>7.2}%", chance * 100.0);
+        }
+        println!(
+            "{:>7.2}%",
+            row.without_blackjack()[DEALER_OUTCOMES - 1] * 100.0
+        );
+    }
+    println!();
+    println!("* once the dealer has peeked and found no blackjack");
+    Ok(())
+}
+
+/// Works out the exact expected result of every move on the hand against the upcard, with the
+/// hand, the upcard and the other cards seen taken out of a full shoe, and prints them.
+pub fn solve_hand(
+    rules: &Rules,
+    hand: &[types::Value],
+    upcard: &types::Value,
+    seen: &[types::Value],
+    format: SummaryFormat,
+) -> Result<(), Error> {
+    let mut cards = Composition::full(rules.deck_count, rules.variant)?;
+    for value in hand.iter().chain(seen).chain([upcard]) {
+        cards.remove(value)?;
+    }
+    let hand = Hand {
+        cards: hand
+            .iter()
+            .map(|value| Card {
+                suit: Suit::Spades,
+                value: value.clone(),
+            })
+            .collect(),
+    };
+    let ev = solve(rules, &cards, &hand, upcard)?;
+    let (best, best_ev) = ev.best();
+    if format == SummaryFormat::Json {
+        println!(
+            "{}",
+            json!({ "rules": rules, "hand": hand, "upcard": upcard, "ev": ev, "best": best })
+        );
+        return Ok(());
+    }
+
+    println!("============ EXPECTED RESULTS ============");
+    println!("{} against {}, {} cards left", hand, upcard, cards.total());
+    for action in [
+        Move::Stand,
+        Move::Hit,
+        Move::Double,
+        Move::Split,
+        Move::Surrender,
+    ] {
+        if let Some(result) = ev.of(action) {
+            println!("  {:<10}{:+.4}", format!("{:?}", action), result);
+        }
+    }
+    println!("Best move: {:?} ({:+.4})", best, best_ev);
+    Ok(())
+}
@@ -0,0 +1,406 @@
+use crate::advisor::EdgeModel;
+use crate::config::GameConfig;
+use crate::deviations::HiLoBot;
+use crate::money::Money;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// What the next bet is sized on: the base bet, the true count and the bankroll.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BetSituation {
+    pub base: Money,
+    pub true_count: f32,
+    pub bankroll: Money,
+}
+
+/// Sizes the player's bets automatically, apart from how the hands are played, so any bot can
+/// bet with any betting system.
+pub trait BetStrategy {
+    fn name(&self) -> &'static str;
+
+    /// The bet for the next round, before it is fitted to the table limits and the bankroll.
+    fn next_bet(&self, situation: &BetSituation) -> Money;
+
+    /// Records the net result of a round, moving a progression along.
+    fn record(&mut self, _result: Money) {}
+}
+
+/// Bets the base bet every round.
+#[derive(Debug, Default)]
+pub struct FlatBet;
+
+impl BetStrategy for FlatBet {
+    fn name(&self) -> &'static str {
+        "Flat"
+    }
+
+    fn next_bet(&self, situation: &BetSituation) -> Money {
+        situation.base
+    }
+}
+
+/// Bets the units of the ramp at the true count.
+impl BetStrategy for BetRamp {
+    fn name(&self) -> &'static str {
+        "Bet ramp"
+    }
+
+    fn next_bet(&self, situation: &BetSituation) -> Money {
+        self.bet(situation.true_count, situation.base)
+    }
+}
+
+/// Bets `fraction` of the Kelly bet for the edge of the model at the true count, the base bet
+/// when the player has no edge.
+#[derive(Debug)]
+pub struct Kelly {
+    pub model: EdgeModel,
+    pub fraction: f64,
+}
+
+impl BetStrategy for Kelly {
+    fn name(&self) -> &'static str {
+        "Kelly"
+    }
+
+    fn next_bet(&self, situation: &BetSituation) -> Money {
+        let kelly = self.model.kelly_fraction(situation.true_count) * self.fraction;
+        situation.bankroll.fraction(kelly).max(situation.base)
+    }
+}
+
+/// Doubles the bet after every loss and returns to the base bet after a win.
+#[derive(Debug, Default)]
+pub struct Martingale {
+    losses: u32,
+}
+
+impl BetStrategy for Martingale {
+    fn name(&self) -> &'static str {
+        "Martingale"
+    }
+
+    fn next_bet(&self, situation: &BetSituation) -> Money {
+        situation.base * 2u64.saturating_pow(self.losses)
+    }
+
+    fn record(&mut self, result: Money) {
+        if result < Money::ZERO {
+            self.losses += 1;
+        } else if result > Money::ZERO {
+            self.losses = 0;
+        }
+    }
+}
+
+/// Doubles the bet after every win, returning to the base bet after a loss or three wins in a row.
+#[derive(Debug, Default)]
+pub struct Paroli {
+    wins: u32,
+}
+
+impl Paroli {
+    const MAX_WINS: u32 = 3;
+}
+
+impl BetStrategy for Paroli {
+    fn name(&self) -> &'static str {
+        "Paroli"
+    }
+
+    fn next_bet(&self, situation: &BetSituation) -> Money {
+        situation.base * 2u64.pow(self.wins)
+    }
+
+    fn record(&mut self, result: Money) {
+        if result > Money::ZERO {
+            self.wins = (self.wins + 1) % Paroli::MAX_WINS;
+        } else if result < Money::ZERO {
+            self.wins = 0;
+        }
+    }
+}
+
+/// Bets 1, 3, 2 and 6 units on consecutive wins, starting over after a loss or a completed run.
+#[derive(Debug, Default)]
+pub struct OneThreeTwoSix {
+    step: usize,
+}
+
+impl OneThreeTwoSix {
+    const UNITS: [u64; 4] = [1, 3, 2, 6];
+}
+
+impl BetStrategy for OneThreeTwoSix {
+    fn name(&self) -> &'static str {
+        "1-3-2-6"
+    }
+
+    fn next_bet(&self, situation: &BetSituation) -> Money {
+        situation.base * OneThreeTwoSix::UNITS[self.step]
+    }
+
+    fn record(&mut self, result: Money) {
+        if result > Money::ZERO {
+            self.step = (self.step + 1) % OneThreeTwoSix::UNITS.len();
+        } else if result < Money::ZERO {
+            self.step = 0;
+        }
+    }
+}
+
+/// The betting systems that can be selected from the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BetSystem {
+    Flat,
+    /// The bet ramp of the game, or the counter bot's without one.
+    Ramp,
+    /// The fraction of the Kelly bet, for the edge model of the advisor or the default one.
+    Kelly(f64),
+    Martingale,
+    Paroli,
+    OneThreeTwoSix,
+}
+
+impl BetSystem {
+    pub fn strategy(&self, conf: &GameConfig) -> Box<dyn BetStrategy> {
+        match self {
+            BetSystem::Flat => Box::new(FlatBet),
+            BetSystem::Ramp => Box::new(conf.bet_ramp.clone().unwrap_or_else(HiLoBot::ramp)),
+            BetSystem::Kelly(fraction) => Box::new(Kelly {
+                model: conf.advisor.unwrap_or(EdgeModel::DEFAULT),
+                fraction: *fraction,
+            }),
+            BetSystem::Martingale => Box::<Martingale>::default(),
+            BetSystem::Paroli => Box::<Paroli>::default(),
+            BetSystem::OneThreeTwoSix => Box::<OneThreeTwoSix>::default(),
+        }
+    }
+}
+
+/// Written as on the command line, the Kelly fraction after a colon unless it is full Kelly.
+impl Display for BetSystem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BetSystem::Flat => "flat",
+            BetSystem::Ramp => "ramp",
+            BetSystem::Kelly(fraction) if *fraction == 1.0 => "kelly",
+            BetSystem::Kelly(fraction) => return write!(f, "kelly:{}", fraction),
+            BetSystem::Martingale => "martingale",
+            BetSystem::Paroli => "paroli",
+            BetSystem::OneThreeTwoSix => "1-3-2-6",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for BetSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "flat" => Ok(BetSystem::Flat),
+            "ramp" => Ok(BetSystem::Ramp),
+            "kelly" => Ok(BetSystem::Kelly(1.0)),
+            "martingale" => Ok(BetSystem::Martingale),
+            "paroli" => Ok(BetSystem::Paroli),
+            "1-3-2-6" => Ok(BetSystem::OneThreeTwoSix),
+            _ => match s.strip_prefix("kelly:") {
+                Some(fraction) => match fraction.parse::<f64>() {
+                    Ok(fraction) if fraction > 0.0 && fraction <= 1.0 => {
+                        Ok(BetSystem::Kelly(fraction))
+                    }
+                    _ => Err(format!(
+                        "expected a Kelly fraction above 0 and up to 1, got '{}'",
+                        fraction
+                    )),
+                },
+                None => Err(format!(
+                    "expected one of 'flat', 'ramp', 'kelly', 'kelly:<fraction>', 'martingale', 'paroli' or '1-3-2-6', got '{}'",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// A bet spread keyed on the true count, each step bets `units` base bets from its true count up.
+/// Below the first step a single unit is bet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BetRamp {
+    /// (minimum true count, units), sorted by true count.
+    pub steps: Vec<(f32, u64)>,
+}
+
+impl BetRamp {
+    pub fn units(&self, true_count: f32) -> u64 {
+        self.steps
+            .iter()
+            .rev()
+            .find(|(min, _)| true_count >= *min)
+            .map_or(1, |(_, units)| *units)
+    }
+
+    pub fn bet(&self, true_count: f32, base: Money) -> Money {
+        base * self.units(true_count)
+    }
+
+    /// The ramp of an unbalanced count on its running count: 2 units at the key count, going up
+    /// evenly to `max_units` at the pivot.
+    pub fn key_count(key: i32, pivot: i32, max_units: u64) -> Self {
+        let steps = (pivot - key).max(1) as u64;
+        let steps = (0..=steps)
+            .map(|step| {
+                let units = 2 + (max_units.saturating_sub(2) * step).div_ceil(steps);
+                (key as f32 + step as f32, units.min(max_units))
+            })
+            .collect();
+        BetRamp { steps }
+    }
+}
+
+impl Display for BetRamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.steps
+                .iter()
+                .map(|(min, units)| format!("{}:{}", min, units))
+                .collect::<Vec<String>>()
+                .join(",")
+        )
+    }
+}
+
+/// Parses comma separated "<true count>:<units>" steps, e.g. "1:2,2:4,3:8".
+impl FromStr for BetRamp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = s
+            .split(',')
+            .map(|step| {
+                let (min, units) = step
+                    .split_once(':')
+                    .ok_or_else(|| format!("expected a step like 2:4, got '{}'", step))?;
+                let min = min
+                    .trim()
+                    .parse::<f32>()
+                    .map_err(|e| format!("invalid true count '{}': {}", min, e))?;
+                let units = units
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|e| format!("invalid number of units '{}': {}", units, e))?;
+                Ok((min, units))
+            })
+            .collect::<Result<Vec<(f32, u64)>, String>>()?;
+        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Ok(BetRamp { steps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::betting::{BetRamp, BetSituation, BetSystem};
+    use crate::config::GameConfig;
+    use crate::money::Money;
+
+    const BASE: Money = Money::from_units(10);
+    const WIN: Money = Money::from_units(1);
+    const LOSS: Money = Money::from_units(-1);
+    const SITUATION: BetSituation = BetSituation {
+        base: BASE,
+        true_count: 0.0,
+        bankroll: Money::from_units(1000),
+    };
+
+    fn bets(system: BetSystem, results: &[Money]) -> Vec<Money> {
+        let mut strategy = system.strategy(&GameConfig::default());
+        let mut bets = vec![strategy.next_bet(&SITUATION)];
+        for result in results {
+            strategy.record(*result);
+            bets.push(strategy.next_bet(&SITUATION));
+        }
+        bets
+    }
+
+    fn units(units: &[i64]) -> Vec<Money> {
+        units.iter().map(|u| Money::from_units(u * 10)).collect()
+    }
+
+    #[test]
+    fn test_bet_ramp() {
+        let ramp = "3:8,2:4".parse::<BetRamp>().unwrap();
+        assert_eq!(ramp.to_string(), "2:4,3:8");
+        assert_eq!(ramp.units(-1.0), 1);
+        assert_eq!(ramp.units(1.9), 1);
+        assert_eq!(ramp.units(2.0), 4);
+        assert_eq!(ramp.bet(5.5, BASE), Money::from_units(80));
+        assert!("2-4".parse::<BetRamp>().is_err());
+
+        let ramp = BetRamp::key_count(-4, 4, 8);
+        assert_eq!(ramp.units(-5.0), 1);
+        assert_eq!(ramp.units(-4.0), 2);
+        assert_eq!(ramp.units(0.0), 5);
+        assert_eq!(ramp.units(4.0), 8);
+        assert_eq!(ramp.units(10.0), 8);
+    }
+
+    #[test]
+    fn test_martingale() {
+        let results = [LOSS, LOSS, Money::ZERO, WIN, LOSS];
+        assert_eq!(
+            bets(BetSystem::Martingale, &results),
+            units(&[1, 2, 4, 4, 1, 2])
+        );
+    }
+
+    #[test]
+    fn test_paroli() {
+        let results = [WIN, WIN, WIN, WIN, LOSS];
+        assert_eq!(
+            bets(BetSystem::Paroli, &results),
+            units(&[1, 2, 4, 1, 2, 1])
+        );
+    }
+
+    #[test]
+    fn test_one_three_two_six() {
+        let results = [WIN, WIN, WIN, WIN, WIN, LOSS];
+        assert_eq!(
+            bets(BetSystem::OneThreeTwoSix, &results),
+            units(&[1, 3, 2, 6, 1, 3, 1])
+        );
+    }
+
+    #[test]
+    fn test_bet_systems() {
+        assert_eq!(bets(BetSystem::Flat, &[WIN, LOSS]), units(&[1, 1, 1]));
+        let conf = GameConfig {
+            bet_ramp: Some("2:4,3:8".parse().unwrap()),
+            ..GameConfig::default()
+        };
+        let at = |system: BetSystem, true_count| {
+            let situation = BetSituation {
+                true_count,
+                ..SITUATION
+            };
+            system.strategy(&conf).next_bet(&situation)
+        };
+        assert_eq!(at(BetSystem::Ramp, 2.5), Money::from_units(40));
+        assert_eq!(at(BetSystem::Flat, 2.5), BASE);
+        // Kelly bets the base bet without an edge, and the fraction of the bankroll with one
+        assert_eq!(at(BetSystem::Kelly(1.0), 0.0), BASE);
+        let full = at(BetSystem::Kelly(1.0), 5.0);
+        assert!(full > BASE);
+        assert!(at(BetSystem::Kelly(0.5), 5.0) < full);
+
+        for system in ["flat", "ramp", "kelly", "kelly:0.5", "1-3-2-6"] {
+            assert_eq!(system.parse::<BetSystem>().unwrap().to_string(), system);
+        }
+        assert!("kelly:2".parse::<BetSystem>().is_err());
+        assert!("fibonacci".parse::<BetSystem>().is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use crate::types::{Payout, PlayerHand, Suit, TWENTY_ONE};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A bonus paid on a 21 made with a special combination of cards, the table decides which are offered.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Bonus21 {
+    /// A 21 of five or more cards, paying 3:2 for five, 2:1 for six and 3:1 for seven or more.
+    FiveCard,
+    /// 6-7-8 paying 3:1 in spades, 2:1 suited and 3:2 mixed.
+    SixSevenEight,
+    /// 7-7-7 paying 3:1 in spades, 2:1 suited and 3:2 mixed.
+    SevenSevenSeven,
+}
+
+impl Bonus21 {
+    pub const ALL: [Bonus21; 3] = [
+        Bonus21::FiveCard,
+        Bonus21::SixSevenEight,
+        Bonus21::SevenSevenSeven,
+    ];
+
+    /// The payout if the hand qualifies for this bonus.
+    fn payout(&self, hand: &PlayerHand) -> Option<Payout> {
+        let cards = &hand.hand;
+        let composition = match self {
+            Bonus21::FiveCard => {
+                return match cards.cards.len() {
+                    0..=4 => None,
+                    5 => Some(Payout::THREE_TO_TWO),
+                    6 => Some(Payout::TWO_TO_ONE),
+                    _ => Some(Payout::THREE_TO_ONE),
+                }
+            }
+            Bonus21::SixSevenEight => [6, 7, 8],
+            Bonus21::SevenSevenSeven => [7, 7, 7],
+        };
+
+        if !cards.is_composed_of(&composition) {
+            return None;
+        }
+
+        Some(match cards.suit() {
+            Some(Suit::Spades) => Payout::THREE_TO_ONE,
+            Some(_) => Payout::TWO_TO_ONE,
+            None => Payout::THREE_TO_TWO,
+        })
+    }
+}
+
+/// Evaluates the bonuses offered at the table against the hand, returning the best payout.
+/// Only 21s qualify and doubled hands never do.
+pub fn bonus_payout(hand: &PlayerHand, bonuses: &[Bonus21]) -> Option<Payout> {
+    if hand.doubled || hand.hand.calc_value() != TWENTY_ONE {
+        return None;
+    }
+
+    bonuses
+        .iter()
+        .filter_map(|bonus| bonus.payout(hand))
+        .max_by(|a, b| {
+            (a.numerator as u64 * b.denominator as u64)
+                .cmp(&(b.numerator as u64 * a.denominator as u64))
+        })
+}
+
+impl Display for Bonus21 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Bonus21::FiveCard => "five-card",
+                Bonus21::SixSevenEight => "6-7-8",
+                Bonus21::SevenSevenSeven => "7-7-7",
+            }
+        )
+    }
+}
+
+impl FromStr for Bonus21 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "five-card" => Ok(Bonus21::FiveCard),
+            "6-7-8" => Ok(Bonus21::SixSevenEight),
+            "7-7-7" => Ok(Bonus21::SevenSevenSeven),
+            _ => Err(format!(
+                "expected one of 'five-card', '6-7-8' or '7-7-7', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bonus::{bonus_payout, Bonus21};
+    use crate::types::tests::{hand, suited_hand};
+    use crate::types::{Card, Payout, Suit, Value};
+
+    #[test]
+    fn test_bonus_payouts() {
+        let bonuses = Bonus21::ALL;
+        let seven_eight_six = [Value::Seven, Value::Eight, Value::Six];
+        assert_eq!(
+            bonus_payout(&hand(&seven_eight_six), &bonuses),
+            Some(Payout::THREE_TO_ONE)
+        );
+        assert_eq!(
+            bonus_payout(&suited_hand(&seven_eight_six, Suit::Hearts), &bonuses),
+            Some(Payout::TWO_TO_ONE)
+        );
+        assert_eq!(
+            bonus_payout(&hand(&seven_eight_six), &[Bonus21::SevenSevenSeven]),
+            None
+        );
+
+        let mut mixed = hand(&[Value::Seven, Value::Seven]);
+        mixed.hand.add_card(Card {
+            suit: Suit::Clubs,
+            value: Value::Seven,
+        });
+        assert_eq!(bonus_payout(&mixed, &bonuses), Some(Payout::THREE_TO_TWO));
+
+        let five_cards = [
+            Value::Two,
+            Value::Three,
+            Value::Four,
+            Value::Five,
+            Value::Seven,
+        ];
+        assert_eq!(
+            bonus_payout(&hand(&five_cards), &bonuses),
+            Some(Payout::THREE_TO_TWO)
+        );
+        assert_eq!(bonus_payout(&hand(&five_cards), &[]), None);
+        assert_eq!(
+            bonus_payout(&hand(&[Value::King, Value::Ace]), &bonuses),
+            None
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use crate::betting::BetSystem;
+use crate::bonus::Bonus21;
+use crate::config::GameConfig;
+use crate::counting::{CountingMethod, DeckEstimation};
+use crate::custom_chart::CustomChart;
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::events::GameObserver;
+use crate::game::Game;
+use crate::money::Money;
+use crate::rules::Rules;
+use crate::shuffle::ShuffleMethod;
+use crate::types::{DoubleRule, Payout, Player};
+use crate::variants::Variant;
+
+/// Sets up a game one setting at a time, starting from the default rules and table.
+pub struct GameBuilder {
+    conf: GameConfig,
+    base_bet: Option<Money>,
+    buy_in: Money,
+    observers: Vec<Box<dyn GameObserver>>,
+}
+
+impl GameBuilder {
+    pub fn new() -> Self {
+        Self {
+            conf: GameConfig::default(),
+            base_bet: None,
+            buy_in: Money::from_units(1000),
+            observers: vec![],
+        }
+    }
+
+    /// Replaces all rules at once, e.g. with a published rule set.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.conf.rules = rules;
+        self
+    }
+
+    pub fn decks(mut self, decks: u32) -> Self {
+        self.conf.rules.deck_count = decks;
+        self
+    }
+
+    /// The number of cards placed behind the cut card.
+    pub fn cut_card(mut self, cards_behind: u32) -> Self {
+        self.conf.rules.cut_card = cards_behind;
+        self
+    }
+
+    /// The fraction of the shoe dealt before the cut card comes out, overrides the cut card.
+    pub fn penetration(mut self, penetration: f32) -> Self {
+        self.conf.rules.penetration = Some(penetration);
+        self
+    }
+
+    pub fn csm(mut self, csm: bool) -> Self {
+        self.conf.rules.csm = csm;
+        self
+    }
+
+    /// Whether the dealer hits soft 17.
+    pub fn h17(mut self, hit_soft_17: bool) -> Self {
+        self.conf.rules.hit_soft_17 = hit_soft_17;
+        self
+    }
+
+    pub fn dealer_peek(mut self, peek: bool) -> Self {
+        self.conf.rules.dealer_peek = peek;
+        self
+    }
+
+    pub fn original_bets_only(mut self, original_bets_only: bool) -> Self {
+        self.conf.rules.original_bets_only = original_bets_only;
+        self
+    }
+
+    pub fn blackjack_payout(mut self, payout: Payout) -> Self {
+        self.conf.rules.blackjack_payout = payout;
+        self
+    }
+
+    pub fn max_splits(mut self, max_splits: u32) -> Self {
+        self.conf.rules.max_splits = max_splits;
+        self
+    }
+
+    pub fn resplit_aces(mut self, resplit_aces: bool) -> Self {
+        self.conf.rules.resplit_aces = resplit_aces;
+        self
+    }
+
+    pub fn hit_split_aces(mut self, hit_split_aces: bool) -> Self {
+        self.conf.rules.hit_split_aces = hit_split_aces;
+        self
+    }
+
+    pub fn double_on(mut self, double_on: DoubleRule) -> Self {
+        self.conf.rules.double_on = double_on;
+        self
+    }
+
+    /// Whether doubling down is allowed after splitting.
+    pub fn das(mut self, double_after_split: bool) -> Self {
+        self.conf.rules.double_after_split = double_after_split;
+        self
+    }
+
+    pub fn surrender(mut self, surrender: bool) -> Self {
+        self.conf.rules.surrender = surrender;
+        self
+    }
+
+    pub fn charlie(mut self, cards: usize) -> Self {
+        self.conf.rules.charlie = Some(cards);
+        self
+    }
+
+    /// Plays a variant, with the bonuses it pays by default.
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.conf.rules.variant = variant;
+        self.conf.rules.bonuses = variant.default_bonuses();
+        self
+    }
+
+    pub fn bonuses(mut self, bonuses: Vec<Bonus21>) -> Self {
+        self.conf.rules.bonuses = bonuses;
+        self
+    }
+
+    /// The number of hands played each round.
+    pub fn hands(mut self, hands: usize) -> Self {
+        self.conf.hands = hands;
+        self
+    }
+
+    pub fn table_min(mut self, min: Money) -> Self {
+        self.conf.table_min = min;
+        self
+    }
+
+    pub fn table_max(mut self, max: Money) -> Self {
+        self.conf.table_max = Some(max);
+        self
+    }
+
+    pub fn bet_unit(mut self, unit: Money) -> Self {
+        self.conf.bet_unit = unit;
+        self
+    }
+
+    /// The base bet of a betting system, defaults to the table minimum.
+    pub fn base_bet(mut self, bet: Money) -> Self {
+        self.base_bet = Some(bet);
+        self
+    }
+
+    /// Sizes the main bets with a betting system, whoever plays the hands.
+    pub fn bet_system(mut self, system: BetSystem) -> Self {
+        self.conf.bet_system = Some(system);
+        self
+    }
+
+    pub fn twenty_one_plus_three(mut self, offered: bool) -> Self {
+        self.conf.twenty_one_plus_three = offered;
+        self
+    }
+
+    pub fn bust_it(mut self, offered: bool) -> Self {
+        self.conf.bust_it = offered;
+        self
+    }
+
+    pub fn tips(mut self, tips: bool) -> Self {
+        self.conf.tips = tips;
+        self
+    }
+
+    pub fn shuffle(mut self, shuffle: ShuffleMethod) -> Self {
+        self.conf.shuffle = shuffle;
+        self
+    }
+
+    pub fn counting(mut self, counting: CountingMethod) -> Self {
+        self.conf.counting = counting;
+        self
+    }
+
+    pub fn ace_side_count(mut self, ace_side_count: bool) -> Self {
+        self.conf.ace_side_count = ace_side_count;
+        self
+    }
+
+    pub fn deck_estimation(mut self, deck_estimation: DeckEstimation) -> Self {
+        self.conf.deck_estimation = deck_estimation;
+        self
+    }
+
+    /// The strategy chart for [`Strategy::Chart`](crate::input::Strategy::Chart) to play.
+    pub fn chart(mut self, chart: CustomChart) -> Self {
+        self.conf.chart = Some(chart);
+        self
+    }
+
+    /// The amount of money the player starts with.
+    pub fn buy_in(mut self, amount: Money) -> Self {
+        self.buy_in = amount;
+        self
+    }
+
+    pub fn observer(mut self, observer: Box<dyn GameObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    /// The settings as configured so far, with the base bet filled in.
+    pub fn config(mut self) -> Result<GameConfig, Error> {
+        self.conf.base_bet = self.base_bet.unwrap_or(self.conf.table_min);
+        self.conf.validate()?;
+        Ok(self.conf)
+    }
+
+    /// Validates the settings and creates an engine waiting for the bets of the first round.
+    pub fn build(mut self) -> Result<GameEngine, Error> {
+        if self.buy_in < Money::ZERO {
+            return Err(Error::Config("the buy-in cannot be negative".to_string()));
+        }
+        let observers = std::mem::take(&mut self.observers);
+        let player = Player::new(self.buy_in);
+        GameEngine::with_observers(self.config()?, player, observers)
+    }
+
+    /// Builds a headless game playing pushed actions, see [`Game`].
+    pub fn build_game(self) -> Result<Game, Error> {
+        Ok(Game::with_engine(self.build()?))
+    }
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::builder::GameBuilder;
+    use crate::error::Error;
+    use crate::money::Money;
+    use crate::variants::Variant;
+
+    #[test]
+    fn test_build() {
+        let engine = GameBuilder::new()
+            .decks(2)
+            .penetration(0.5)
+            .h17(true)
+            .table_min(Money::from_units(10))
+            .buy_in(Money::from_units(2500))
+            .build()
+            .unwrap();
+        assert_eq!(engine.conf.rules.deck_count, 2);
+        assert!(engine.conf.rules.hit_soft_17);
+        assert_eq!(engine.conf.base_bet, Money::from_units(10));
+        assert_eq!(engine.player.money, Money::from_units(2500));
+        // Half of the 104 cards are behind the cut card, one more is burned
+        assert_eq!(engine.shoe.cut_card, 52);
+        assert_eq!(engine.shoe.num_cards(), 103);
+    }
+
+    #[test]
+    fn test_inconsistent_settings() {
+        let invalid = |builder: GameBuilder| matches!(builder.build(), Err(Error::Config(_)));
+        assert!(invalid(GameBuilder::new().decks(0)));
+        assert!(invalid(GameBuilder::new().decks(1).cut_card(60)));
+        assert!(invalid(
+            GameBuilder::new().variant(Variant::Spanish21).decks(1)
+        ));
+        assert!(invalid(GameBuilder::new().penetration(1.5)));
+        assert!(invalid(
+            GameBuilder::new()
+                .table_min(Money::from_units(25))
+                .table_max(Money::from_units(10))
+        ));
+        assert!(invalid(GameBuilder::new().hands(5)));
+        assert!(invalid(GameBuilder::new().charlie(2)));
+        assert!(invalid(GameBuilder::new().buy_in(Money::from_units(-1))));
+
+        // A continuous shuffling machine has no cut card
+        assert!(GameBuilder::new()
+            .decks(1)
+            .cut_card(60)
+            .csm(true)
+            .build()
+            .is_ok());
+    }
+}
@@ -0,0 +1,466 @@
+use crate::error::Error;
+use crate::rules::Rules;
+use crate::types::{Move, Shoe, Value, TWENTY_ONE};
+use crate::variants::Variant;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The ranks by their lowest value, aces at 1 and every ten valued card at 10.
+pub(crate) const RANKS: usize = 10;
+
+/// The dealer's final totals from 17 to 21, then a bust.
+pub(crate) const DEALER_RESULTS: usize = 6;
+
+/// The highest hard total that is tracked before it is counted as a bust.
+pub(crate) const MAX_TOTAL: usize = 31;
+
+/// The move a chart recommends, with what to do instead where the first choice isn't allowed,
+/// e.g. doubling after hitting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChartMove {
+    Hit,
+    Stand,
+    DoubleOrHit,
+    DoubleOrStand,
+    Split,
+    SurrenderOrHit,
+    SurrenderOrStand,
+    SurrenderOrSplit,
+}
+
+/// The familiar shorthand of printed charts, e.g. "Ds" for double, otherwise stand.
+impl Display for ChartMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ChartMove::Hit => "H",
+                ChartMove::Stand => "S",
+                ChartMove::DoubleOrHit => "D",
+                ChartMove::DoubleOrStand => "Ds",
+                ChartMove::Split => "P",
+                ChartMove::SurrenderOrHit => "Rh",
+                ChartMove::SurrenderOrStand => "Rs",
+                ChartMove::SurrenderOrSplit => "Rp",
+            }
+        )
+    }
+}
+
+impl FromStr for ChartMove {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "h" => Ok(ChartMove::Hit),
+            "s" => Ok(ChartMove::Stand),
+            "d" | "dh" => Ok(ChartMove::DoubleOrHit),
+            "ds" => Ok(ChartMove::DoubleOrStand),
+            "p" => Ok(ChartMove::Split),
+            "rh" => Ok(ChartMove::SurrenderOrHit),
+            "rs" => Ok(ChartMove::SurrenderOrStand),
+            "rp" => Ok(ChartMove::SurrenderOrSplit),
+            _ => Err(format!(
+                "expected one of 'H', 'S', 'D', 'Ds', 'P', 'Rh', 'Rs' or 'Rp', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+impl ChartMove {
+    /// The moves to make in order of preference, the first one that is allowed.
+    pub fn moves(&self) -> &'static [Move] {
+        match self {
+            ChartMove::Hit => &[Move::Hit],
+            ChartMove::Stand => &[Move::Stand],
+            ChartMove::DoubleOrHit => &[Move::Double, Move::Hit],
+            ChartMove::DoubleOrStand => &[Move::Double, Move::Stand],
+            ChartMove::Split => &[Move::Split],
+            ChartMove::SurrenderOrHit => &[Move::Surrender, Move::Hit],
+            ChartMove::SurrenderOrStand => &[Move::Surrender, Move::Stand],
+            ChartMove::SurrenderOrSplit => &[Move::Surrender, Move::Split],
+        }
+    }
+}
+
+/// The best move for a hand against one upcard and its expected result in initial bets.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ChartCell {
+    pub play: ChartMove,
+    pub ev: f64,
+}
+
+/// A hand of the chart against every upcard, from 2 to ace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartRow {
+    pub label: String,
+    pub cells: Vec<ChartCell>,
+}
+
+/// The best play of every two card hand against every upcard.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyChart {
+    /// Hard 5 to 19.
+    pub hard: Vec<ChartRow>,
+    /// An ace with 2 to 9.
+    pub soft: Vec<ChartRow>,
+    /// The pairs of 2s to aces.
+    pub pairs: Vec<ChartRow>,
+}
+
+impl StrategyChart {
+    /// The labels of the upcards, in the order of the cells of every row.
+    pub const UPCARDS: [&'static str; RANKS] = ["2", "3", "4", "5", "6", "7", "8", "9", "10", "A"];
+}
+
+/// The index of the upcard among the cells of a row, from 2 to ace.
+pub(crate) fn upcard_column(upcard: &Value) -> usize {
+    match upcard {
+        Value::Ace => RANKS - 1,
+        value => value.value() as usize - 2,
+    }
+}
+
+/// The chance of drawing each rank, indexed by its lowest value.
+pub(crate) type Odds = [f64; RANKS + 1];
+
+/// The value of a hand from its hard total and whether it holds an ace.
+pub(crate) fn hand_value(hard: usize, ace: bool) -> usize {
+    if ace && hard + 10 <= TWENTY_ONE as usize {
+        hard + 10
+    } else {
+        hard
+    }
+}
+
+/// The expected results of the moves against one upcard, drawing every card with the odds of
+/// a full shoe.
+struct UpcardSolver<'a> {
+    rules: &'a Rules,
+    odds: Odds,
+    /// The chance the dealer has a blackjack behind the upcard.
+    blackjack: f64,
+    /// The dealer's final totals, given there is no blackjack.
+    dealer: [f64; DEALER_RESULTS],
+    hits: Vec<[Option<f64>; 2]>,
+}
+
+impl<'a> UpcardSolver<'a> {
+    fn new(rules: &'a Rules, odds: Odds, upcard: usize) -> Self {
+        let blackjack_card = match upcard {
+            1 => Some(10),
+            10 => Some(1),
+            _ => None,
+        };
+        let blackjack = blackjack_card.map_or(0.0, |card| odds[card]);
+        let mut finals = vec![[None; 2]; MAX_TOTAL + 1];
+        let mut dealer = [0.0; DEALER_RESULTS];
+        for hole in 1..=RANKS {
+            if Some(hole) == blackjack_card {
+                continue;
+            }
+            let chance = odds[hole] / (1.0 - blackjack);
+            let results = dealer_results(
+                rules,
+                &odds,
+                upcard + hole,
+                upcard == 1 || hole == 1,
+                &mut finals,
+            );
+            for (total, result) in dealer.iter_mut().zip(results) {
+                *total += chance * result;
+            }
+        }
+
+        Self {
+            rules,
+            odds,
+            blackjack,
+            dealer,
+            hits: vec![[None; 2]; MAX_TOTAL + 1],
+        }
+    }
+
+    /// The expected result of standing on `value`, given the dealer has no blackjack.
+    fn stand(&self, value: usize) -> f64 {
+        if value > TWENTY_ONE as usize {
+            return -1.0;
+        }
+        let bust = self.dealer[DEALER_RESULTS - 1];
+        let mut ev = bust;
+        for (total, chance) in (17..).zip(&self.dealer[..DEALER_RESULTS - 1]) {
+            if value > total {
+                ev += chance;
+            } else if value < total {
+                ev -= chance;
+            }
+        }
+        ev
+    }
+
+    /// The expected result of playing on the hand as well as possible by hitting or standing.
+    fn best_hit_or_stand(&mut self, hard: usize, ace: bool) -> f64 {
+        if hard > TWENTY_ONE as usize {
+            return -1.0;
+        }
+        if let Some(ev) = self.hits[hard][ace as usize] {
+            return ev;
+        }
+        let ev = self.stand(hand_value(hard, ace)).max(self.hit(hard, ace));
+        self.hits[hard][ace as usize] = Some(ev);
+        ev
+    }
+
+    fn hit(&mut self, hard: usize, ace: bool) -> f64 {
+        (1..=RANKS)
+            .map(|card| self.odds[card] * self.best_hit_or_stand(hard + card, ace || card == 1))
+            .sum()
+    }
+
+    fn double(&self, hard: usize, ace: bool) -> f64 {
+        (1..=RANKS)
+            .map(|card| {
+                let hard = (hard + card).min(MAX_TOTAL);
+                2.0 * self.odds[card] * self.stand(hand_value(hard, ace || card == 1))
+            })
+            .sum()
+    }
+
+    fn can_double(&self, hard: usize, ace: bool) -> bool {
+        self.rules.double_on.allows(hand_value(hard, ace) as u32)
+    }
+
+    /// The expected result of one of the hands after splitting a pair of `card`s, which isn't
+    /// split again.
+    fn split_hand(&mut self, card: usize) -> f64 {
+        (1..=RANKS)
+            .map(|drawn| {
+                let (hard, ace) = (card + drawn, card == 1 || drawn == 1);
+                let ev = if card == 1 && !self.rules.hit_split_aces {
+                    self.stand(hand_value(hard, ace))
+                } else if self.rules.double_after_split && self.can_double(hard, ace) {
+                    self.best_hit_or_stand(hard, ace)
+                        .max(self.double(hard, ace))
+                } else {
+                    self.best_hit_or_stand(hard, ace)
+                };
+                self.odds[drawn] * ev
+            })
+            .sum()
+    }
+
+    /// The expected result of a move overall, from its result without a dealer blackjack. Unless
+    /// the dealer peeks, the `stake` is lost to a blackjack found after the player acted.
+    fn overall(&self, ev: f64, stake: f64) -> f64 {
+        if self.rules.dealer_peek {
+            ev
+        } else {
+            let stake = if self.rules.original_bets_only {
+                1.0
+            } else {
+                stake
+            };
+            (1.0 - self.blackjack) * ev - self.blackjack * stake
+        }
+    }
+
+    /// The best move for the two card hand, `pair` being the rank of a pair.
+    fn cell(&mut self, hard: usize, ace: bool, pair: Option<usize>) -> ChartCell {
+        let stand = self.overall(self.stand(hand_value(hard, ace)), 1.0);
+        let hit = self.hit(hard, ace);
+        let hit = self.overall(hit, 1.0);
+        let (mut play, mut ev) = if hit > stand {
+            (ChartMove::Hit, hit)
+        } else {
+            (ChartMove::Stand, stand)
+        };
+        let fallback = play;
+
+        if let Some(card) = pair.filter(|_| self.rules.max_splits > 0) {
+            let split = 2.0 * self.split_hand(card);
+            let split = self.overall(split, 2.0);
+            if split > ev {
+                (play, ev) = (ChartMove::Split, split);
+            }
+        }
+        if self.can_double(hard, ace) {
+            let double = self.overall(self.double(hard, ace), 2.0);
+            if double > ev {
+                play = match fallback {
+                    ChartMove::Stand => ChartMove::DoubleOrStand,
+                    _ => ChartMove::DoubleOrHit,
+                };
+                ev = double;
+            }
+        }
+        if self.rules.surrender {
+            let surrender = self.overall(-0.5, 1.0);
+            if surrender > ev {
+                play = match play {
+                    ChartMove::Split => ChartMove::SurrenderOrSplit,
+                    ChartMove::Stand => ChartMove::SurrenderOrStand,
+                    _ => ChartMove::SurrenderOrHit,
+                };
+                ev = surrender;
+            }
+        }
+        ChartCell { play, ev }
+    }
+}
+
+/// The chances of the dealer's final totals from the hand, the dealer drawing to the rules.
+pub(crate) fn dealer_results(
+    rules: &Rules,
+    odds: &Odds,
+    hard: usize,
+    ace: bool,
+    finals: &mut Vec<[Option<[f64; DEALER_RESULTS]>; 2]>,
+) -> [f64; DEALER_RESULTS] {
+    let value = hand_value(hard, ace);
+    let mut results = [0.0; DEALER_RESULTS];
+    if value > TWENTY_ONE as usize {
+        results[DEALER_RESULTS - 1] = 1.0;
+        return results;
+    }
+    let soft = value != hard;
+    let hits = value < 17 || (rules.hit_soft_17 && value == 17 && soft);
+    if !hits {
+        results[value - 17] = 1.0;
+        return results;
+    }
+    if let Some(results) = finals[hard][ace as usize] {
+        return results;
+    }
+    for card in 1..=RANKS {
+        let next = dealer_results(
+            rules,
+            odds,
+            (hard + card).min(MAX_TOTAL),
+            ace || card == 1,
+            finals,
+        );
+        for (total, result) in results.iter_mut().zip(next) {
+            *total += odds[card] * result;
+        }
+    }
+    finals[hard][ace as usize] = Some(results);
+    results
+}
+
+/// The chance of drawing each rank from a full shoe.
+pub(crate) fn shoe_odds(rules: &Rules) -> Result<Odds, Error> {
+    let shoe = Shoe::new(rules.deck_count, rules.variant)?;
+    let mut odds = [0.0; RANKS + 1];
+    for (value, count) in shoe.remaining_by_rank() {
+        let rank = match value {
+            Value::Ace => 1,
+            value => value.value() as usize,
+        };
+        odds[rank] += count as f64 / shoe.num_cards() as f64;
+    }
+    Ok(odds)
+}
+
+/// Works out the best play of every two card hand against every upcard for the rules, from the
+/// exact expected result of each move when every card is drawn with the odds of a full shoe.
+///
+/// The odds don't change as the cards are dealt, which is exact for an infinite shoe and a close
+/// match for a real one. Pairs are split once, without the value of splitting again, and the
+/// chart is for the standard game without a Charlie.
+pub fn generate_chart(rules: &Rules) -> Result<StrategyChart, Error> {
+    rules.validate()?;
+    if rules.variant != Variant::Standard || rules.charlie.is_some() {
+        return Err(Error::Config(
+            "strategy charts can only be worked out for standard blackjack without a Charlie"
+                .to_string(),
+        ));
+    }
+    let odds = shoe_odds(rules)?;
+    // The upcards from 2 to ace
+    let mut solvers = (2..=RANKS)
+        .chain([1])
+        .map(|upcard| UpcardSolver::new(rules, odds, upcard))
+        .collect::<Vec<UpcardSolver>>();
+    let mut row = |label: String, hard: usize, ace: bool, pair: Option<usize>| ChartRow {
+        label,
+        cells: solvers
+            .iter_mut()
+            .map(|solver| solver.cell(hard, ace, pair))
+            .collect(),
+    };
+
+    let hard = (5..=19)
+        .map(|total| row(format!("{}", total), total, false, None))
+        .collect();
+    let soft = (2..=9)
+        .map(|card| row(format!("A,{}", card), card + 1, true, None))
+        .collect();
+    let pairs = (2..=RANKS)
+        .chain([1])
+        .map(|card| {
+            let label = match card {
+                1 => "A,A".to_string(),
+                card => format!("{},{}", card, card),
+            };
+            row(label, card * 2, card == 1, Some(card))
+        })
+        .collect();
+    Ok(StrategyChart { hard, soft, pairs })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chart::{generate_chart, ChartMove, ChartRow, StrategyChart};
+    use crate::rules::Rules;
+    use crate::types::DoubleRule;
+
+    fn play(rows: &[ChartRow], label: &str, upcard: &str) -> ChartMove {
+        let row = rows.iter().find(|row| row.label == label).unwrap();
+        let at = StrategyChart::UPCARDS
+            .iter()
+            .position(|u| *u == upcard)
+            .unwrap();
+        row.cells[at].play
+    }
+
+    #[test]
+    fn test_generate_chart() {
+        let chart = generate_chart(&Rules::default()).unwrap();
+        assert_eq!(chart.hard.len(), 15);
+        assert_eq!(play(&chart.hard, "11", "6"), ChartMove::DoubleOrHit);
+        assert_eq!(play(&chart.hard, "12", "2"), ChartMove::Hit);
+        assert_eq!(play(&chart.hard, "12", "4"), ChartMove::Stand);
+        assert_eq!(play(&chart.hard, "16", "10"), ChartMove::SurrenderOrHit);
+        assert_eq!(play(&chart.hard, "17", "7"), ChartMove::Stand);
+        assert_eq!(play(&chart.soft, "A,7", "3"), ChartMove::DoubleOrStand);
+        assert_eq!(play(&chart.soft, "A,7", "9"), ChartMove::Hit);
+        assert_eq!(play(&chart.pairs, "A,A", "10"), ChartMove::Split);
+        assert_eq!(play(&chart.pairs, "8,8", "10"), ChartMove::Split);
+        assert_eq!(play(&chart.pairs, "10,10", "6"), ChartMove::Stand);
+        assert_eq!(play(&chart.pairs, "5,5", "9"), ChartMove::DoubleOrHit);
+        // Standing on 20 against a 10 wins more than it loses
+        let row = chart.pairs.iter().find(|row| row.label == "10,10").unwrap();
+        assert!(row.cells[8].ev > 0.0);
+
+        // The dealer hitting soft 17 makes doubling soft 19 against a 6 worth it
+        let h17 = Rules {
+            hit_soft_17: true,
+            ..Rules::default()
+        };
+        let chart = generate_chart(&h17).unwrap();
+        assert_eq!(play(&chart.soft, "A,8", "6"), ChartMove::DoubleOrStand);
+
+        // Without surrender or doubling 11, the fallbacks are played
+        let strict = Rules {
+            surrender: false,
+            double_on: DoubleRule::TenToEleven,
+            ..Rules::default()
+        };
+        let chart = generate_chart(&strict).unwrap();
+        assert_eq!(play(&chart.hard, "16", "10"), ChartMove::Hit);
+        assert_eq!(play(&chart.soft, "A,7", "3"), ChartMove::Stand);
+    }
+}
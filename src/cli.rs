@@ -0,0 +1,1401 @@
+use crate::render::{seat_label, Console, OutputFormat};
+use blackjack::advisor::EdgeModel;
+use blackjack::chart::generate_chart;
+use blackjack::config::{GameConfig, MAX_HANDS};
+use blackjack::custom_chart::CustomChart;
+use blackjack::deviations::{HiLoBot, INSURANCE_INDEX};
+use blackjack::drills::{CountDrill, Drill, DrillHistory, DrillSession, Situation, StrategyDrill};
+use blackjack::engine::GameEngine;
+use blackjack::error::Error;
+use blackjack::input::{BasicStrategy, PlayerInput, PlayerKind, Strategy};
+use blackjack::mistakes::MistakeTracker;
+use blackjack::money::Money;
+use blackjack::outcomes::StartingHand;
+use blackjack::rules::HandOptions;
+use blackjack::side_bets::SideBet;
+use blackjack::table::SeatPosition;
+use blackjack::types::{Card, ChipStack, Move, Player, PlayerHand, Shoe};
+use blackjack::variants::Terminology;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Plays shoe after shoe until the session ends, with the decisions asked for on stdin or made by
+/// the bot of `kind` for the player to watch.
+///
+/// Several `players` take turns at the same terminal from the spots they sit at, each with their
+/// own bankroll, and the session ends once the last of them leaves the table.
+///
+/// With a `script` the prompts are answered from the file instead of stdin, and with a
+/// `shuffle_seed` the same cards are dealt every time, so a session can be replayed exactly.
+///
+/// A round that runs out of cards even with the discards shuffled back is void and play continues
+/// with a fresh shoe. Closed input voids the round in progress and leaves the table. Other errors
+/// end the session.
+pub fn run(
+    conf: GameConfig,
+    players: Vec<(SeatPosition, Player)>,
+    kind: PlayerKind,
+    output: OutputFormat,
+    script: Option<&Path>,
+    shuffle_seed: Option<u64>,
+) -> Result<(), Error> {
+    let out = &console(&conf, output, script)?;
+    play_session(out, conf, players, kind, shuffle_seed)
+}
+
+/// Plays the session of [`run`] on `out`.
+fn play_session(
+    out: &Console,
+    conf: GameConfig,
+    players: Vec<(SeatPosition, Player)>,
+    kind: PlayerKind,
+    shuffle_seed: Option<u64>,
+) -> Result<(), Error> {
+    let mistakes = (conf.track_mistakes && kind == PlayerKind::Human)
+        .then(|| Rc::new(RefCell::new(MistakeTracker::default())));
+    let mut input: Box<dyn PlayerInput> = match kind {
+        PlayerKind::Human => Box::new(Interactive {
+            out: out.clone(),
+            mistakes: mistakes.clone(),
+        }),
+        PlayerKind::Bot(strategy) => {
+            out.info(&format!("Watching the {} bot play", strategy));
+            Box::new(Watched {
+                out: out.clone(),
+                strategy,
+            })
+        }
+    };
+    let shoe = Shoe::new(conf.rules.deck_count, conf.rules.variant)?;
+    let shuffler = shuffle_seed.map(|seed| conf.shuffle.seeded(seed));
+    let mut engine = GameEngine::seated_at(conf, shoe, players, vec![Box::new(out.clone())])?;
+    if let Some(shuffler) = shuffler {
+        engine.set_shuffler(shuffler);
+    }
+    engine.shuffle();
+    let mut at_table = vec![true; engine.seats()];
+    loop {
+        let end = match play_shoe(out, &mut engine, input.as_mut(), &mut at_table) {
+            Ok(end) => end,
+            Err(Error::OutOfCards) => {
+                out.info("The shoe ran out of cards, the round is void and all bets are returned");
+                engine.void_round();
+                engine.shuffle();
+                continue;
+            }
+            Err(Error::InputClosed) => {
+                engine.void_round();
+                ShoeEnd::Left
+            }
+            Err(e) => return Err(e),
+        };
+        if let Some(mistakes) = &mistakes {
+            print_mistakes(out, &engine, &mistakes.borrow());
+            mistakes.borrow_mut().reset();
+        }
+        match end {
+            ShoeEnd::CutCard => {
+                print_session_summaries(out, &engine);
+                out.info("Shoe over, reshuffling\n");
+                engine.shuffle();
+            }
+            ShoeEnd::Left => {
+                out.info("Leaving the table");
+                print_session_summaries(out, &engine);
+                return Ok(());
+            }
+            ShoeEnd::GameOver => {
+                out.info("GAME OVER");
+                return Ok(());
+            }
+            ShoeEnd::StopLimit(reason) => {
+                out.info(&format!("{}, ending the session", reason));
+                print_session_summaries(out, &engine);
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Why play at the current shoe stopped.
+enum ShoeEnd {
+    /// The cut card came out, the shoe is reshuffled and play continues.
+    CutCard,
+    /// The player left the table.
+    Left,
+    /// The player went broke and didn't rebuy.
+    GameOver,
+    /// The session result passed the stop-win or stop-loss limit.
+    StopLimit(String),
+}
+
+/// The player at the terminal, every decision is asked for on stdin.
+struct Interactive {
+    out: Console,
+    /// Where the moves are compared to basic strategy when mistakes are tracked.
+    mistakes: Option<Rc<RefCell<MistakeTracker>>>,
+}
+
+impl PlayerInput for Interactive {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        choose_num_hands(&self.out, &engine.player, &engine.conf)
+    }
+
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error> {
+        place_bet(&self.out, &engine.player, hand, num_hands, &engine.conf)
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        place_side_bet(
+            &self.out,
+            &engine.player,
+            &side_bet.to_string(),
+            engine.conf.bet_unit,
+        )
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        if engine.conf.hints {
+            hint_insurance(&self.out, engine, "even money");
+        }
+        take_even_money(&self.out, &engine.player.hands[hand])
+    }
+
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        if engine.conf.hints {
+            hint_insurance(&self.out, engine, "insurance");
+        }
+        place_insurance(&self.out, &engine.player)
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        let action = choose_move(&self.out, engine, hand, options)?;
+        if let Some(mistakes) = &self.mistakes {
+            mistakes.borrow_mut().record(engine, hand, options, action);
+        }
+        Ok(action)
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        choose_buy_amount(&self.out, &engine.player, engine.player.hands[hand].bet)
+    }
+
+    fn rescue(&mut self, _engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        offer_double_rescue(&self.out)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        offer_tip(&self.out, &engine.player)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        offer_rebuy(&self.out, &engine.player, &engine.conf)
+    }
+}
+
+/// A bot at the table, its bets and moves are shown as it makes them.
+struct Watched {
+    out: Console,
+    strategy: Strategy,
+}
+
+impl PlayerInput for Watched {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        self.strategy.num_hands(engine)
+    }
+
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error> {
+        let bet = self.strategy.bet(engine, hand, num_hands)?;
+        self.out
+            .info(&format!("Bet {} ({})", bet, ChipStack::from_amount(bet)));
+        Ok(bet)
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        self.strategy.side_bet(engine, side_bet)
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        self.strategy.even_money(engine, hand)
+    }
+
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        self.strategy.insurance(engine)
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        let action = self.strategy.action(engine, hand, options)?;
+        self.out.info(&format!(
+            "The {} bot plays {}",
+            self.strategy,
+            move_name(engine, action)
+        ));
+        Ok(action)
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        self.strategy.buy_amount(engine, hand)
+    }
+
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        self.strategy.rescue(engine, hand)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        self.strategy.tip(engine)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        self.strategy.rebuy(engine)
+    }
+}
+
+/// Plays rounds until the cut card comes out or every player has left the table, `at_table`
+/// keeps track of the seats still playing.
+fn play_shoe(
+    out: &Console,
+    engine: &mut GameEngine,
+    input: &mut dyn PlayerInput,
+    at_table: &mut [bool],
+) -> Result<ShoeEnd, Error> {
+    loop {
+        let mut end = None;
+        for seat in seats_at_table(at_table) {
+            if let Some(seat_end) = check_bankroll(out, engine, input, seat)? {
+                end = Some(leave_table(out, engine, at_table, seat, seat_end));
+            }
+        }
+        if !at_table.contains(&true) {
+            return Ok(end.unwrap_or(ShoeEnd::Left));
+        }
+
+        out.heading("============ ROUND BEGIN ============");
+        for seat in seats_at_table(at_table) {
+            engine.take_seat(seat)?;
+            if !place_bets(out, engine, input)? {
+                end = Some(leave_table(out, engine, at_table, seat, ShoeEnd::Left));
+            }
+        }
+        if !at_table.contains(&true) {
+            return Ok(end.unwrap_or(ShoeEnd::Left));
+        }
+
+        engine.play_round(input)?;
+        if engine.seats() > 1 {
+            print_bankrolls(out, engine);
+        }
+
+        // The round in progress is always finished, even if the cut card came out during it
+        if engine.shoe.cut_card_reached {
+            out.info(&format!(
+                "Penetration reached {:.1}%",
+                engine.shoe.penetration() * 100.0
+            ));
+            return Ok(ShoeEnd::CutCard);
+        }
+    }
+}
+
+/// The seats whose players haven't left the table.
+fn seats_at_table(at_table: &[bool]) -> Vec<usize> {
+    (0..at_table.len()).filter(|&seat| at_table[seat]).collect()
+}
+
+/// Checks the stop limits and the bankroll of the player at `seat` before a round, offering a
+/// rebuy to a player below the table minimum. Returns why the player leaves, if so.
+fn check_bankroll(
+    out: &Console,
+    engine: &mut GameEngine,
+    input: &mut dyn PlayerInput,
+    seat: usize,
+) -> Result<Option<ShoeEnd>, Error> {
+    let player = engine.player_at(seat);
+    if let Some(reason) = engine.conf.stop_limit_reached(&player.stats) {
+        return Ok(Some(ShoeEnd::StopLimit(reason)));
+    }
+    if engine
+        .conf
+        .fit_bet(engine.conf.table_min, player.money)
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    engine.take_seat(seat)?;
+    out.info(&format!(
+        "Your bankroll of {} is below the table minimum of {}",
+        engine.player.money, engine.conf.table_min
+    ));
+    print_session_summary(out, &engine.player);
+    match input.rebuy(engine)? {
+        Some(amount) => {
+            engine.rebuy(amount);
+            out.info(&format!(
+                "Rebought for {}, bankroll: {}\n",
+                amount, engine.player.money
+            ));
+            Ok(None)
+        }
+        None => Ok(Some(ShoeEnd::GameOver)),
+    }
+}
+
+/// Takes the player at `seat` off the table, the others play on without them.
+fn leave_table(
+    out: &Console,
+    engine: &GameEngine,
+    at_table: &mut [bool],
+    seat: usize,
+    end: ShoeEnd,
+) -> ShoeEnd {
+    at_table[seat] = false;
+    if engine.seats() > 1 {
+        let name = seat_name(engine, seat);
+        match &end {
+            ShoeEnd::StopLimit(reason) => {
+                out.info(&format!("{}, {} leaves the table", reason, name))
+            }
+            _ => out.info(&format!("{} leaves the table", name)),
+        }
+    }
+    end
+}
+
+fn seat_name(engine: &GameEngine, seat: usize) -> String {
+    seat_label(
+        engine.player_at(seat).name.as_deref(),
+        engine.position(seat),
+    )
+}
+
+fn print_bankrolls(out: &Console, engine: &GameEngine) {
+    let bankrolls: Vec<String> = (0..engine.seats())
+        .map(|seat| {
+            format!(
+                "{} {}",
+                seat_name(engine, seat),
+                engine.player_at(seat).money
+            )
+        })
+        .collect();
+    out.info(&format!("Bankrolls: {}\n", bankrolls.join(", ")));
+}
+
+fn print_session_summary(out: &Console, player: &Player) {
+    match &player.name {
+        Some(name) => out.heading(&format!(
+            "============ SESSION SUMMARY: {} ============",
+            name
+        )),
+        None => out.heading("============ SESSION SUMMARY ============"),
+    }
+    out.info(&format!("{}\n", player.stats));
+}
+
+/// The session summary of every player at the table.
+fn print_session_summaries(out: &Console, engine: &GameEngine) {
+    for seat in 0..engine.seats() {
+        print_session_summary(out, engine.player_at(seat));
+    }
+}
+
+/// The moves of the shoe compared to basic strategy, with the mistakes made most often.
+fn print_mistakes(out: &Console, engine: &GameEngine, mistakes: &MistakeTracker) {
+    out.heading("============ STRATEGY ACCURACY ============");
+    out.info(&format!(
+        "{} of {} moves by basic strategy ({:.1}%), the mistakes cost {:.2} bets in expected value",
+        mistakes.decisions - mistakes.mistakes,
+        mistakes.decisions,
+        mistakes.accuracy() * 100.0,
+        mistakes.cost
+    ));
+    let common = mistakes.most_common(5);
+    if !common.is_empty() {
+        out.info("Most common mistakes:");
+    }
+    for (kind, count) in common {
+        out.info(&format!(
+            "  {}x {} against {}: {} instead of {} ({:.2} bets)",
+            count.times,
+            kind.hand,
+            kind.upcard,
+            move_name(engine, kind.played),
+            move_name(engine, kind.advised),
+            count.cost
+        ));
+    }
+    out.info("");
+}
+
+/// Asks a broke player for a rebuy, `None` means the player walks away.
+fn offer_rebuy(out: &Console, player: &Player, conf: &GameConfig) -> Result<Option<Money>, Error> {
+    let needed = conf.table_min - player.money;
+    loop {
+        let input = read_input(
+            out,
+            &format!("Rebuy? (at least {}, enter to leave the table)", needed),
+        )?;
+        if input.is_empty() || input == "q" {
+            return Ok(None);
+        }
+
+        match input.parse::<Money>() {
+            Ok(amount) if amount < needed => {
+                out.warn(&format!("A rebuy of at least {} is needed to play", needed))
+            }
+            Ok(amount) => return Ok(Some(amount)),
+            Err(_) => out.warn(&format!("Invalid amount '{}', please try again", input)),
+        }
+    }
+}
+
+/// Reads a line of input after showing the prompt, failing once the input is closed.
+fn read_input(out: &Console, prompt: &str) -> Result<String, Error> {
+    out.prompt(prompt);
+    out.read_line()
+}
+
+/// The console of `output`, answering the prompts from the `script` if there is one.
+pub fn console(
+    conf: &GameConfig,
+    output: OutputFormat,
+    script: Option<&Path>,
+) -> Result<Console, Error> {
+    let script = script.map(load_script).transpose()?;
+    Ok(Console::new(output.renderer(conf), script))
+}
+
+/// Reads the answers of a script, a line for every prompt in the order they are asked. Blank
+/// lines answer with enter and lines starting with `#` are comments.
+fn load_script(path: &Path) -> Result<VecDeque<String>, Error> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("cannot read {}: {}", path.display(), e)))?;
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .map(|line| line.trim().to_string())
+        .collect())
+}
+
+/// The number of rounds shown by the history command by default.
+const HISTORY_ROUNDS: usize = 5;
+
+fn print_history(out: &Console, player: &Player, rounds: usize) {
+    let history = player.ledger.last(rounds);
+    if history.is_empty() {
+        out.info("No rounds played yet");
+    }
+    for round in history {
+        out.info(&format!("{}", round));
+    }
+}
+
+/// Asks for the number of hands to play, `None` if the player leaves the table.
+fn choose_num_hands(
+    out: &Console,
+    player: &Player,
+    conf: &GameConfig,
+) -> Result<Option<usize>, Error> {
+    loop {
+        let input = read_input(
+            out,
+            &format!(
+                "Hands? [1-{}] (enter for {}, history [n] for past rounds, q to leave the table)",
+                MAX_HANDS, conf.hands
+            ),
+        )?;
+        if input == "q" {
+            return Ok(None);
+        }
+
+        if let Some(rounds) = input.strip_prefix("history") {
+            print_history(out, player, rounds.trim().parse().unwrap_or(HISTORY_ROUNDS));
+            continue;
+        }
+
+        let num_hands = if input.is_empty() {
+            Ok(conf.hands)
+        } else {
+            input.parse::<usize>()
+        };
+
+        match num_hands {
+            Ok(n) if !(1..=MAX_HANDS).contains(&n) => out.warn(&format!(
+                "Invalid number of hands '{}', please try again",
+                input
+            )),
+            Ok(n) if !player.can_afford(conf.table_min * n as u64) => out.warn(&format!(
+                "You cannot afford {} hands at the table minimum of {}",
+                n, conf.table_min
+            )),
+            Ok(n) => return Ok(Some(n)),
+            Err(_) => out.warn(&format!(
+                "Invalid number of hands '{}', please try again",
+                input
+            )),
+        }
+    }
+}
+
+/// Asks for the bet on hand number `hand` of `num_hands`, leaving enough of the bankroll to bet
+/// the table minimum on the hands after it.
+fn place_bet(
+    out: &Console,
+    player: &Player,
+    hand: usize,
+    num_hands: usize,
+    conf: &GameConfig,
+) -> Result<Money, Error> {
+    let prompt = if num_hands > 1 {
+        format!("Bet for hand {}? (money: {})", hand, player.money)
+    } else {
+        format!("Bet? (money: {})", player.money)
+    };
+    let hands_left = num_hands.saturating_sub(hand);
+    let reserve = conf.table_min * hands_left as u64;
+
+    loop {
+        let input = read_input(out, &prompt)?;
+        match input.parse::<Money>() {
+            Ok(bet) if bet < conf.table_min => out.warn(&format!(
+                "The bet must be at least the table minimum of {}",
+                conf.table_min
+            )),
+            Ok(bet) if conf.table_max.is_some_and(|max| bet > max) => out.warn(&format!(
+                "The bet cannot exceed the table maximum of {}",
+                conf.table_max.unwrap_or_default()
+            )),
+            Ok(bet) if !bet.is_multiple_of(conf.bet_unit) => {
+                out.warn(&format!("Bets must be in increments of {}", conf.bet_unit))
+            }
+            Ok(bet) if !player.can_afford(bet) => {
+                out.warn(&format!("You cannot afford a bet of {}", bet))
+            }
+            Ok(bet) if !player.can_afford(bet + reserve) => out.warn(&format!(
+                "A bet of {} leaves too little to bet the table minimum of {} on the hands left",
+                bet, conf.table_min
+            )),
+            Ok(bet) => {
+                out.info(&format!("Bet {} ({})", bet, ChipStack::from_amount(bet)));
+                return Ok(bet);
+            }
+            Err(_) => out.warn(&format!("Invalid bet '{}', please try again", input)),
+        }
+    }
+}
+
+/// Fits a bet sized automatically by `name` within the table limits and the bankroll, `None` if
+/// the bankroll can't cover the table minimum.
+fn auto_bet(
+    out: &Console,
+    player: &Player,
+    name: &str,
+    bet: Money,
+    conf: &GameConfig,
+) -> Option<Money> {
+    let bet = conf.fit_bet(bet, player.money)?;
+    out.info(&format!(
+        "{} bet {} ({})",
+        name,
+        bet,
+        ChipStack::from_amount(bet)
+    ));
+    Some(bet)
+}
+
+/// Places the main bets for every hand of the round, followed by any side bets.
+/// Returns false if the player leaves the table instead.
+fn place_bets(
+    out: &Console,
+    engine: &mut GameEngine,
+    input: &mut dyn PlayerInput,
+) -> Result<bool, Error> {
+    let true_count = engine.counter.true_count;
+    let conf = &engine.conf;
+    if let Some(model) = conf.advisor {
+        advise_bet(out, &engine.player, true_count, &model, conf);
+    }
+    let running_count = engine.counter.running_count;
+    if let Some(key) = engine
+        .counter
+        .key_count()
+        .filter(|key| running_count >= *key)
+    {
+        out.info(&format!(
+            "Running count {} is at the key count of {} or above, the edge is yours",
+            running_count, key
+        ));
+    }
+    let ramp_bet = conf
+        .bet_ramp
+        .as_ref()
+        .map(|ramp| ramp.bet(true_count, conf.base_bet));
+    if let (Some(bet), false) = (ramp_bet, conf.auto_ramp) {
+        out.info(&format!(
+            "Bet ramp: true count {:.1}, bet {}",
+            true_count, bet
+        ));
+    }
+
+    let num_hands = match input.num_hands(engine)? {
+        Some(n) => n,
+        None => return Ok(false),
+    };
+    for i in 0..num_hands {
+        let player = &engine.player;
+        let conf = &engine.conf;
+        let bet = match (&player.bet_strategy, ramp_bet) {
+            (Some(strategy), _) => auto_bet(
+                out,
+                player,
+                strategy.name(),
+                strategy.next_bet(&engine.bet_situation()),
+                conf,
+            ),
+            (None, Some(bet)) if conf.auto_ramp => auto_bet(out, player, "Bet ramp", bet, conf),
+            _ => Some(input.bet(engine, i + 1, num_hands)?),
+        };
+        // The bankroll covered the first hand, the hands it can't cover are left out
+        match bet {
+            Some(bet) => engine.place_bet(bet)?,
+            None => {
+                out.warn(&format!(
+                    "The bankroll of {} cannot cover hand {} at the table minimum of {}",
+                    player.money,
+                    i + 1,
+                    conf.table_min
+                ));
+                break;
+            }
+        }
+    }
+
+    if engine.conf.twenty_one_plus_three {
+        let bet = input.side_bet(engine, SideBet::TwentyOnePlusThree)?;
+        engine.place_twenty_one_plus_three(bet)?;
+    }
+    if engine.conf.bust_it {
+        let bet = input.side_bet(engine, SideBet::BustIt)?;
+        engine.place_bust_it(bet)?;
+    }
+
+    Ok(true)
+}
+
+/// Prints the Kelly criterion bet for the true count under the edge model.
+fn advise_bet(
+    out: &Console,
+    player: &Player,
+    true_count: f32,
+    model: &EdgeModel,
+    conf: &GameConfig,
+) {
+    let edge = model.edge(true_count) * 100.0;
+    let bet = model.kelly_bet(true_count, player.money, conf.bet_unit);
+    if bet < conf.table_min {
+        out.info(&format!(
+            "Advisor: true count {:.1}, edge {:+.2}%, no advantage, bet the table minimum of {}",
+            true_count, edge, conf.table_min
+        ));
+    } else {
+        out.info(&format!(
+            "Advisor: true count {:.1}, edge {:+.2}%, Kelly bet {} ({:.2}% of bankroll)",
+            true_count,
+            edge,
+            bet,
+            model.kelly_fraction(true_count) * 100.0
+        ));
+    }
+}
+
+fn take_even_money(out: &Console, hand: &PlayerHand) -> Result<bool, Error> {
+    out.info(&format!("Hand: {}", hand.hand));
+    loop {
+        match read_input(out, "BlackJack! Take even money? [y/n]")?.as_str() {
+            "y" => return Ok(true),
+            "n" => return Ok(false),
+            c => out.warn(&format!("Invalid choice '{}', please try again", c)),
+        }
+    }
+}
+
+fn place_side_bet(
+    out: &Console,
+    player: &Player,
+    name: &str,
+    bet_unit: Money,
+) -> Result<Money, Error> {
+    loop {
+        let input = read_input(out, &format!("{} side bet? (0 for none)", name))?;
+        match input.parse::<Money>() {
+            Ok(amount) if !amount.is_multiple_of(bet_unit) => {
+                out.warn(&format!("Bets must be in increments of {}", bet_unit))
+            }
+            Ok(amount) if !player.can_afford(amount) => {
+                out.warn(&format!("You cannot afford a side bet of {}", amount))
+            }
+            Ok(amount) => return Ok(amount),
+            Err(_) => out.warn(&format!("Invalid amount '{}', please try again", input)),
+        }
+    }
+}
+
+fn place_insurance(out: &Console, player: &Player) -> Result<Money, Error> {
+    let max = player.max_insurance();
+    if max == Money::ZERO || !player.can_afford(Money::from_cents(1)) {
+        return Ok(Money::ZERO);
+    }
+
+    loop {
+        let input = read_input(out, &format!("Insurance? (max {}, 0 for none)", max))?;
+        match input.parse::<Money>() {
+            Ok(amount) if amount > max => {
+                out.warn(&format!("Insurance can be at most half the bet ({})", max))
+            }
+            Ok(amount) if !player.can_afford(amount) => {
+                out.warn(&format!("You cannot afford an insurance of {}", amount))
+            }
+            Ok(amount) => return Ok(amount),
+            Err(_) => out.warn(&format!("Invalid amount '{}', please try again", input)),
+        }
+    }
+}
+
+/// The name of the move in the variant's terminology.
+fn move_name(engine: &GameEngine, action: Move) -> &'static str {
+    move_term(&engine.conf.rules.variant.terminology(), action)
+}
+
+/// The name of the move in the terminology of the variant.
+fn move_term(terms: &Terminology, action: Move) -> &'static str {
+    match action {
+        Move::Hit => terms.hit,
+        Move::Stand => terms.stand,
+        Move::Double => terms.double,
+        Move::Split => "split",
+        Move::Surrender => "surrender",
+    }
+}
+
+/// The key the move is made with, the first letter of its name or p to split and u to
+/// surrender.
+fn move_key(terms: &Terminology, action: Move) -> &'static str {
+    match action {
+        Move::Hit => &terms.hit[..1],
+        Move::Stand => &terms.stand[..1],
+        Move::Double => &terms.double[..1],
+        Move::Split => "p",
+        Move::Surrender => "u",
+    }
+}
+
+/// The move basic strategy, or the loaded chart, recommends for the hand, followed by the index
+/// play where the count says otherwise, e.g. "(basic strategy: hit, at true count +4.2: stand)".
+fn move_hint(engine: &GameEngine, hand: usize, options: &HandOptions) -> String {
+    let (source, advised) = match &engine.conf.chart {
+        Some(chart) => (chart.name.as_str(), chart.decide(engine, hand, options)),
+        None => (
+            "basic strategy",
+            BasicStrategy::decide(engine, hand, options),
+        ),
+    };
+    match HiLoBot::deviation(engine, hand, options).filter(|play| *play != advised) {
+        Some(play) => format!(
+            "({}: {}, at true count {:+.1}: {})",
+            source,
+            move_name(engine, advised),
+            HiLoBot::true_count(engine),
+            move_name(engine, play)
+        ),
+        None => format!("({}: {})", source, move_name(engine, advised)),
+    }
+}
+
+/// Tells whether the count makes insurance, or even money, worth taking. Basic strategy never
+/// takes it.
+fn hint_insurance(out: &Console, engine: &GameEngine, name: &str) {
+    if HiLoBot::insures(engine) {
+        out.info(&format!(
+            "Hint: take {}, the true count of {:+.1} is at the index of {:+.0} or above",
+            name,
+            HiLoBot::true_count(engine),
+            INSURANCE_INDEX
+        ));
+    } else {
+        out.info(&format!("Hint: basic strategy never takes {}", name));
+    }
+}
+
+/// Asks for a move on the hand, the keys depend on the variant's terminology.
+/// In practice mode the last move can be taken back with undo, with hints the recommended move
+/// is shown next to the prompt.
+fn choose_move(
+    out: &Console,
+    engine: &GameEngine,
+    hand: usize,
+    options: &HandOptions,
+) -> Result<Move, Error> {
+    let terms = engine.conf.rules.variant.terminology();
+    let moves = options
+        .moves
+        .iter()
+        .map(|m| (move_key(&terms, *m), *m))
+        .collect::<Vec<(&str, Move)>>();
+    let mut keys = moves.iter().map(|(k, _)| *k).collect::<Vec<&str>>();
+    if engine.conf.practice {
+        keys.push("undo");
+    }
+
+    let mut free_moves = vec![];
+    if options.free_double {
+        free_moves.push(terms.double);
+    }
+    if options.free_split {
+        free_moves.push("split");
+    }
+    let mut prompt = if free_moves.is_empty() {
+        format!("Move? [{}]", keys.join("/"))
+    } else {
+        format!("Move? [{}] (free {})", keys.join("/"), free_moves.join("/"))
+    };
+    if engine.conf.hints {
+        prompt = format!("{} {}", prompt, move_hint(engine, hand, options));
+    }
+
+    loop {
+        let choice = read_input(out, &prompt)?;
+        match moves.iter().find(|(k, _)| *k == choice) {
+            Some((_, m)) => return Ok(*m),
+            None if engine.conf.practice && choice == "undo" => {
+                if engine.can_undo() {
+                    return Err(Error::Undo);
+                }
+                out.warn("There is no move to undo this round");
+            }
+            None => out.warn(&format!("Invalid choice '{}', please try again", choice)),
+        }
+    }
+}
+
+fn choose_buy_amount(out: &Console, player: &Player, max: Money) -> Result<Money, Error> {
+    loop {
+        let input = read_input(out, &format!("Buy for how much? (1-{})", max))?;
+        match input.parse::<Money>() {
+            Ok(amount) if amount == Money::ZERO || amount > max => {
+                out.warn(&format!("The amount must be between 1 and {}", max))
+            }
+            Ok(amount) if !player.can_afford(amount) => {
+                out.warn(&format!("You cannot afford to buy for {}", amount))
+            }
+            Ok(amount) => return Ok(amount),
+            Err(_) => out.warn(&format!("Invalid amount '{}', please try again", input)),
+        }
+    }
+}
+
+fn offer_tip(out: &Console, player: &Player) -> Result<Money, Error> {
+    loop {
+        let input = read_input(out, "Tip the dealer? (0 or enter for none)")?;
+        if input.is_empty() {
+            return Ok(Money::ZERO);
+        }
+
+        match input.parse::<Money>() {
+            Ok(amount) if !player.can_afford(amount) => {
+                out.warn(&format!("You cannot afford a tip of {}", amount))
+            }
+            Ok(amount) => return Ok(amount),
+            Err(_) => out.warn(&format!("Invalid amount '{}', please try again", input)),
+        }
+    }
+}
+
+fn offer_double_rescue(out: &Console) -> Result<bool, Error> {
+    loop {
+        match read_input(
+            out,
+            "Rescue the double and surrender the original bet? [y/n]",
+        )?
+        .as_str()
+        {
+            "y" => return Ok(true),
+            "n" => return Ok(false),
+            c => out.warn(&format!("Invalid choice '{}', please try again", c)),
+        }
+    }
+}
+
+/// Runs the count drill, flashing the cards in place on the terminal and asking for the running
+/// count after each round, then sums up the session and how it compares with the sessions kept
+/// in the `history` file, which it is added to.
+pub fn count_drill(
+    out: &Console,
+    conf: &GameConfig,
+    drill: &CountDrill,
+    history: Option<&Path>,
+    seed: Option<u64>,
+) -> Result<(), Error> {
+    drill.validate(conf)?;
+    let mut history = history.map(DrillHistory::load).transpose()?;
+    let mut shuffler = match seed {
+        Some(seed) => conf.shuffle.seeded(seed),
+        None => conf.shuffle.shuffler(),
+    };
+    let system = conf.counting.system();
+    let start = system.initial_running_count(conf.rules.deck_count);
+    out.heading("============ COUNT DRILL ============");
+    out.info(&format!(
+        "{} rounds of {} cards shown for {}ms each, keep the {} running count{}",
+        drill.rounds,
+        drill.cards,
+        drill.pace.as_millis(),
+        system.name(),
+        if start == 0 {
+            String::new()
+        } else {
+            format!(" from {:+}", start)
+        }
+    ));
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let (mut correct, mut answered, mut answer_time) = (0, 0, Duration::ZERO);
+    for round in 1..=drill.rounds {
+        let (cards, count) = drill.deal(conf, shuffler.as_mut())?;
+        let (answer, elapsed) = match flash_cards(out, &cards, drill.pace, round) {
+            Ok(answer) => answer,
+            Err(Error::InputClosed) => break,
+            Err(e) => return Err(e),
+        };
+        answer_time += elapsed;
+        answered += 1;
+        if answer == count {
+            correct += 1;
+            out.info(&format!("Correct, the count is {:+}", count));
+        } else {
+            out.warn(&format!(
+                "The count is {:+}, off by {:+}: {}",
+                count,
+                answer - count,
+                cards
+                    .iter()
+                    .map(|card| card.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+    }
+
+    if answered == 0 {
+        return Ok(());
+    }
+    let session = DrillSession {
+        drill: Drill::Count,
+        started,
+        system: system.name().to_string(),
+        questions: answered,
+        correct,
+        cards: Some(drill.cards),
+        pace_ms: Some(drill.pace.as_millis() as u64),
+        answer_secs: answer_time.as_secs_f64() / answered as f64,
+        missed: vec![],
+    };
+    out.heading("============ DRILL SUMMARY ============");
+    out.info(&format!(
+        "{} of {} counts right ({:.0}%), answered in {:.1}s on average",
+        session.correct,
+        session.questions,
+        session.accuracy() * 100.0,
+        session.answer_secs
+    ));
+    if let Some(history) = &mut history {
+        history.record(session)?;
+        print_drill_history(out, history, Drill::Count);
+    }
+    Ok(())
+}
+
+/// Shows the cards of a round of the count drill one after the other in the same place, and
+/// reads the running count after them with the time it took to answer.
+fn flash_cards(
+    out: &Console,
+    cards: &[Card],
+    pace: Duration,
+    round: u32,
+) -> Result<(i32, Duration), Error> {
+    read_input(out, &format!("Round {}: enter when ready", round))?;
+    for card in cards {
+        out.flash(&card.to_string(), pace);
+    }
+    out.flash("", Duration::ZERO);
+
+    let asked = Instant::now();
+    loop {
+        let input = read_input(out, "Running count?")?;
+        match input.trim_start_matches('+').parse::<i32>() {
+            Ok(answer) => return Ok((answer, asked.elapsed())),
+            Err(_) => out.warn(&format!("Invalid count '{}', please try again", input)),
+        }
+    }
+}
+
+/// The number of past sessions shown after a drill.
+const DRILL_HISTORY_SESSIONS: usize = 10;
+
+/// Shows the last sessions of the drill, to see the accuracy and speed improve.
+fn print_drill_history(out: &Console, history: &DrillHistory, drill: Drill) {
+    let sessions = history.of(drill).collect::<Vec<_>>();
+    out.info(&format!(
+        "{} sessions kept in {}",
+        sessions.len(),
+        history.path.display()
+    ));
+    let count = drill == Drill::Count;
+    out.info(&format!(
+        "Session  Questions  Correct  Answer (s){}",
+        if count { "  Cards  Pace (ms)" } else { "" }
+    ));
+    let first = sessions.len().saturating_sub(DRILL_HISTORY_SESSIONS);
+    for (number, session) in sessions.iter().enumerate().skip(first) {
+        let mut line = format!(
+            "{:>7}  {:>9}  {:>6.0}%  {:>10.1}",
+            number + 1,
+            session.questions,
+            session.accuracy() * 100.0,
+            session.answer_secs
+        );
+        if count {
+            let optional = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
+            line += &format!(
+                "  {:>5}  {:>9}",
+                optional(session.cards.map(u64::from)),
+                optional(session.pace_ms)
+            );
+        }
+        out.info(&line);
+    }
+}
+
+/// The most missed hands of the past strategy drills shown after a drill.
+const WEAK_SPOTS: usize = 5;
+
+/// Runs the strategy drill on the --chart, or basic strategy for the rules, asking for the move
+/// of two card hands against an upcard and telling the right one after a wrong answer. The
+/// hands missed are asked again at the end, and with a `history` file half the hands are picked
+/// from the ones missed most in the sessions before.
+pub fn strategy_drill(
+    out: &Console,
+    conf: &GameConfig,
+    drill: &StrategyDrill,
+    history: Option<&Path>,
+    seed: Option<u64>,
+) -> Result<(), Error> {
+    let chart = match &conf.chart {
+        Some(chart) => chart.clone(),
+        None => CustomChart::from_chart("basic strategy", &generate_chart(&conf.rules)?),
+    };
+    let mut history = history.map(DrillHistory::load).transpose()?;
+    let weak_spots = history
+        .as_ref()
+        .map(|history| history.weak_spots())
+        .unwrap_or_default();
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let situations = drill.situations(
+        &chart,
+        &weak_spots
+            .iter()
+            .map(|(situation, _)| *situation)
+            .collect::<Vec<_>>(),
+        &mut rng,
+    );
+    out.heading("============ STRATEGY DRILL ============");
+    out.info(&format!(
+        "{} hands to play by {}, the hands are your first two cards",
+        situations.len(),
+        chart.name
+    ));
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let mut answer_time = Duration::ZERO;
+    let mut missed = vec![];
+    // The hands asked and answered right, of the hard totals, the soft hands and the pairs
+    let mut sections = [("Hard", 0, 0), ("Soft", 0, 0), ("Pairs", 0, 0)];
+    for (number, situation) in situations.iter().enumerate() {
+        let asked = Instant::now();
+        let prompt = format!("{}/{}: {}?", number + 1, situations.len(), situation);
+        let right = match ask_strategy(out, conf, &chart, situation, &prompt) {
+            Ok(Some(right)) => right,
+            Ok(None) => continue,
+            Err(Error::InputClosed) => break,
+            Err(e) => return Err(e),
+        };
+        answer_time += asked.elapsed();
+        let section = match situation.hand {
+            StartingHand::Hard(_) => &mut sections[0],
+            StartingHand::Soft(_) => &mut sections[1],
+            StartingHand::Pair(_) => &mut sections[2],
+        };
+        section.1 += 1;
+        section.2 += right as u32;
+        if !right {
+            missed.push(*situation);
+        }
+    }
+
+    if !missed.is_empty() {
+        out.heading("============ REVIEW ============");
+        let (mut reviewed, mut right) = (0, 0);
+        for (number, situation) in missed.iter().enumerate() {
+            let prompt = format!("Review {}/{}: {}?", number + 1, missed.len(), situation);
+            match ask_strategy(out, conf, &chart, situation, &prompt) {
+                Ok(answer) => {
+                    reviewed += 1;
+                    right += (answer == Some(true)) as u32;
+                }
+                Err(Error::InputClosed) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        out.info(&format!(
+            "{} of the {} missed hands reviewed right the second time",
+            right, reviewed
+        ));
+    }
+
+    let questions = sections.iter().map(|(_, asked, _)| asked).sum::<u32>();
+    if questions == 0 {
+        return Ok(());
+    }
+    let session = DrillSession {
+        drill: Drill::Strategy,
+        started,
+        system: chart.name.clone(),
+        questions,
+        correct: sections.iter().map(|(_, _, right)| right).sum(),
+        cards: None,
+        pace_ms: None,
+        answer_secs: answer_time.as_secs_f64() / questions as f64,
+        missed: missed
+            .iter()
+            .map(|situation| situation.to_string())
+            .collect(),
+    };
+    out.heading("============ DRILL SUMMARY ============");
+    out.info(&format!(
+        "{} of {} hands right ({:.0}%), answered in {:.1}s on average",
+        session.correct,
+        session.questions,
+        session.accuracy() * 100.0,
+        session.answer_secs
+    ));
+    for (name, asked, right) in sections.iter().filter(|(_, asked, _)| *asked > 0) {
+        out.info(&format!(
+            "  {:<5} {} of {} ({:.0}%)",
+            name,
+            right,
+            asked,
+            *right as f64 / *asked as f64 * 100.0
+        ));
+    }
+    if let Some(history) = &mut history {
+        history.record(session)?;
+        print_drill_history(out, history, Drill::Strategy);
+        let weak_spots = history.weak_spots();
+        if !weak_spots.is_empty() {
+            out.info("Most missed hands:");
+        }
+        for (situation, times) in weak_spots.iter().take(WEAK_SPOTS) {
+            out.info(&format!("  {}x {}", times, situation));
+        }
+    }
+    Ok(())
+}
+
+/// Asks for the move of the hand and grades it against the chart, `None` for a hand the chart
+/// has no move for under the rules.
+fn ask_strategy(
+    out: &Console,
+    conf: &GameConfig,
+    chart: &CustomChart,
+    situation: &Situation,
+    prompt: &str,
+) -> Result<Option<bool>, Error> {
+    let Some(answer) = StrategyDrill::answer(chart, &conf.rules, situation) else {
+        return Ok(None);
+    };
+    let terms = conf.rules.variant.terminology();
+    let moves = StrategyDrill::allowed(&conf.rules, situation)
+        .into_iter()
+        .map(|m| (move_key(&terms, m), m))
+        .collect::<Vec<(&str, Move)>>();
+    let keys = moves.iter().map(|(k, _)| *k).collect::<Vec<&str>>();
+    let prompt = format!("{} [{}]", prompt, keys.join("/"));
+    let choice = loop {
+        let choice = read_input(out, &prompt)?;
+        match moves.iter().find(|(k, _)| *k == choice) {
+            Some((_, m)) => break *m,
+            None => out.warn(&format!("Invalid choice '{}', please try again", choice)),
+        }
+    };
+    if choice == answer {
+        out.info("Correct");
+    } else {
+        out.warn(&format!(
+            "Wrong, {} by {} is to {}",
+            situation,
+            chart.name,
+            move_term(&terms, answer)
+        ));
+    }
+    Ok(Some(choice == answer))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{count_drill, load_script, play_session};
+    use crate::render::{Console, OutputRenderer, TextKind};
+    use blackjack::config::GameConfig;
+    use blackjack::drills::CountDrill;
+    use blackjack::events::GameEvent;
+    use blackjack::input::PlayerKind;
+    use blackjack::money::Money;
+    use blackjack::table::SeatPosition;
+    use blackjack::types::Player;
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    /// Keeps the text shown and the events of a session.
+    #[derive(Clone, Default)]
+    struct Transcript {
+        lines: Rc<RefCell<Vec<String>>>,
+        events: Rc<RefCell<Vec<GameEvent>>>,
+    }
+
+    impl OutputRenderer for Transcript {
+        fn event(&mut self, event: &GameEvent) {
+            self.events.borrow_mut().push(event.clone());
+        }
+
+        fn text(&mut self, text: &str, _kind: TextKind) {
+            self.lines.borrow_mut().push(text.to_string());
+        }
+    }
+
+    /// A script file in the temporary directory, removed once dropped.
+    struct Script(PathBuf);
+
+    impl Script {
+        fn new(name: &str, text: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+            std::fs::write(&path, text).unwrap();
+            Script(path)
+        }
+    }
+
+    impl Drop for Script {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Plays a session answered by `script` on a shoe shuffled with `seed`.
+    fn scripted_session(script: &Script, seed: u64) -> Transcript {
+        let transcript = Transcript::default();
+        let answers = load_script(&script.0).unwrap();
+        let out = Console::new(Box::new(transcript.clone()), Some(answers));
+        let player = Player::new(Money::from_units(100));
+        let players = vec![(SeatPosition::FIRST_BASE, player)];
+        play_session(
+            &out,
+            GameConfig::default(),
+            players,
+            PlayerKind::Human,
+            Some(seed),
+        )
+        .unwrap();
+        transcript
+    }
+
+    #[test]
+    fn test_load_script() {
+        let script = Script::new("answers.txt", "# Bet and stand\n\n  10 \n  # aside\ns\n");
+        let answers = load_script(&script.0).unwrap();
+        assert_eq!(answers, ["", "10", "s"]);
+        assert!(load_script(&script.0.with_extension("missing")).is_err());
+    }
+
+    #[test]
+    fn test_scripted_session() {
+        // Three rounds of a hand of 10 stood on, then the table is left
+        let script = Script::new(
+            "session.txt",
+            "# A hand a round\n1\n10\ns\n1\n10\ns\n1\n10\ns\nq\n",
+        );
+        let first = scripted_session(&script, 7);
+        let replay = scripted_session(&script, 7);
+        assert_eq!(*first.lines.borrow(), *replay.lines.borrow());
+        assert_eq!(*first.events.borrow(), *replay.events.borrow());
+
+        let lines = first.lines.borrow();
+        assert!(lines.iter().any(|line| line == "> 10"));
+        assert!(!lines.iter().any(|line| line.contains("A hand a round")));
+        let finished = first
+            .events
+            .borrow()
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::RoundFinished { bankroll, .. } => *bankroll,
+                _ => None,
+            })
+            .collect::<Vec<Money>>();
+        assert_eq!(finished.len(), 3);
+        // The session summary shows the bankroll the last round finished with
+        let summary = lines.last().unwrap();
+        assert!(summary.contains("Rounds played:     3"));
+        let bankroll = summary
+            .lines()
+            .find_map(|line| line.strip_prefix("Bankroll:"))
+            .unwrap();
+        assert_eq!(bankroll.trim(), finished[2].to_string());
+    }
+
+    #[test]
+    fn test_scripted_count_drill() {
+        // The cards are flashed through the renderer, and a script doesn't wait an hour on each
+        let transcript = Transcript::default();
+        let answers = ["", "0", "", "0"].map(String::from).into();
+        let out = Console::new(Box::new(transcript.clone()), Some(answers));
+        let drill = CountDrill {
+            cards: 3,
+            pace: Duration::from_secs(3600),
+            rounds: 2,
+        };
+        count_drill(&out, &GameConfig::default(), &drill, None, Some(5)).unwrap();
+
+        let lines = transcript.lines.borrow();
+        let flashes = lines.iter().filter(|line| line.is_empty()).count();
+        assert_eq!(flashes, 2);
+        assert!(lines.iter().any(|line| line.contains("DRILL SUMMARY")));
+    }
+}
@@ -0,0 +1,218 @@
+use crate::advisor::EdgeModel;
+use crate::betting::{BetRamp, BetSystem};
+use crate::counting::{CountingMethod, DeckEstimation};
+use crate::custom_chart::CustomChart;
+use crate::error::Error;
+use crate::money::Money;
+use crate::rules::Rules;
+use crate::shuffle::ShuffleMethod;
+use crate::stats::SessionStats;
+use std::time::Duration;
+
+/// The most hands a player can play at once.
+pub const MAX_HANDS: usize = 4;
+
+/// The most players seated at a table.
+pub const MAX_SEATS: usize = 7;
+
+/// Everything that configures a game at the table: the rules, the limits and the player aids.
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    /// The delay between moves when the game is shown to a person.
+    pub sleep_duration: Duration,
+    pub rules: Rules,
+    pub hands: usize,
+    pub twenty_one_plus_three: bool,
+    pub bust_it: bool,
+    pub table_min: Money,
+    pub table_max: Option<Money>,
+    pub bet_unit: Money,
+    pub shuffle: ShuffleMethod,
+    pub counting: CountingMethod,
+    /// Side count the aces for the counts that leave them out, to size the bets on them too.
+    pub ace_side_count: bool,
+    /// How closely the decks left are estimated for the true count.
+    pub deck_estimation: DeckEstimation,
+    pub advisor: Option<EdgeModel>,
+    pub base_bet: Money,
+    /// Sizes every main bet, whoever plays the hands.
+    pub bet_system: Option<BetSystem>,
+    pub bet_ramp: Option<BetRamp>,
+    pub auto_ramp: bool,
+    /// The strategy chart the chart bot plays.
+    pub chart: Option<CustomChart>,
+    pub tips: bool,
+    pub stop_win: Option<Money>,
+    pub stop_loss: Option<Money>,
+    /// Practice mode, the last hit, double or split of the round can be undone at the next decision.
+    pub practice: bool,
+    /// Show the basic strategy move at every decision, and the index play where the count says
+    /// otherwise.
+    pub hints: bool,
+    /// Compare every move to basic strategy and report the mistakes at the end of every shoe.
+    pub track_mistakes: bool,
+}
+
+impl GameConfig {
+    /// Checks the rules and that the table limits and betting aids fit together.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.rules.validate()?;
+        if !(1..=MAX_HANDS).contains(&self.hands) {
+            return Err(Error::Config(format!(
+                "a player can play 1 to {} hands",
+                MAX_HANDS
+            )));
+        }
+        if self.table_min <= Money::ZERO || self.bet_unit <= Money::ZERO {
+            return Err(Error::Config(
+                "the table minimum and the bet unit must be positive".to_string(),
+            ));
+        }
+        if self.table_max.is_some_and(|max| max < self.table_min) {
+            return Err(Error::Config(
+                "the table maximum cannot be lower than the table minimum".to_string(),
+            ));
+        }
+        if self.base_bet < self.table_min {
+            return Err(Error::Config(
+                "the base bet cannot be lower than the table minimum".to_string(),
+            ));
+        }
+        if self.auto_ramp && self.bet_ramp.is_none() {
+            return Err(Error::Config(
+                "automatic ramp bets need a bet ramp".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fits an automatically sized bet within the table limits and the bankroll, in whole bet
+    /// units. `None` if the bankroll can't cover the table minimum.
+    pub fn fit_bet(&self, bet: Money, bankroll: Money) -> Option<Money> {
+        let mut bet = bet.max(self.table_min);
+        if let Some(max) = self.table_max {
+            bet = bet.min(max);
+        }
+        let bet = bet.min(bankroll).round_down_to(self.bet_unit);
+        if bet >= self.table_min {
+            return Some(bet);
+        }
+        // A table minimum that isn't a whole number of units takes the next unit up
+        let min_bet =
+            (self.table_min + self.bet_unit - Money::from_cents(1)).round_down_to(self.bet_unit);
+        let over_max = self.table_max.is_some_and(|max| min_bet > max);
+        (min_bet <= bankroll && !over_max).then_some(min_bet)
+    }
+
+    /// The reason to end the session if the result has passed one of the stop limits.
+    pub fn stop_limit_reached(&self, stats: &SessionStats) -> Option<String> {
+        let profit = stats.profit();
+        if let Some(limit) = self.stop_win.filter(|limit| profit >= *limit) {
+            return Some(format!("Stop-win of {} reached ({:+})", limit, profit));
+        }
+        if let Some(limit) = self.stop_loss.filter(|limit| profit <= -*limit) {
+            return Some(format!("Stop-loss of {} reached ({:+})", limit, profit));
+        }
+        None
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            sleep_duration: Duration::ZERO,
+            rules: Rules::default(),
+            hands: 1,
+            twenty_one_plus_three: false,
+            bust_it: false,
+            table_min: Money::from_units(1),
+            table_max: None,
+            bet_unit: Money::from_units(1),
+            shuffle: ShuffleMethod::Uniform,
+            counting: CountingMethod::HiLo,
+            ace_side_count: false,
+            deck_estimation: DeckEstimation::HalfDecks,
+            advisor: None,
+            base_bet: Money::from_units(1),
+            bet_system: None,
+            bet_ramp: None,
+            auto_ramp: false,
+            chart: None,
+            tips: false,
+            stop_win: None,
+            stop_loss: None,
+            practice: false,
+            hints: false,
+            track_mistakes: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{GameConfig, MAX_HANDS};
+    use crate::money::Money;
+
+    #[test]
+    fn test_fit_bet() {
+        let conf = GameConfig {
+            table_min: Money::from_units(5),
+            table_max: Some(Money::from_units(100)),
+            bet_unit: Money::from_units(5),
+            ..GameConfig::default()
+        };
+        let bankroll = Money::from_units(1000);
+        assert_eq!(
+            conf.fit_bet(Money::from_units(1), bankroll),
+            Some(Money::from_units(5))
+        );
+        assert_eq!(
+            conf.fit_bet(Money::from_units(640), bankroll),
+            Some(Money::from_units(100))
+        );
+        assert_eq!(
+            conf.fit_bet(Money::from_units(40), Money::from_units(22)),
+            Some(Money::from_units(20))
+        );
+        assert_eq!(
+            conf.fit_bet(Money::from_units(40), Money::from_units(4)),
+            None
+        );
+
+        let conf = GameConfig {
+            table_min: Money::from_units(7),
+            ..conf
+        };
+        assert_eq!(
+            conf.fit_bet(Money::from_units(7), bankroll),
+            Some(Money::from_units(10))
+        );
+        assert_eq!(
+            conf.fit_bet(Money::from_units(7), Money::from_units(8)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(GameConfig::default().validate().is_ok());
+        for hands in [0, MAX_HANDS + 1] {
+            let conf = GameConfig {
+                hands,
+                ..GameConfig::default()
+            };
+            assert!(conf.validate().is_err());
+        }
+        let conf = GameConfig {
+            table_min: Money::ZERO,
+            base_bet: Money::ZERO,
+            ..GameConfig::default()
+        };
+        assert!(conf.validate().is_err());
+        let conf = GameConfig {
+            bet_unit: Money::ZERO,
+            ..GameConfig::default()
+        };
+        assert!(conf.validate().is_err());
+    }
+}
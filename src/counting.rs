@@ -0,0 +1,766 @@
+use crate::error::Error;
+use crate::types::{Card, Value, DECK_SIZE};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const ACES_PER_DECK: u32 = 4;
+
+/// A card counting system, tagging every card seen to keep a running count of the shoe.
+pub trait CountingSystem {
+    fn name(&self) -> &str;
+
+    /// The tag added to the running count when a card of `value` is seen.
+    fn tag(&self, value: &Value) -> i32;
+
+    /// The running count of a freshly shuffled shoe of `decks` decks.
+    fn initial_running_count(&self, _decks: u32) -> i32 {
+        0
+    }
+
+    /// The most any card counts either way, a level two count is about twice the Hi-Lo count.
+    fn level(&self) -> i32 {
+        Value::ALL
+            .iter()
+            .map(|value| self.tag(value).abs())
+            .max()
+            .unwrap_or(1)
+    }
+
+    /// What every ace left in the shoe above the usual share of 4 a deck is worth to the count
+    /// when the aces are side counted, `None` if the aces are counted in the tags already.
+    fn ace_weight(&self) -> Option<i32> {
+        None
+    }
+
+    /// The running count of an unbalanced count from which the player has the edge and bets
+    /// more, `None` for a balanced count, which is converted to a true count instead.
+    fn key_count(&self, _decks: u32) -> Option<i32> {
+        None
+    }
+
+    /// Converts the running count to a true count with `decks_remaining` decks left unseen.
+    fn true_count(&self, running_count: i32, decks_remaining: f32) -> f32 {
+        running_count as f32 / decks_remaining
+    }
+}
+
+/// The high-low count: 2-6 count +1, 7-9 count 0 and tens and aces count -1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HiLo;
+
+impl CountingSystem for HiLo {
+    fn name(&self) -> &str {
+        "Hi-Lo"
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        match value.value() {
+            2..=6 => 1,
+            7..=9 => 0,
+            _ => -1,
+        }
+    }
+}
+
+/// The Knock-Out count, an unbalanced Hi-Lo that counts sevens as +1.
+///
+/// It starts below zero so the running count is used as is, without converting to a true count.
+/// The bets go up from the key count, and the running count at the pivot is about a Hi-Lo true
+/// count of the same value whatever the number of decks.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KnockOut;
+
+impl KnockOut {
+    pub const PIVOT: i32 = 4;
+}
+
+impl CountingSystem for KnockOut {
+    fn name(&self) -> &str {
+        "KO"
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        match value.value() {
+            2..=7 => 1,
+            8 | 9 => 0,
+            _ => -1,
+        }
+    }
+
+    fn initial_running_count(&self, decks: u32) -> i32 {
+        4 - 4 * decks as i32
+    }
+
+    /// The key counts of the KO book, +2 for a single deck, +1 for two and then one lower for
+    /// every extra deck, -4 for six decks.
+    fn key_count(&self, decks: u32) -> Option<i32> {
+        Some(match decks {
+            1 => 2,
+            2 => 1,
+            decks => 2 - decks as i32,
+        })
+    }
+
+    fn true_count(&self, running_count: i32, _decks_remaining: f32) -> f32 {
+        running_count as f32
+    }
+}
+
+/// Hi-Opt I: 3-6 count +1 and tens count -1, the aces are left out of the count.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HiOptI;
+
+impl CountingSystem for HiOptI {
+    fn name(&self) -> &str {
+        "Hi-Opt I"
+    }
+
+    fn ace_weight(&self) -> Option<i32> {
+        Some(1)
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        match value.value() {
+            3..=6 => 1,
+            10 => -1,
+            _ => 0,
+        }
+    }
+}
+
+/// Hi-Opt II, a level two count: 4 and 5 count +2, 2, 3, 6 and 7 count +1 and tens count -2.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HiOptII;
+
+impl CountingSystem for HiOptII {
+    fn name(&self) -> &str {
+        "Hi-Opt II"
+    }
+
+    fn ace_weight(&self) -> Option<i32> {
+        Some(2)
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        match value.value() {
+            4 | 5 => 2,
+            2 | 3 | 6 | 7 => 1,
+            10 => -2,
+            _ => 0,
+        }
+    }
+}
+
+/// Omega II, a level two count: 4-6 count +2, 2, 3 and 7 count +1, nines -1 and tens -2.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OmegaII;
+
+impl CountingSystem for OmegaII {
+    fn name(&self) -> &str {
+        "Omega II"
+    }
+
+    fn ace_weight(&self) -> Option<i32> {
+        Some(2)
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        match value.value() {
+            4..=6 => 2,
+            2 | 3 | 7 => 1,
+            9 => -1,
+            10 => -2,
+            _ => 0,
+        }
+    }
+}
+
+/// The Zen count, a level two count like Omega II that counts aces -1 instead of nines.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ZenCount;
+
+impl CountingSystem for ZenCount {
+    fn name(&self) -> &str {
+        "Zen"
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        match value.value() {
+            4..=6 => 2,
+            2 | 3 | 7 => 1,
+            10 => -2,
+            11 => -1,
+            _ => 0,
+        }
+    }
+}
+
+/// How the running count of a custom count is turned into the true count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrueCountMethod {
+    /// Divided by the decks left, as most balanced counts are.
+    #[default]
+    Decks,
+    /// Divided by the half decks left, as some level two counts are.
+    HalfDecks,
+    /// Used as it is, as unbalanced counts are.
+    Running,
+}
+
+/// How closely the decks left in the shoe are estimated to turn the running count into the true
+/// count, as counts and their books teach it differently.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DeckEstimation {
+    /// The cards left exactly, as a fraction of a deck.
+    Exact,
+    /// To the nearest quarter deck.
+    QuarterDecks,
+    /// To the nearest half deck, as most players estimate the discard tray.
+    #[default]
+    HalfDecks,
+    /// In whole decks, rounded down.
+    Decks,
+}
+
+impl DeckEstimation {
+    /// The decks left estimated from the cards left, never less than the smallest step so the
+    /// count can be divided by it.
+    pub fn decks_remaining(&self, cards_remaining: u32) -> f32 {
+        let decks = cards_remaining as f32 / DECK_SIZE as f32;
+        let steps = |per_deck: f32| (decks * per_deck).round().max(1.0) / per_deck;
+        match self {
+            DeckEstimation::Exact => cards_remaining.max(1) as f32 / DECK_SIZE as f32,
+            DeckEstimation::QuarterDecks => steps(4.0),
+            DeckEstimation::HalfDecks => steps(2.0),
+            DeckEstimation::Decks => decks.floor().max(1.0),
+        }
+    }
+}
+
+impl Display for DeckEstimation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DeckEstimation::Exact => "exact",
+                DeckEstimation::QuarterDecks => "quarter-decks",
+                DeckEstimation::HalfDecks => "half-decks",
+                DeckEstimation::Decks => "decks",
+            }
+        )
+    }
+}
+
+impl FromStr for DeckEstimation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(DeckEstimation::Exact),
+            "quarter-decks" => Ok(DeckEstimation::QuarterDecks),
+            "half-decks" => Ok(DeckEstimation::HalfDecks),
+            "decks" => Ok(DeckEstimation::Decks),
+            _ => Err(format!(
+                "expected one of 'exact', 'quarter-decks', 'half-decks' or 'decks', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// A custom count as it is written in its TOML file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CountFile {
+    name: Option<String>,
+    tags: BTreeMap<String, i32>,
+    #[serde(default)]
+    true_count: TrueCountMethod,
+    #[serde(default)]
+    initial_count_per_deck: i32,
+    key_count: Option<i32>,
+    ace_weight: Option<i32>,
+}
+
+/// A counting system with its tags read from a TOML file. The tags are given by rank, with
+/// the ranks left out counting 0, the ten valued cards all counting as one of them does, e.g.
+///
+/// ```toml
+/// name = "Unbalanced Zen"
+/// true_count = "running"
+/// initial_count_per_deck = -4
+/// key_count = 0
+///
+/// [tags]
+/// 2 = 1
+/// 3 = 1
+/// 4 = 2
+/// 5 = 2
+/// 6 = 2
+/// 7 = 1
+/// 10 = -2
+/// A = -1
+/// ```
+///
+/// The true count is worked out by `true_count`, `decks` (the default), `half-decks` or
+/// `running`. An unbalanced count starts each shoe at `initial_count_per_deck` times the decks
+/// and can have a `key_count` to bet from, and a count leaving the aces out can give them an
+/// `ace_weight` to side count them with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCount {
+    pub name: String,
+    /// The file the count was read from.
+    pub path: PathBuf,
+    /// The tags of the card values from 2 to 11, the ace.
+    pub tags: [i32; 10],
+    pub true_count: TrueCountMethod,
+    pub initial_count_per_deck: i32,
+    pub key_count: Option<i32>,
+    pub ace_weight: Option<i32>,
+}
+
+impl CustomCount {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("cannot read {}: {}", path.display(), e)))?;
+        Self::parse(&text, path)
+            .map_err(|reason| Error::Config(format!("{}: {}", path.display(), reason)))
+    }
+
+    /// Reads the count from the text of the file at `path`.
+    pub fn parse(text: &str, path: &Path) -> Result<Self, String> {
+        let file = toml::from_str::<CountFile>(text).map_err(|e| e.message().to_string())?;
+        let mut tags = [None; 10];
+        for (rank, tag) in &file.tags {
+            let value = rank.parse::<Value>()?.value() as usize;
+            match tags[value - 2] {
+                Some(other) if other != *tag => {
+                    return Err(format!(
+                        "the ten valued cards count the same, got {} and {}",
+                        other, tag
+                    ))
+                }
+                _ => tags[value - 2] = Some(*tag),
+            }
+        }
+        let tags = tags.map(|tag| tag.unwrap_or(0));
+        if tags.iter().all(|tag| *tag == 0) {
+            return Err("a count needs at least one card that doesn't count 0".to_string());
+        }
+        let name = file.name.unwrap_or_else(|| {
+            path.file_stem().map_or("Custom".to_string(), |stem| {
+                stem.to_string_lossy().to_string()
+            })
+        });
+        Ok(CustomCount {
+            name,
+            path: path.to_path_buf(),
+            tags,
+            true_count: file.true_count,
+            initial_count_per_deck: file.initial_count_per_deck,
+            key_count: file.key_count,
+            ace_weight: file.ace_weight,
+        })
+    }
+}
+
+impl CountingSystem for CustomCount {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn tag(&self, value: &Value) -> i32 {
+        self.tags[value.value() as usize - 2]
+    }
+
+    fn initial_running_count(&self, decks: u32) -> i32 {
+        self.initial_count_per_deck * decks as i32
+    }
+
+    fn ace_weight(&self) -> Option<i32> {
+        self.ace_weight
+    }
+
+    fn key_count(&self, _decks: u32) -> Option<i32> {
+        self.key_count
+    }
+
+    fn true_count(&self, running_count: i32, decks_remaining: f32) -> f32 {
+        match self.true_count {
+            TrueCountMethod::Decks => running_count as f32 / decks_remaining,
+            TrueCountMethod::HalfDecks => running_count as f32 / (decks_remaining * 2.0),
+            TrueCountMethod::Running => running_count as f32,
+        }
+    }
+}
+
+/// The counting systems that can be selected from the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CountingMethod {
+    HiLo,
+    KnockOut,
+    HiOptI,
+    HiOptII,
+    OmegaII,
+    Zen,
+    /// A count read from a file, see [CustomCount].
+    Custom(CustomCount),
+}
+
+impl CountingMethod {
+    pub fn system(&self) -> Box<dyn CountingSystem> {
+        match self {
+            CountingMethod::HiLo => Box::new(HiLo),
+            CountingMethod::KnockOut => Box::new(KnockOut),
+            CountingMethod::HiOptI => Box::new(HiOptI),
+            CountingMethod::HiOptII => Box::new(HiOptII),
+            CountingMethod::OmegaII => Box::new(OmegaII),
+            CountingMethod::Zen => Box::new(ZenCount),
+            CountingMethod::Custom(count) => Box::new(count.clone()),
+        }
+    }
+}
+
+/// Written as on the command line, a custom count as "custom:" and the path of its file.
+impl Display for CountingMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CountingMethod::HiLo => "hi-lo",
+            CountingMethod::KnockOut => "ko",
+            CountingMethod::HiOptI => "hi-opt-1",
+            CountingMethod::HiOptII => "hi-opt-2",
+            CountingMethod::OmegaII => "omega-2",
+            CountingMethod::Zen => "zen",
+            CountingMethod::Custom(count) => {
+                return write!(f, "custom:{}", count.path.display());
+            }
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CountingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hi-lo" => Ok(CountingMethod::HiLo),
+            "ko" => Ok(CountingMethod::KnockOut),
+            "hi-opt-1" => Ok(CountingMethod::HiOptI),
+            "hi-opt-2" => Ok(CountingMethod::HiOptII),
+            "omega-2" => Ok(CountingMethod::OmegaII),
+            "zen" => Ok(CountingMethod::Zen),
+            _ => match s.strip_prefix("custom:") {
+                Some(path) => CustomCount::load(Path::new(path))
+                    .map(CountingMethod::Custom)
+                    .map_err(|e| e.to_string()),
+                None => Err(format!(
+                    "expected one of 'hi-lo', 'ko', 'hi-opt-1', 'hi-opt-2', 'omega-2', 'zen' or 'custom:<file>', got '{}'",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+/// The effects of removal of the ranks from 2 to ace on the player's edge in percent, of a
+/// single deck game. How closely the tags of a count follow them is its betting correlation.
+const EFFECTS_OF_REMOVAL: [f64; 13] = [
+    0.38, 0.44, 0.55, 0.69, 0.46, 0.28, 0.0, -0.18, -0.51, -0.51, -0.51, -0.51, -0.61,
+];
+
+/// The correlation of the tags of the count with the effects of removal of the cards, over every
+/// rank of a deck, 1 for a count betting as well as a count can. With `ace_side_count` the aces
+/// count their weight against the count as they do when they are side counted.
+pub fn betting_correlation(system: &dyn CountingSystem, ace_side_count: bool) -> f64 {
+    let ace_weight = system.ace_weight().filter(|_| ace_side_count);
+    let tags = Value::ALL.map(|value| match (value, ace_weight) {
+        (Value::Ace, Some(weight)) => -weight as f64,
+        (value, _) => system.tag(&value) as f64,
+    });
+    correlation(&tags, &EFFECTS_OF_REMOVAL)
+}
+
+fn correlation(xs: &[f64], ys: &[f64]) -> f64 {
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let (x_mean, y_mean) = (mean(xs), mean(ys));
+    let (mut covariance, mut x_squares, mut y_squares) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        covariance += (x - x_mean) * (y - y_mean);
+        x_squares += (x - x_mean).powi(2);
+        y_squares += (y - y_mean).powi(2);
+    }
+    covariance / (x_squares * y_squares).sqrt()
+}
+
+/// Keeps the count of the cards seen since the shoe was last shuffled.
+pub struct CardCounter {
+    system: Box<dyn CountingSystem>,
+    decks: u32,
+    /// Whether the aces are side counted, for a count that leaves them out of its tags.
+    ace_side_count: bool,
+    deck_estimation: DeckEstimation,
+    /// The aces seen since the shuffle, for the side count.
+    pub aces_seen: u32,
+    pub running_count: i32,
+    /// The true count bets are sized on, with the aces left in the shoe when they are side
+    /// counted.
+    pub true_count: f32,
+}
+
+impl CardCounter {
+    pub fn new(system: Box<dyn CountingSystem>, decks: u32) -> Self {
+        let mut counter = Self {
+            system,
+            decks,
+            ace_side_count: false,
+            deck_estimation: DeckEstimation::default(),
+            aces_seen: 0,
+            running_count: 0,
+            true_count: 0f32,
+        };
+        counter.reset();
+        counter
+    }
+
+    /// Also keeps a side count of the aces, if the count leaves them out of its tags.
+    pub fn with_ace_side_count(mut self, ace_side_count: bool) -> Self {
+        self.ace_side_count = ace_side_count && self.system.ace_weight().is_some();
+        self
+    }
+
+    /// Estimates the decks left for the true count this closely instead of to the half deck.
+    pub fn with_deck_estimation(mut self, deck_estimation: DeckEstimation) -> Self {
+        self.deck_estimation = deck_estimation;
+        self
+    }
+
+    pub fn system(&self) -> &dyn CountingSystem {
+        self.system.as_ref()
+    }
+
+    /// The key count of an unbalanced count for the shoe.
+    pub fn key_count(&self) -> Option<i32> {
+        self.system.key_count(self.decks)
+    }
+
+    /// Counts a card as it is seen, with `cards_remaining` cards left in the shoe.
+    /// The decks remaining are estimated as a player would, to the nearest half deck unless
+    /// another [DeckEstimation] is set.
+    pub fn count(&mut self, card: &Card, cards_remaining: u32) {
+        self.running_count += self.system.tag(&card.value);
+        self.aces_seen += (card.value == Value::Ace) as u32;
+        let decks_remaining = self.deck_estimation.decks_remaining(cards_remaining);
+        self.true_count = self
+            .system
+            .true_count(self.betting_count(decks_remaining), decks_remaining);
+    }
+
+    /// The running count with the surplus of aces left in the shoe, or their shortage, added at
+    /// the weight of the count when they are side counted.
+    fn betting_count(&self, decks_remaining: f32) -> i32 {
+        match self.system.ace_weight().filter(|_| self.ace_side_count) {
+            Some(weight) => {
+                let aces_left = (ACES_PER_DECK * self.decks).saturating_sub(self.aces_seen);
+                let surplus = aces_left as f32 - ACES_PER_DECK as f32 * decks_remaining;
+                self.running_count + (weight as f32 * surplus).round() as i32
+            }
+            None => self.running_count,
+        }
+    }
+
+    /// Starts the count over for a freshly shuffled shoe.
+    pub fn reset(&mut self) {
+        self.aces_seen = 0;
+        self.running_count = self.system.initial_running_count(self.decks);
+        self.true_count = self
+            .system
+            .true_count(self.running_count, self.decks as f32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::counting::{
+        betting_correlation, CardCounter, CountingMethod, CountingSystem, CustomCount,
+        DeckEstimation, HiLo, HiOptI, KnockOut, OmegaII,
+    };
+    use crate::types::{Card, Deck, Suit, Value};
+    use crate::variants::Variant;
+    use std::path::Path;
+
+    fn card(value: Value) -> Card {
+        Card {
+            suit: Suit::Hearts,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_balanced_counts() {
+        let deck = Deck::new(Variant::Standard).unwrap();
+        for method in [
+            CountingMethod::HiLo,
+            CountingMethod::HiOptI,
+            CountingMethod::HiOptII,
+            CountingMethod::OmegaII,
+            CountingMethod::Zen,
+        ] {
+            let system = method.system();
+            let total = deck.cards.iter().map(|c| system.tag(&c.value)).sum::<i32>();
+            assert_eq!(total, 0, "{} isn't balanced", system.name());
+        }
+    }
+
+    #[test]
+    fn test_card_counter() {
+        let mut counter = CardCounter::new(Box::new(HiLo), 6);
+        counter.count(&card(Value::Five), 104);
+        counter.count(&card(Value::Two), 103);
+        counter.count(&card(Value::King), 102);
+        assert_eq!(counter.running_count, 1);
+        assert_eq!(counter.true_count, 0.5);
+
+        // The unbalanced count starts below zero and isn't converted
+        let mut counter = CardCounter::new(Box::new(KnockOut), 6);
+        assert_eq!(counter.running_count, -20);
+        counter.count(&card(Value::Seven), 104);
+        assert_eq!(counter.true_count, -19.0);
+        counter.reset();
+        assert_eq!(counter.running_count, -20);
+        assert_eq!(counter.key_count(), Some(-4));
+        assert_eq!(KnockOut.key_count(1), Some(2));
+        assert_eq!(KnockOut.key_count(2), Some(1));
+        assert_eq!(KnockOut.key_count(8), Some(-6));
+        assert_eq!(CardCounter::new(Box::new(HiLo), 6).key_count(), None);
+    }
+
+    #[test]
+    fn test_deck_estimation() {
+        // Two and a third decks left
+        let cards = 121;
+        assert!((DeckEstimation::Exact.decks_remaining(cards) - 121.0 / 52.0).abs() < 1e-6);
+        assert_eq!(DeckEstimation::QuarterDecks.decks_remaining(cards), 2.25);
+        assert_eq!(DeckEstimation::HalfDecks.decks_remaining(cards), 2.5);
+        assert_eq!(DeckEstimation::Decks.decks_remaining(cards), 2.0);
+        // Never nothing left to divide by
+        assert_eq!(DeckEstimation::QuarterDecks.decks_remaining(0), 0.25);
+        assert_eq!(DeckEstimation::HalfDecks.decks_remaining(5), 0.5);
+        assert_eq!(DeckEstimation::Decks.decks_remaining(40), 1.0);
+        assert!(DeckEstimation::Exact.decks_remaining(0) > 0.0);
+
+        let mut counter =
+            CardCounter::new(Box::new(HiLo), 6).with_deck_estimation(DeckEstimation::Decks);
+        counter.count(&card(Value::Five), 150);
+        counter.count(&card(Value::Two), 149);
+        assert_eq!(counter.true_count, 1.0);
+        assert_eq!("quarter-decks".parse(), Ok(DeckEstimation::QuarterDecks));
+        assert!("thirds".parse::<DeckEstimation>().is_err());
+    }
+
+    #[test]
+    fn test_ace_side_count() {
+        let mut counter = CardCounter::new(Box::new(HiOptI), 1).with_ace_side_count(true);
+        // A ten and three aces gone leave a single ace in what is still about a deck, three short
+        counter.count(&card(Value::Ten), 51);
+        for cards_remaining in [50, 49, 48] {
+            counter.count(&card(Value::Ace), cards_remaining);
+        }
+        assert_eq!(counter.running_count, -1);
+        assert_eq!(counter.true_count, -4.0);
+        counter.reset();
+        assert_eq!(counter.true_count, 0.0);
+
+        // Hi-Lo counts the aces already
+        let counter = CardCounter::new(Box::new(HiLo), 1).with_ace_side_count(true);
+        assert!(!counter.ace_side_count);
+        assert_eq!(OmegaII.level(), 2);
+        assert_eq!(KnockOut.level(), 1);
+    }
+
+    #[test]
+    fn test_betting_correlation() {
+        let correlation = |method: CountingMethod, side_count| {
+            betting_correlation(method.system().as_ref(), side_count)
+        };
+        // The published betting correlations: 0.97 for Hi-Lo, 0.88 for Hi-Opt I, 0.92 for Omega II
+        assert!((correlation(CountingMethod::HiLo, false) - 0.97).abs() < 0.01);
+        assert!((correlation(CountingMethod::HiOptI, false) - 0.88).abs() < 0.01);
+        assert!((correlation(CountingMethod::OmegaII, false) - 0.92).abs() < 0.01);
+        // Side counting the aces makes up for leaving them out
+        assert!(correlation(CountingMethod::HiOptI, true) > 0.94);
+        assert!(correlation(CountingMethod::OmegaII, true) > 0.98);
+        assert_eq!(
+            correlation(CountingMethod::HiLo, true),
+            correlation(CountingMethod::HiLo, false)
+        );
+    }
+
+    #[test]
+    fn test_custom_count() {
+        let ko = r#"
+            true_count = "running"
+            initial_count_per_deck = -4
+            key_count = -4
+
+            [tags]
+            2 = 1
+            3 = 1
+            4 = 1
+            5 = 1
+            6 = 1
+            7 = 1
+            10 = -1
+            K = -1
+            A = -1
+        "#;
+        let count = CustomCount::parse(ko, Path::new("counts/my-ko.toml")).unwrap();
+        assert_eq!(count.name(), "my-ko");
+        for value in Value::ALL {
+            assert_eq!(count.tag(&value), KnockOut.tag(&value));
+        }
+        let mut counter = CardCounter::new(Box::new(count.clone()), 6);
+        assert_eq!(counter.running_count, -24);
+        assert_eq!(counter.key_count(), Some(-4));
+        counter.count(&card(Value::Two), 300);
+        assert_eq!(counter.true_count, -23.0);
+
+        let half_decks = CustomCount::parse(
+            "name = 'Halves'\ntrue_count = 'half-decks'\n[tags]\n5 = 2\n10 = -2",
+            Path::new("halves.toml"),
+        )
+        .unwrap();
+        assert_eq!(half_decks.name(), "Halves");
+        assert_eq!(half_decks.true_count(4, 2.0), 1.0);
+        assert_eq!(half_decks.level(), 2);
+
+        let error = |text| CustomCount::parse(text, Path::new("bad.toml")).unwrap_err();
+        assert!(error("[tags]\n10 = -1\nK = -2").contains("ten valued"));
+        assert!(error("[tags]\n1 = 1").contains("rank"));
+        assert!(error("[tags]\n5 = 0").contains("at least one"));
+        assert!(error("colour = 'red'\n[tags]\n5 = 1").contains("unknown field"));
+
+        let path = std::env::temp_dir().join(format!("count-{}.toml", std::process::id()));
+        std::fs::write(&path, ko).unwrap();
+        let method = format!("custom:{}", path.display())
+            .parse::<CountingMethod>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let name = format!("count-{}", std::process::id());
+        assert_eq!(
+            method,
+            CountingMethod::Custom(CustomCount {
+                name,
+                path,
+                ..count
+            })
+        );
+        assert!(method.to_string().starts_with("custom:"));
+        assert!("custom:missing.toml".parse::<CountingMethod>().is_err());
+    }
+}
@@ -0,0 +1,665 @@
+use crate::chart::{upcard_column, ChartMove, StrategyChart, RANKS};
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::input::{hand_situation, BasicStrategy};
+use crate::outcomes::StartingHand;
+use crate::rules::HandOptions;
+use crate::types::{Move, Value};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+type ChartRows = BTreeMap<StartingHand, [ChartMove; RANKS]>;
+
+/// The cells of a hand in a TOML chart, either as one string or as a list of codes.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ChartLine {
+    Text(String),
+    Codes(Vec<String>),
+}
+
+/// A strategy chart as written in a TOML file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ChartFile {
+    name: Option<String>,
+    hands: BTreeMap<String, ChartLine>,
+}
+
+/// A strategy chart loaded from a CSV or TOML file, with a row for every hand it covers and the
+/// move against each upcard in the shorthand of printed charts.
+///
+/// A CSV chart starts with a header of the upcards, from 2 to A in any order, after a first
+/// column that labels the hands:
+///
+/// ```text
+/// hand,2,3,4,5,6,7,8,9,10,A
+/// 16,S,S,S,S,S,H,H,Rh,Rh,Rh
+/// "A,7",Ds,Ds,Ds,Ds,Ds,S,S,H,H,H
+/// 8,8,P,P,P,P,P,P,P,P,P,Rp
+/// ```
+///
+/// A TOML chart lists the moves from 2 to A under `[hands]`:
+///
+/// ```toml
+/// name = "My chart"
+///
+/// [hands]
+/// "16" = "S S S S S H H Rh Rh Rh"
+/// "A,7" = ["Ds", "Ds", "Ds", "Ds", "Ds", "S", "S", "H", "H", "H"]
+/// ```
+///
+/// Hands are labelled as hard totals from 4 to 21, or by their two cards like "A,7" or "8,8",
+/// also written "A7", "88" or "AA".
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomChart {
+    pub name: String,
+    pub path: PathBuf,
+    /// The moves of every hand of the chart against the upcards from 2 to ace.
+    pub rows: ChartRows,
+}
+
+impl CustomChart {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("cannot read {}: {}", path.display(), e)))?;
+        Self::parse(&text, path)
+            .map_err(|reason| Error::Config(format!("{}: {}", path.display(), reason)))
+    }
+
+    /// Reads the chart from the text of the file at `path`, as CSV or TOML by its extension.
+    pub fn parse(text: &str, path: &Path) -> Result<Self, String> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let (name, rows) = match extension.to_ascii_lowercase().as_str() {
+            "csv" => (None, parse_csv(text)?),
+            "toml" => parse_toml(text)?,
+            _ => {
+                return Err(format!(
+                    "expected a chart in a .csv or .toml file, got '{}'",
+                    path.display()
+                ))
+            }
+        };
+        if rows.is_empty() {
+            return Err("the chart has no hands".to_string());
+        }
+        let name = name.unwrap_or_else(|| {
+            path.file_stem().map_or("chart".to_string(), |stem| {
+                stem.to_string_lossy().to_string()
+            })
+        });
+        Ok(Self {
+            name,
+            path: path.to_path_buf(),
+            rows,
+        })
+    }
+
+    /// The chart of the rows of `chart`, e.g. to save a chart that was worked out.
+    pub fn from_chart(name: &str, chart: &StrategyChart) -> Self {
+        let rows = chart
+            .hard
+            .iter()
+            .chain(&chart.soft)
+            .chain(&chart.pairs)
+            .filter_map(|row| {
+                let hand = parse_hand(&row.label).ok()?;
+                let mut cells = [ChartMove::Hit; RANKS];
+                for (cell, chart_cell) in cells.iter_mut().zip(&row.cells) {
+                    *cell = chart_cell.play;
+                }
+                Some((hand, cells))
+            })
+            .collect();
+        Self {
+            name: name.to_string(),
+            path: PathBuf::new(),
+            rows,
+        }
+    }
+
+    /// Writes the chart to `path` as CSV or TOML by its extension, to be loaded again with
+    /// [CustomChart::load].
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let row = |cells: &[ChartMove; RANKS], separator: &str| {
+            cells
+                .iter()
+                .map(|cell| cell.to_string())
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        let text = match extension.to_ascii_lowercase().as_str() {
+            "csv" => {
+                let mut text = format!("hand,{}\n", StrategyChart::UPCARDS.join(","));
+                for (hand, cells) in &self.rows {
+                    text += &format!("\"{}\",{}\n", hand, row(cells, ","));
+                }
+                text
+            }
+            "toml" => {
+                let mut text = format!("name = {:?}\n\n[hands]\n", self.name);
+                for (hand, cells) in &self.rows {
+                    text += &format!("\"{}\" = \"{}\"\n", hand, row(cells, " "));
+                }
+                text
+            }
+            _ => {
+                return Err(Error::Config(format!(
+                    "expected a chart in a .csv or .toml file, got '{}'",
+                    path.display()
+                )))
+            }
+        };
+        std::fs::write(path, text)
+            .map_err(|e| Error::Config(format!("cannot write {}: {}", path.display(), e)))
+    }
+
+    /// The move of the chart against the upcard, counting an ace as 11. A pair is played from
+    /// its own row unless that says to split where splitting isn't allowed, every other hand
+    /// from the row of its total, so hands of more cards are played like the two card hands of
+    /// the same total. `None` if the chart doesn't cover the hand or none of its moves is allowed.
+    pub fn choose(
+        &self,
+        value: u32,
+        soft: bool,
+        pair: Option<u32>,
+        upcard: u32,
+        allowed: impl Fn(Move) -> bool,
+    ) -> Option<Move> {
+        let column = match upcard {
+            Value::ACE_HIGH_VAL => RANKS - 1,
+            upcard => upcard as usize - 2,
+        };
+        let play = |hand| {
+            let cell: &ChartMove = &self.rows.get(&hand)?[column];
+            cell.moves().iter().copied().find(|m| allowed(*m))
+        };
+        let total = if soft {
+            StartingHand::Soft(value - Value::ACE_HIGH_VAL)
+        } else {
+            StartingHand::Hard(value)
+        };
+        pair.and_then(|card| play(StartingHand::Pair(card)))
+            .or_else(|| play(total))
+    }
+
+    /// The move of the chart for the hand at `hand`, basic strategy for the hands it doesn't
+    /// cover.
+    pub fn decide(&self, engine: &GameEngine, hand: usize, options: &HandOptions) -> Move {
+        let (value, soft, pair, upcard) = hand_situation(engine, hand);
+        self.choose(value, soft, pair, upcard, |m| options.moves.contains(&m))
+            .unwrap_or_else(|| BasicStrategy::decide(engine, hand, options))
+    }
+}
+
+/// How a chart is laid out for printing or sharing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartFormat {
+    /// Aligned columns in a box of plain characters, for a terminal or a text file.
+    Ascii,
+    Markdown,
+    /// A page of its own, with the cells colored by the move.
+    Html,
+}
+
+impl Display for ChartFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ChartFormat::Ascii => "ascii",
+                ChartFormat::Markdown => "markdown",
+                ChartFormat::Html => "html",
+            }
+        )
+    }
+}
+
+impl FromStr for ChartFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ascii" => Ok(ChartFormat::Ascii),
+            "markdown" | "md" => Ok(ChartFormat::Markdown),
+            "html" => Ok(ChartFormat::Html),
+            _ => Err(format!(
+                "expected one of 'ascii', 'markdown' or 'html', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// What the shorthand of the moves means, under every exported chart.
+const LEGEND: &str = "H hit, S stand, P split, D double (or hit), Ds double (or stand), \
+    Rh, Rs and Rp surrender (or hit, stand and split)";
+
+impl CustomChart {
+    /// The chart as tables of the hard totals, the soft hands and the pairs it covers, with the
+    /// moves against the upcards from 2 to A and a legend of the moves.
+    pub fn render(&self, format: ChartFormat) -> String {
+        let section = |hand: &StartingHand| match hand {
+            StartingHand::Hard(_) => "Hard",
+            StartingHand::Soft(_) => "Soft",
+            StartingHand::Pair(_) => "Pairs",
+        };
+        let sections = ["Hard", "Soft", "Pairs"].into_iter().filter_map(|name| {
+            let rows = self
+                .rows
+                .iter()
+                .filter(|(hand, _)| section(hand) == name)
+                .collect::<Vec<_>>();
+            (!rows.is_empty()).then_some((name, rows))
+        });
+        // A row of the ASCII and Markdown tables, the label and then a cell for every upcard
+        let line = |label: &str, cells: &mut dyn Iterator<Item = String>| {
+            let cells = cells
+                .map(|cell| format!(" {:>2} |", cell))
+                .collect::<String>();
+            format!("| {:<5} |{}\n", label, cells)
+        };
+        let header = |name: &str| {
+            line(
+                name,
+                &mut StrategyChart::UPCARDS.iter().map(|u| u.to_string()),
+            )
+        };
+        let row = |hand: &StartingHand, cells: &[ChartMove; RANKS]| {
+            line(
+                &hand.to_string(),
+                &mut cells.iter().map(|cell| cell.to_string()),
+            )
+        };
+        match format {
+            ChartFormat::Ascii => {
+                let border = format!("+{}+{}\n", "-".repeat(7), "----+".repeat(RANKS));
+                let mut text = format!("{}\n", self.name);
+                for (name, rows) in sections {
+                    text += &format!("\n{}{}{}", border, header(name), border);
+                    for (hand, cells) in rows {
+                        text += &row(hand, cells);
+                    }
+                    text += &border;
+                }
+                text + &format!("\n{}\n", LEGEND)
+            }
+            ChartFormat::Markdown => {
+                let mut text = format!("# {}\n", self.name);
+                for (name, rows) in sections {
+                    text += &format!("\n{}|:------|{}\n", header(name), ":--:|".repeat(RANKS));
+                    for (hand, cells) in rows {
+                        text += &row(hand, cells);
+                    }
+                }
+                text + &format!("\n{}\n", LEGEND)
+            }
+            ChartFormat::Html => {
+                let name = html_escape(&self.name);
+                let mut text = format!(
+                    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+                     <title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+                    name, HTML_STYLE, name
+                );
+                for (name, rows) in sections {
+                    text += &format!("<table>\n<tr><th>{}</th>", name);
+                    for upcard in StrategyChart::UPCARDS {
+                        text += &format!("<th>{}</th>", upcard);
+                    }
+                    text += "</tr>\n";
+                    for (hand, cells) in rows {
+                        text += &format!("<tr><th>{}</th>", hand);
+                        for cell in cells.iter() {
+                            text += &format!(
+                                "<td class=\"{}\">{}</td>",
+                                cell.to_string().to_ascii_lowercase(),
+                                cell
+                            );
+                        }
+                        text += "</tr>\n";
+                    }
+                    text += "</table>\n";
+                }
+                text + &format!("<p>{}</p>\n</body>\n</html>\n", LEGEND)
+            }
+        }
+    }
+}
+
+/// The colors of the cells of an HTML chart by their move, as printed charts usually have them.
+const HTML_STYLE: &str = "body { font-family: sans-serif; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #444; padding: 2px 8px; text-align: center; }
+td.h { background: #ffffff; }
+td.s { background: #f4d03f; }
+td.d, td.ds { background: #58d68d; }
+td.p { background: #5dade2; }
+td.rh, td.rs, td.rp { background: #ec7063; }
+@media print { td { -webkit-print-color-adjust: exact; print-color-adjust: exact; } }
+";
+
+/// Escapes the characters with a meaning in HTML, for the name of a chart.
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Reads a hand label, a hard total or two cards with or without a comma between them. The
+/// numbers above 21 are taken as pairs, e.g. "88" or "1010".
+fn parse_hand(label: &str) -> Result<StartingHand, String> {
+    let label = label.trim();
+    if let Ok(total @ 4..=21) = label.parse::<u32>() {
+        return Ok(StartingHand::Hard(total));
+    }
+    if !label.contains(',') && label.is_ascii() && label.len() >= 2 {
+        for middle in 1..label.len() {
+            let (first, second) = label.split_at(middle);
+            if let (Ok(first), Ok(second)) = (first.parse::<Value>(), second.parse::<Value>()) {
+                return Ok(StartingHand::from_cards(&first, &second));
+            }
+        }
+    }
+    label.parse().map_err(|_| {
+        format!(
+            "expected a hard total from 4 to 21 or two cards, e.g. A,7 or 8,8, got '{}'",
+            label
+        )
+    })
+}
+
+/// Reads the moves of one hand against the upcards in `columns`.
+fn parse_cells<'a>(
+    codes: impl Iterator<Item = &'a str>,
+    columns: &[usize],
+) -> Result<[ChartMove; RANKS], String> {
+    let codes = codes.collect::<Vec<_>>();
+    if codes.len() != columns.len() {
+        return Err(format!(
+            "expected a move against each of the {} upcards, got {}",
+            columns.len(),
+            codes.len()
+        ));
+    }
+    let mut cells = [ChartMove::Hit; RANKS];
+    for (code, column) in codes.into_iter().zip(columns) {
+        cells[*column] = code.parse()?;
+    }
+    Ok(cells)
+}
+
+/// Adds a row, refusing a hand that was already given.
+fn add_row(
+    rows: &mut ChartRows,
+    hand: StartingHand,
+    cells: [ChartMove; RANKS],
+) -> Result<(), String> {
+    if rows.insert(hand, cells).is_some() {
+        return Err(format!("the hand {} is in the chart twice", hand));
+    }
+    Ok(())
+}
+
+/// Splits a line of CSV into its fields, a field in double quotes may hold commas.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+        .iter()
+        .map(|field| field.trim().to_string())
+        .collect()
+}
+
+fn parse_csv(text: &str) -> Result<ChartRows, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+    let Some((number, header)) = lines.next() else {
+        return Err("the chart is empty".to_string());
+    };
+    let mut columns = vec![];
+    for label in csv_fields(header).iter().skip(1) {
+        let upcard = label
+            .parse::<Value>()
+            .map_err(|e| format!("line {}: {}", number, e))?;
+        let column = upcard_column(&upcard);
+        if columns.contains(&column) {
+            return Err(format!(
+                "line {}: the upcard {} is there twice",
+                number, label
+            ));
+        }
+        columns.push(column);
+    }
+    if columns.len() != RANKS {
+        return Err(format!(
+            "line {}: expected a column for each upcard from 2 to A",
+            number
+        ));
+    }
+    let mut rows = BTreeMap::new();
+    for (number, line) in lines {
+        let fields = csv_fields(line);
+        // Pairs written as 8,8 without quotes take an extra field, the label is what's left
+        let (label, codes) = fields.split_at(fields.len().saturating_sub(RANKS).max(1));
+        let hand = parse_hand(&label.join(",")).map_err(|e| format!("line {}: {}", number, e))?;
+        let cells = parse_cells(codes.iter().map(String::as_str), &columns)
+            .map_err(|e| format!("line {}: {}", number, e))?;
+        add_row(&mut rows, hand, cells).map_err(|e| format!("line {}: {}", number, e))?;
+    }
+    Ok(rows)
+}
+
+fn parse_toml(text: &str) -> Result<(Option<String>, ChartRows), String> {
+    let file = toml::from_str::<ChartFile>(text).map_err(|e| e.message().to_string())?;
+    let columns = (0..RANKS).collect::<Vec<_>>();
+    let mut rows = BTreeMap::new();
+    for (label, line) in &file.hands {
+        let hand = parse_hand(label)?;
+        let cells = match line {
+            ChartLine::Text(text) => parse_cells(
+                text.split([' ', ',', '\t']).filter(|c| !c.is_empty()),
+                &columns,
+            ),
+            ChartLine::Codes(codes) => parse_cells(codes.iter().map(String::as_str), &columns),
+        }
+        .map_err(|e| format!("{}: {}", label, e))?;
+        add_row(&mut rows, hand, cells)?;
+    }
+    Ok((file.name, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chart::{generate_chart, ChartMove};
+    use crate::config::GameConfig;
+    use crate::custom_chart::{ChartFormat, CustomChart};
+    use crate::input::Strategy;
+    use crate::outcomes::StartingHand;
+    use crate::rules::Rules;
+    use crate::simulation::{simulate, NoProgress};
+    use crate::types::Move;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_chart() {
+        let csv = "# Upcards in the wrong order\n\
+            hand,A,2,3,4,5,6,7,8,9,10\n\
+            16,Rh,S,S,S,S,S,H,H,Rh,Rh\n\
+            \"A,7\",H,S,Ds,Ds,Ds,Ds,S,S,H,H\n\
+            8,8,Rp,P,P,P,P,P,P,P,P,P\n\
+            AA,P,P,P,P,P,P,P,P,P,P\n";
+        let chart = CustomChart::parse(csv, Path::new("charts/mine.csv")).unwrap();
+        assert_eq!(chart.name, "mine");
+        assert_eq!(chart.rows.len(), 4);
+        let sixteen = chart.rows[&StartingHand::Hard(16)];
+        assert_eq!(sixteen[0], ChartMove::Stand);
+        assert_eq!(sixteen[9], ChartMove::SurrenderOrHit);
+        assert_eq!(
+            chart.rows[&StartingHand::Soft(7)][1],
+            ChartMove::DoubleOrStand
+        );
+        assert_eq!(
+            chart.rows[&StartingHand::Pair(8)][9],
+            ChartMove::SurrenderOrSplit
+        );
+        assert!(chart.rows.contains_key(&StartingHand::Pair(11)));
+
+        let toml = r#"
+            name = "Stiffs"
+
+            [hands]
+            "16" = "S S S S S H H Rh Rh Rh"
+            A6 = ["H", "D", "D", "D", "D", "H", "H", "H", "H", "H"]
+            1010 = "S,S,S,S,S,S,S,S,S,S"
+        "#;
+        let chart = CustomChart::parse(toml, Path::new("stiffs.toml")).unwrap();
+        assert_eq!(chart.name, "Stiffs");
+        assert_eq!(
+            chart.rows[&StartingHand::Soft(6)][1],
+            ChartMove::DoubleOrHit
+        );
+        assert_eq!(chart.rows[&StartingHand::Pair(10)][0], ChartMove::Stand);
+
+        let error = |text, file| CustomChart::parse(text, Path::new(file)).unwrap_err();
+        assert!(error(toml, "chart.txt").contains(".csv or .toml"));
+        assert!(error("hand,2,3\n", "short.csv").contains("each upcard"));
+        assert!(error("[hands]\n16 = 'S S X S S H H H H H'", "bad.toml").contains("'X'"));
+        assert!(error("[hands]\n16 = 'S S'", "bad.toml").contains("10 upcards"));
+        assert!(error("[hands]\n30 = 'S S S S S S S S S S'", "bad.toml").contains("'30'"));
+        assert!(error(
+            "[hands]\n16 = 'H H H H H H H H H H'\n106 = 'S S S S S S S S S S'",
+            "bad.toml"
+        )
+        .contains("16"));
+        assert!(error("[hands]", "empty.toml").contains("no hands"));
+    }
+
+    #[test]
+    fn test_chart_moves() {
+        let toml = r#"
+            [hands]
+            "11" = "D D D D D D D D D H"
+            "16" = "S S S S S H H Rh Rh Rh"
+            "A,7" = "S Ds Ds Ds Ds S S H H H"
+            "8,8" = "P P P P P P P P P Rp"
+        "#;
+        let chart = CustomChart::parse(toml, Path::new("chart.toml")).unwrap();
+        let all = |_| true;
+        let no_double = |m| m != Move::Double;
+        assert_eq!(chart.choose(11, false, None, 6, all), Some(Move::Double));
+        // Doubling after hitting isn't allowed, so a three card 11 hits
+        assert_eq!(chart.choose(11, false, None, 6, no_double), Some(Move::Hit));
+        assert_eq!(
+            chart.choose(18, true, None, 3, no_double),
+            Some(Move::Stand)
+        );
+        assert_eq!(
+            chart.choose(16, false, None, 11, all),
+            Some(Move::Surrender)
+        );
+        assert_eq!(
+            chart.choose(16, false, Some(8), 11, all),
+            Some(Move::Surrender)
+        );
+        // A pair that can't be split any more is played as its total
+        let no_split = |m| m != Move::Split && m != Move::Surrender;
+        assert_eq!(
+            chart.choose(16, false, Some(8), 11, no_split),
+            Some(Move::Hit)
+        );
+        // Hands outside the chart are left to basic strategy
+        assert_eq!(chart.choose(12, false, None, 4, all), None);
+    }
+
+    #[test]
+    fn test_chart_bot() {
+        let generated = generate_chart(&Rules::default()).unwrap();
+        let chart = CustomChart::from_chart("generated", &generated);
+        assert_eq!(chart.rows.len(), 15 + 8 + 10);
+        for extension in ["csv", "toml"] {
+            let path =
+                std::env::temp_dir().join(format!("chart-{}.{}", std::process::id(), extension));
+            chart.save(&path).unwrap();
+            let loaded = CustomChart::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(loaded.rows, chart.rows);
+        }
+
+        let conf = GameConfig::default();
+        assert!(simulate(
+            conf.clone(),
+            &mut Strategy::Chart,
+            10,
+            Some(5),
+            &mut NoProgress
+        )
+        .is_err());
+        let basic = simulate(
+            conf.clone(),
+            &mut Strategy::Basic,
+            50_000,
+            Some(5),
+            &mut NoProgress,
+        )
+        .unwrap();
+        let conf = GameConfig {
+            chart: Some(chart),
+            ..conf
+        };
+        let charted =
+            simulate(conf, &mut Strategy::Chart, 50_000, Some(5), &mut NoProgress).unwrap();
+        // The generated chart is basic strategy, give or take a close call
+        assert!((charted.player_edge() - basic.player_edge()).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_render_chart() {
+        let toml = r#"
+            name = "Stiffs & pairs"
+
+            [hands]
+            "16" = "S S S S S H H Rh Rh Rh"
+            "8,8" = "P P P P P P P P P Rp"
+        "#;
+        let chart = CustomChart::parse(toml, Path::new("stiffs.toml")).unwrap();
+        let ascii = chart.render(ChartFormat::Ascii);
+        assert!(ascii.starts_with("Stiffs & pairs\n"));
+        assert!(ascii.contains("| Hard  |  2 |  3 |  4 |  5 |  6 |  7 |  8 |  9 | 10 |  A |\n"));
+        assert!(ascii.contains("| 16    |  S |  S |  S |  S |  S |  H |  H | Rh | Rh | Rh |\n"));
+        assert!(ascii.contains("| 8,8   |  P |"));
+        // Only the kinds of hands in the chart get a table
+        assert!(!ascii.contains("Soft"));
+        let widths = ascii
+            .lines()
+            .filter(|line| line.starts_with(['|', '+']))
+            .map(|line| line.chars().count())
+            .collect::<Vec<_>>();
+        assert!(widths.iter().all(|width| *width == widths[0]));
+
+        let markdown = chart.render(ChartFormat::Markdown);
+        assert!(markdown.starts_with("# Stiffs & pairs\n"));
+        assert!(markdown.contains("|:------|:--:|"));
+        let html = chart.render(ChartFormat::Html);
+        assert!(html.contains("<h1>Stiffs &amp; pairs</h1>"));
+        assert!(html.contains("<td class=\"rh\">Rh</td>"));
+        assert_eq!(html.matches("<table>").count(), 2);
+
+        assert_eq!("md".parse(), Ok(ChartFormat::Markdown));
+        assert!("pdf".parse::<ChartFormat>().is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::chart::{
+    dealer_results, shoe_odds, upcard_column, StrategyChart, DEALER_RESULTS, MAX_TOTAL, RANKS,
+};
+use crate::error::Error;
+use crate::rules::Rules;
+use crate::shuffle::FisherYates;
+use crate::simulation::Progress;
+use crate::types::{Hand, Shoe};
+use serde::Serialize;
+
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// The dealer's results in the order of the table: the final totals from 17 to 21, a blackjack
+/// and a bust.
+pub const DEALER_OUTCOMES: usize = DEALER_RESULTS + 1;
+
+/// The index of a dealer blackjack among the results.
+const BLACKJACK: usize = DEALER_OUTCOMES - 2;
+
+/// The chances of the dealer's results behind one upcard.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DealerRow {
+    pub upcard: String,
+    /// The chance of each result, in the order of [DealerTable::OUTCOMES].
+    pub chances: [f64; DEALER_OUTCOMES],
+}
+
+impl DealerRow {
+    pub fn blackjack(&self) -> f64 {
+        self.chances[BLACKJACK]
+    }
+
+    pub fn bust(&self) -> f64 {
+        self.chances[DEALER_OUTCOMES - 1]
+    }
+
+    /// The chances once the dealer has peeked and found no blackjack.
+    pub fn without_blackjack(&self) -> [f64; DEALER_OUTCOMES] {
+        let rest = 1.0 - self.blackjack();
+        let mut chances = self.chances.map(|chance| chance / rest);
+        chances[BLACKJACK] = 0.0;
+        chances
+    }
+}
+
+/// How the dealer's hand ends up behind every upcard, from 2 to ace.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DealerTable {
+    pub rows: Vec<DealerRow>,
+    /// The number of hands dealt to work out the table, `None` if it is exact.
+    pub hands: Option<u64>,
+}
+
+impl DealerTable {
+    /// The labels of the results, in the order of the chances of every row.
+    pub const OUTCOMES: [&'static str; DEALER_OUTCOMES] =
+        ["17", "18", "19", "20", "21", "BJ", "Bust"];
+}
+
+/// Works out the exact chance of each result of the dealer's hand behind every upcard, drawing
+/// to the rules with every card dealt with the odds of a full shoe, as for [generate_chart].
+///
+/// [generate_chart]: crate::chart::generate_chart
+pub fn dealer_table(rules: &Rules) -> Result<DealerTable, Error> {
+    rules.validate()?;
+    let odds = shoe_odds(rules)?;
+    let mut finals = vec![[None; 2]; MAX_TOTAL + 1];
+    let rows = (2..=RANKS)
+        .chain([1])
+        .zip(StrategyChart::UPCARDS)
+        .map(|(upcard, label)| {
+            let mut chances = [0.0; DEALER_OUTCOMES];
+            for hole in 1..=RANKS {
+                if upcard + hole == 11 && (upcard == 1 || hole == 1) {
+                    chances[BLACKJACK] += odds[hole];
+                    continue;
+                }
+                let results = dealer_results(
+                    rules,
+                    &odds,
+                    upcard + hole,
+                    upcard == 1 || hole == 1,
+                    &mut finals,
+                );
+                for (i, result) in results.into_iter().enumerate() {
+                    // Busts go after the blackjacks
+                    let i = if i < DEALER_RESULTS - 1 { i } else { i + 1 };
+                    chances[i] += odds[hole] * result;
+                }
+            }
+            DealerRow {
+                upcard: label.to_string(),
+                chances,
+            }
+        })
+        .collect();
+    Ok(DealerTable { rows, hands: None })
+}
+
+/// Deals `hands` dealer hands one after another from a shuffled shoe, reshuffling at the cut card,
+/// and counts how each one ends behind its upcard. Unlike [dealer_table] the cards already dealt
+/// from the shoe change the odds, as they do at the table. With a `seed` the same shoes are
+/// dealt every time.
+pub fn simulate_dealer_table(
+    rules: &Rules,
+    hands: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<DealerTable, Error> {
+    rules.validate()?;
+    let mut shuffler = seed.map_or_else(FisherYates::new, FisherYates::seeded);
+    let mut shoe = Shoe::new(rules.deck_count, rules.variant)?;
+    let mut shuffle = |shoe: Shoe| {
+        let mut shoe = shoe.shuffle(&mut shuffler);
+        shoe.place_cut_card(rules.cut_card_position(shoe.num_cards()));
+        shoe
+    };
+    shoe = shuffle(shoe);
+    let mut counts = [[0u64; DEALER_OUTCOMES]; RANKS];
+    for dealt in 1..=hands {
+        let mut hand = Hand::from_card(shoe.take_card()?);
+        hand.add_card(shoe.take_card()?);
+        let outcome = if hand.is_blackjack() {
+            BLACKJACK
+        } else {
+            while rules.dealer_hits(&hand) {
+                hand.add_card(shoe.take_card()?);
+            }
+            match hand.calc_value() {
+                value @ 17..=21 => value as usize - 17,
+                _ => DEALER_OUTCOMES - 1,
+            }
+        };
+        counts[upcard_column(&hand.cards[0].value)][outcome] += 1;
+        shoe.discard(hand.cards);
+        // A shoe without a cut card is reshuffled once it can no longer deal a full hand
+        if shoe.cut_card_reached || shoe.num_cards() < MAX_TOTAL as u32 {
+            shoe = shuffle(shoe);
+        }
+        if dealt % PROGRESS_INTERVAL == 0 {
+            progress.update(dealt, hands);
+        }
+    }
+    progress.update(hands, hands);
+
+    let rows = counts
+        .iter()
+        .zip(StrategyChart::UPCARDS)
+        .map(|(counts, label)| {
+            let dealt = counts.iter().sum::<u64>().max(1);
+            DealerRow {
+                upcard: label.to_string(),
+                chances: counts.map(|count| count as f64 / dealt as f64),
+            }
+        })
+        .collect();
+    Ok(DealerTable {
+        rows,
+        hands: Some(hands),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dealer::{dealer_table, simulate_dealer_table};
+    use crate::rules::Rules;
+    use crate::simulation::NoProgress;
+
+    #[test]
+    fn test_dealer_table() {
+        let rules = Rules::default();
+        let table = dealer_table(&rules).unwrap();
+        assert_eq!(table.rows.len(), 10);
+        for row in &table.rows {
+            assert!((row.chances.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        // The well known bust chances of an infinite shoe, S17: 35.3% behind a 2, 42.3% behind a
+        // 6 and 11.5% behind an ace
+        assert!((table.rows[0].bust() - 0.353).abs() < 0.002);
+        assert!((table.rows[4].bust() - 0.423).abs() < 0.002);
+        assert!((table.rows[9].bust() - 0.115).abs() < 0.002);
+        assert_eq!(table.rows[0].blackjack(), 0.0);
+        assert!((table.rows[9].blackjack() - 96.0 / 312.0).abs() < 1e-9);
+        assert!(table.rows[9].without_blackjack()[5] == 0.0);
+        assert!((table.rows[9].without_blackjack().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+        // Hitting soft 17 busts more often behind an ace
+        let h17 = Rules {
+            hit_soft_17: true,
+            ..Rules::default()
+        };
+        assert!(dealer_table(&h17).unwrap().rows[9].bust() > table.rows[9].bust());
+
+        let simulated = simulate_dealer_table(&rules, 200_000, Some(1), &mut NoProgress).unwrap();
+        assert_eq!(simulated.hands, Some(200_000));
+        for (row, exact) in simulated.rows.iter().zip(&table.rows) {
+            assert!((row.bust() - exact.bust()).abs() < 0.02);
+        }
+    }
+}
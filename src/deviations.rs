@@ -0,0 +1,303 @@
+use crate::betting::BetRamp;
+use crate::counting::KnockOut;
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::input::{hand_situation, BasicStrategy, PlayerInput};
+use crate::money::Money;
+use crate::rules::{HandOptions, Rules};
+use crate::side_bets::SideBet;
+use crate::types::Move;
+
+/// The true count from which insurance, and even money, is taken.
+pub const INSURANCE_INDEX: f32 = 3.0;
+
+/// The Illustrious 18 after insurance, the index plays worth the most with Hi-Lo in a multi-deck
+/// game: (hard total, upcard, true count, the move from the true count up, the move below it).
+/// A pair of tens is the hard 20 that is split.
+pub const ILLUSTRIOUS_18: [(u32, u32, f32, Move, Move); 17] = [
+    (16, 10, 0.0, Move::Stand, Move::Hit),
+    (15, 10, 4.0, Move::Stand, Move::Hit),
+    (20, 5, 5.0, Move::Split, Move::Stand),
+    (20, 6, 4.0, Move::Split, Move::Stand),
+    (10, 10, 4.0, Move::Double, Move::Hit),
+    (12, 3, 2.0, Move::Stand, Move::Hit),
+    (12, 2, 3.0, Move::Stand, Move::Hit),
+    (11, 11, 1.0, Move::Double, Move::Hit),
+    (9, 2, 1.0, Move::Double, Move::Hit),
+    (10, 11, 4.0, Move::Double, Move::Hit),
+    (9, 7, 3.0, Move::Double, Move::Hit),
+    (16, 9, 5.0, Move::Stand, Move::Hit),
+    (13, 2, -1.0, Move::Stand, Move::Hit),
+    (12, 4, 0.0, Move::Stand, Move::Hit),
+    (12, 5, -2.0, Move::Stand, Move::Hit),
+    (12, 6, -1.0, Move::Stand, Move::Hit),
+    (13, 3, -2.0, Move::Stand, Move::Hit),
+];
+
+/// The Fab 4 surrenders: (hard total, upcard, the true count they are surrendered from).
+pub const FAB_4: [(u32, u32, f32); 4] = [(14, 10, 3.0), (15, 10, 0.0), (15, 9, 2.0), (15, 11, 1.0)];
+
+/// A card counting bot: basic strategy with the Illustrious 18 and Fab 4 deviations at the true
+/// count, and bets ramped on the true count, the bet ramp (--bet-ramp) if there is one or else
+/// [HiLoBot::ramp]. The indices are the Hi-Lo indices of a game where the dealer stands on soft 17,
+/// they are looked up with the true count of the counting system at the table, divided by its
+/// level to bring a level two count down to about the Hi-Lo count.
+///
+/// With an unbalanced count such as KO it plays the rookie system instead: basic strategy,
+/// without insurance, and bets going up from the key count to the most at the pivot.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HiLoBot;
+
+impl HiLoBot {
+    /// The most units bet, the top of the spread.
+    pub const MAX_UNITS: u64 = 8;
+
+    /// The true count at the table on the scale of the Hi-Lo count.
+    pub fn true_count(engine: &GameEngine) -> f32 {
+        let counter = &engine.counter;
+        counter.true_count / counter.system().level() as f32
+    }
+
+    /// Whether insurance is worth taking at the count.
+    pub fn insures(engine: &GameEngine) -> bool {
+        engine.counter.key_count().is_none() && Self::true_count(engine) >= INSURANCE_INDEX
+    }
+
+    /// The 1 to 8 spread bet without a bet ramp: a unit up to a true count of 2, then 2, 4, 6
+    /// and 8 units from 2, 3, 4 and 5.
+    pub fn ramp() -> BetRamp {
+        BetRamp {
+            steps: vec![(2.0, 2), (3.0, 4), (4.0, 6), (5.0, Self::MAX_UNITS)],
+        }
+    }
+
+    /// The move with the deviations at the true count of the table, as [BasicStrategy::decide]
+    /// finds it otherwise.
+    pub fn decide(engine: &GameEngine, hand: usize, options: &HandOptions) -> Move {
+        if engine.counter.key_count().is_some() {
+            return BasicStrategy::decide(engine, hand, options);
+        }
+        let (value, soft, pair, upcard) = hand_situation(engine, hand);
+        Self::choose(
+            &engine.conf.rules,
+            value,
+            soft,
+            pair,
+            upcard,
+            Self::true_count(engine),
+            |m| options.moves.contains(&m),
+        )
+        .unwrap_or(options.moves[0])
+    }
+
+    /// The index play at the true count of the table where it differs from the basic strategy
+    /// move, `None` where basic strategy is right at the count.
+    pub fn deviation(engine: &GameEngine, hand: usize, options: &HandOptions) -> Option<Move> {
+        let play = Self::decide(engine, hand, options);
+        (play != BasicStrategy::decide(engine, hand, options)).then_some(play)
+    }
+
+    /// The move of [BasicStrategy::choose] with the index plays at `true_count`.
+    pub fn choose(
+        rules: &Rules,
+        value: u32,
+        soft: bool,
+        pair: Option<u32>,
+        upcard: u32,
+        true_count: f32,
+        allowed: impl Fn(Move) -> bool,
+    ) -> Option<Move> {
+        if soft {
+            return BasicStrategy::choose(rules, value, soft, pair, upcard, allowed);
+        }
+        let surrender = FAB_4
+            .iter()
+            .find(|(total, card, _)| *total == value && *card == upcard);
+        match surrender {
+            Some((.., index)) if allowed(Move::Surrender) && true_count >= *index => {
+                Some(Move::Surrender)
+            }
+            // Below its index the hand is played as if it couldn't be surrendered
+            Some(_) => Self::play(rules, value, pair, upcard, true_count, |m| {
+                m != Move::Surrender && allowed(m)
+            }),
+            None => Self::play(rules, value, pair, upcard, true_count, allowed),
+        }
+    }
+
+    /// The index play of a hard hand if one of them is allowed, or else the basic strategy move.
+    fn play(
+        rules: &Rules,
+        value: u32,
+        pair: Option<u32>,
+        upcard: u32,
+        true_count: f32,
+        allowed: impl Fn(Move) -> bool,
+    ) -> Option<Move> {
+        let basic = BasicStrategy::choose(rules, value, false, pair, upcard, &allowed);
+        if basic == Some(Move::Surrender) {
+            return basic;
+        }
+        ILLUSTRIOUS_18
+            .iter()
+            .filter(|(total, card, ..)| *total == value && *card == upcard)
+            .map(|(.., index, above, below)| if true_count >= *index { *above } else { *below })
+            .find(|m| allowed(*m))
+            .or(basic)
+    }
+}
+
+impl PlayerInput for HiLoBot {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        BasicStrategy.num_hands(engine)
+    }
+
+    fn bet(
+        &mut self,
+        engine: &GameEngine,
+        _hand: usize,
+        _num_hands: usize,
+    ) -> Result<Money, Error> {
+        let conf = &engine.conf;
+        let true_count = HiLoBot::true_count(engine);
+        let ramp = match (&conf.bet_ramp, engine.counter.key_count()) {
+            (Some(ramp), _) => ramp.clone(),
+            (None, Some(key)) => BetRamp::key_count(key, KnockOut::PIVOT, Self::MAX_UNITS),
+            (None, None) => HiLoBot::ramp(),
+        };
+        let bet = ramp.bet(true_count, conf.base_bet);
+        conf.fit_bet(bet, engine.player.money)
+            .ok_or(Error::InvalidBet(conf.table_min))
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        BasicStrategy.side_bet(engine, side_bet)
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        Ok(HiLoBot::insures(engine))
+    }
+
+    /// The full insurance from the insurance index up.
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        let player = &engine.player;
+        if !HiLoBot::insures(engine) {
+            return Ok(Money::ZERO);
+        }
+        Ok(player.max_insurance().min(player.money))
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        Ok(HiLoBot::decide(engine, hand, options))
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        BasicStrategy.buy_amount(engine, hand)
+    }
+
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.rescue(engine, hand)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        BasicStrategy.tip(engine)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        BasicStrategy.rebuy(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::counting::CountingMethod;
+    use crate::deviations::HiLoBot;
+    use crate::engine::tests::dealt;
+    use crate::input::{BasicStrategy, Strategy};
+    use crate::rules::Rules;
+    use crate::simulation::{simulate, NoProgress};
+    use crate::types::{Move, Value};
+
+    #[test]
+    fn test_index_plays() {
+        let rules = Rules::default();
+        let all = |_: Move| true;
+        let choose = |value, pair, upcard, true_count, allowed: &dyn Fn(Move) -> bool| {
+            HiLoBot::choose(&rules, value, false, pair, upcard, true_count, allowed)
+        };
+        // 16 against a ten is surrendered whatever the count, and stood on from 0 if it can't be
+        let no_surrender = |m: Move| m != Move::Surrender;
+        assert_eq!(choose(16, None, 10, -3.0, &all), Some(Move::Surrender));
+        assert_eq!(choose(16, None, 10, 0.0, &no_surrender), Some(Move::Stand));
+        assert_eq!(choose(16, None, 10, -0.5, &no_surrender), Some(Move::Hit));
+        // The Fab 4
+        assert_eq!(choose(15, None, 10, -0.5, &all), Some(Move::Hit));
+        assert_eq!(choose(15, None, 10, 0.0, &all), Some(Move::Surrender));
+        assert_eq!(choose(14, None, 10, 3.0, &all), Some(Move::Surrender));
+        assert_eq!(choose(15, None, 10, 4.0, &no_surrender), Some(Move::Stand));
+        // Splitting tens, but only a pair of them
+        assert_eq!(choose(20, Some(10), 6, 4.0, &all), Some(Move::Split));
+        assert_eq!(choose(20, Some(10), 6, 3.0, &all), Some(Move::Stand));
+        let no_split = |m: Move| m != Move::Split;
+        assert_eq!(choose(20, None, 6, 4.0, &no_split), Some(Move::Stand));
+        // A double that isn't allowed any more is a hit
+        let no_double = |m: Move| m != Move::Double;
+        assert_eq!(choose(11, None, 11, 1.0, &all), Some(Move::Double));
+        assert_eq!(choose(11, None, 11, 1.0, &no_double), Some(Move::Hit));
+        assert_eq!(choose(12, None, 2, 3.0, &all), Some(Move::Stand));
+        assert_eq!(choose(12, None, 4, -1.0, &all), Some(Move::Hit));
+        // Soft hands are played by basic strategy
+        assert_eq!(
+            HiLoBot::choose(&rules, 18, true, None, 3, -5.0, all),
+            BasicStrategy::choose(&rules, 18, true, None, 3, all)
+        );
+    }
+
+    #[test]
+    fn test_deviation() {
+        let mut engine = dealt(GameConfig::default(), &[Value::Ten, Value::Two], Value::Two);
+        let options = engine.conf.rules.hand_options(&engine.player, 0);
+        // Basic strategy hits 12 against a 2, from a true count of 3 it is stood on
+        engine.counter.true_count = 1.0;
+        assert_eq!(HiLoBot::deviation(&engine, 0, &options), None);
+        engine.counter.true_count = 3.5;
+        assert_eq!(HiLoBot::deviation(&engine, 0, &options), Some(Move::Stand));
+        assert!(HiLoBot::insures(&engine));
+    }
+
+    #[test]
+    fn test_hi_lo_bot() {
+        assert_eq!(HiLoBot::ramp().units(1.9), 1);
+        assert_eq!(HiLoBot::ramp().units(6.0), 8);
+
+        let conf = GameConfig::default();
+        let basic = simulate(
+            conf.clone(),
+            &mut Strategy::Basic,
+            100_000,
+            Some(3),
+            &mut NoProgress,
+        )
+        .unwrap();
+        let counted = simulate(conf, &mut HiLoBot, 100_000, Some(3), &mut NoProgress).unwrap();
+        // The bot bets more when the count is high, and gets an edge out of it
+        assert!(counted.initial_bets > basic.initial_bets);
+        assert!(counted.initial_bets < basic.initial_bets * 8);
+        assert!(counted.player_edge() > basic.player_edge());
+
+        // KO bets from its key count
+        let conf = GameConfig {
+            counting: CountingMethod::KnockOut,
+            ..GameConfig::default()
+        };
+        let counted = simulate(conf, &mut HiLoBot, 100_000, Some(3), &mut NoProgress).unwrap();
+        assert!(counted.initial_bets > basic.initial_bets);
+        assert!(counted.player_edge() > basic.player_edge());
+    }
+}
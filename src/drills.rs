@@ -0,0 +1,455 @@
+use crate::config::GameConfig;
+use crate::custom_chart::CustomChart;
+use crate::error::Error;
+use crate::outcomes::StartingHand;
+use crate::rules::Rules;
+use crate::shuffle::Shuffler;
+use crate::types::{Card, Move, Shoe, Value};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The drills a player can practice on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Drill {
+    /// Keeping the running count of cards shown one at a time.
+    Count,
+    /// Playing two card hands against an upcard as the strategy chart says.
+    Strategy,
+}
+
+/// A count drill: `rounds` times, `cards` cards off a freshly shuffled shoe are shown one at a
+/// time for `pace` each, and the running count asked for after the last one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountDrill {
+    pub cards: u32,
+    pub pace: Duration,
+    pub rounds: u32,
+}
+
+impl CountDrill {
+    pub fn validate(&self, conf: &GameConfig) -> Result<(), Error> {
+        let shoe = Shoe::new(conf.rules.deck_count, conf.rules.variant)?;
+        if !(1..=shoe.num_cards()).contains(&self.cards) {
+            return Err(Error::Config(format!(
+                "a count drill shows 1 to {} cards, the cards of the shoe, got {}",
+                shoe.num_cards(),
+                self.cards
+            )));
+        }
+        if self.rounds == 0 {
+            return Err(Error::Config(
+                "a count drill needs at least one round".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The cards of a round off a shoe shuffled with `shuffler`, and the running count of the
+    /// counting system of `conf` after them, from the count a shoe starts at.
+    pub fn deal(
+        &self,
+        conf: &GameConfig,
+        shuffler: &mut dyn Shuffler,
+    ) -> Result<(Vec<Card>, i32), Error> {
+        let mut shoe = Shoe::new(conf.rules.deck_count, conf.rules.variant)?.shuffle(shuffler);
+        let cards = (0..self.cards)
+            .map(|_| shoe.take_card())
+            .collect::<Result<Vec<_>, _>>()?;
+        let system = conf.counting.system();
+        let running_count = system.initial_running_count(conf.rules.deck_count)
+            + cards
+                .iter()
+                .map(|card| system.tag(&card.value))
+                .sum::<i32>();
+        Ok((cards, running_count))
+    }
+}
+
+/// A two card hand of a strategy drill against the dealer's upcard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Situation {
+    pub hand: StartingHand,
+    /// The value of the upcard, from 2 to 11 for an ace.
+    pub upcard: u32,
+}
+
+impl Situation {
+    /// The value of the hand, whether it is soft and the value of the cards of a pair, as a
+    /// chart looks them up.
+    fn lookup(&self) -> (u32, bool, Option<u32>) {
+        match self.hand {
+            StartingHand::Hard(total) => (total, false, None),
+            StartingHand::Soft(card) => (Value::ACE_HIGH_VAL + card, true, None),
+            StartingHand::Pair(card) if card == Value::ACE_HIGH_VAL => {
+                (Value::ACE_HIGH_VAL + Value::ACE_LOW_VAL, true, Some(card))
+            }
+            StartingHand::Pair(card) => (2 * card, false, Some(card)),
+        }
+    }
+}
+
+/// Written as the hand against the upcard, e.g. "A,7 v 6" or "16 v A".
+impl Display for Situation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.upcard {
+            Value::ACE_HIGH_VAL => write!(f, "{} v A", self.hand),
+            upcard => write!(f, "{} v {}", self.hand, upcard),
+        }
+    }
+}
+
+impl FromStr for Situation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hand, upcard) = s.split_once(" v ").ok_or_else(|| {
+            format!(
+                "expected a hand against an upcard, e.g. A,7 v 6, got '{}'",
+                s
+            )
+        })?;
+        let upcard = match upcard.trim().parse::<Value>()? {
+            Value::Ace => Value::ACE_HIGH_VAL,
+            value => value.value(),
+        };
+        Ok(Self {
+            hand: hand.trim().parse()?,
+            upcard,
+        })
+    }
+}
+
+/// A strategy drill: `questions` two card hands against an upcard, picked from the hands of the
+/// chart, to be played as the chart says.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyDrill {
+    pub questions: u32,
+}
+
+impl StrategyDrill {
+    /// The hands to ask about, every one at random from the chart, or half of them from the
+    /// `weak_spots` of the chart if there are any.
+    pub fn situations(
+        &self,
+        chart: &CustomChart,
+        weak_spots: &[Situation],
+        rng: &mut impl Rng,
+    ) -> Vec<Situation> {
+        let hands = chart.rows.keys().copied().collect::<Vec<_>>();
+        let weak_spots = weak_spots
+            .iter()
+            .filter(|situation| chart.rows.contains_key(&situation.hand))
+            .collect::<Vec<_>>();
+        (0..self.questions)
+            .map(|_| {
+                if !weak_spots.is_empty() && rng.gen_bool(0.5) {
+                    *weak_spots[rng.gen_range(0..weak_spots.len())]
+                } else {
+                    Situation {
+                        hand: hands[rng.gen_range(0..hands.len())],
+                        upcard: rng.gen_range(2..=Value::ACE_HIGH_VAL),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// The moves a two card hand can make under the rules.
+    pub fn allowed(rules: &Rules, situation: &Situation) -> Vec<Move> {
+        let (value, _, pair) = situation.lookup();
+        let mut moves = vec![Move::Hit, Move::Stand];
+        if rules.double_on.allows(value) {
+            moves.push(Move::Double);
+        }
+        if pair.is_some() {
+            moves.push(Move::Split);
+        }
+        if rules.surrender && rules.variant.allows_surrender() {
+            moves.push(Move::Surrender);
+        }
+        moves
+    }
+
+    /// The move of the chart for the hand, of the moves it is allowed under the rules.
+    pub fn answer(chart: &CustomChart, rules: &Rules, situation: &Situation) -> Option<Move> {
+        let allowed = Self::allowed(rules, situation);
+        let (value, soft, pair) = situation.lookup();
+        chart.choose(value, soft, pair, situation.upcard, |m| {
+            allowed.contains(&m)
+        })
+    }
+}
+
+/// How a session of a drill went, as it is kept in the history of the drills.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DrillSession {
+    pub drill: Drill,
+    /// When the session started, in seconds since the Unix epoch.
+    pub started: u64,
+    /// The counting system or the strategy chart drilled on.
+    pub system: String,
+    pub questions: u32,
+    pub correct: u32,
+    /// The cards of every question of a count drill.
+    pub cards: Option<u32>,
+    /// How long every card of a count drill was shown for, in milliseconds.
+    pub pace_ms: Option<u64>,
+    /// The average time taken to answer, in seconds.
+    pub answer_secs: f64,
+    /// The hands of a strategy drill that were played wrong.
+    #[serde(default)]
+    pub missed: Vec<String>,
+}
+
+impl DrillSession {
+    /// The share of the questions answered correctly.
+    pub fn accuracy(&self) -> f64 {
+        if self.questions == 0 {
+            return 0.0;
+        }
+        self.correct as f64 / self.questions as f64
+    }
+}
+
+/// The sessions of the drills played so far, kept in a file with a line of JSON for each, to
+/// follow how the accuracy and speed improve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrillHistory {
+    pub path: PathBuf,
+    pub sessions: Vec<DrillSession>,
+}
+
+impl DrillHistory {
+    /// Reads the history at `path`, empty if there is no file yet.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(Error::Config(format!(
+                    "cannot read {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+        let sessions = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    Error::Config(format!("{} line {}: {}", path.display(), index + 1, e))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            sessions,
+        })
+    }
+
+    /// Adds the session to the history and appends it to the file.
+    pub fn record(&mut self, session: DrillSession) -> Result<(), Error> {
+        let cannot_write = |e: std::io::Error| {
+            Error::Config(format!("cannot write {}: {}", self.path.display(), e))
+        };
+        let line = serde_json::to_string(&session).map_err(|e| Error::Config(e.to_string()))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(cannot_write)?;
+        writeln!(file, "{}", line).map_err(cannot_write)?;
+        self.sessions.push(session);
+        Ok(())
+    }
+
+    /// The hands missed most often in the sessions of the strategy drill, with the times they
+    /// were missed, the most missed first.
+    pub fn weak_spots(&self) -> Vec<(Situation, u32)> {
+        let mut missed = BTreeMap::new();
+        for situation in self
+            .of(Drill::Strategy)
+            .flat_map(|session| &session.missed)
+            .filter_map(|situation| situation.parse::<Situation>().ok())
+        {
+            *missed.entry(situation).or_insert(0) += 1;
+        }
+        let mut missed = missed.into_iter().collect::<Vec<_>>();
+        missed.sort_by(|(_, a), (_, b)| b.cmp(a));
+        missed
+    }
+
+    /// The sessions of one drill, from the first.
+    pub fn of(&self, drill: Drill) -> impl Iterator<Item = &DrillSession> {
+        self.sessions
+            .iter()
+            .filter(move |session| session.drill == drill)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chart::generate_chart;
+    use crate::config::GameConfig;
+    use crate::counting::{CountingMethod, CountingSystem, KnockOut};
+    use crate::custom_chart::CustomChart;
+    use crate::drills::{CountDrill, Drill, DrillHistory, DrillSession, Situation, StrategyDrill};
+    use crate::outcomes::StartingHand;
+    use crate::rules::Rules;
+    use crate::shuffle::ShuffleMethod;
+    use crate::types::Move;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::time::Duration;
+
+    #[test]
+    fn test_count_drill() {
+        let conf = GameConfig::default();
+        let drill = CountDrill {
+            cards: 20,
+            pace: Duration::from_millis(500),
+            rounds: 3,
+        };
+        drill.validate(&conf).unwrap();
+        let (cards, count) = drill
+            .deal(&conf, ShuffleMethod::Uniform.seeded(3).as_mut())
+            .unwrap();
+        assert_eq!(cards.len(), 20);
+        let system = conf.counting.system();
+        assert_eq!(
+            count,
+            cards
+                .iter()
+                .map(|card| system.tag(&card.value))
+                .sum::<i32>()
+        );
+        // A full shoe counts back down to where it started
+        let shoe = CountDrill {
+            cards: 6 * 52,
+            ..drill.clone()
+        };
+        let (_, count) = shoe
+            .deal(&conf, ShuffleMethod::Uniform.seeded(3).as_mut())
+            .unwrap();
+        assert_eq!(count, 0);
+        let ko = GameConfig {
+            counting: CountingMethod::KnockOut,
+            ..GameConfig::default()
+        };
+        let (_, count) = shoe
+            .deal(&ko, ShuffleMethod::Uniform.seeded(3).as_mut())
+            .unwrap();
+        assert_eq!(count, KnockOut.initial_running_count(6) + 6 * 4);
+
+        for drill in [
+            CountDrill {
+                cards: 6 * 52 + 1,
+                ..drill.clone()
+            },
+            CountDrill { rounds: 0, ..drill },
+        ] {
+            assert!(drill.validate(&conf).is_err());
+        }
+    }
+
+    #[test]
+    fn test_drill_history() {
+        let path = std::env::temp_dir().join(format!("drills-{}.jsonl", std::process::id()));
+        let mut history = DrillHistory::load(&path).unwrap();
+        assert!(history.sessions.is_empty());
+        let session = DrillSession {
+            drill: Drill::Count,
+            started: 1_700_000_000,
+            system: "Hi-Lo".to_string(),
+            questions: 5,
+            correct: 4,
+            cards: Some(20),
+            pace_ms: Some(800),
+            answer_secs: 2.5,
+            missed: vec![],
+        };
+        assert_eq!(session.accuracy(), 0.8);
+        history.record(session.clone()).unwrap();
+        history
+            .record(DrillSession {
+                correct: 5,
+                ..session.clone()
+            })
+            .unwrap();
+        let loaded = DrillHistory::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, history);
+        assert_eq!(loaded.of(Drill::Count).count(), 2);
+        assert_eq!(loaded.sessions[1].accuracy(), 1.0);
+        assert!(loaded.weak_spots().is_empty());
+
+        let strategy = |missed: &[&str]| DrillSession {
+            drill: Drill::Strategy,
+            cards: None,
+            pace_ms: None,
+            missed: missed.iter().map(|m| m.to_string()).collect(),
+            ..session.clone()
+        };
+        history.sessions.push(strategy(&["16 v 10", "A,7 v 3"]));
+        history.sessions.push(strategy(&["16 v 10"]));
+        let sixteen = "16 v 10".parse::<Situation>().unwrap();
+        let soft = "A,7 v 3".parse::<Situation>().unwrap();
+        assert_eq!(history.weak_spots(), vec![(sixteen, 2), (soft, 1)]);
+    }
+
+    #[test]
+    fn test_strategy_drill() {
+        let rules = Rules::default();
+        let chart = CustomChart::from_chart("basic", &generate_chart(&rules).unwrap());
+        let situation = |text: &str| text.parse::<Situation>().unwrap();
+        assert_eq!(
+            situation("A,7 v A"),
+            Situation {
+                hand: StartingHand::Soft(7),
+                upcard: 11
+            }
+        );
+        assert_eq!(situation("8,8 v 10").to_string(), "8,8 v 10");
+        assert!("16 against 10".parse::<Situation>().is_err());
+
+        let answer = |text| StrategyDrill::answer(&chart, &rules, &situation(text));
+        assert_eq!(answer("16 v 10"), Some(Move::Surrender));
+        assert_eq!(answer("11 v 6"), Some(Move::Double));
+        assert_eq!(answer("A,A v 10"), Some(Move::Split));
+        assert_eq!(answer("A,7 v 2"), Some(Move::Stand));
+        let no_surrender = Rules {
+            surrender: false,
+            ..rules.clone()
+        };
+        assert_eq!(
+            StrategyDrill::answer(&chart, &no_surrender, &situation("16 v 10")),
+            Some(Move::Hit)
+        );
+        assert!(
+            !StrategyDrill::allowed(&no_surrender, &situation("16 v 10"))
+                .contains(&Move::Surrender)
+        );
+
+        let drill = StrategyDrill { questions: 200 };
+        let mut rng = StdRng::seed_from_u64(4);
+        let situations = drill.situations(&chart, &[], &mut rng);
+        assert_eq!(situations.len(), 200);
+        assert!(situations
+            .iter()
+            .all(|s| chart.rows.contains_key(&s.hand) && (2..=11).contains(&s.upcard)));
+        // About half the hands are the weak spots
+        let weak = situation("12 v 3");
+        let situations = drill.situations(&chart, &[weak], &mut rng);
+        let asked = situations.iter().filter(|s| **s == weak).count();
+        assert!((70..=130).contains(&asked), "{}", asked);
+    }
+}
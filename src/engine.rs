@@ -0,0 +1,1624 @@
+use crate::betting::BetSituation;
+use crate::config::{GameConfig, MAX_SEATS};
+use crate::counting::CardCounter;
+use crate::error::Error;
+use crate::events::{FinishReason, GameEvent, GameObserver, HandResult, Seat};
+use crate::input::PlayerInput;
+use crate::money::Money;
+use crate::poker::ThreeCardHand;
+use crate::shuffle::Shuffler;
+use crate::side_bets::{bust_it_payout, SideBet};
+use crate::table::SeatPosition;
+use crate::types::{Card, Hand, Move, Outcome, Player, PlayerHand, Shoe, Value, TWENTY_ONE};
+use crate::variants::{Variant, FIVE_CARD_TRICK};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The stages of a round, the engine moves through them in order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GameState {
+    /// Waiting for the bets of the next round.
+    Betting,
+    /// The bets are placed and the cards are about to be dealt.
+    Dealing,
+    /// Waiting for the player's even money and insurance decisions on a dealer ace.
+    Insurance,
+    /// Waiting for the player to act on the hand at the index.
+    PlayerTurn(usize),
+    /// The hand at the index was doubled and may be rescued by surrendering the original bet.
+    DoubleRescue(usize),
+    /// The hole card is revealed and the dealer draws.
+    DealerTurn,
+    /// The dealer is done and the hands are about to be paid out.
+    Settlement,
+    /// The round is settled, the player may tip the dealer before the next round.
+    RoundOver,
+}
+
+impl Display for GameState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameState::Betting => write!(f, "betting"),
+            GameState::Dealing => write!(f, "the deal"),
+            GameState::Insurance => write!(f, "insurance"),
+            GameState::PlayerTurn(index) => write!(f, "the player's turn on hand {}", index + 1),
+            GameState::DoubleRescue(index) => write!(f, "the double rescue of hand {}", index + 1),
+            GameState::DealerTurn => write!(f, "the dealer's turn"),
+            GameState::Settlement => write!(f, "settlement"),
+            GameState::RoundOver => write!(f, "the end of the round"),
+        }
+    }
+}
+
+/// Runs rounds of blackjack as a state machine.
+///
+/// The automatic stages are advanced one at a time with [`GameEngine::step`], the stages waiting
+/// for the player are advanced by the player's decisions instead.
+///
+/// A table seats up to [`MAX_SEATS`] players who play their hands in seat order from first base
+/// to third base, the dealer plays once the last seat is done.
+///
+/// The decisions are always made for the seat in play, see [`GameEngine::take_seat`].
+pub struct GameEngine {
+    pub conf: GameConfig,
+    pub shoe: Shoe,
+    /// The count of the cards the player has seen since the last shuffle.
+    pub counter: CardCounter,
+    /// The player of the seat in play.
+    pub player: Player,
+    /// The dealer's cards in view, the hole card is added when the dealer's turn starts.
+    pub dealer_hand: Hand,
+    /// The 21+3 hand of the round, once the side bet is settled.
+    pub twenty_one_plus_three: Option<ThreeCardHand>,
+    /// Whether the dealer checked the hole card for blackjack this round.
+    pub peeked: bool,
+    /// The outcome of every hand, once the round is settled.
+    pub outcomes: Vec<Outcome>,
+    state: GameState,
+    hole_card: Option<Card>,
+    /// The bankroll before the bets of the round were placed.
+    round_bankroll: Money,
+    /// The table before each hit, double and split of the round, in practice mode.
+    undo_history: Vec<Snapshot>,
+    /// Every seat taken, from first base on. The seat in play is left empty while its player is
+    /// swapped into `player`.
+    seats: Vec<TableSeat>,
+    seat: usize,
+    shuffler: Box<dyn Shuffler>,
+    observers: Vec<Box<dyn GameObserver>>,
+}
+
+/// A player waiting at the table while another seat is in play.
+#[derive(Serialize, Deserialize)]
+pub struct TableSeat {
+    #[serde(default)]
+    pub position: SeatPosition,
+    pub player: Player,
+    /// The bankroll before the bets of the round were placed.
+    pub round_bankroll: Money,
+    /// The outcome of every hand, once the round is settled.
+    pub outcomes: Vec<Outcome>,
+}
+
+impl TableSeat {
+    fn empty() -> Self {
+        Self {
+            position: SeatPosition::FIRST_BASE,
+            player: Player::new(Money::ZERO),
+            round_bankroll: Money::ZERO,
+            outcomes: vec![],
+        }
+    }
+
+    fn detached(&self) -> Self {
+        Self {
+            position: self.position,
+            player: self.player.detached(),
+            round_bankroll: self.round_bankroll,
+            outcomes: self.outcomes.clone(),
+        }
+    }
+}
+
+/// A game frozen at any point of a round, e.g. waiting for a decision, that can be saved and
+/// resumed later. The shoe is kept card for card, so the round continues exactly as it would have.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub state: GameState,
+    pub shoe: Shoe,
+    pub running_count: i32,
+    pub true_count: f32,
+    /// The aces counted aside, 0 in a game saved before the aces were side counted.
+    #[serde(default)]
+    pub aces_seen: u32,
+    pub player: Player,
+    pub dealer_hand: Hand,
+    pub hole_card: Option<Card>,
+    pub twenty_one_plus_three: Option<ThreeCardHand>,
+    pub peeked: bool,
+    pub outcomes: Vec<Outcome>,
+    pub round_bankroll: Money,
+    /// The seats waiting while `player` is in play, empty in a game saved with a single seat.
+    #[serde(default)]
+    pub seats: Vec<TableSeat>,
+    #[serde(default)]
+    pub seat: usize,
+}
+
+impl GameEngine {
+    /// Creates an engine with a freshly shuffled shoe, waiting for the bets of the first round.
+    pub fn new(conf: GameConfig, player: Player) -> Result<Self, Error> {
+        Self::with_observers(conf, player, vec![])
+    }
+
+    /// Creates an engine like [`GameEngine::new`], with observers subscribed from the start.
+    pub fn with_observers(
+        conf: GameConfig,
+        player: Player,
+        observers: Vec<Box<dyn GameObserver>>,
+    ) -> Result<Self, Error> {
+        let shoe = Shoe::new(conf.rules.deck_count, conf.rules.variant)?;
+        let mut engine = Self::with_shoe(conf, shoe, player, observers);
+        engine.shuffle();
+        Ok(engine)
+    }
+
+    /// Creates an engine dealing from `shoe` in its current order, e.g. a shoe stacked for a test
+    /// or shuffled elsewhere. The cut card is placed as configured but no card is burned.
+    pub fn with_shoe(
+        conf: GameConfig,
+        shoe: Shoe,
+        player: Player,
+        observers: Vec<Box<dyn GameObserver>>,
+    ) -> Self {
+        Self::seat_players(
+            conf,
+            shoe,
+            vec![(SeatPosition::FIRST_BASE, player)],
+            observers,
+        )
+    }
+
+    /// Creates an engine like [`GameEngine::with_shoe`] with `players` seated from first base
+    /// on, each with their own bankroll, bets and statistics.
+    pub fn seated(
+        conf: GameConfig,
+        shoe: Shoe,
+        players: Vec<Player>,
+        observers: Vec<Box<dyn GameObserver>>,
+    ) -> Result<Self, Error> {
+        if !(1..=MAX_SEATS).contains(&players.len()) {
+            return Err(Error::Seats(players.len()));
+        }
+        let players = SeatPosition::all().zip(players).collect();
+        Self::seated_at(conf, shoe, players, observers)
+    }
+
+    /// Creates an engine like [`GameEngine::seated`] with every player at their own spot. The
+    /// seats are numbered in the order they are dealt, from the spot closest to first base.
+    pub fn seated_at(
+        conf: GameConfig,
+        shoe: Shoe,
+        mut players: Vec<(SeatPosition, Player)>,
+        observers: Vec<Box<dyn GameObserver>>,
+    ) -> Result<Self, Error> {
+        if !(1..=MAX_SEATS).contains(&players.len()) {
+            return Err(Error::Seats(players.len()));
+        }
+        players.sort_by_key(|(position, _)| *position);
+        if let Some(pair) = players.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(Error::Config(format!(
+                "two players cannot sit at {}",
+                pair[0].0
+            )));
+        }
+        Ok(Self::seat_players(conf, shoe, players, observers))
+    }
+
+    fn seat_players(
+        conf: GameConfig,
+        mut shoe: Shoe,
+        players: Vec<(SeatPosition, Player)>,
+        observers: Vec<Box<dyn GameObserver>>,
+    ) -> Self {
+        let mut seats: Vec<TableSeat> = players
+            .into_iter()
+            .map(|(position, mut player)| {
+                if player.bet_strategy.is_none() {
+                    player.bet_strategy = conf.bet_system.map(|system| system.strategy(&conf));
+                }
+                TableSeat {
+                    position,
+                    player,
+                    round_bankroll: Money::ZERO,
+                    outcomes: vec![],
+                }
+            })
+            .collect();
+        let player = std::mem::replace(&mut seats[0].player, Player::new(Money::ZERO));
+        if !conf.rules.csm {
+            shoe.place_cut_card(conf.rules.cut_card_position(shoe.num_cards()));
+        }
+        let shuffler = conf.shuffle.shuffler();
+        let counter = CardCounter::new(conf.counting.system(), conf.rules.deck_count)
+            .with_ace_side_count(conf.ace_side_count)
+            .with_deck_estimation(conf.deck_estimation);
+        let mut engine = Self {
+            conf,
+            shoe,
+            counter,
+            player,
+            dealer_hand: Hand::default(),
+            twenty_one_plus_three: None,
+            peeked: false,
+            outcomes: vec![],
+            state: GameState::Betting,
+            hole_card: None,
+            round_bankroll: Money::ZERO,
+            undo_history: vec![],
+            seats,
+            seat: 0,
+            shuffler,
+            observers,
+        };
+        engine.start_round();
+        engine
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    /// The number of players at the table.
+    pub fn seats(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// The seat in play, whose player is [`GameEngine::player`].
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    /// Where at the table the player at `seat` sits.
+    pub fn position(&self, seat: usize) -> SeatPosition {
+        self.seats[seat].position
+    }
+
+    /// The player at `seat`, whether the seat is in play or waiting.
+    pub fn player_at(&self, seat: usize) -> &Player {
+        if seat == self.seat {
+            &self.player
+        } else {
+            &self.seats[seat].player
+        }
+    }
+
+    /// The seats with a main bet on the round, in dealing order.
+    pub fn seats_in_round(&self) -> Vec<usize> {
+        (0..self.seats.len())
+            .filter(|&seat| !self.player_at(seat).hands.is_empty())
+            .collect()
+    }
+
+    /// Replaces the player at `seat` between rounds, when a player leaves the table and another
+    /// sits down. The bets already placed on the round are returned to the player leaving.
+    pub fn seat_player(&mut self, seat: usize, mut player: Player) -> Result<Player, Error> {
+        self.expect_state(GameState::Betting)?;
+        if seat >= self.seats.len() {
+            return Err(Error::NoSeat(seat));
+        }
+        if player.bet_strategy.is_none() {
+            player.bet_strategy = self
+                .conf
+                .bet_system
+                .map(|system| system.strategy(&self.conf));
+        }
+        player.ledger.start_round(player.stats.rounds + 1);
+
+        let in_play = self.seat;
+        self.sit(seat);
+        let net = self.player.money - self.round_bankroll;
+        if net != Money::ZERO {
+            self.player.credit("Left the table", -net);
+        }
+        self.player.clear_bets();
+        self.round_bankroll = player.money;
+        let leaving = std::mem::replace(&mut self.player, player);
+        self.sit(in_play);
+        Ok(leaving)
+    }
+
+    /// Puts the player at `seat` in play, to place the bets, insure or tip the dealer seat by
+    /// seat. The hands are played seat by seat as the round goes on.
+    pub fn take_seat(&mut self, seat: usize) -> Result<(), Error> {
+        match self.state {
+            GameState::Betting | GameState::Insurance | GameState::RoundOver => {}
+            state => return Err(Error::WrongState(state)),
+        }
+        if seat >= self.seats.len() {
+            return Err(Error::NoSeat(seat));
+        }
+        self.sit(seat);
+        self.announce_seat();
+        Ok(())
+    }
+
+    /// Saves the table as it stands, the config, the shuffle and the observers aren't included.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.state,
+            shoe: self.shoe.clone(),
+            running_count: self.counter.running_count,
+            true_count: self.counter.true_count,
+            aces_seen: self.counter.aces_seen,
+            player: self.player.detached(),
+            dealer_hand: self.dealer_hand.clone(),
+            hole_card: self.hole_card.clone(),
+            twenty_one_plus_three: self.twenty_one_plus_three,
+            peeked: self.peeked,
+            outcomes: self.outcomes.clone(),
+            round_bankroll: self.round_bankroll,
+            seats: self.seats.iter().map(TableSeat::detached).collect(),
+            seat: self.seat,
+        }
+    }
+
+    /// Resumes the game saved in `snapshot`, keeping this engine's config, shuffle, observers
+    /// and the betting systems of the seats.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        let mut bet_strategies: Vec<_> = (0..self.seats.len())
+            .map(|seat| self.player_at_mut(seat).bet_strategy.take())
+            .collect();
+        self.player = snapshot.player;
+        self.seats = if snapshot.seats.is_empty() {
+            vec![TableSeat::empty()]
+        } else {
+            snapshot.seats
+        };
+        self.seat = snapshot.seat;
+        bet_strategies.truncate(self.seats.len());
+        for (seat, bet_strategy) in bet_strategies.into_iter().enumerate() {
+            self.player_at_mut(seat).bet_strategy = bet_strategy;
+        }
+        self.state = snapshot.state;
+        self.shoe = snapshot.shoe;
+        self.counter.running_count = snapshot.running_count;
+        self.counter.true_count = snapshot.true_count;
+        self.counter.aces_seen = snapshot.aces_seen;
+        self.dealer_hand = snapshot.dealer_hand;
+        self.hole_card = snapshot.hole_card;
+        self.twenty_one_plus_three = snapshot.twenty_one_plus_three;
+        self.peeked = snapshot.peeked;
+        self.outcomes = snapshot.outcomes;
+        self.round_bankroll = snapshot.round_bankroll;
+    }
+
+    pub fn subscribe(&mut self, observer: Box<dyn GameObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Replaces the shuffle picked by the config, from the next shuffle on.
+    pub fn set_shuffler(&mut self, shuffler: Box<dyn Shuffler>) {
+        self.shuffler = shuffler;
+    }
+
+    /// Shuffles all cards back into the shoe, then places the cut card and burns a card.
+    /// A continuous shuffling machine has neither.
+    pub fn shuffle(&mut self) {
+        self.shoe = self.shoe.clone().shuffle(self.shuffler.as_mut());
+        if !self.conf.rules.csm {
+            let cut_card = self.conf.rules.cut_card_position(self.shoe.num_cards());
+            self.shoe.place_cut_card(cut_card);
+            self.shoe.burn_card();
+        }
+        self.counter.reset();
+        self.emit(GameEvent::Shuffled {
+            continuous: self.conf.rules.csm,
+        });
+    }
+
+    /// Places the main bet for another hand.
+    pub fn place_bet(&mut self, bet: Money) -> Result<(), Error> {
+        self.expect_state(GameState::Betting)?;
+        if bet <= Money::ZERO || !self.player.can_afford(bet) {
+            return Err(Error::InvalidBet(bet));
+        }
+        let origin = self.player.hands.len();
+        self.player.wager(&format!("Bet hand {}", origin + 1), bet);
+        let mut hand = PlayerHand::new(Hand::default(), bet);
+        hand.origin = origin;
+        self.player.hands.push(hand);
+        self.emit(GameEvent::BetPlaced {
+            hand: origin,
+            amount: bet,
+        });
+        Ok(())
+    }
+
+    /// Places the 21+3 side bet, 0 for none.
+    pub fn place_twenty_one_plus_three(&mut self, bet: Money) -> Result<(), Error> {
+        self.expect_state(GameState::Betting)?;
+        self.check_side_bet(bet)?;
+
+        self.player.twenty_one_plus_three = bet;
+        self.player.wager("21+3 side bet", bet);
+        self.emit_side_bet(SideBet::TwentyOnePlusThree, bet);
+        Ok(())
+    }
+
+    /// Places the Bust It side bet, 0 for none.
+    pub fn place_bust_it(&mut self, bet: Money) -> Result<(), Error> {
+        self.expect_state(GameState::Betting)?;
+        self.check_side_bet(bet)?;
+        self.player.bust_it = bet;
+        self.player.wager("Bust It side bet", bet);
+        self.emit_side_bet(SideBet::BustIt, bet);
+        Ok(())
+    }
+
+    /// Takes even money on the blackjack at `index`. Hands without a blackjack are left as is.
+    pub fn take_even_money(&mut self, index: usize) -> Result<(), Error> {
+        self.expect_state(GameState::Insurance)?;
+        if let Some(hand) = self.player.hands.get_mut(index) {
+            hand.even_money = hand.is_blackjack();
+        }
+        Ok(())
+    }
+
+    /// Insures the hands against a dealer blackjack, for at most half the bets.
+    pub fn insure(&mut self, amount: Money) -> Result<(), Error> {
+        self.expect_state(GameState::Insurance)?;
+        if amount < Money::ZERO
+            || amount > self.player.max_insurance()
+            || !self.player.can_afford(amount)
+        {
+            return Err(Error::InvalidBet(amount));
+        }
+        self.player.insurance = amount;
+        self.player.wager("Insurance", amount);
+        Ok(())
+    }
+
+    /// Makes a move on the hand being played.
+    /// Doubling a hand that doesn't end on a double buys another card for the full bet.
+    pub fn act(&mut self, action: Move) -> Result<(), Error> {
+        let index = match self.state {
+            GameState::PlayerTurn(index) => index,
+            state => return Err(Error::WrongState(state)),
+        };
+        let options = self.conf.rules.hand_options(&self.player, index);
+        if !options.moves.contains(&action) {
+            return Err(Error::IllegalMove(action));
+        }
+
+        match action {
+            Move::Hit => {
+                self.save_undo();
+                let card = self.draw(Seat::Hand(index))?;
+                self.player.hands[index].hand.add_card(card);
+            }
+            Move::Stand => {
+                self.state = GameState::PlayerTurn(index + 1);
+            }
+            Move::Double if !self.conf.rules.variant.double_ends_hand() => {
+                let amount = self.player.hands[index].bet.min(self.player.money);
+                return self.buy(amount);
+            }
+            Move::Double => {
+                self.save_undo();
+                if !options.free_double {
+                    let stake = self.player.hands[index].stake();
+                    self.player
+                        .wager(&format!("Double hand {}", index + 1), stake);
+                }
+                let card = self.draw(Seat::Hand(index))?;
+                let hand = &mut self.player.hands[index];
+                if options.free_double {
+                    hand.free_double(card);
+                } else {
+                    hand.double(card);
+                }
+                self.state = if self.conf.rules.variant.allows_double_rescue() && !hand.is_bust() {
+                    GameState::DoubleRescue(index)
+                } else {
+                    GameState::PlayerTurn(index + 1)
+                };
+            }
+            Move::Split => {
+                self.save_undo();
+                let mut new_hand = self.player.hands[index].split();
+                if options.free_split {
+                    new_hand.free_bet += new_hand.bet;
+                    new_hand.bet = Money::ZERO;
+                }
+                self.player
+                    .wager(&format!("Split hand {}", index + 1), new_hand.bet);
+                // The new hand is played right after the current one
+                self.player.hands.insert(index + 1, new_hand);
+                for i in [index, index + 1] {
+                    let card = self.draw(Seat::Hand(i))?;
+                    self.player.hands[i].hand.add_card(card);
+                }
+            }
+            Move::Surrender => {
+                self.player.hands[index].surrendered = true;
+                self.state = GameState::PlayerTurn(index + 1);
+            }
+        }
+        self.emit_action(index, action);
+        self.skip_finished_hands();
+        Ok(())
+    }
+
+    /// Raises the bet by `amount` and takes another card, on a hand that doesn't end on a double.
+    pub fn buy(&mut self, amount: Money) -> Result<(), Error> {
+        let index = match self.state {
+            GameState::PlayerTurn(index) => index,
+            state => return Err(Error::WrongState(state)),
+        };
+        let options = self.conf.rules.hand_options(&self.player, index);
+        if self.conf.rules.variant.double_ends_hand() || !options.moves.contains(&Move::Double) {
+            return Err(Error::IllegalMove(Move::Double));
+        }
+        if amount <= Money::ZERO || !self.player.can_afford(amount) {
+            return Err(Error::InvalidBet(amount));
+        }
+
+        self.save_undo();
+        self.player
+            .wager(&format!("Buy hand {}", index + 1), amount);
+        let card = self.draw(Seat::Hand(index))?;
+        self.player.hands[index].buy(card, amount);
+        self.emit_action(index, Move::Double);
+        self.skip_finished_hands();
+        Ok(())
+    }
+
+    /// Whether the last move of the round can be taken back, see [`GameEngine::undo`].
+    pub fn can_undo(&self) -> bool {
+        matches!(self.state, GameState::PlayerTurn(_)) && !self.undo_history.is_empty()
+    }
+
+    /// Takes back the last hit, double or split in practice mode, returning the cards to the shoe
+    /// and the count and bankroll to what they were.
+    pub fn undo(&mut self) -> Result<(), Error> {
+        if !self.can_undo() {
+            return Err(Error::Undo);
+        }
+        let snapshot = self.undo_history.pop().expect("There is a move to undo");
+        self.restore(snapshot);
+        if let GameState::PlayerTurn(index) = self.state {
+            self.emit(GameEvent::MoveUndone {
+                hand: index,
+                hands: self.player.hands.len(),
+                cards: self.player.hands[index].hand.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Decides whether to rescue a doubled hand, once a rescue is offered.
+    pub fn rescue(&mut self, rescue: bool) -> Result<(), Error> {
+        let index = match self.state {
+            GameState::DoubleRescue(index) => index,
+            state => return Err(Error::WrongState(state)),
+        };
+        self.player.hands[index].surrendered = rescue;
+        if rescue {
+            self.emit(GameEvent::DoubleRescued { hand: index });
+        }
+        self.state = GameState::PlayerTurn(index + 1);
+        self.skip_finished_hands();
+        Ok(())
+    }
+
+    /// Buys in for more money, the rebuy isn't counted in the result of the round.
+    pub fn rebuy(&mut self, amount: Money) {
+        self.player.rebuy(amount);
+        self.round_bankroll += amount;
+    }
+
+    /// Tips the dealer once the round is settled, 0 for no tip.
+    pub fn tip(&mut self, amount: Money) -> Result<(), Error> {
+        self.expect_state(GameState::RoundOver)?;
+        if amount < Money::ZERO || !self.player.can_afford(amount) {
+            return Err(Error::InvalidBet(amount));
+        }
+        if amount > Money::ZERO {
+            self.player.tip(amount);
+            self.emit(GameEvent::DealerTipped { amount });
+        }
+        Ok(())
+    }
+
+    /// Calls off the round in progress, returning every bet placed on it and clearing the table.
+    /// A round is void when the shoe runs out of cards before it's finished.
+    pub fn void_round(&mut self) {
+        for seat in 0..self.seats.len() {
+            self.sit(seat);
+            let net = self.player.money - self.round_bankroll;
+            if net != Money::ZERO {
+                self.player.credit("Round void", -net);
+            }
+            for hand in self.player.hands.iter_mut() {
+                self.shoe.discard(std::mem::take(&mut hand.hand.cards));
+            }
+        }
+        self.shoe
+            .discard(std::mem::take(&mut self.dealer_hand.cards));
+        self.shoe
+            .discard(self.hole_card.take().into_iter().collect());
+        self.start_round();
+    }
+
+    /// Advances the round by one automatic stage and returns the new state.
+    /// Nothing happens while waiting for bets or a decision of the player.
+    pub fn step(&mut self) -> Result<GameState, Error> {
+        match self.state {
+            GameState::Betting if !self.seats_in_round().is_empty() => {
+                self.state = GameState::Dealing;
+            }
+            GameState::Dealing => self.deal()?,
+            GameState::Insurance => self.peek(),
+            GameState::DealerTurn => {
+                if self.dealer_must_play() && self.conf.rules.dealer_hits(&self.dealer_hand) {
+                    let card = self.draw(Seat::Dealer)?;
+                    self.dealer_hand.add_card(card);
+                } else {
+                    self.settle_bust_it();
+                    self.state = GameState::Settlement;
+                }
+            }
+            GameState::Settlement => self.settle_hands(),
+            GameState::RoundOver => self.finish_round(),
+            GameState::Betting | GameState::PlayerTurn(_) | GameState::DoubleRescue(_) => {}
+        }
+        Ok(self.state)
+    }
+
+    /// Plays the round through to the end once the bets are placed, asking `input` for every
+    /// decision on the way.
+    pub fn play_round(&mut self, input: &mut dyn PlayerInput) -> Result<(), Error> {
+        loop {
+            match self.state {
+                GameState::Betting if self.seats_in_round().is_empty() => return Ok(()),
+                GameState::Insurance => {
+                    for seat in self.seats_in_round() {
+                        self.take_seat(seat)?;
+                        for i in 0..self.player.hands.len() {
+                            if self.player.hands[i].is_blackjack() && input.even_money(self, i)? {
+                                self.take_even_money(i)?;
+                            }
+                        }
+                        if self.player.hands.iter().any(|h| !h.is_blackjack()) {
+                            let amount = input.insurance(self)?;
+                            self.insure(amount)?;
+                        }
+                    }
+                    self.step()?;
+                }
+                GameState::PlayerTurn(index) => {
+                    let options = self.conf.rules.hand_options(&self.player, index);
+                    let action = match input.action(self, index, &options) {
+                        Err(Error::Undo) => {
+                            self.undo()?;
+                            continue;
+                        }
+                        action => action?,
+                    };
+                    match action {
+                        Move::Double if !self.conf.rules.variant.double_ends_hand() => {
+                            let amount = input.buy_amount(self, index)?;
+                            self.buy(amount)?;
+                        }
+                        action => self.act(action)?,
+                    }
+                }
+                GameState::DoubleRescue(index) => {
+                    let rescue = input.rescue(self, index)?;
+                    self.rescue(rescue)?;
+                }
+                GameState::RoundOver => {
+                    for seat in self.seats_in_round() {
+                        if self.conf.tips && self.seat_won(seat) {
+                            self.take_seat(seat)?;
+                            let tip = input.tip(self)?;
+                            self.tip(tip)?;
+                        }
+                    }
+                    self.step()?;
+                    return Ok(());
+                }
+                _ => {
+                    self.step()?;
+                }
+            }
+        }
+    }
+
+    /// Whether any hand of the seat in play won money in the settled round.
+    pub fn round_won(&self) -> bool {
+        self.seat_won(self.seat)
+    }
+
+    /// Whether any hand of the player at `seat` won money in the settled round.
+    pub fn seat_won(&self, seat: usize) -> bool {
+        let outcomes = if seat == self.seat {
+            &self.outcomes
+        } else {
+            &self.seats[seat].outcomes
+        };
+        let blackjack_payout = self.conf.rules.blackjack_payout();
+        self.player_at(seat)
+            .hands
+            .iter()
+            .zip(outcomes.iter())
+            .any(|(hand, outcome)| outcome.net_winnings(hand, &blackjack_payout) > Money::ZERO)
+    }
+
+    /// Steps through the automatic stages until the player has to decide something.
+    pub fn run_until_input(&mut self) -> Result<GameState, Error> {
+        loop {
+            let state = self.state;
+            if self.step()? == state {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// The dealer's face up card, once the cards are dealt.
+    pub fn upcard(&self) -> Option<&Card> {
+        self.dealer_hand.cards.first()
+    }
+
+    /// What the betting system sizes the next bet on.
+    pub fn bet_situation(&self) -> BetSituation {
+        BetSituation {
+            base: self.conf.base_bet,
+            true_count: self.counter.true_count,
+            bankroll: self.player.money,
+        }
+    }
+
+    /// The dealer's face down card, which a player at the table doesn't get to see.
+    pub fn hole_card(&self) -> Option<&Card> {
+        self.hole_card.as_ref()
+    }
+
+    /// The number of cards the dealer busted with, if the dealer is bust.
+    pub fn dealer_bust_cards(&self) -> Option<usize> {
+        (self.dealer_hand.calc_value() > TWENTY_ONE).then_some(self.dealer_hand.cards.len())
+    }
+
+    fn start_round(&mut self) {
+        for seat in 0..self.seats.len() {
+            self.sit(seat);
+            self.player.clear_bets();
+            self.player.ledger.start_round(self.player.stats.rounds + 1);
+            self.outcomes.clear();
+            self.round_bankroll = self.player.money;
+        }
+        self.sit(0);
+        self.dealer_hand = Hand::default();
+        self.twenty_one_plus_three = None;
+        self.peeked = false;
+        self.hole_card = None;
+        self.undo_history.clear();
+        self.state = GameState::Betting;
+    }
+
+    /// Deals one card at a time to each hand from first base to third base and then to the
+    /// dealer, twice around the table.
+    fn deal(&mut self) -> Result<(), Error> {
+        let seats = self.seats_in_round();
+        self.deal_seats(&seats)?;
+        let upcard = if self.conf.rules.variant.dealer_shows_upcard() {
+            self.draw(Seat::Dealer)?
+        } else {
+            self.draw_face_down(Seat::Dealer)?
+        };
+        self.dealer_hand = Hand::from_card(upcard);
+        self.deal_seats(&seats)?;
+        self.hole_card = Some(self.draw_face_down(Seat::Dealer)?);
+
+        for &seat in seats.iter() {
+            self.sit(seat);
+            if self.player.twenty_one_plus_three > Money::ZERO {
+                self.announce_seat();
+                self.settle_twenty_one_plus_three();
+            }
+        }
+
+        if self.conf.rules.variant.offers_insurance()
+            && self.dealer_hand.cards[0].value == Value::Ace
+        {
+            self.sit(seats[0]);
+            self.state = GameState::Insurance;
+        } else {
+            self.peek();
+        }
+        Ok(())
+    }
+
+    /// Deals a card to every hand of `seats`, seat by seat.
+    fn deal_seats(&mut self, seats: &[usize]) -> Result<(), Error> {
+        for &seat in seats {
+            self.sit(seat);
+            for i in 0..self.player.hands.len() {
+                let card = self.draw(Seat::Hand(i))?;
+                self.player.hands[i].hand.add_card(card);
+            }
+        }
+        Ok(())
+    }
+
+    fn settle_twenty_one_plus_three(&mut self) {
+        let first = &self.player.hands[0].hand.cards;
+        let cards = [
+            first[0].clone(),
+            first[1].clone(),
+            self.dealer_hand.cards[0].clone(),
+        ];
+        let poker_hand = ThreeCardHand::evaluate(&cards);
+        let bet = self.player.twenty_one_plus_three;
+        match poker_hand.payout() {
+            Some(payout) => self
+                .player
+                .credit(&format!("21+3 {}", poker_hand), bet + payout.apply(bet)),
+            None => self.player.credit("21+3 lost", Money::ZERO),
+        }
+        self.twenty_one_plus_three = Some(poker_hand);
+        self.emit(GameEvent::TwentyOnePlusThreeSettled {
+            poker_hand,
+            bet,
+            payout: poker_hand.payout(),
+        });
+    }
+
+    /// The dealer checks the hole card for blackjack so the player doesn't act in vain.
+    fn peek(&mut self) {
+        self.peeked = !self.conf.rules.variant.dealer_shows_upcard()
+            || (self.conf.rules.dealer_peek && self.dealer_hand.cards[0].value.value() >= 10);
+        let dealer_blackjack = self.peeked && {
+            let mut peek_hand = Hand::from_card(self.dealer_hand.cards[0].clone());
+            peek_hand.add_card(self.hole_card.clone().expect("No hole card dealt"));
+            peek_hand.is_blackjack()
+        };
+        if self.peeked {
+            self.emit(GameEvent::DealerPeeked {
+                blackjack: dealer_blackjack,
+            });
+        }
+
+        if dealer_blackjack {
+            self.start_dealer_turn();
+        } else {
+            self.sit(self.seats_in_round()[0]);
+            self.announce_seat();
+            self.state = GameState::PlayerTurn(0);
+            self.skip_finished_hands();
+        }
+    }
+
+    /// Why the player has nothing left to do on the hand, if so.
+    fn finish_reason(&self, index: usize) -> Option<FinishReason> {
+        let hand = &self.player.hands[index];
+        let value = hand.hand.calc_value();
+        if value > TWENTY_ONE {
+            Some(FinishReason::Bust)
+        } else if hand.is_blackjack() {
+            Some(FinishReason::Blackjack)
+        } else if value == TWENTY_ONE {
+            Some(FinishReason::TwentyOne)
+        } else if self.conf.rules.variant == Variant::Pontoon
+            && hand.hand.is_charlie(FIVE_CARD_TRICK)
+        {
+            Some(FinishReason::FiveCardTrick)
+        } else if self
+            .conf
+            .rules
+            .charlie
+            .is_some_and(|n| hand.hand.is_charlie(n))
+        {
+            Some(FinishReason::Charlie)
+        } else if self
+            .conf
+            .rules
+            .hand_options(&self.player, index)
+            .moves
+            .is_empty()
+        {
+            Some(FinishReason::SplitAces)
+        } else {
+            None
+        }
+    }
+
+    /// Moves past the hands that can't be played on, to the next seat once all hands of the seat
+    /// are played and to the dealer after the last seat.
+    fn skip_finished_hands(&mut self) {
+        if let GameState::PlayerTurn(mut index) = self.state {
+            loop {
+                let hands = self.player.hands.len();
+                while index < hands {
+                    match self.finish_reason(index) {
+                        Some(reason) => self.emit(GameEvent::HandFinished {
+                            hand: index,
+                            hands,
+                            cards: self.player.hands[index].hand.clone(),
+                            reason,
+                        }),
+                        None => break,
+                    }
+                    index += 1;
+                }
+                if index < hands {
+                    self.state = GameState::PlayerTurn(index);
+                    self.emit(GameEvent::PlayerTurn {
+                        hand: index,
+                        hands,
+                        cards: self.player.hands[index].hand.clone(),
+                    });
+                    return;
+                }
+                let next = self
+                    .seats_in_round()
+                    .into_iter()
+                    .find(|&seat| seat > self.seat);
+                match next {
+                    Some(seat) => {
+                        // Moves of the previous seat can't be taken back any more
+                        self.undo_history.clear();
+                        self.sit(seat);
+                        self.announce_seat();
+                        index = 0;
+                    }
+                    None => {
+                        self.start_dealer_turn();
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reveals the hole card and settles the insurance.
+    fn start_dealer_turn(&mut self) {
+        if let Some(card) = self.hole_card.take() {
+            if !self.conf.rules.variant.dealer_shows_upcard() {
+                self.see(&self.dealer_hand.cards[0].clone());
+            }
+            self.see(&card);
+            self.dealer_hand.add_card(card);
+            self.emit(GameEvent::DealerReveal {
+                hand: self.dealer_hand.clone(),
+            });
+        }
+
+        for seat in 0..self.seats.len() {
+            self.sit(seat);
+            self.settle_insurance();
+        }
+        self.state = GameState::DealerTurn;
+    }
+
+    fn settle_insurance(&mut self) {
+        let insurance = self.player.insurance;
+        if insurance > Money::ZERO {
+            self.announce_seat();
+            let won = self.dealer_hand.is_blackjack();
+            if won {
+                let winnings = Player::INSURANCE_PAYOUT.apply(insurance);
+                self.player.credit("Insurance won", insurance + winnings);
+            } else {
+                self.player.credit("Insurance lost", Money::ZERO);
+            }
+            self.emit(GameEvent::InsuranceSettled {
+                bet: insurance,
+                won,
+            });
+        }
+    }
+
+    /// The dealer only has to play if there is a hand or a Bust It bet that isn't already decided.
+    fn dealer_must_play(&self) -> bool {
+        !self.dealer_hand.is_blackjack()
+            && (0..self.seats.len()).any(|seat| {
+                let player = self.player_at(seat);
+                player.bust_it > Money::ZERO || player.hands.iter().any(|h| !h.is_finished())
+            })
+    }
+
+    fn settle_bust_it(&mut self) {
+        for seat in 0..self.seats.len() {
+            self.sit(seat);
+            self.settle_seat_bust_it();
+        }
+    }
+
+    fn settle_seat_bust_it(&mut self) {
+        let bet = self.player.bust_it;
+        if bet == Money::ZERO {
+            return;
+        }
+        self.announce_seat();
+        let dealer_cards = self.dealer_bust_cards();
+        let payout = dealer_cards.and_then(bust_it_payout);
+        match payout {
+            Some(payout) => self.player.credit("Bust It won", bet + payout.apply(bet)),
+            None => self.player.credit("Bust It lost", Money::ZERO),
+        }
+        self.emit(GameEvent::BustItSettled {
+            bet,
+            dealer_cards,
+            payout,
+        });
+    }
+
+    fn settle_hands(&mut self) {
+        for seat in self.seats_in_round() {
+            self.sit(seat);
+            self.announce_seat();
+            self.settle_seat_hands();
+        }
+        self.state = GameState::RoundOver;
+    }
+
+    fn settle_seat_hands(&mut self) {
+        let blackjack_payout = self.conf.rules.blackjack_payout();
+        let mut results = vec![];
+        for hand in self.player.hands.iter() {
+            let outcome = self.conf.rules.resolve(hand, &self.dealer_hand);
+            results.push(HandResult {
+                hand: hand.hand.clone(),
+                outcome,
+                winnings: outcome.net_winnings(hand, &blackjack_payout),
+                payout: outcome.total_return(hand, &blackjack_payout),
+                doubled: hand.doubled,
+                split: hand.split,
+                origin: hand.origin,
+            });
+            self.outcomes.push(outcome);
+        }
+        for (i, result) in results.iter().enumerate() {
+            self.player
+                .credit(&format!("Hand {} {}", i + 1, result.outcome), result.payout);
+        }
+        self.emit(GameEvent::RoundSettled {
+            dealer: self.dealer_hand.clone(),
+            hands: results,
+        });
+    }
+
+    /// Records the round of every seat that played it, clears the table and waits for the bets
+    /// of the next round.
+    fn finish_round(&mut self) {
+        for seat in self.seats_in_round() {
+            self.sit(seat);
+            self.player.stats.record_round(self.player.money);
+            let result = self.player.money - self.round_bankroll;
+            if let Some(strategy) = self.player.bet_strategy.as_mut() {
+                strategy.record(result);
+            }
+            for hand in self.player.hands.iter_mut() {
+                self.shoe.discard(std::mem::take(&mut hand.hand.cards));
+            }
+        }
+        self.sit(0);
+
+        self.shoe
+            .discard(std::mem::take(&mut self.dealer_hand.cards));
+        self.emit(GameEvent::RoundFinished {
+            bankroll: (self.seats.len() == 1).then_some(self.player.money),
+            running_count: self.counter.running_count,
+            true_count: self.counter.true_count,
+        });
+        if self.conf.rules.csm {
+            // The full composition is restored, so the count starts over
+            self.shoe.return_discards(self.shuffler.as_mut());
+            self.counter.reset();
+        }
+
+        self.start_round();
+    }
+
+    /// Swaps the player at `seat` into play, and the player in play back to their seat.
+    fn sit(&mut self, seat: usize) {
+        if seat != self.seat {
+            self.swap_seat(self.seat);
+            self.swap_seat(seat);
+            self.seat = seat;
+        }
+    }
+
+    fn swap_seat(&mut self, seat: usize) {
+        let parked = &mut self.seats[seat];
+        std::mem::swap(&mut self.player, &mut parked.player);
+        std::mem::swap(&mut self.round_bankroll, &mut parked.round_bankroll);
+        std::mem::swap(&mut self.outcomes, &mut parked.outcomes);
+    }
+
+    fn player_at_mut(&mut self, seat: usize) -> &mut Player {
+        if seat == self.seat {
+            &mut self.player
+        } else {
+            &mut self.seats[seat].player
+        }
+    }
+
+    /// Tells the observers whose turn it is, at a table with several players.
+    fn announce_seat(&mut self) {
+        if self.seats.len() > 1 {
+            self.emit(GameEvent::SeatInPlay {
+                seat: self.seat,
+                position: self.seats[self.seat].position,
+                name: self.player.name.clone(),
+            });
+        }
+    }
+
+    fn expect_state(&self, state: GameState) -> Result<(), Error> {
+        if self.state == state {
+            Ok(())
+        } else {
+            Err(Error::WrongState(self.state))
+        }
+    }
+
+    /// Side bets are only taken alongside a main bet.
+    fn check_side_bet(&self, bet: Money) -> Result<(), Error> {
+        if bet < Money::ZERO
+            || !self.player.can_afford(bet)
+            || (bet > Money::ZERO && self.player.hands.is_empty())
+        {
+            return Err(Error::InvalidBet(bet));
+        }
+        Ok(())
+    }
+
+    /// Takes the next card from the shoe, dealt face up to `seat`.
+    fn draw(&mut self, seat: Seat) -> Result<Card, Error> {
+        let card = self.take_card()?;
+        self.see(&card);
+        self.emit(GameEvent::CardDealt {
+            seat,
+            card: Some(card.clone()),
+        });
+        Ok(card)
+    }
+
+    /// Counts a card once it is face up, face down cards are counted when they are revealed.
+    fn see(&mut self, card: &Card) {
+        self.counter.count(card, self.shoe.num_cards());
+    }
+
+    fn draw_face_down(&mut self, seat: Seat) -> Result<Card, Error> {
+        let card = self.take_card()?;
+        self.emit(GameEvent::CardDealt { seat, card: None });
+        Ok(card)
+    }
+
+    /// Takes the top card, shuffling the discards into a new shoe first if the shoe ran out in the
+    /// middle of a round. The cards on the table stay out until the full shuffle after the round.
+    fn take_card(&mut self) -> Result<Card, Error> {
+        if self.shoe.cards.is_empty() && !self.shoe.discards.is_empty() {
+            self.shoe = self.shoe.clone().shuffle(self.shuffler.as_mut());
+            if self.shoe.num_cards() > 1 {
+                self.shoe.burn_card();
+            }
+            self.shoe.cut_card_reached = true;
+            self.counter.reset();
+            self.emit(GameEvent::DiscardsShuffled);
+        }
+        self.shoe.take_card()
+    }
+
+    /// Remembers the table before a move that can be undone in practice mode.
+    fn save_undo(&mut self) {
+        if self.conf.practice {
+            let snapshot = self.snapshot();
+            self.undo_history.push(snapshot);
+        }
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        for observer in self.observers.iter_mut() {
+            observer.on_event(&event);
+        }
+    }
+
+    fn emit_side_bet(&mut self, side_bet: SideBet, amount: Money) {
+        if amount > Money::ZERO {
+            self.emit(GameEvent::SideBetPlaced { side_bet, amount });
+        }
+    }
+
+    fn emit_action(&mut self, hand: usize, action: Move) {
+        let cards = self.player.hands[hand].hand.clone();
+        self.emit(GameEvent::PlayerAction {
+            hand,
+            action,
+            cards,
+        });
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::config::GameConfig;
+    use crate::counting::{CardCounter, HiOptI};
+    use crate::engine::{GameEngine, GameState, Snapshot};
+    use crate::error::Error;
+    use crate::events::{GameEvent, GameObserver, Seat};
+    use crate::input::ScriptedInput;
+    use crate::money::Money;
+    use crate::table::SeatPosition;
+    use crate::types::{
+        Card, Hand, Move, Outcome, PackedCard, Player, PlayerHand, Shoe, Suit, Value,
+    };
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records every event in a list shared with the test.
+    struct Recorder(Rc<RefCell<Vec<GameEvent>>>);
+
+    impl GameObserver for Recorder {
+        fn on_event(&mut self, event: &GameEvent) {
+            self.0.borrow_mut().push(event.clone());
+        }
+    }
+
+    /// An engine whose shoe deals `values` in order.
+    fn engine(values: &[Value]) -> GameEngine {
+        let mut engine =
+            GameEngine::new(GameConfig::default(), Player::new(Money::from_units(100))).unwrap();
+        engine.shoe.cards = values
+            .iter()
+            .rev()
+            .map(|value| Card {
+                suit: Suit::Clubs,
+                value: value.clone(),
+            })
+            .map(PackedCard::from)
+            .collect();
+        engine.shoe.discards.clear();
+        engine.shoe.cut_card = 0;
+        engine
+    }
+
+    /// An engine where the player was dealt `cards` for a bet of 10 against the dealer's
+    /// `upcard`, for asking the strategies what to do with the hand.
+    pub(crate) fn dealt(conf: GameConfig, cards: &[Value], upcard: Value) -> GameEngine {
+        let mut engine = GameEngine::new(conf, Player::new(Money::from_units(100))).unwrap();
+        let card = |value: &Value| Card {
+            suit: Suit::Clubs,
+            value: value.clone(),
+        };
+        let mut hand = Hand::default();
+        for value in cards {
+            hand.add_card(card(value));
+        }
+        engine
+            .player
+            .hands
+            .push(PlayerHand::new(hand, Money::from_units(10)));
+        engine.dealer_hand = Hand::from_card(card(&upcard));
+        engine
+    }
+
+    #[test]
+    fn test_round_states() {
+        // Player 10+6 against a dealer 9+7, the player hits a 4 and the dealer busts on a ten
+        let mut engine = engine(&[
+            Value::Ten,
+            Value::Nine,
+            Value::Six,
+            Value::Seven,
+            Value::Four,
+            Value::Ten,
+        ]);
+        assert_eq!(engine.step().unwrap(), GameState::Betting);
+
+        engine.place_bet(Money::from_units(10)).unwrap();
+        assert_eq!(engine.player.money, Money::from_units(90));
+        assert_eq!(engine.step().unwrap(), GameState::Dealing);
+        assert_eq!(engine.step().unwrap(), GameState::PlayerTurn(0));
+        assert_eq!(engine.step().unwrap(), GameState::PlayerTurn(0));
+
+        engine.act(Move::Hit).unwrap();
+        assert_eq!(engine.player.hands[0].hand.calc_value(), 20);
+        engine.act(Move::Stand).unwrap();
+        assert_eq!(engine.state(), GameState::DealerTurn);
+        assert_eq!(engine.dealer_hand.calc_value(), 16);
+
+        assert_eq!(engine.step().unwrap(), GameState::DealerTurn);
+        assert_eq!(engine.dealer_bust_cards(), Some(3));
+        assert_eq!(engine.step().unwrap(), GameState::Settlement);
+        assert_eq!(engine.step().unwrap(), GameState::RoundOver);
+        assert_eq!(engine.outcomes, vec![Outcome::DealerBust]);
+        assert_eq!(engine.player.money, Money::from_units(110));
+
+        assert_eq!(engine.step().unwrap(), GameState::Betting);
+        assert_eq!(engine.player.stats.rounds, 1);
+        assert!(engine.player.hands.is_empty());
+        assert_eq!(engine.shoe.discards.len(), 6);
+    }
+
+    #[test]
+    fn test_dealing_order() {
+        let players = vec![
+            (
+                SeatPosition::THIRD_BASE,
+                Player::new(Money::from_units(100)),
+            ),
+            (
+                SeatPosition::FIRST_BASE,
+                Player::new(Money::from_units(100)),
+            ),
+        ];
+        let shoe = Shoe::new(1, GameConfig::default().rules.variant).unwrap();
+        let mut engine =
+            GameEngine::seated_at(GameConfig::default(), shoe, players, vec![]).unwrap();
+        assert_eq!(engine.position(0), SeatPosition::FIRST_BASE);
+        assert_eq!(engine.position(1), SeatPosition::THIRD_BASE);
+        let next = engine.shoe.preview(6);
+
+        for seat in 0..2 {
+            engine.take_seat(seat).unwrap();
+            engine.place_bet(Money::from_units(10)).unwrap();
+        }
+        engine.step().unwrap();
+        engine.step().unwrap();
+        // First base, third base and the dealer, twice around the table
+        assert_eq!(
+            engine.player_at(0).hands[0].hand.cards,
+            vec![next[0].clone(), next[3].clone()]
+        );
+        assert_eq!(
+            engine.player_at(1).hands[0].hand.cards,
+            vec![next[1].clone(), next[4].clone()]
+        );
+        assert_eq!(engine.dealer_hand.cards[0], next[2]);
+
+        let taken = vec![
+            (
+                SeatPosition::FIRST_BASE,
+                Player::new(Money::from_units(100)),
+            ),
+            (
+                SeatPosition::FIRST_BASE,
+                Player::new(Money::from_units(100)),
+            ),
+        ];
+        let shoe = Shoe::new(1, GameConfig::default().rules.variant).unwrap();
+        assert!(matches!(
+            GameEngine::seated_at(GameConfig::default(), shoe, taken, vec![]),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_dealer_blackjack_skips_player_turn() {
+        let mut engine = engine(&[Value::Eight, Value::King, Value::Eight, Value::Ace]);
+        engine.place_bet(Money::from_units(10)).unwrap();
+        engine.step().unwrap();
+        assert_eq!(engine.step().unwrap(), GameState::DealerTurn);
+        assert!(engine.peeked);
+        assert!(engine.dealer_hand.is_blackjack());
+        // The revealed hole card is counted along with the cards dealt face up
+        assert_eq!(engine.counter.running_count, -2);
+        assert_eq!(engine.run_until_input().unwrap(), GameState::Betting);
+        assert_eq!(engine.player.stats.rounds, 1);
+        assert_eq!(engine.player.money, Money::from_units(90));
+    }
+
+    #[test]
+    fn test_split_hands_are_played_in_order() {
+        let mut engine = engine(&[
+            Value::Eight,
+            Value::Ten,
+            Value::Eight,
+            Value::Seven,
+            Value::Three,
+            Value::Ten,
+        ]);
+        engine.place_bet(Money::from_units(10)).unwrap();
+        assert_eq!(engine.run_until_input().unwrap(), GameState::PlayerTurn(0));
+
+        engine.act(Move::Split).unwrap();
+        assert_eq!(engine.player.hands.len(), 2);
+        assert_eq!(engine.player.money, Money::from_units(80));
+        assert_eq!(engine.player.hands[0].hand.calc_value(), 11);
+        engine.act(Move::Stand).unwrap();
+        assert_eq!(engine.state(), GameState::PlayerTurn(1));
+        assert_eq!(engine.player.hands[1].hand.calc_value(), 18);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut engine = engine(&[Value::Ten, Value::Nine, Value::Six, Value::Seven]);
+        assert_eq!(
+            engine.act(Move::Hit),
+            Err(Error::WrongState(GameState::Betting))
+        );
+        assert_eq!(
+            engine.place_bet(Money::from_units(200)),
+            Err(Error::InvalidBet(Money::from_units(200)))
+        );
+
+        engine.place_bet(Money::from_units(10)).unwrap();
+        assert_eq!(engine.run_until_input(), Ok(GameState::PlayerTurn(0)));
+        assert_eq!(
+            engine.act(Move::Split),
+            Err(Error::IllegalMove(Move::Split))
+        );
+
+        // The shoe is empty, so the round can't be finished and is called off
+        assert_eq!(engine.act(Move::Hit), Err(Error::OutOfCards));
+        engine.void_round();
+        assert_eq!(engine.state(), GameState::Betting);
+        assert_eq!(engine.player.money, Money::from_units(100));
+        assert_eq!(engine.shoe.discards.len(), 4);
+    }
+
+    #[test]
+    fn test_discards_shuffled_mid_round() {
+        let mut engine = engine(&[Value::Ten, Value::Nine, Value::Six, Value::Seven]);
+        let discard = |value| {
+            PackedCard::from(Card {
+                suit: Suit::Spades,
+                value,
+            })
+        };
+        engine.shoe.discards = vec![discard(Value::Two), discard(Value::Three)];
+        let events = Rc::new(RefCell::new(vec![]));
+        engine.subscribe(Box::new(Recorder(events.clone())));
+
+        engine.place_bet(Money::from_units(10)).unwrap();
+        engine.run_until_input().unwrap();
+        engine.act(Move::Hit).unwrap();
+        // One of the discards was burned, the other one dealt
+        assert_eq!(engine.player.hands[0].hand.cards.len(), 3);
+        assert!(engine.shoe.cards.is_empty());
+        assert!(engine.shoe.cut_card_reached);
+        assert!(events.borrow().contains(&GameEvent::DiscardsShuffled));
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut engine = engine(&[
+            Value::Ten,
+            Value::Nine,
+            Value::Six,
+            Value::Seven,
+            Value::Four,
+            Value::Ten,
+        ]);
+        engine.place_bet(Money::from_units(10)).unwrap();
+        assert_eq!(engine.run_until_input().unwrap(), GameState::PlayerTurn(0));
+        let saved = serde_json::to_string(&engine.snapshot()).unwrap();
+
+        let mut resumed =
+            GameEngine::new(GameConfig::default(), Player::new(Money::from_units(5))).unwrap();
+        resumed.restore(serde_json::from_str::<Snapshot>(&saved).unwrap());
+        assert_eq!(resumed.state(), GameState::PlayerTurn(0));
+        assert_eq!(resumed.shoe, engine.shoe);
+        assert_eq!(resumed.player.hands, engine.player.hands);
+        assert_eq!(resumed.counter.running_count, engine.counter.running_count);
+
+        // Both play on with the same cards, including the hole card
+        for engine in [&mut engine, &mut resumed] {
+            engine.act(Move::Hit).unwrap();
+            engine.act(Move::Stand).unwrap();
+            while engine.step().unwrap() != GameState::Betting {}
+        }
+        assert_eq!(resumed.player.ledger, engine.player.ledger);
+        assert_eq!(resumed.player.money, Money::from_units(110));
+    }
+
+    #[test]
+    fn test_practice_undo() {
+        let mut engine = engine(&[
+            Value::Eight,
+            Value::Nine,
+            Value::Eight,
+            Value::Seven,
+            Value::Two,
+            Value::Three,
+            Value::Ten,
+        ]);
+        engine.conf.practice = true;
+        engine.place_bet(Money::from_units(10)).unwrap();
+        engine.run_until_input().unwrap();
+        assert_eq!(engine.undo(), Err(Error::Undo));
+        let count = engine.counter.running_count;
+        let shoe = engine.shoe.preview(3);
+
+        engine.act(Move::Split).unwrap();
+        engine.act(Move::Hit).unwrap();
+        assert_eq!(engine.player.hands[0].hand.calc_value(), 20);
+        assert_eq!(engine.player.money, Money::from_units(80));
+
+        // Each undo returns the cards of a move to the shoe
+        engine.undo().unwrap();
+        assert_eq!(engine.player.hands[0].hand.calc_value(), 10);
+        assert_eq!(engine.shoe.preview(1)[0].value, Value::Ten);
+        engine.undo().unwrap();
+        assert_eq!(engine.player.hands.len(), 1);
+        assert_eq!(engine.player.money, Money::from_units(90));
+        assert_eq!(engine.counter.running_count, count);
+        assert_eq!(engine.shoe.preview(3), shoe);
+        assert!(!engine.can_undo());
+    }
+
+    #[test]
+    fn test_undo_ace_side_count() {
+        // Player 10+2 against a dealer 9+7, hitting an ace
+        let mut engine = engine(&[
+            Value::Ten,
+            Value::Nine,
+            Value::Two,
+            Value::Seven,
+            Value::Ace,
+        ]);
+        engine.conf.practice = true;
+        engine.counter = CardCounter::new(Box::new(HiOptI), engine.conf.rules.deck_count)
+            .with_ace_side_count(true);
+        engine.place_bet(Money::from_units(10)).unwrap();
+        engine.run_until_input().unwrap();
+        let aces_seen = engine.counter.aces_seen;
+
+        engine.act(Move::Hit).unwrap();
+        let true_count = engine.counter.true_count;
+        assert_eq!(engine.counter.aces_seen, aces_seen + 1);
+
+        // The ace goes back into the shoe and out of the side count
+        engine.undo().unwrap();
+        assert_eq!(engine.counter.aces_seen, aces_seen);
+        engine.act(Move::Hit).unwrap();
+        assert_eq!(engine.counter.aces_seen, aces_seen + 1);
+        assert_eq!(engine.counter.true_count, true_count);
+    }
+
+    #[test]
+    fn test_events() {
+        let mut engine = engine(&[
+            Value::Ten,
+            Value::Nine,
+            Value::Six,
+            Value::Seven,
+            Value::Four,
+            Value::Ten,
+        ]);
+        let events = Rc::new(RefCell::new(vec![]));
+        engine.subscribe(Box::new(Recorder(events.clone())));
+
+        engine.place_bet(Money::from_units(10)).unwrap();
+        engine
+            .play_round(&mut ScriptedInput::new(&[], &[Move::Hit, Move::Stand]))
+            .unwrap();
+        assert_eq!(engine.state(), GameState::Betting);
+
+        let events = events.borrow();
+        // The hole card is dealt last, face down, and only shown when revealed
+        assert_eq!(
+            events[4],
+            GameEvent::CardDealt {
+                seat: Seat::Dealer,
+                card: None
+            }
+        );
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, GameEvent::DealerReveal { hand } if hand.calc_value() == 16)));
+        let settled = events.iter().find_map(|e| match e {
+            GameEvent::RoundSettled { hands, .. } => Some(hands),
+            _ => None,
+        });
+        assert_eq!(settled.unwrap()[0].outcome, Outcome::DealerBust);
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::RoundFinished { .. })
+        ));
+    }
+}
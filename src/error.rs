@@ -0,0 +1,66 @@
+use crate::config::MAX_SEATS;
+use crate::engine::GameState;
+use crate::money::Money;
+use crate::types::Move;
+use std::fmt::{Display, Formatter};
+
+/// Everything that can go wrong while setting up or playing the game.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    InvalidCardVal(u32),
+    /// The rules or table settings contradict each other or can't be played.
+    Config(String),
+    /// The shoe ran out of cards before the round could be finished, with no discards to shuffle.
+    OutOfCards,
+    /// The engine isn't waiting for the attempted action in its current state.
+    WrongState(GameState),
+    /// The move isn't available on the hand being played.
+    IllegalMove(Move),
+    /// A bet, insurance or tip that is zero where it can't be, too large or unaffordable.
+    InvalidBet(Money),
+    /// A game was set up with this many players, but the table seats 1 to [`MAX_SEATS`].
+    Seats(usize),
+    /// There is no seat at the index, the table has fewer players.
+    NoSeat(usize),
+    /// The player's input ended, so no more decisions can be made.
+    InputClosed,
+    /// Reading the player's input failed.
+    Input(String),
+    /// The player asked to take back the last move in practice mode, or there was none to undo.
+    Undo,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidCardVal(val) => write!(f, "invalid card value {}", val),
+            Error::Config(reason) => write!(f, "invalid configuration: {}", reason),
+            Error::OutOfCards => write!(f, "the shoe ran out of cards"),
+            Error::WrongState(state) => write!(f, "that isn't possible during {}", state),
+            Error::IllegalMove(action) => {
+                let action = match action {
+                    Move::Hit => "hit",
+                    Move::Stand => "stand",
+                    Move::Double => "double",
+                    Move::Split => "split",
+                    Move::Surrender => "surrender",
+                };
+                write!(f, "cannot {} on this hand", action)
+            }
+            Error::InvalidBet(amount) => write!(f, "invalid bet of {}", amount),
+            Error::Seats(players) => {
+                write!(
+                    f,
+                    "the table seats 1 to {} players, not {}",
+                    MAX_SEATS, players
+                )
+            }
+            Error::NoSeat(seat) => write!(f, "there is no seat {} at the table", seat + 1),
+            Error::InputClosed => write!(f, "the input was closed"),
+            Error::Input(reason) => write!(f, "failed to read input: {}", reason),
+            Error::Undo => write!(f, "there is no move to undo"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
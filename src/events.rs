@@ -0,0 +1,145 @@
+use crate::money::Money;
+use crate::poker::ThreeCardHand;
+use crate::side_bets::SideBet;
+use crate::table::SeatPosition;
+use crate::types::{Card, Hand, Move, Outcome, Payout};
+use serde::Serialize;
+
+/// Where a card is dealt to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Seat {
+    Dealer,
+    /// The player's hand at the index.
+    Hand(usize),
+}
+
+/// Why a hand was finished without a decision from the player.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum FinishReason {
+    Blackjack,
+    TwentyOne,
+    Bust,
+    FiveCardTrick,
+    Charlie,
+    /// Split aces only receive a single card.
+    SplitAces,
+}
+
+/// The settlement of a single hand.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HandResult {
+    pub hand: Hand,
+    pub outcome: Outcome,
+    /// The net result of the hand.
+    pub winnings: Money,
+    /// The amount paid back, including the returned bet.
+    pub payout: Money,
+    pub doubled: bool,
+    /// The hand came from splitting a pair.
+    pub split: bool,
+    /// The index of the dealt hand this hand originates from, shared by all hands split from it.
+    pub origin: usize,
+}
+
+/// Everything that happens at the table, in the order it happens.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event")]
+pub enum GameEvent {
+    /// The shoe was shuffled, by hand or by a continuous shuffling machine.
+    Shuffled {
+        continuous: bool,
+    },
+    /// The shoe ran out in the middle of a round and the discards were shuffled to finish it.
+    DiscardsShuffled,
+    BetPlaced {
+        hand: usize,
+        amount: Money,
+    },
+    SideBetPlaced {
+        side_bet: SideBet,
+        amount: Money,
+    },
+    /// Play moved on to the player at `seat`, at a table with several players.
+    SeatInPlay {
+        seat: usize,
+        position: SeatPosition,
+        name: Option<String>,
+    },
+    /// A card was dealt, `None` if it was dealt face down.
+    CardDealt {
+        seat: Seat,
+        card: Option<Card>,
+    },
+    TwentyOnePlusThreeSettled {
+        poker_hand: ThreeCardHand,
+        bet: Money,
+        payout: Option<Payout>,
+    },
+    /// The dealer checked the hole card for blackjack.
+    DealerPeeked {
+        blackjack: bool,
+    },
+    /// The player is asked to act on the hand at `hand`, one of `hands`.
+    PlayerTurn {
+        hand: usize,
+        hands: usize,
+        cards: Hand,
+    },
+    /// The player made a move, `cards` is the hand after the move.
+    PlayerAction {
+        hand: usize,
+        action: Move,
+        cards: Hand,
+    },
+    /// The last move was taken back in practice mode, `cards` is the hand before the move.
+    MoveUndone {
+        hand: usize,
+        hands: usize,
+        cards: Hand,
+    },
+    /// The doubled hand was rescued, surrendering the original bet.
+    DoubleRescued {
+        hand: usize,
+    },
+    /// The hand at `hand`, one of `hands`, was finished without a decision from the player.
+    HandFinished {
+        hand: usize,
+        hands: usize,
+        cards: Hand,
+        reason: FinishReason,
+    },
+    /// The hole card was turned over.
+    DealerReveal {
+        hand: Hand,
+    },
+    InsuranceSettled {
+        bet: Money,
+        won: bool,
+    },
+    BustItSettled {
+        bet: Money,
+        /// The number of cards the dealer busted with, `None` if the dealer didn't bust.
+        dealer_cards: Option<usize>,
+        payout: Option<Payout>,
+    },
+    /// Every hand was paid out against the final dealer hand.
+    RoundSettled {
+        dealer: Hand,
+        hands: Vec<HandResult>,
+    },
+    DealerTipped {
+        amount: Money,
+    },
+    /// The table was cleared for the next round.
+    RoundFinished {
+        /// The bankroll of the player, `None` at a table with several players.
+        bankroll: Option<Money>,
+        running_count: i32,
+        true_count: f32,
+    },
+}
+
+/// Subscribes to the events of a game, e.g. to render it, log it or collect statistics.
+pub trait GameObserver {
+    fn on_event(&mut self, event: &GameEvent);
+}
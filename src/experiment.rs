@@ -0,0 +1,310 @@
+use crate::betting::{BetRamp, BetSystem};
+use crate::bonus::Bonus21;
+use crate::config::GameConfig;
+use crate::counting::{CountingMethod, DeckEstimation};
+use crate::error::Error;
+use crate::input::Strategy;
+use crate::money::Money;
+use crate::rules::Rules;
+use crate::shuffle::ShuffleMethod;
+use crate::types::{DoubleRule, Payout};
+use crate::variants::Variant;
+use serde::{Deserialize, Deserializer};
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A setting written as on the command line, e.g. "3:2", or as a plain TOML number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Setting {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+}
+
+impl Setting {
+    fn parse<T: FromStr>(self) -> Result<T, String>
+    where
+        T::Err: Display,
+    {
+        let text = match self {
+            Setting::Text(text) => text,
+            Setting::Integer(number) => number.to_string(),
+            Setting::Float(number) => number.to_string(),
+        };
+        text.parse::<T>().map_err(|e| e.to_string())
+    }
+}
+
+/// Parses an optional setting with the `FromStr` of the command line.
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Option::<Setting>::deserialize(deserializer)?
+        .map(|setting| setting.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// Parses an optional list of settings with the `FromStr` of the command line.
+fn parsed_list<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    Option::<Vec<Setting>>::deserialize(deserializer)?
+        .map(|settings| {
+            settings
+                .into_iter()
+                .map(|setting| setting.parse().map_err(serde::de::Error::custom))
+                .collect()
+        })
+        .transpose()
+}
+
+/// The `[rules]` of a simulation file, every rule left out keeps its value.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesFile {
+    pub deck_count: Option<u32>,
+    pub cut_card: Option<u32>,
+    pub penetration: Option<f32>,
+    pub csm: Option<bool>,
+    pub hit_soft_17: Option<bool>,
+    pub dealer_peek: Option<bool>,
+    pub original_bets_only: Option<bool>,
+    #[serde(deserialize_with = "parsed")]
+    pub blackjack_payout: Option<Payout>,
+    pub max_splits: Option<u32>,
+    pub resplit_aces: Option<bool>,
+    pub hit_split_aces: Option<bool>,
+    #[serde(deserialize_with = "parsed")]
+    pub double_on: Option<DoubleRule>,
+    pub double_after_split: Option<bool>,
+    pub surrender: Option<bool>,
+    pub charlie: Option<usize>,
+    #[serde(deserialize_with = "parsed")]
+    pub variant: Option<Variant>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub bonuses: Option<Vec<Bonus21>>,
+}
+
+impl RulesFile {
+    /// Replaces the rules set in the file. Setting the variant brings its default bonuses,
+    /// unless the bonuses are set as well.
+    pub fn apply(&self, rules: &mut Rules) {
+        let set = |rule: &mut bool, value: Option<bool>| *rule = value.unwrap_or(*rule);
+        rules.deck_count = self.deck_count.unwrap_or(rules.deck_count);
+        rules.cut_card = self.cut_card.unwrap_or(rules.cut_card);
+        rules.penetration = self.penetration.or(rules.penetration);
+        set(&mut rules.csm, self.csm);
+        set(&mut rules.hit_soft_17, self.hit_soft_17);
+        set(&mut rules.dealer_peek, self.dealer_peek);
+        set(&mut rules.original_bets_only, self.original_bets_only);
+        rules.blackjack_payout = self.blackjack_payout.unwrap_or(rules.blackjack_payout);
+        rules.max_splits = self.max_splits.unwrap_or(rules.max_splits);
+        set(&mut rules.resplit_aces, self.resplit_aces);
+        set(&mut rules.hit_split_aces, self.hit_split_aces);
+        rules.double_on = self.double_on.unwrap_or(rules.double_on);
+        set(&mut rules.double_after_split, self.double_after_split);
+        set(&mut rules.surrender, self.surrender);
+        rules.charlie = self.charlie.or(rules.charlie);
+        if let Some(variant) = self.variant {
+            rules.variant = variant;
+            rules.bonuses = variant.default_bonuses();
+        }
+        if let Some(bonuses) = &self.bonuses {
+            rules.bonuses = bonuses.clone();
+        }
+    }
+}
+
+/// The `[table]` of a simulation file: the limits, the bets and the count behind them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TableFile {
+    #[serde(deserialize_with = "parsed")]
+    pub table_min: Option<Money>,
+    #[serde(deserialize_with = "parsed")]
+    pub table_max: Option<Money>,
+    #[serde(deserialize_with = "parsed")]
+    pub bet_unit: Option<Money>,
+    #[serde(deserialize_with = "parsed")]
+    pub base_bet: Option<Money>,
+    #[serde(deserialize_with = "parsed")]
+    pub bet_system: Option<BetSystem>,
+    #[serde(deserialize_with = "parsed")]
+    pub bet_ramp: Option<BetRamp>,
+    #[serde(deserialize_with = "parsed")]
+    pub count: Option<CountingMethod>,
+    pub ace_side_count: Option<bool>,
+    #[serde(deserialize_with = "parsed")]
+    pub deck_estimation: Option<DeckEstimation>,
+    #[serde(deserialize_with = "parsed")]
+    pub shuffle: Option<ShuffleMethod>,
+}
+
+/// The `[output]` of a simulation file, the summary format is left to the program printing it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputFile {
+    pub format: Option<String>,
+    pub true_counts: Option<bool>,
+}
+
+/// A simulation described in a TOML file, so a long experiment can be repeated and shared
+/// instead of being typed out as a command line. Everything left out keeps the value it has
+/// otherwise, e.g.
+///
+/// ```toml
+/// rounds = 10000000
+/// strategies = ["basic", "never-bust"]
+/// seed = 42
+///
+/// [rules]
+/// deck_count = 2
+/// hit_soft_17 = true
+/// blackjack_payout = "6:5"
+///
+/// [table]
+/// base_bet = 10
+/// bet_system = "ramp"
+/// bet_ramp = "2:4,3:8"
+///
+/// [output]
+/// format = "json"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SimulationFile {
+    pub rounds: Option<u64>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub strategies: Option<Vec<Strategy>>,
+    pub seed: Option<u64>,
+    pub rules: RulesFile,
+    pub table: TableFile,
+    pub output: OutputFile,
+}
+
+impl FromStr for SimulationFile {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(|e| Error::Config(e.message().to_string()))
+    }
+}
+
+impl SimulationFile {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("cannot read {}: {}", path.display(), e)))?;
+        text.parse().map_err(|e| match e {
+            Error::Config(reason) => Error::Config(format!("{}: {}", path.display(), reason)),
+            e => e,
+        })
+    }
+
+    /// Replaces the rules and table settings set in the file and checks they still fit together.
+    pub fn apply(&self, conf: &mut GameConfig) -> Result<(), Error> {
+        self.rules.apply(&mut conf.rules);
+        let table = &self.table;
+        conf.table_min = table.table_min.unwrap_or(conf.table_min);
+        conf.table_max = table.table_max.or(conf.table_max);
+        conf.bet_unit = table.bet_unit.unwrap_or(conf.bet_unit);
+        conf.base_bet = table.base_bet.unwrap_or(conf.base_bet.max(conf.table_min));
+        conf.bet_system = table.bet_system.or(conf.bet_system);
+        conf.bet_ramp = table.bet_ramp.clone().or(conf.bet_ramp.take());
+        if let Some(count) = &table.count {
+            conf.counting = count.clone();
+        }
+        conf.ace_side_count = table.ace_side_count.unwrap_or(conf.ace_side_count);
+        conf.deck_estimation = table.deck_estimation.unwrap_or(conf.deck_estimation);
+        conf.shuffle = table.shuffle.unwrap_or(conf.shuffle);
+        conf.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::betting::BetSystem;
+    use crate::bonus::Bonus21;
+    use crate::config::GameConfig;
+    use crate::counting::{CountingMethod, DeckEstimation};
+    use crate::error::Error;
+    use crate::experiment::SimulationFile;
+    use crate::input::Strategy;
+    use crate::money::Money;
+    use crate::types::Payout;
+    use crate::variants::Variant;
+
+    #[test]
+    fn test_simulation_file() {
+        let file = r#"
+            rounds = 5000
+            strategies = ["basic", "mimic-dealer"]
+
+            [rules]
+            deck_count = 2
+            hit_soft_17 = true
+            blackjack_payout = "6:5"
+            variant = "spanish21"
+
+            [table]
+            table_min = 5
+            bet_system = "kelly:0.5"
+            bet_ramp = "2:4,3:8"
+            count = "omega-2"
+            ace_side_count = true
+            deck_estimation = "quarter-decks"
+
+            [output]
+            format = "json"
+        "#
+        .parse::<SimulationFile>()
+        .unwrap();
+        assert_eq!(file.rounds, Some(5000));
+        assert_eq!(
+            file.strategies,
+            Some(vec![Strategy::Basic, Strategy::MimicDealer])
+        );
+        assert_eq!(file.output.format.as_deref(), Some("json"));
+
+        let mut conf = GameConfig::default();
+        file.apply(&mut conf).unwrap();
+        let rules = &conf.rules;
+        assert_eq!(rules.deck_count, 2);
+        assert!(rules.hit_soft_17);
+        assert_eq!(rules.blackjack_payout.to_string(), "6:5");
+        assert_eq!(rules.variant, Variant::Spanish21);
+        assert!(rules.bonuses.contains(&Bonus21::FiveCard));
+        // Left out, so kept as it was
+        assert_eq!(rules.max_splits, 3);
+        assert_eq!(conf.table_min, Money::from_units(5));
+        assert_eq!(conf.base_bet, Money::from_units(5));
+        assert_eq!(conf.bet_system, Some(BetSystem::Kelly(0.5)));
+        assert!(conf.bet_ramp.is_some());
+        assert_eq!(conf.counting, CountingMethod::OmegaII);
+        assert!(conf.ace_side_count);
+        assert_eq!(conf.deck_estimation, DeckEstimation::QuarterDecks);
+
+        assert!(matches!(
+            "payout = 1".parse::<SimulationFile>(),
+            Err(Error::Config(_))
+        ));
+        assert!("[rules]\nblackjack_payout = \"3-2\""
+            .parse::<SimulationFile>()
+            .is_err());
+        assert_eq!(
+            "[rules]\nblackjack_payout = \"2:1\""
+                .parse::<SimulationFile>()
+                .unwrap()
+                .rules
+                .blackjack_payout,
+            Some(Payout::TWO_TO_ONE)
+        );
+    }
+}
@@ -0,0 +1,504 @@
+use crate::config::GameConfig;
+use crate::error::Error;
+use crate::input::BasicStrategy;
+use crate::money::Money;
+use crate::rules::Rules;
+use crate::shuffle::ShuffleMethod;
+use crate::simulation::{Progress, SimulationResult, BATCH_ROUNDS};
+use crate::types::{Move, Shoe, Value};
+use crate::variants::Variant;
+
+/// The most hands a dealt hand can be split into.
+const MAX_HANDS: usize = 16;
+
+/// The rounds played between updates of the progress, a power of two to keep the check cheap.
+const PROGRESS_INTERVAL: u64 = 1 << 16;
+
+/// The ranks of a deck by their index in [Value::ALL].
+const RANKS: usize = Value::ALL.len();
+
+/// The wyrand generator, a few times faster to shuffle with than the cryptographic generator
+/// behind the shuffles of the full engine and more than random enough for a simulation.
+#[derive(Debug, Clone)]
+struct WyRand(u64);
+
+impl WyRand {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0xa076_1d64_78bd_642f);
+        let product = self.0 as u128 * (self.0 ^ 0xe703_7ed1_a0b4_28db) as u128;
+        ((product >> 64) ^ product) as u64
+    }
+
+    /// A number below `n`, by multiplying instead of dividing. The bias is far too small to
+    /// matter for the few hundred cards of a shoe.
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+/// A hand as the fast engine keeps it: the totals and little else, without its cards.
+#[derive(Debug, Clone, Copy, Default)]
+struct FastHand {
+    /// The total with every ace counted as 1.
+    hard: u8,
+    ace: bool,
+    cards: u8,
+    /// The ranks of the first two cards, to tell a pair.
+    ranks: [u8; 2],
+    /// The bet in initial bets, doubled when the hand is doubled.
+    bet: u8,
+    doubled: bool,
+    split: bool,
+    surrendered: bool,
+}
+
+impl FastHand {
+    fn new(rank: u8, points: u8, split: bool) -> Self {
+        let mut hand = FastHand {
+            bet: 1,
+            split,
+            ..Default::default()
+        };
+        hand.add(rank, points);
+        hand
+    }
+
+    fn add(&mut self, rank: u8, points: u8) {
+        if self.cards < 2 {
+            self.ranks[self.cards as usize] = rank;
+        }
+        self.hard += points;
+        self.ace |= points == 1;
+        self.cards += 1;
+    }
+
+    fn is_soft(&self) -> bool {
+        self.ace && self.hard <= 11
+    }
+
+    fn value(&self) -> u8 {
+        if self.is_soft() {
+            self.hard + 10
+        } else {
+            self.hard
+        }
+    }
+
+    fn is_pair(&self) -> bool {
+        self.cards == 2 && self.ranks[0] == self.ranks[1]
+    }
+
+    fn is_blackjack(&self) -> bool {
+        !self.split && self.cards == 2 && self.value() == 21
+    }
+
+    fn is_bust(&self) -> bool {
+        self.hard > 21
+    }
+
+    /// Whether the hand is decided before the dealer plays.
+    fn is_finished(&self) -> bool {
+        self.is_bust() || self.is_blackjack() || self.surrendered
+    }
+}
+
+/// The totals of the hands settled, kept in initial bets until the end of the simulation.
+#[derive(Debug, Clone, Copy, Default)]
+struct FastTally {
+    hands: u64,
+    base: f64,
+    blackjacks: f64,
+    doubles: f64,
+    splits: f64,
+    surrenders: f64,
+}
+
+impl FastTally {
+    fn add(&mut self, hand: &FastHand, result: f64) {
+        self.hands += 1;
+        let part = if hand.split {
+            &mut self.splits
+        } else if hand.doubled {
+            &mut self.doubles
+        } else if hand.is_blackjack() {
+            &mut self.blackjacks
+        } else if hand.surrendered {
+            &mut self.surrenders
+        } else {
+            &mut self.base
+        };
+        *part += result;
+    }
+}
+
+/// A stripped down engine that plays basic strategy with flat bets as fast as it can, for
+/// simulations of billions of rounds. The cards are single bytes in a shoe that is dealt by
+/// moving an index, the hands live in a fixed array and nothing is allocated, formatted or
+/// observed while the rounds are played.
+///
+/// It plays the standard game with the rules of [GameEngine] and [BasicStrategy], but without
+/// side bets, insurance, Charlies or bonus 21s. A continuous shuffling machine is taken to
+/// shuffle the whole shoe before every round.
+///
+/// [GameEngine]: crate::engine::GameEngine
+pub struct FastEngine {
+    rules: Rules,
+    /// A blackjack's winnings in initial bets.
+    payout: f64,
+    /// The points of each rank, aces as 1.
+    points: [u8; RANKS],
+    /// The ranks of the cards, dealt from the front.
+    shoe: Vec<u8>,
+    /// The index of the next card to deal.
+    next: usize,
+    /// The shoe is shuffled before a round once this many cards are dealt.
+    cut: usize,
+    rng: WyRand,
+    hands: [FastHand; MAX_HANDS],
+    tally: FastTally,
+}
+
+impl FastEngine {
+    /// An engine dealing the shoe of `conf`, the same shoes every time with a `seed`.
+    pub fn new(conf: &GameConfig, seed: Option<u64>) -> Result<Self, Error> {
+        conf.validate()?;
+        let rules = &conf.rules;
+        let unsupported = if rules.variant != Variant::Standard {
+            Some(format!("{}", rules.variant))
+        } else if rules.charlie.is_some() {
+            Some("a Charlie".to_string())
+        } else if !rules.bonuses.is_empty() {
+            Some("bonus 21s".to_string())
+        } else if conf.bet_ramp.is_some() {
+            Some("a bet ramp".to_string())
+        } else if conf.shuffle != ShuffleMethod::Uniform {
+            Some(format!("the {} shuffle", conf.shuffle))
+        } else if rules.max_splits as usize >= MAX_HANDS {
+            Some(format!("more than {} splits", MAX_HANDS - 1))
+        } else {
+            None
+        };
+        if let Some(unsupported) = unsupported {
+            return Err(Error::Config(format!(
+                "the fast engine only flat bets the standard game, not {}",
+                unsupported
+            )));
+        }
+
+        let rank = |value: &Value| Value::ALL.iter().position(|v| v == value).unwrap() as u8;
+        let mut points = [0; RANKS];
+        for value in Value::ALL.iter() {
+            points[rank(value) as usize] = match value {
+                Value::Ace => 1,
+                value => value.value() as u8,
+            };
+        }
+        let shoe = Shoe::new(rules.deck_count, rules.variant)?
+            .cards
+            .iter()
+            .map(|card| rank(&card.value()))
+            .collect::<Vec<u8>>();
+        let cut = if rules.csm {
+            0
+        } else {
+            shoe.len() - rules.cut_card_position(shoe.len() as u32) as usize
+        };
+        let payout = rules.blackjack_payout();
+        let mut engine = Self {
+            payout: payout.numerator as f64 / payout.denominator as f64,
+            rules: rules.clone(),
+            points,
+            next: 0,
+            cut,
+            shoe,
+            rng: WyRand(seed.unwrap_or_else(rand::random)),
+            hands: [FastHand::default(); MAX_HANDS],
+            tally: FastTally::default(),
+        };
+        engine.shuffle();
+        Ok(engine)
+    }
+
+    /// Shuffles the shoe by Fisher-Yates.
+    fn shuffle(&mut self) {
+        for i in (1..self.shoe.len()).rev() {
+            let j = self.rng.below(i + 1);
+            self.shoe.swap(i, j);
+        }
+        self.next = !self.rules.csm as usize;
+    }
+
+    /// The rank and points of the next card, `None` once the shoe is empty.
+    fn draw(&mut self) -> Option<(u8, u8)> {
+        let rank = *self.shoe.get(self.next)?;
+        self.next += 1;
+        Some((rank, self.points[rank as usize]))
+    }
+
+    /// Plays a round of a single hand and returns its result in initial bets, `None` if the shoe
+    /// ran out of cards and the round is void.
+    pub fn play_round(&mut self) -> Option<f64> {
+        if self.next >= self.cut {
+            self.shuffle();
+        }
+        let round = self.deal_round();
+        if round.is_none() {
+            self.shuffle();
+        }
+        round
+    }
+
+    fn deal_round(&mut self) -> Option<f64> {
+        // Dealt in the order of the table, the player's cards before the dealer's
+        let (rank, points) = self.draw()?;
+        let (up_rank, up_points) = self.draw()?;
+        let mut hand = FastHand::new(rank, points, false);
+        let mut dealer = FastHand::new(up_rank, up_points, false);
+        let (rank, points) = self.draw()?;
+        hand.add(rank, points);
+        let (hole_rank, hole_points) = self.draw()?;
+        dealer.add(hole_rank, hole_points);
+        self.hands[0] = hand;
+        let mut count = 1;
+
+        let upcard = if up_points == 1 { 11 } else { up_points as u32 };
+        let peeked = self.rules.dealer_peek && upcard >= 10;
+        if !(peeked && dealer.is_blackjack()) {
+            let mut index = 0;
+            while index < count {
+                if self.play_hand(index, &mut count, upcard)? {
+                    index += 1;
+                }
+            }
+            let dealer_plays = !dealer.is_blackjack()
+                && self.hands[..count].iter().any(|hand| !hand.is_finished());
+            if dealer_plays {
+                while dealer.value() < 17
+                    || (self.rules.hit_soft_17 && dealer.value() == 17 && dealer.is_soft())
+                {
+                    let (rank, points) = self.draw()?;
+                    dealer.add(rank, points);
+                }
+            }
+        }
+
+        let mut round = 0.0;
+        for (i, hand) in self.hands[..count].iter().enumerate() {
+            let result = self.settle(hand, &dealer, i == 0);
+            self.tally.add(hand, result);
+            round += result;
+        }
+        Some(round)
+    }
+
+    /// Makes a move on the hand at `index`, `Some(false)` while it is still being played.
+    fn play_hand(&mut self, index: usize, count: &mut usize, upcard: u32) -> Option<bool> {
+        let rules = &self.rules;
+        let hand = self.hands[index];
+        let value = hand.value() as u32;
+        if hand.is_bust() || value == 21 {
+            return Some(true);
+        }
+        let split_aces = hand.split && hand.ranks[0] == self.ace_rank();
+        let locked = split_aces && !rules.hit_split_aces;
+        let can_split = hand.is_pair()
+            && (*count as u32 - 1) < rules.max_splits
+            && (!split_aces || rules.resplit_aces);
+        if locked && !can_split {
+            return Some(true);
+        }
+        let allowed = |action: Move| match action {
+            Move::Hit => !locked,
+            Move::Stand => true,
+            Move::Double => {
+                hand.cards == 2
+                    && !locked
+                    && (rules.double_after_split || !hand.split)
+                    && rules.double_on.allows(value)
+            }
+            Move::Split => can_split,
+            Move::Surrender => rules.surrender && !hand.split && hand.cards == 2,
+        };
+        let pair = hand.is_pair().then_some(match hand.ranks[0] {
+            rank if self.points[rank as usize] == 1 => 11,
+            rank => self.points[rank as usize] as u32,
+        });
+        let action = BasicStrategy::choose(rules, value, hand.is_soft(), pair, upcard, allowed)
+            .unwrap_or(Move::Stand);
+
+        match action {
+            Move::Hit => {
+                let (rank, points) = self.draw()?;
+                self.hands[index].add(rank, points);
+                Some(false)
+            }
+            Move::Stand => Some(true),
+            Move::Double => {
+                let (rank, points) = self.draw()?;
+                let hand = &mut self.hands[index];
+                hand.add(rank, points);
+                hand.bet *= 2;
+                hand.doubled = true;
+                Some(true)
+            }
+            Move::Split => {
+                self.hands.copy_within(index + 1..*count, index + 2);
+                *count += 1;
+                for (i, rank) in [index, index + 1].into_iter().zip(hand.ranks) {
+                    self.hands[i] = FastHand::new(rank, self.points[rank as usize], true);
+                }
+                for i in [index, index + 1] {
+                    let (rank, points) = self.draw()?;
+                    self.hands[i].add(rank, points);
+                }
+                Some(false)
+            }
+            Move::Surrender => {
+                self.hands[index].surrendered = true;
+                Some(true)
+            }
+        }
+    }
+
+    /// The result of the hand in initial bets, `original` for the hand that carries the bet
+    /// placed before the deal.
+    fn settle(&self, hand: &FastHand, dealer: &FastHand, original: bool) -> f64 {
+        let bet = hand.bet as f64;
+        if hand.surrendered {
+            -0.5
+        } else if hand.is_blackjack() {
+            if dealer.is_blackjack() {
+                0.0
+            } else {
+                self.payout
+            }
+        } else if hand.is_bust() {
+            -bet
+        } else if dealer.is_blackjack() {
+            match (self.rules.original_bets_only, original) {
+                (true, true) => -1.0,
+                (true, false) => 0.0,
+                (false, _) => -bet,
+            }
+        } else if dealer.is_bust() || hand.value() > dealer.value() {
+            bet
+        } else if hand.value() == dealer.value() {
+            0.0
+        } else {
+            -bet
+        }
+    }
+
+    fn ace_rank(&self) -> u8 {
+        self.points.iter().position(|points| *points == 1).unwrap() as u8
+    }
+}
+
+/// Plays `rounds` rounds of basic strategy flat betting the table minimum on the [FastEngine],
+/// many times faster than [simulate] plays them on the full engine. The result has no true
+/// counts.
+///
+/// [simulate]: crate::simulation::simulate
+pub fn simulate_fast(
+    conf: &GameConfig,
+    rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<SimulationResult, Error> {
+    let mut engine = FastEngine::new(conf, seed)?;
+    let bet = conf.table_min.units_f64();
+    let mut result = SimulationResult::default();
+    let (mut sum, mut squares) = (0.0, 0.0);
+    let mut batch = 0.0;
+    let mut played = 0;
+    while played < rounds {
+        let Some(round) = engine.play_round() else {
+            continue;
+        };
+        played += 1;
+        sum += round;
+        squares += round * round;
+        batch += round;
+        if played % BATCH_ROUNDS == 0 {
+            let mean = batch / BATCH_ROUNDS as f64;
+            result.batches.add_batch(mean * bet, mean);
+            batch = 0.0;
+        }
+        if played % PROGRESS_INTERVAL == 0 {
+            progress.update(played, rounds);
+        }
+    }
+    progress.update(played, rounds);
+
+    let money = |bets: f64| Money::from_units_f64(bets * bet);
+    let tally = engine.tally;
+    result.rounds = played;
+    result.hands = tally.hands;
+    result.initial_bets = money(played as f64);
+    result.net = money(sum);
+    result.result_sum = sum * bet;
+    result.result_squares = squares * bet * bet;
+    let breakdown = &mut result.breakdown;
+    breakdown.base = money(tally.base);
+    breakdown.blackjacks = money(tally.blackjacks);
+    breakdown.doubles = money(tally.doubles);
+    breakdown.splits = money(tally.splits);
+    breakdown.surrenders = money(tally.surrenders);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::fast::{simulate_fast, FastEngine};
+    use crate::input::BasicStrategy;
+    use crate::rules::Rules;
+    use crate::simulation::{simulate, NoProgress};
+    use crate::variants::Variant;
+
+    #[test]
+    fn test_simulate_fast() {
+        let conf = GameConfig::default();
+        let rounds = 400_000;
+        let fast = simulate_fast(&conf, rounds, Some(3), &mut NoProgress).unwrap();
+        assert_eq!(fast.rounds, rounds);
+        assert!(fast.hands > rounds);
+        let breakdown = fast.breakdown;
+        let parts = breakdown.base
+            + breakdown.blackjacks
+            + breakdown.doubles
+            + breakdown.splits
+            + breakdown.surrenders;
+        assert!((parts - fast.net).units_f64().abs() < 0.1);
+        assert!(fast.house_edge_margin().is_some());
+
+        // The same strategy under the same rules as the full engine, so the same edge within
+        // the noise of both
+        let full = simulate(
+            conf.clone(),
+            &mut BasicStrategy,
+            100_000,
+            Some(3),
+            &mut NoProgress,
+        )
+        .unwrap();
+        let margin = fast.house_edge_margin().unwrap() + full.house_edge_margin().unwrap();
+        assert!((fast.house_edge() - full.house_edge()).abs() < margin);
+        let variance = |result: &crate::simulation::SimulationResult| result.variance();
+        assert!((variance(&fast) - variance(&full)).abs() < 0.1);
+
+        // The same seed deals the same shoes
+        let again = simulate_fast(&conf, 10_000, Some(3), &mut NoProgress).unwrap();
+        let first = simulate_fast(&conf, 10_000, Some(3), &mut NoProgress).unwrap();
+        assert_eq!(again, first);
+
+        let spanish = GameConfig {
+            rules: Rules {
+                variant: Variant::Spanish21,
+                ..Rules::default()
+            },
+            ..GameConfig::default()
+        };
+        assert!(FastEngine::new(&spanish, None).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::builder::GameBuilder;
+use crate::game::{Action, Game};
+use crate::money::Money;
+use crate::types::Move;
+use serde_json::json;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+// The C interface of the engine, declared in `include/blackjack.h`. Amounts are passed in cents
+// and everything returned is a JSON string that must be released with `blackjack_string_free`.
+
+/// Parses an action by name, with `amount` in cents, a hand index or a yes/no as the argument.
+fn parse_action(name: &str, amount: i64) -> Result<Action, String> {
+    let money = Money::from_cents(amount);
+    Ok(match name {
+        "bet" => Action::Bet(money),
+        "deal" => Action::Deal,
+        "even-money" => Action::EvenMoney(amount.max(0) as usize),
+        "insure" => Action::Insure(money),
+        "hit" => Action::Move(Move::Hit),
+        "stand" => Action::Move(Move::Stand),
+        "double" => Action::Move(Move::Double),
+        "split" => Action::Move(Move::Split),
+        "surrender" => Action::Move(Move::Surrender),
+        "buy" => Action::Buy(money),
+        "rescue" => Action::Rescue(amount != 0),
+        "tip" => Action::Tip(money),
+        _ => return Err(format!("unknown action '{}'", name)),
+    })
+}
+
+fn into_c_string(value: serde_json::Value) -> *mut c_char {
+    // JSON escapes control characters, so there is never an interior nul
+    CString::new(value.to_string())
+        .expect("JSON contains no nul bytes")
+        .into_raw()
+}
+
+/// Runs `f` and returns its result as a JSON string, `{"error": "..."}` if it failed or panicked,
+/// as a panic must not unwind into the caller.
+fn guarded(f: impl FnOnce() -> Result<serde_json::Value, String>) -> *mut c_char {
+    into_c_string(match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(error)) => json!({ "error": error }),
+        Err(_) => json!({ "error": "the game failed unexpectedly" }),
+    })
+}
+
+const NO_GAME: &str = "no game was given";
+
+/// Sits down at a freshly shuffled shoe with the default rules otherwise.
+/// Returns null if the settings can't be played.
+#[no_mangle]
+pub extern "C" fn blackjack_game_new(
+    decks: u32,
+    penetration: f32,
+    hit_soft_17: bool,
+    buy_in_cents: i64,
+) -> *mut Game {
+    GameBuilder::new()
+        .decks(decks)
+        .penetration(penetration)
+        .h17(hit_soft_17)
+        .buy_in(Money::from_cents(buy_in_cents))
+        .build_game()
+        .map_or(ptr::null_mut(), |game| Box::into_raw(Box::new(game)))
+}
+
+/// Ends the game and releases it.
+///
+/// # Safety
+/// `game` must come from `blackjack_game_new` and not be used afterwards, null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_free(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Plays the action named `action`, e.g. "bet" or "hit", see `parse_action` for the argument.
+/// Returns `{"events": [...]}` with what happened, or `{"error": "..."}` if it was rejected.
+///
+/// # Safety
+/// `game` must be a live game or null, and `action` a nul-terminated string or null.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_act(
+    game: *mut Game,
+    action: *const c_char,
+    amount: i64,
+) -> *mut c_char {
+    guarded(|| {
+        let game = game.as_mut().ok_or(NO_GAME)?;
+        if action.is_null() {
+            return Err("no action was given".to_string());
+        }
+        let name = CStr::from_ptr(action)
+            .to_str()
+            .map_err(|_| "the action isn't valid UTF-8".to_string())?;
+        let events = game
+            .play(parse_action(name, amount)?)
+            .map_err(|e| e.to_string())?;
+        Ok(json!({ "events": events }))
+    })
+}
+
+/// Calls off a round the shoe ran out of cards for and reshuffles, returning `{"events": [...]}`.
+///
+/// # Safety
+/// `game` must be a live game or null.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_void_round(game: *mut Game) -> *mut c_char {
+    guarded(|| {
+        let events = game.as_mut().ok_or(NO_GAME)?.void_round();
+        Ok(json!({ "events": events }))
+    })
+}
+
+/// The table as JSON: the state, the dealer's cards, the hands, the bankroll, the available
+/// moves and the count.
+///
+/// # Safety
+/// `game` must be a live game or null.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_game_state(game: *const Game) -> *mut c_char {
+    guarded(|| Ok(json!(game.as_ref().ok_or(NO_GAME)?.view())))
+}
+
+/// Releases a string returned by the other functions.
+///
+/// # Safety
+/// `string` must come from this library and not be used afterwards, null is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn blackjack_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi::{
+        blackjack_game_act, blackjack_game_free, blackjack_game_new, blackjack_game_state,
+        blackjack_game_void_round, blackjack_string_free,
+    };
+    use std::ffi::{c_char, CStr};
+    use std::ptr;
+
+    /// Reads and releases a string returned by the library.
+    unsafe fn take_json(string: *mut c_char) -> serde_json::Value {
+        let value = serde_json::from_str(CStr::from_ptr(string).to_str().unwrap()).unwrap();
+        blackjack_string_free(string);
+        value
+    }
+
+    #[test]
+    fn test_c_api() {
+        assert!(blackjack_game_new(0, 0.75, false, 100_000).is_null());
+
+        let game = blackjack_game_new(6, 0.75, false, 100_000);
+        unsafe {
+            let bet = take_json(blackjack_game_act(game, c"bet".as_ptr(), 1_000));
+            assert_eq!(bet["events"][0]["event"], "BetPlaced");
+            let state = take_json(blackjack_game_state(game));
+            assert_eq!(state["state"], "Betting");
+            assert_eq!(state["bankroll"], 990.0);
+
+            let hit = take_json(blackjack_game_act(game, c"hit".as_ptr(), 0));
+            assert_eq!(hit["error"], "that isn't possible during betting");
+            let unknown = take_json(blackjack_game_act(game, c"fold".as_ptr(), 0));
+            assert_eq!(unknown["error"], "unknown action 'fold'");
+            let no_action = take_json(blackjack_game_act(game, ptr::null(), 0));
+            assert_eq!(no_action["error"], "no action was given");
+            blackjack_game_free(game);
+
+            let no_game = take_json(blackjack_game_state(ptr::null()));
+            assert_eq!(no_game["error"], "no game was given");
+            let no_game = take_json(blackjack_game_void_round(ptr::null_mut()));
+            assert_eq!(no_game["error"], "no game was given");
+        }
+    }
+}
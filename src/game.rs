@@ -0,0 +1,417 @@
+use crate::config::GameConfig;
+use crate::engine::{GameEngine, GameState, Snapshot};
+use crate::error::Error;
+use crate::events::{GameEvent, GameObserver};
+use crate::money::Money;
+use crate::rules::{HandOptions, Rules};
+use crate::side_bets::SideBet;
+use crate::types::{Hand, Move, Player, PlayerHand, Shoe};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A decision pushed to a [`Game`], answering what the game is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    /// Puts another player at the table in play, to bet, insure or tip for them.
+    TakeSeat(usize),
+    /// Places the main bet for another hand, before the deal.
+    Bet(Money),
+    SideBet(SideBet, Money),
+    /// Deals the cards once the bets are placed.
+    Deal,
+    /// Takes even money on the blackjack at the index, before insuring.
+    EvenMoney(usize),
+    /// Insures against a dealer blackjack, 0 to decline. Ends the insurance decisions of the seat
+    /// in play, the dealer peeks once every seat has decided.
+    Insure(Money),
+    Move(Move),
+    /// Buys another card for the amount, on a hand that doesn't end on a double.
+    Buy(Money),
+    Rescue(bool),
+    /// Tips the dealer after a winning round, 0 for no tip. At a table with several players
+    /// every winning seat is offered to tip in turn, the last one ends the round.
+    Tip(Money),
+}
+
+/// Collects the events of the game until they are handed back to the caller.
+struct EventLog(Rc<RefCell<Vec<GameEvent>>>);
+
+impl GameObserver for EventLog {
+    fn on_event(&mut self, event: &GameEvent) {
+        self.0.borrow_mut().push(event.clone());
+    }
+}
+
+/// What the player can see at the table, e.g. to draw it in another program.
+#[derive(Serialize)]
+pub struct TableView<'a> {
+    pub state: GameState,
+    /// The seat in play, the hands and the bankroll are its player's.
+    pub seat: usize,
+    /// The dealer's cards in view.
+    pub dealer: &'a Hand,
+    pub hands: &'a [PlayerHand],
+    pub bankroll: Money,
+    /// The moves available on the hand being played.
+    pub moves: Vec<Move>,
+    pub running_count: i32,
+    pub true_count: f32,
+}
+
+/// A game played without a terminal, e.g. by a bot, a server or another program.
+///
+/// Every action is played synchronously: the game runs on until it waits for the next decision
+/// and returns the events that happened on the way. The shoe is reshuffled between rounds once
+/// the cut card comes out.
+pub struct Game {
+    engine: GameEngine,
+    events: Rc<RefCell<Vec<GameEvent>>>,
+}
+
+impl Game {
+    /// Seats `players` at a table playing by `rules`, dealing from `shoe` in its current order.
+    /// The players bet seat by seat with [`Action::TakeSeat`] and play their hands in seat order.
+    pub fn new(rules: Rules, shoe: Shoe, players: Vec<Player>) -> Result<Self, Error> {
+        let events = Rc::new(RefCell::new(vec![]));
+        let observer = Box::new(EventLog(events.clone()));
+        let conf = GameConfig {
+            rules,
+            ..GameConfig::default()
+        };
+        let engine = GameEngine::seated(conf, shoe, players, vec![observer])?;
+        Ok(Self { engine, events })
+    }
+
+    /// Plays on an engine set up elsewhere, e.g. by a [`crate::builder::GameBuilder`].
+    pub fn with_engine(mut engine: GameEngine) -> Self {
+        let events = Rc::new(RefCell::new(vec![]));
+        engine.subscribe(Box::new(EventLog(events.clone())));
+        Self { engine, events }
+    }
+
+    /// What the game is waiting for.
+    pub fn state(&self) -> GameState {
+        self.engine.state()
+    }
+
+    /// The table as it stands, for inspecting the hands, the shoe and the bankroll.
+    pub fn engine(&self) -> &GameEngine {
+        &self.engine
+    }
+
+    /// The moves available on the hand being played, if it's the player's turn.
+    pub fn options(&self) -> Option<HandOptions> {
+        match self.engine.state() {
+            GameState::PlayerTurn(index) => Some(
+                self.engine
+                    .conf
+                    .rules
+                    .hand_options(&self.engine.player, index),
+            ),
+            _ => None,
+        }
+    }
+
+    pub fn view(&self) -> TableView<'_> {
+        TableView {
+            state: self.engine.state(),
+            seat: self.engine.seat(),
+            dealer: &self.engine.dealer_hand,
+            hands: &self.engine.player.hands,
+            bankroll: self.engine.player.money,
+            moves: self.options().map(|o| o.moves).unwrap_or_default(),
+            running_count: self.engine.counter.running_count,
+            true_count: self.engine.counter.true_count,
+        }
+    }
+
+    /// Plays `action` and runs the game until the next decision, returning the events so far.
+    ///
+    /// A rejected action leaves the game as it was. When the shoe and the discards run out of
+    /// cards the round is left unfinished, see [`Game::void_round`].
+    pub fn play(&mut self, action: Action) -> Result<Vec<GameEvent>, Error> {
+        let state = self.engine.state();
+        match action {
+            Action::TakeSeat(seat) => self.engine.take_seat(seat)?,
+            Action::Bet(amount) => self.engine.place_bet(amount)?,
+            Action::SideBet(SideBet::TwentyOnePlusThree, amount) => {
+                self.engine.place_twenty_one_plus_three(amount)?
+            }
+            Action::SideBet(SideBet::BustIt, amount) => self.engine.place_bust_it(amount)?,
+            Action::Deal => {
+                if state != GameState::Betting || self.engine.step()? == GameState::Betting {
+                    return Err(Error::WrongState(state));
+                }
+            }
+            Action::EvenMoney(index) => self.engine.take_even_money(index)?,
+            Action::Insure(amount) => {
+                self.engine.insure(amount)?;
+                // The insurance is offered seat by seat before the dealer peeks
+                match self.next_seat(|_| true) {
+                    Some(seat) => self.engine.take_seat(seat)?,
+                    None => {
+                        self.engine.step()?;
+                    }
+                }
+            }
+            Action::Move(action) => self.engine.act(action)?,
+            Action::Buy(amount) => self.engine.buy(amount)?,
+            Action::Rescue(rescue) => self.engine.rescue(rescue)?,
+            Action::Tip(amount) => {
+                self.engine.tip(amount)?;
+                // The other winning seats are offered to tip before the table is cleared
+                if let Some(seat) = self.next_seat(|seat| self.engine.seat_won(seat)) {
+                    self.engine.take_seat(seat)?;
+                    return Ok(self.events.borrow_mut().drain(..).collect());
+                }
+                self.finish_round()?;
+            }
+        }
+        self.advance()?;
+        Ok(self.events.borrow_mut().drain(..).collect())
+    }
+
+    /// Sits `player` down at `seat` between rounds in place of the player there, who is returned
+    /// with the bets they placed on the round.
+    pub fn seat_player(&mut self, seat: usize, player: Player) -> Result<Player, Error> {
+        self.engine.seat_player(seat, player)
+    }
+
+    /// Shuffles every card back into the shoe, e.g. before the first round.
+    pub fn shuffle(&mut self) -> Vec<GameEvent> {
+        self.engine.shuffle();
+        self.events.borrow_mut().drain(..).collect()
+    }
+
+    /// Calls off a round the shoe ran out of cards for, returning the bets, and reshuffles.
+    pub fn void_round(&mut self) -> Vec<GameEvent> {
+        self.engine.void_round();
+        self.engine.shuffle();
+        self.events.borrow_mut().drain(..).collect()
+    }
+
+    /// Saves the game, e.g. while waiting for a decision, to be resumed with [`Game::restore`].
+    pub fn snapshot(&self) -> Snapshot {
+        self.engine.snapshot()
+    }
+
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.engine.restore(snapshot);
+    }
+
+    /// Steps through the automatic stages until the player has to decide something.
+    fn advance(&mut self) -> Result<(), Error> {
+        loop {
+            match self.engine.state() {
+                GameState::Betting
+                | GameState::Insurance
+                | GameState::PlayerTurn(_)
+                | GameState::DoubleRescue(_) => return Ok(()),
+                GameState::RoundOver if self.engine.conf.tips => {
+                    let winner = self
+                        .engine
+                        .seats_in_round()
+                        .into_iter()
+                        .find(|&seat| self.engine.seat_won(seat));
+                    match winner {
+                        Some(seat) => return self.engine.take_seat(seat),
+                        None => self.finish_round()?,
+                    }
+                }
+                GameState::RoundOver => self.finish_round()?,
+                _ => {
+                    self.engine.step()?;
+                }
+            }
+        }
+    }
+
+    /// The first seat playing the round after the seat in play that `pick` picks.
+    fn next_seat(&self, pick: impl Fn(usize) -> bool) -> Option<usize> {
+        let in_play = self.engine.seat();
+        self.engine
+            .seats_in_round()
+            .into_iter()
+            .find(|&seat| seat > in_play && pick(seat))
+    }
+
+    /// Clears the table for the next round, reshuffling once the cut card has come out.
+    fn finish_round(&mut self) -> Result<(), Error> {
+        self.engine.step()?;
+        if self.engine.shoe.cut_card_reached && !self.engine.conf.rules.csm {
+            self.engine.shuffle();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::GameState;
+    use crate::error::Error;
+    use crate::events::GameEvent;
+    use crate::game::{Action, Game};
+    use crate::money::Money;
+    use crate::rules::Rules;
+    use crate::table::SeatPosition;
+    use crate::types::{Card, Move, Outcome, PackedCard, Player, Shoe, Suit, Value};
+
+    /// A game whose shoe deals `values` in order.
+    fn game(values: &[Value]) -> Game {
+        let mut shoe = Shoe::new(1, Rules::default().variant).unwrap();
+        shoe.cards = values
+            .iter()
+            .rev()
+            .map(|value| Card {
+                suit: Suit::Hearts,
+                value: value.clone(),
+            })
+            .map(PackedCard::from)
+            .collect();
+        let player = Player::new(Money::from_units(100));
+        Game::new(Rules::default(), shoe, vec![player]).unwrap()
+    }
+
+    #[test]
+    fn test_play_a_round() {
+        // Player 10+6 against a dealer 9+7, the player hits a 4 and the dealer busts on a ten
+        let mut game = game(&[
+            Value::Ten,
+            Value::Nine,
+            Value::Six,
+            Value::Seven,
+            Value::Four,
+            Value::Ten,
+        ]);
+        game.play(Action::Bet(Money::from_units(10))).unwrap();
+        let events = game.play(Action::Deal).unwrap();
+        assert_eq!(game.state(), GameState::PlayerTurn(0));
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::PlayerTurn { hand: 0, .. })
+        ));
+        assert!(game.options().unwrap().moves.contains(&Move::Hit));
+        let view = serde_json::to_value(game.view()).unwrap();
+        assert_eq!(view["state"]["PlayerTurn"], 0);
+        assert_eq!(view["bankroll"], 90.0);
+
+        game.play(Action::Move(Move::Hit)).unwrap();
+        let events = game.play(Action::Move(Move::Stand)).unwrap();
+        assert_eq!(game.state(), GameState::Betting);
+        let settled = events.iter().find_map(|e| match e {
+            GameEvent::RoundSettled { hands, .. } => Some(hands),
+            _ => None,
+        });
+        assert_eq!(settled.unwrap()[0].outcome, Outcome::DealerBust);
+        assert_eq!(game.engine().player.money, Money::from_units(110));
+    }
+
+    #[test]
+    fn test_rejected_actions() {
+        assert_eq!(
+            Game::new(
+                Rules::default(),
+                Shoe::new(1, Rules::default().variant).unwrap(),
+                vec![]
+            )
+            .err(),
+            Some(Error::Seats(0))
+        );
+
+        let mut game = game(&[Value::Ten, Value::Nine, Value::Six, Value::Seven]);
+        assert_eq!(
+            game.play(Action::Deal),
+            Err(Error::WrongState(GameState::Betting))
+        );
+        assert_eq!(
+            game.play(Action::Move(Move::Hit)),
+            Err(Error::WrongState(GameState::Betting))
+        );
+        game.play(Action::Bet(Money::from_units(10))).unwrap();
+        game.play(Action::Deal).unwrap();
+
+        // The shoe is empty, so the round can't be finished and is called off
+        assert_eq!(game.play(Action::Move(Move::Hit)), Err(Error::OutOfCards));
+        game.void_round();
+        assert_eq!(game.state(), GameState::Betting);
+        assert_eq!(game.engine().player.money, Money::from_units(100));
+    }
+
+    #[test]
+    fn test_several_players() {
+        let mut shoe = Shoe::new(1, Rules::default().variant).unwrap();
+        // Ann 10+6 and Bob 10+9 against a dealer 9+7, Ann hits a 4 and the dealer busts on a ten
+        shoe.cards = [
+            Value::Ten,
+            Value::Ten,
+            Value::Nine,
+            Value::Six,
+            Value::Nine,
+            Value::Seven,
+            Value::Four,
+            Value::Ten,
+        ]
+        .iter()
+        .rev()
+        .map(|value| Card {
+            suit: Suit::Spades,
+            value: value.clone(),
+        })
+        .map(PackedCard::from)
+        .collect();
+        let players = vec![
+            Player::new(Money::from_units(100)).with_name("Ann"),
+            Player::new(Money::from_units(200)).with_name("Bob"),
+        ];
+        let mut game = Game::new(Rules::default(), shoe, players).unwrap();
+
+        game.play(Action::Bet(Money::from_units(10))).unwrap();
+        game.play(Action::TakeSeat(1)).unwrap();
+        game.play(Action::Bet(Money::from_units(20))).unwrap();
+        assert_eq!(game.play(Action::TakeSeat(2)), Err(Error::NoSeat(2)));
+        let events = game.play(Action::Deal).unwrap();
+        assert_eq!(game.state(), GameState::PlayerTurn(0));
+        assert_eq!(game.engine().seat(), 0);
+        assert!(events.contains(&GameEvent::SeatInPlay {
+            seat: 0,
+            position: SeatPosition::FIRST_BASE,
+            name: Some("Ann".to_string())
+        }));
+        assert_eq!(
+            game.play(Action::TakeSeat(1)),
+            Err(Error::WrongState(GameState::PlayerTurn(0)))
+        );
+
+        game.play(Action::Move(Move::Hit)).unwrap();
+        game.play(Action::Move(Move::Stand)).unwrap();
+        assert_eq!(game.engine().seat(), 1);
+        assert_eq!(game.state(), GameState::PlayerTurn(0));
+        let events = game.play(Action::Move(Move::Stand)).unwrap();
+        let settled = events
+            .iter()
+            .filter(|e| matches!(e, GameEvent::RoundSettled { .. }))
+            .count();
+        assert_eq!(settled, 2);
+
+        // Each player keeps their own bankroll and statistics
+        assert_eq!(game.state(), GameState::Betting);
+        let engine = game.engine();
+        assert_eq!(engine.player_at(0).money, Money::from_units(110));
+        assert_eq!(engine.player_at(1).money, Money::from_units(220));
+        assert_eq!(engine.player_at(1).stats.rounds, 1);
+
+        let too_many = (0..8)
+            .map(|_| Player::new(Money::from_units(100)))
+            .collect();
+        assert_eq!(
+            Game::new(
+                Rules::default(),
+                Shoe::new(1, Rules::default().variant).unwrap(),
+                too_many
+            )
+            .err(),
+            Some(Error::Seats(8))
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use crate::config::GameConfig;
+use crate::deviations::{HiLoBot, FAB_4, ILLUSTRIOUS_18};
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::input::{hand_situation, BasicStrategy, PlayerInput};
+use crate::money::Money;
+use crate::rules::HandOptions;
+use crate::side_bets::SideBet;
+use crate::solver::{solve, Composition};
+use crate::types::{Card, Hand, Move, Suit, Value, DECK_SIZE};
+
+/// The true counts the index numbers are searched between.
+pub const MIN_INDEX: i32 = -10;
+pub const MAX_INDEX: i32 = 10;
+
+/// An index play: the hand against the upcard is played `deviation` from the true count `index`
+/// up and `otherwise` below it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexPlay {
+    /// The hard total, a pair of `total / 2` if `pair`.
+    pub total: u32,
+    pub pair: bool,
+    /// The upcard from 2 to 11, the ace.
+    pub upcard: u32,
+    pub deviation: Move,
+    pub otherwise: Move,
+    /// The true count of the counting system the deviation is played from, `None` if it isn't
+    /// worth it at any count up to [MAX_INDEX]. [MIN_INDEX] if it is worth it at every count.
+    pub index: Option<f32>,
+    /// The Hi-Lo index of the Illustrious 18 or the Fab 4.
+    pub published: f32,
+}
+
+/// The index numbers of the common decisions for a counting system and the rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexChart {
+    /// The counting system the true counts are of.
+    pub system: String,
+    /// The true count from which insurance is worth taking, `None` if it never is.
+    pub insurance: Option<f32>,
+    pub plays: Vec<IndexPlay>,
+}
+
+/// The card of a value from 2 to 11, the ace.
+fn card(value: u32) -> Result<Card, Error> {
+    let value = match value {
+        11 => Value::Ace,
+        value => Value::try_from(value)?,
+    };
+    Ok(Card {
+        suit: Suit::Spades,
+        value,
+    })
+}
+
+/// Two cards making up the hard total, or the pair.
+fn two_cards(total: u32, pair: bool) -> Result<Hand, Error> {
+    let (first, second) = match total {
+        _ if pair => (total / 2, total / 2),
+        12.. => (10, total - 10),
+        _ if total == 8 => (5, 3),
+        _ => (total - 4, 4),
+    };
+    let mut hand = Hand::from_card(card(first)?);
+    hand.add_card(card(second)?);
+    Ok(hand)
+}
+
+/// The cards of the shoe left with half of it dealt, the cards of the hand and the upcard among
+/// them, the running count of the cards dealt making `true_count`. The ranks are dealt in their
+/// share of the cards dealt, with each one's share moved by its tag to make the count.
+///
+/// Returns the cards left, the hand and upcard taken out, and the true count they really make,
+/// as only whole cards are dealt.
+fn shoe_at(conf: &GameConfig, seen: &[Card], true_count: f32) -> Result<(Composition, f32), Error> {
+    let rules = &conf.rules;
+    let system = conf.counting.system();
+    let mut left = Composition::full(rules.deck_count, rules.variant)?;
+    let total = left.total();
+    let dealt = total / 2;
+    let decks_left = (total - dealt) as f32 / DECK_SIZE as f32;
+    let running_count = true_count / system.true_count(1, decks_left);
+
+    let mut count = 0;
+    for card in seen {
+        left.remove(&card.value)?;
+        count += system.tag(&card.value);
+    }
+    let ranks = [
+        Value::Ace,
+        Value::Two,
+        Value::Three,
+        Value::Four,
+        Value::Five,
+        Value::Six,
+        Value::Seven,
+        Value::Eight,
+        Value::Nine,
+        Value::Ten,
+    ];
+    let remaining = left.total() as f32;
+    let share = (dealt - seen.len() as u32) as f32 / remaining;
+    let squares = ranks
+        .iter()
+        .map(|value| left.count(value) as f32 * system.tag(value).pow(2) as f32)
+        .sum::<f32>();
+    let shift = (running_count - count as f32) / squares.max(1.0);
+    for value in &ranks {
+        let cards = left.count(value);
+        let share = (share + shift * system.tag(value) as f32).clamp(0.0, 1.0);
+        for _ in 0..(cards as f32 * share).round() as u32 {
+            left.remove(value)?;
+            count += system.tag(value);
+        }
+    }
+    let decks_left = left.total() as f32 / DECK_SIZE as f32;
+    Ok((left, system.true_count(count, decks_left)))
+}
+
+/// Where the `gains`, by true count from the lowest, go from negative to positive, interpolated
+/// between the counts around it. `None` if they never do.
+fn crossing(gains: &[(f32, f64)]) -> Option<f32> {
+    let first = gains.first()?;
+    if first.1 >= 0.0 {
+        return Some(MIN_INDEX as f32);
+    }
+    gains.windows(2).find_map(|pair| {
+        let ((low, below), (high, above)) = (pair[0], pair[1]);
+        (below < 0.0 && above >= 0.0).then(|| {
+            let index = low + (high - low) * (-below / (above - below)) as f32;
+            // Without a negative zero
+            index.round() + 0.0
+        })
+    })
+}
+
+/// Works out the index numbers of the Illustrious 18 and the Fab 4, and of insurance, for the
+/// counting system and the rules of `conf` from the exact expected results of the moves.
+///
+/// The true counts are those of a shoe dealt half way, the cards of each rank dealt in their
+/// share of the cards moved by their tag, so the richer the count the richer the cards left in
+/// the cards the count favours. An index is the true count from which the deviation is worth at
+/// least as much as the move it replaces.
+pub fn index_chart(conf: &GameConfig) -> Result<IndexChart, Error> {
+    let system = conf.counting.system();
+    let balance = Value::ALL
+        .iter()
+        .map(|value| system.tag(value))
+        .sum::<i32>();
+    if balance != 0 || system.initial_running_count(conf.rules.deck_count) != 0 {
+        return Err(Error::Config(format!(
+            "index numbers are worked out for a balanced count converted to a true count, {} is unbalanced",
+            system.name()
+        )));
+    }
+    let rules = &conf.rules;
+    let counts = MIN_INDEX..=MAX_INDEX;
+
+    let ace = [card(11)?];
+    let mut gains = vec![];
+    for true_count in counts.clone() {
+        let (left, true_count) = shoe_at(conf, &ace, true_count as f32)?;
+        let tens = left.count(&Value::Ten) as f64 / left.total() as f64;
+        // Insurance pays 2 to 1 on a ten
+        gains.push((true_count, 3.0 * tens - 1.0));
+    }
+    let insurance = crossing(&gains);
+
+    let surrenders = FAB_4
+        .iter()
+        .filter(|_| rules.surrender && rules.variant.allows_surrender())
+        .map(|(total, upcard, index)| {
+            let otherwise = BasicStrategy::choose(rules, *total, false, None, *upcard, |m| {
+                m != Move::Surrender
+            })
+            .unwrap_or(Move::Hit);
+            (*total, false, *upcard, Move::Surrender, otherwise, *index)
+        });
+    let plays = ILLUSTRIOUS_18
+        .iter()
+        .map(|(total, upcard, index, above, below)| {
+            let pair = *above == Move::Split;
+            (*total, pair, *upcard, *above, *below, *index)
+        });
+    let mut chart = vec![];
+    for (total, pair, upcard, deviation, otherwise, published) in surrenders.chain(plays) {
+        let hand = two_cards(total, pair)?;
+        let dealer = card(upcard)?;
+        let mut seen = hand.cards.clone();
+        seen.push(dealer.clone());
+        let mut gains = vec![];
+        for true_count in counts.clone() {
+            let (left, true_count) = shoe_at(conf, &seen, true_count as f32)?;
+            let evs = solve(rules, &left, &hand, &dealer.value)?;
+            let (Some(deviation), Some(otherwise)) = (evs.of(deviation), evs.of(otherwise)) else {
+                break;
+            };
+            gains.push((true_count, deviation - otherwise));
+        }
+        // A move the rules don't allow has no index
+        if gains.len() < counts.clone().count() {
+            continue;
+        }
+        chart.push(IndexPlay {
+            total,
+            pair,
+            upcard,
+            deviation,
+            otherwise,
+            index: crossing(&gains),
+            published,
+        });
+    }
+    Ok(IndexChart {
+        system: system.name().to_string(),
+        insurance,
+        plays: chart,
+    })
+}
+
+/// A card counting bot betting as [HiLoBot] does and playing basic strategy with the index plays
+/// of `plays` at the true count of the counting system at the table, to compare index numbers by
+/// simulation. Surrendering is left out below its index, as a hand is played if it couldn't be.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexBot {
+    pub plays: Vec<IndexPlay>,
+    /// Insurance, and even money, is taken from this true count up, never without it.
+    pub insurance: Option<f32>,
+}
+
+impl IndexBot {
+    pub fn new(chart: &IndexChart) -> Self {
+        Self {
+            plays: chart.plays.clone(),
+            insurance: chart.insurance,
+        }
+    }
+
+    /// The move with the index plays at the true count, as [BasicStrategy::decide] finds it
+    /// otherwise.
+    pub fn decide(&self, engine: &GameEngine, hand: usize, options: &HandOptions) -> Move {
+        let (value, soft, pair, upcard) = hand_situation(engine, hand);
+        let true_count = engine.counter.true_count;
+        let mut allowed = options.moves.clone();
+        if !soft {
+            let plays = self.plays.iter().filter(|play| {
+                play.total == value && play.upcard == upcard && (!play.pair || pair.is_some())
+            });
+            for play in plays {
+                let Some(index) = play.index else {
+                    continue;
+                };
+                if true_count >= index && allowed.contains(&play.deviation) {
+                    return play.deviation;
+                }
+                if play.deviation == Move::Surrender {
+                    allowed.retain(|m| *m != Move::Surrender);
+                } else if true_count < index && allowed.contains(&play.otherwise) {
+                    return play.otherwise;
+                }
+            }
+        }
+        BasicStrategy::choose(&engine.conf.rules, value, soft, pair, upcard, |m| {
+            allowed.contains(&m)
+        })
+        .unwrap_or(options.moves[0])
+    }
+
+    fn insures(&self, engine: &GameEngine) -> bool {
+        self.insurance
+            .is_some_and(|index| engine.counter.true_count >= index)
+    }
+}
+
+impl PlayerInput for IndexBot {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        BasicStrategy.num_hands(engine)
+    }
+
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error> {
+        HiLoBot.bet(engine, hand, num_hands)
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        BasicStrategy.side_bet(engine, side_bet)
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        Ok(self.insures(engine))
+    }
+
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        let player = &engine.player;
+        if !self.insures(engine) {
+            return Ok(Money::ZERO);
+        }
+        Ok(player.max_insurance().min(player.money))
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        Ok(self.decide(engine, hand, options))
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        BasicStrategy.buy_amount(engine, hand)
+    }
+
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.rescue(engine, hand)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        BasicStrategy.tip(engine)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        BasicStrategy.rebuy(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::counting::CountingMethod;
+    use crate::engine::tests::dealt;
+    use crate::indices::{index_chart, IndexBot, MIN_INDEX};
+    use crate::input::PlayerInput;
+    use crate::types::{Move, Value};
+
+    #[test]
+    fn test_index_chart() {
+        let conf = GameConfig::default();
+        let chart = index_chart(&conf).unwrap();
+        assert_eq!(chart.system, "Hi-Lo");
+        // Insurance is worth it from about +3
+        let insurance = chart.insurance.unwrap();
+        assert!((2.0..=4.0).contains(&insurance), "{}", insurance);
+        assert_eq!(chart.plays.len(), 21);
+        let index = |total, upcard, deviation| {
+            chart
+                .plays
+                .iter()
+                .find(|p| p.total == total && p.upcard == upcard && p.deviation == deviation)
+                .and_then(|p| p.index)
+                .unwrap()
+        };
+        // Within a couple of counts of the published indices
+        for play in &chart.plays {
+            let found = play.index.unwrap();
+            assert!(
+                (found - play.published).abs() <= 2.0,
+                "{} against {}: {} for {}",
+                play.total,
+                play.upcard,
+                found,
+                play.published
+            );
+        }
+        assert!(index(16, 10, Move::Stand) > MIN_INDEX as f32);
+        assert!(index(12, 2, Move::Stand) > index(12, 4, Move::Stand));
+
+        // The bot plays them at the true count of the table
+        let mut bot = IndexBot::new(&chart);
+        let mut play = |cards: [Value; 2], upcard, true_count| {
+            let mut engine = dealt(conf.clone(), &cards, upcard);
+            engine.counter.true_count = true_count;
+            let options = engine.conf.rules.hand_options(&engine.player, 0);
+            bot.action(&engine, 0, &options).unwrap()
+        };
+        assert_eq!(play([Value::Ten, Value::Two], Value::Two, 1.0), Move::Hit);
+        assert_eq!(play([Value::Ten, Value::Two], Value::Two, 4.5), Move::Stand);
+        // 15 against a ten is surrendered from 0, and below it hit rather than surrendered
+        assert_eq!(
+            play([Value::Ten, Value::Five], Value::King, -1.0),
+            Move::Hit
+        );
+        assert_eq!(
+            play([Value::Ten, Value::Five], Value::King, 0.5),
+            Move::Surrender
+        );
+        // Soft hands are played by basic strategy
+        assert_eq!(
+            play([Value::Ace, Value::Seven], Value::Nine, 9.0),
+            Move::Hit
+        );
+
+        let ko = GameConfig {
+            counting: CountingMethod::KnockOut,
+            ..GameConfig::default()
+        };
+        assert!(index_chart(&ko).is_err());
+    }
+}
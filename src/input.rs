@@ -0,0 +1,609 @@
+use crate::deviations::HiLoBot;
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::money::Money;
+use crate::perfect::PerfectPlay;
+use crate::rules::{HandOptions, Rules};
+use crate::side_bets::SideBet;
+use crate::types::{Move, Value};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A source of the player's decisions, such as a person at the terminal, a script or a bot.
+///
+/// Bets sized by a betting system or an automatic bet ramp are placed without asking.
+pub trait PlayerInput {
+    /// The number of hands to play in the next round, `None` to leave the table.
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error>;
+
+    /// The main bet on hand number `hand` of `num_hands`.
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error>;
+
+    /// The amount to place on an optional side bet, 0 for none.
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error>;
+
+    /// Whether to take even money on the blackjack at `hand` against a dealer ace.
+    fn even_money(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error>;
+
+    /// The amount to insure against a dealer blackjack, 0 for none.
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error>;
+
+    /// The move to make on the hand at `hand`, one of `options`.
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error>;
+
+    /// The amount to raise the bet by when buying a card on a hand that doesn't end on a double.
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error>;
+
+    /// Whether to rescue the doubled hand at `hand` by surrendering the original bet.
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error>;
+
+    /// The tip for the dealer after a winning round, 0 for none.
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error>;
+
+    /// The amount to rebuy for when the bankroll is below the table minimum, `None` to leave.
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error>;
+}
+
+/// Plays a fixed sequence of bets and moves, then leaves the table once the bets run out.
+///
+/// Moves that aren't available, or run out, are replaced by standing (or the first available
+/// move if standing isn't). Side bets, insurance, tips and rebuys are always declined.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScriptedInput {
+    /// One bet per round, each round is played with a single hand.
+    pub bets: VecDeque<Money>,
+    pub moves: VecDeque<Move>,
+}
+
+impl ScriptedInput {
+    pub fn new(bets: &[Money], moves: &[Move]) -> Self {
+        Self {
+            bets: bets.iter().copied().collect(),
+            moves: moves.iter().copied().collect(),
+        }
+    }
+}
+
+impl PlayerInput for ScriptedInput {
+    fn num_hands(&mut self, _engine: &GameEngine) -> Result<Option<usize>, Error> {
+        Ok((!self.bets.is_empty()).then_some(1))
+    }
+
+    fn bet(
+        &mut self,
+        engine: &GameEngine,
+        _hand: usize,
+        _num_hands: usize,
+    ) -> Result<Money, Error> {
+        Ok(self.bets.pop_front().unwrap_or(engine.conf.table_min))
+    }
+
+    fn side_bet(&mut self, _engine: &GameEngine, _side_bet: SideBet) -> Result<Money, Error> {
+        Ok(Money::ZERO)
+    }
+
+    fn even_money(&mut self, _engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn insurance(&mut self, _engine: &GameEngine) -> Result<Money, Error> {
+        Ok(Money::ZERO)
+    }
+
+    fn action(
+        &mut self,
+        _engine: &GameEngine,
+        _hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        Ok(match self.moves.pop_front() {
+            Some(action) if options.moves.contains(&action) => action,
+            _ if options.moves.contains(&Move::Stand) => Move::Stand,
+            _ => options.moves[0],
+        })
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        Ok(engine.player.hands[hand].bet.min(engine.player.money))
+    }
+
+    fn rescue(&mut self, _engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn tip(&mut self, _engine: &GameEngine) -> Result<Money, Error> {
+        Ok(Money::ZERO)
+    }
+
+    fn rebuy(&mut self, _engine: &GameEngine) -> Result<Option<Money>, Error> {
+        Ok(None)
+    }
+}
+
+/// What the bots look up a hand by: its value, whether it is soft, the value of its pair if it can
+/// be split and the dealer upcard, with aces counted as 11.
+pub(crate) fn hand_situation(engine: &GameEngine, hand: usize) -> (u32, bool, Option<u32>, u32) {
+    let hand = &engine.player.hands[hand].hand;
+    let upcard = match engine.dealer_hand.cards.first().map(|c| &c.value) {
+        Some(Value::Ace) => Value::ACE_HIGH_VAL,
+        Some(value) => value.value(),
+        None => 10,
+    };
+    let pair = hand.can_split().then(|| match hand.cards[0].value {
+        Value::Ace => Value::ACE_HIGH_VAL,
+        ref value => value.value(),
+    });
+    (hand.calc_value(), hand.is_soft(), pair, upcard)
+}
+
+/// A bot playing multi-deck basic strategy for the rules at the table, whether the dealer hits
+/// soft 17 and doubling after a split is allowed, with flat bets of the table minimum on a single
+/// hand. It never leaves the table by itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BasicStrategy;
+
+impl BasicStrategy {
+    /// The basic strategy move against the dealer upcard, counting an ace as 11.
+    /// Falls back to the next best move if the best one isn't available.
+    pub fn decide(engine: &GameEngine, hand: usize, options: &HandOptions) -> Move {
+        let (value, soft, pair, upcard) = hand_situation(engine, hand);
+        Self::choose(&engine.conf.rules, value, soft, pair, upcard, |m| {
+            options.moves.contains(&m)
+        })
+        .unwrap_or(options.moves[0])
+    }
+
+    /// The basic strategy move under `rules` for a hand of `value`, a pair of `pair` if it is
+    /// one, against the `upcard`, with aces counted as 11. `None` if none of the moves it would
+    /// make are allowed.
+    pub fn choose(
+        rules: &Rules,
+        value: u32,
+        soft: bool,
+        pair: Option<u32>,
+        upcard: u32,
+        allowed: impl Fn(Move) -> bool,
+    ) -> Option<Move> {
+        let h17 = rules.hit_soft_17;
+        let das = rules.double_after_split;
+
+        // Against a dealer hitting soft 17, 8s are surrendered to an ace rather than split
+        let surrender_eights = h17 && pair == Some(8) && upcard == 11;
+        if let Some(pair) = pair.filter(|_| allowed(Move::Split)) {
+            let split = match pair {
+                11 => true,
+                8 => !(surrender_eights && allowed(Move::Surrender)),
+                9 => !matches!(upcard, 7 | 10 | 11),
+                7 => upcard <= 7,
+                3 | 2 if das => upcard <= 7,
+                3 | 2 => (4..=7).contains(&upcard),
+                6 if das => upcard <= 6,
+                6 => (3..=6).contains(&upcard),
+                4 => das && matches!(upcard, 5 | 6),
+                _ => false,
+            };
+            if split {
+                return Some(Move::Split);
+            }
+        }
+
+        if allowed(Move::Surrender)
+            && !soft
+            && (value == 16 && upcard >= 9
+                || value == 15 && upcard == 10
+                || h17 && upcard == 11 && matches!(value, 15 | 17)
+                || surrender_eights)
+        {
+            return Some(Move::Surrender);
+        }
+
+        let (best, fallback) = if soft {
+            match value {
+                20.. => (Move::Stand, Move::Stand),
+                19 if h17 && upcard == 6 => (Move::Double, Move::Stand),
+                18 if (3..=6).contains(&upcard) || h17 && upcard == 2 => {
+                    (Move::Double, Move::Stand)
+                }
+                18 if upcard <= 8 => (Move::Stand, Move::Stand),
+                17 if (3..=6).contains(&upcard) => (Move::Double, Move::Hit),
+                15 | 16 if (4..=6).contains(&upcard) => (Move::Double, Move::Hit),
+                13 | 14 if (5..=6).contains(&upcard) => (Move::Double, Move::Hit),
+                19 => (Move::Stand, Move::Stand),
+                _ => (Move::Hit, Move::Hit),
+            }
+        } else {
+            match value {
+                17.. => (Move::Stand, Move::Stand),
+                13..=16 if upcard <= 6 => (Move::Stand, Move::Stand),
+                12 if (4..=6).contains(&upcard) => (Move::Stand, Move::Stand),
+                11 if upcard <= 10 || h17 => (Move::Double, Move::Hit),
+                10 if upcard <= 9 => (Move::Double, Move::Hit),
+                9 if (3..=6).contains(&upcard) => (Move::Double, Move::Hit),
+                _ => (Move::Hit, Move::Hit),
+            }
+        };
+        [best, fallback, Move::Stand, Move::Hit]
+            .into_iter()
+            .find(|m| allowed(*m))
+    }
+}
+
+impl PlayerInput for BasicStrategy {
+    fn num_hands(&mut self, _engine: &GameEngine) -> Result<Option<usize>, Error> {
+        Ok(Some(1))
+    }
+
+    fn bet(
+        &mut self,
+        engine: &GameEngine,
+        _hand: usize,
+        _num_hands: usize,
+    ) -> Result<Money, Error> {
+        Ok(engine.conf.table_min)
+    }
+
+    fn side_bet(&mut self, _engine: &GameEngine, _side_bet: SideBet) -> Result<Money, Error> {
+        Ok(Money::ZERO)
+    }
+
+    /// Even money is the same bet as insurance, which basic strategy never takes.
+    fn even_money(&mut self, _engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn insurance(&mut self, _engine: &GameEngine) -> Result<Money, Error> {
+        Ok(Money::ZERO)
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        Ok(BasicStrategy::decide(engine, hand, options))
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        Ok(engine.player.hands[hand].bet.min(engine.player.money))
+    }
+
+    fn rescue(&mut self, _engine: &GameEngine, _hand: usize) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn tip(&mut self, _engine: &GameEngine) -> Result<Money, Error> {
+        Ok(Money::ZERO)
+    }
+
+    fn rebuy(&mut self, _engine: &GameEngine) -> Result<Option<Money>, Error> {
+        Ok(None)
+    }
+}
+
+/// The playing strategies of the bots that can be selected from the command line. They bet like
+/// [`BasicStrategy`] and only differ in how the hands are played, apart from the Hi-Lo counter
+/// which plays and bets as [`HiLoBot`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strategy {
+    Basic,
+    /// Never takes a card that could bust the hand, and never doubles or splits.
+    NeverBust,
+    /// Plays like the dealer has to: hits below 17, and soft 17 if the dealer does.
+    MimicDealer,
+    /// Counts cards, with the index plays and the bet ramp of [`HiLoBot`].
+    HiLo,
+    /// Plays the strategy chart loaded into the game config, basic strategy where it has no row.
+    Chart,
+    /// Plays every hand perfectly for the cards left in the shoe, see [`PerfectPlay`].
+    Perfect,
+}
+
+impl Strategy {
+    pub fn decide(&self, engine: &GameEngine, hand: usize, options: &HandOptions) -> Move {
+        let cards = &engine.player.hands[hand].hand;
+        let hit = match self {
+            Strategy::Basic => return BasicStrategy::decide(engine, hand, options),
+            Strategy::NeverBust => {
+                cards.hard_value() <= 11 || cards.is_soft() && cards.calc_value() <= 17
+            }
+            Strategy::MimicDealer => engine.conf.rules.dealer_hits(cards),
+            Strategy::HiLo => return HiLoBot::decide(engine, hand, options),
+            Strategy::Chart => {
+                return match &engine.conf.chart {
+                    Some(chart) => chart.decide(engine, hand, options),
+                    None => BasicStrategy::decide(engine, hand, options),
+                }
+            }
+            Strategy::Perfect => return PerfectPlay::decide(engine, hand, options),
+        };
+        let choice = if hit { Move::Hit } else { Move::Stand };
+        [choice, Move::Stand, Move::Hit]
+            .into_iter()
+            .find(|m| options.moves.contains(m))
+            .unwrap_or(options.moves[0])
+    }
+}
+
+impl PlayerInput for Strategy {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        BasicStrategy.num_hands(engine)
+    }
+
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error> {
+        match self {
+            Strategy::HiLo => HiLoBot.bet(engine, hand, num_hands),
+            _ => BasicStrategy.bet(engine, hand, num_hands),
+        }
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        BasicStrategy.side_bet(engine, side_bet)
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        match self {
+            Strategy::HiLo => HiLoBot.even_money(engine, hand),
+            Strategy::Perfect => Ok(PerfectPlay::insures(engine)),
+            _ => BasicStrategy.even_money(engine, hand),
+        }
+    }
+
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        match self {
+            Strategy::HiLo => HiLoBot.insurance(engine),
+            Strategy::Perfect if PerfectPlay::insures(engine) => {
+                let player = &engine.player;
+                Ok(player.max_insurance().min(player.money))
+            }
+            _ => BasicStrategy.insurance(engine),
+        }
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        if *self == Strategy::Chart && engine.conf.chart.is_none() {
+            return Err(Error::Config(
+                "the chart bot needs a strategy chart, load one with --chart".to_string(),
+            ));
+        }
+        Ok(self.decide(engine, hand, options))
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        BasicStrategy.buy_amount(engine, hand)
+    }
+
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.rescue(engine, hand)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        BasicStrategy.tip(engine)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        BasicStrategy.rebuy(engine)
+    }
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Strategy::Basic => "basic",
+                Strategy::NeverBust => "never-bust",
+                Strategy::MimicDealer => "mimic-dealer",
+                Strategy::HiLo => "hi-lo",
+                Strategy::Chart => "chart",
+                Strategy::Perfect => "perfect",
+            }
+        )
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "basic" => Ok(Strategy::Basic),
+            "never-bust" => Ok(Strategy::NeverBust),
+            "mimic-dealer" => Ok(Strategy::MimicDealer),
+            "hi-lo" => Ok(Strategy::HiLo),
+            "chart" => Ok(Strategy::Chart),
+            "perfect" => Ok(Strategy::Perfect),
+            _ => Err(format!(
+                "expected one of 'basic', 'never-bust', 'mimic-dealer', 'hi-lo', 'chart' or 'perfect', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// Who makes the decisions at the table: the person at the terminal, or one of the bots of
+/// [`Strategy`] to watch it play or to simulate.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PlayerKind {
+    #[default]
+    Human,
+    Bot(Strategy),
+}
+
+impl PlayerKind {
+    /// The strategy of the bot, `None` for a person.
+    pub fn bot(&self) -> Option<Strategy> {
+        match self {
+            PlayerKind::Human => None,
+            PlayerKind::Bot(strategy) => Some(*strategy),
+        }
+    }
+}
+
+/// Written as "human" or "bot:" followed by the strategy, e.g. "bot:basic".
+impl Display for PlayerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlayerKind::Human => write!(f, "human"),
+            PlayerKind::Bot(strategy) => write!(f, "bot:{}", strategy),
+        }
+    }
+}
+
+impl FromStr for PlayerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "human" => Ok(PlayerKind::Human),
+            Some(("bot", strategy)) => strategy.parse().map(PlayerKind::Bot),
+            _ => Err(format!(
+                "expected 'human' or 'bot:' and a strategy, e.g. 'bot:basic', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::engine::tests::dealt;
+    use crate::input::{BasicStrategy, PlayerInput, PlayerKind, ScriptedInput, Strategy};
+    use crate::money::Money;
+    use crate::types::{Move, Value};
+
+    fn decide(cards: &[Value], upcard: Value) -> Move {
+        let engine = dealt(GameConfig::default(), cards, upcard);
+        let options = engine.conf.rules.hand_options(&engine.player, 0);
+        BasicStrategy.action(&engine, 0, &options).unwrap()
+    }
+
+    #[test]
+    fn test_basic_strategy() {
+        assert_eq!(
+            decide(&[Value::Eight, Value::Eight], Value::Ten),
+            Move::Split
+        );
+        assert_eq!(decide(&[Value::Ten, Value::Ten], Value::Six), Move::Stand);
+        assert_eq!(
+            decide(&[Value::Ten, Value::Six], Value::Ten),
+            Move::Surrender
+        );
+        assert_eq!(decide(&[Value::Ten, Value::Two], Value::Three), Move::Hit);
+        assert_eq!(decide(&[Value::Six, Value::Five], Value::Six), Move::Double);
+        assert_eq!(decide(&[Value::Ace, Value::Seven], Value::Nine), Move::Hit);
+        assert_eq!(decide(&[Value::Ace, Value::Seven], Value::Two), Move::Stand);
+
+        // A soft 18 that can't be doubled any more stands
+        assert_eq!(
+            decide(&[Value::Ace, Value::Four, Value::Three], Value::Four),
+            Move::Stand
+        );
+    }
+
+    #[test]
+    fn test_basic_strategy_rules() {
+        let decide = |h17: bool, das: bool, cards: &[Value], upcard: Value| {
+            let mut engine = dealt(GameConfig::default(), cards, upcard);
+            engine.conf.rules.hit_soft_17 = h17;
+            engine.conf.rules.double_after_split = das;
+            let options = engine.conf.rules.hand_options(&engine.player, 0);
+            BasicStrategy.action(&engine, 0, &options).unwrap()
+        };
+        let eleven = [Value::Six, Value::Five];
+        assert_eq!(decide(false, true, &eleven, Value::Ace), Move::Hit);
+        assert_eq!(decide(true, true, &eleven, Value::Ace), Move::Double);
+        let soft_19 = [Value::Ace, Value::Eight];
+        assert_eq!(decide(false, true, &soft_19, Value::Six), Move::Stand);
+        assert_eq!(decide(true, true, &soft_19, Value::Six), Move::Double);
+        let eights = [Value::Eight, Value::Eight];
+        assert_eq!(decide(false, true, &eights, Value::Ace), Move::Split);
+        assert_eq!(decide(true, true, &eights, Value::Ace), Move::Surrender);
+        let seventeen = [Value::Ten, Value::Seven];
+        assert_eq!(decide(true, true, &seventeen, Value::Ace), Move::Surrender);
+
+        // Without doubling after a split, small pairs are only split against the weakest upcards
+        let twos = [Value::Two, Value::Two];
+        assert_eq!(decide(false, true, &twos, Value::Two), Move::Split);
+        assert_eq!(decide(false, false, &twos, Value::Two), Move::Hit);
+        assert_eq!(decide(false, false, &twos, Value::Four), Move::Split);
+        let fours = [Value::Four, Value::Four];
+        assert_eq!(decide(false, true, &fours, Value::Five), Move::Split);
+        assert_eq!(decide(false, false, &fours, Value::Five), Move::Hit);
+    }
+
+    #[test]
+    fn test_player_kind() {
+        assert_eq!("human".parse(), Ok(PlayerKind::Human));
+        assert_eq!(
+            "bot:never-bust".parse(),
+            Ok(PlayerKind::Bot(Strategy::NeverBust))
+        );
+        assert_eq!(PlayerKind::Bot(Strategy::Basic).to_string(), "bot:basic");
+        assert_eq!(
+            PlayerKind::Bot(Strategy::Basic).bot(),
+            Some(Strategy::Basic)
+        );
+        assert!("bot:psychic".parse::<PlayerKind>().is_err());
+        assert!("basic".parse::<PlayerKind>().is_err());
+    }
+
+    #[test]
+    fn test_strategies() {
+        let decide = |strategy: Strategy, cards: &[Value], upcard: Value| {
+            let engine = dealt(GameConfig::default(), cards, upcard);
+            let options = engine.conf.rules.hand_options(&engine.player, 0);
+            strategy.decide(&engine, 0, &options)
+        };
+        let hard_12 = [Value::Ten, Value::Two];
+        let soft_17 = [Value::Ace, Value::Six];
+        assert_eq!(
+            decide(Strategy::NeverBust, &hard_12, Value::Ten),
+            Move::Stand
+        );
+        assert_eq!(decide(Strategy::NeverBust, &soft_17, Value::Ten), Move::Hit);
+        assert_eq!(
+            decide(Strategy::MimicDealer, &hard_12, Value::Six),
+            Move::Hit
+        );
+        assert_eq!(
+            decide(Strategy::MimicDealer, &soft_17, Value::Ten),
+            Move::Stand
+        );
+        assert_eq!(
+            decide(Strategy::Basic, &[Value::Six, Value::Five], Value::Six),
+            Move::Double
+        );
+    }
+
+    #[test]
+    fn test_scripted_input() {
+        let engine = dealt(GameConfig::default(), &[Value::Ten, Value::Six], Value::Ten);
+        let options = engine.conf.rules.hand_options(&engine.player, 0);
+        let mut input = ScriptedInput::new(&[Money::from_units(5)], &[Move::Split, Move::Hit]);
+
+        assert_eq!(input.num_hands(&engine).unwrap(), Some(1));
+        assert_eq!(input.bet(&engine, 1, 1).unwrap(), Money::from_units(5));
+        assert_eq!(input.num_hands(&engine).unwrap(), None);
+
+        // A split isn't possible, so the player stands instead
+        assert_eq!(input.action(&engine, 0, &options).unwrap(), Move::Stand);
+        assert_eq!(input.action(&engine, 0, &options).unwrap(), Move::Hit);
+        assert_eq!(input.action(&engine, 0, &options).unwrap(), Move::Stand);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::config::GameConfig;
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::input::{BasicStrategy, PlayerInput};
+use crate::money::Money;
+use crate::rules::HandOptions;
+use crate::side_bets::SideBet;
+use crate::simulation::{simulate, Progress};
+use crate::types::{Move, Player};
+use std::collections::BTreeMap;
+
+/// The expected result of an insurance bet per unit bet when `ten_density` of the cards the
+/// player hasn't seen, the hole card among them, are tens.
+pub fn insurance_ev(ten_density: f64) -> f64 {
+    let payout = Player::INSURANCE_PAYOUT;
+    ten_density * payout.numerator as f64 / payout.denominator as f64 - (1.0 - ten_density)
+}
+
+/// The share of tens among the unseen cards above which insurance is worth taking, a third as
+/// it pays 2:1.
+pub fn breakeven_density() -> f64 {
+    let payout = Player::INSURANCE_PAYOUT;
+    payout.denominator as f64 / (payout.numerator + payout.denominator) as f64
+}
+
+/// The insurance bets offered in a simulation, at one true count or ten density.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InsuranceOffers {
+    pub offers: u64,
+    /// The sum of the share of tens among the unseen cards at each offer.
+    pub ten_density_sum: f64,
+    /// The offers where the hole card was a ten, so insurance would have won.
+    pub dealer_blackjacks: u64,
+}
+
+impl InsuranceOffers {
+    fn add(&mut self, ten_density: f64, dealer_blackjack: bool) {
+        self.offers += 1;
+        self.ten_density_sum += ten_density;
+        self.dealer_blackjacks += dealer_blackjack as u64;
+    }
+
+    /// The average share of tens among the unseen cards.
+    pub fn ten_density(&self) -> f64 {
+        if self.offers == 0 {
+            return 0.0;
+        }
+        self.ten_density_sum / self.offers as f64
+    }
+
+    /// The expected result of insuring a unit from the ten density.
+    pub fn expected_ev(&self) -> f64 {
+        insurance_ev(self.ten_density())
+    }
+
+    /// The result of insuring a unit every time it was offered, per offer.
+    pub fn simulated_ev(&self) -> f64 {
+        if self.offers == 0 {
+            return 0.0;
+        }
+        insurance_ev(self.dealer_blackjacks as f64 / self.offers as f64)
+    }
+}
+
+/// How insurance would have done in a simulation, by the count it was offered at.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InsuranceResult {
+    pub rounds: u64,
+    /// The offers by the true count, rounded down to a whole count.
+    pub true_counts: BTreeMap<i32, InsuranceOffers>,
+    /// The offers by the share of tens among the unseen cards, rounded down to a whole percent.
+    pub ten_densities: BTreeMap<u32, InsuranceOffers>,
+}
+
+/// Plays basic strategy, looking at the shoe and the hole card whenever insurance is offered.
+struct InsuranceRecorder(InsuranceResult);
+
+impl InsuranceRecorder {
+    fn record(&mut self, engine: &GameEngine) {
+        let is_ten = |value: u32| value == 10;
+        let hole_card = engine.hole_card();
+        let dealer_blackjack = hole_card.is_some_and(|card| is_ten(card.value.value()));
+        let tens = engine
+            .shoe
+            .remaining_by_rank()
+            .iter()
+            .filter(|(value, _)| is_ten(value.value()))
+            .map(|(_, count)| count)
+            .sum::<u32>()
+            + dealer_blackjack as u32;
+        let unseen = engine.shoe.num_cards() + hole_card.is_some() as u32;
+        let ten_density = tens as f64 / unseen.max(1) as f64;
+        let true_count = engine.counter.true_count.floor() as i32;
+        let result = &mut self.0;
+        result
+            .true_counts
+            .entry(true_count)
+            .or_default()
+            .add(ten_density, dealer_blackjack);
+        result
+            .ten_densities
+            .entry((ten_density * 100.0).floor() as u32)
+            .or_default()
+            .add(ten_density, dealer_blackjack);
+    }
+}
+
+impl PlayerInput for InsuranceRecorder {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        BasicStrategy.num_hands(engine)
+    }
+
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error> {
+        BasicStrategy.bet(engine, hand, num_hands)
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        BasicStrategy.side_bet(engine, side_bet)
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.even_money(engine, hand)
+    }
+
+    /// Asked whenever insurance is offered, even with a blackjack.
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        self.record(engine);
+        BasicStrategy.insurance(engine)
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        BasicStrategy.action(engine, hand, options)
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        BasicStrategy.buy_amount(engine, hand)
+    }
+
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.rescue(engine, hand)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        BasicStrategy.tip(engine)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        BasicStrategy.rebuy(engine)
+    }
+}
+
+/// Plays `rounds` rounds of basic strategy as [simulate] does, recording the true count, the
+/// share of tens among the unseen cards and the hole card every time insurance is offered.
+pub fn simulate_insurance(
+    conf: GameConfig,
+    rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<InsuranceResult, Error> {
+    if !conf.rules.variant.offers_insurance() {
+        return Err(Error::Config(format!(
+            "{} doesn't offer insurance",
+            conf.rules.variant
+        )));
+    }
+    let mut recorder = InsuranceRecorder(InsuranceResult::default());
+    let result = simulate(conf, &mut recorder, rounds, seed, progress)?;
+    let mut insurance = recorder.0;
+    insurance.rounds = result.rounds;
+    Ok(insurance)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::insurance::{breakeven_density, insurance_ev, simulate_insurance, InsuranceOffers};
+    use crate::simulation::NoProgress;
+    use crate::variants::Variant;
+
+    #[test]
+    fn test_insurance_ev() {
+        assert!((breakeven_density() - 1.0 / 3.0).abs() < 1e-12);
+        assert!(insurance_ev(breakeven_density()).abs() < 1e-12);
+        // A full shoe has 16 tens in every 52 cards
+        assert!((insurance_ev(16.0 / 52.0) - (-1.0 / 13.0)).abs() < 1e-12);
+        assert!(insurance_ev(0.4) > 0.0);
+    }
+
+    #[test]
+    fn test_simulate_insurance() {
+        let result =
+            simulate_insurance(GameConfig::default(), 5000, Some(2), &mut NoProgress).unwrap();
+        assert_eq!(result.rounds, 5000);
+        let offers = result.true_counts.values().map(|o| o.offers).sum::<u64>();
+        // An ace comes up about once in 13 rounds
+        assert!((250..550).contains(&offers));
+        let by_density = result.ten_densities.values().map(|o| o.offers).sum::<u64>();
+        assert_eq!(by_density, offers);
+        // The tens thin out as the count falls
+        let density = |offers: Vec<&InsuranceOffers>| {
+            let tens = offers.iter().map(|o| o.ten_density_sum).sum::<f64>();
+            tens / offers.iter().map(|o| o.offers).sum::<u64>() as f64
+        };
+        let low = density(result.true_counts.range(..-1).map(|(_, o)| o).collect());
+        let high = density(result.true_counts.range(2..).map(|(_, o)| o).collect());
+        assert!(low < breakeven_density());
+        assert!(low < high);
+
+        let mut conf = GameConfig::default();
+        conf.rules.variant = Variant::Pontoon;
+        assert!(simulate_insurance(conf, 10, None, &mut NoProgress).is_err());
+    }
+}
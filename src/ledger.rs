@@ -0,0 +1,97 @@
+use crate::money::Money;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// A single movement of money, bets are negative and payouts positive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub description: String,
+    pub amount: Money,
+}
+
+/// Every bet and payout of one round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    pub round: u32,
+    pub entries: Vec<LedgerEntry>,
+}
+
+impl RoundRecord {
+    /// The net result of the round.
+    pub fn net(&self) -> Money {
+        self.entries.iter().map(|e| e.amount).sum()
+    }
+}
+
+impl Display for RoundRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Round {} ({:+}):", self.round, self.net())?;
+        for entry in self.entries.iter() {
+            write!(f, "\n  {:<28} {:+}", entry.description, entry.amount)?;
+        }
+        Ok(())
+    }
+}
+
+/// The in-memory history of every round played during the session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Ledger {
+    pub rounds: Vec<RoundRecord>,
+}
+
+impl Ledger {
+    pub fn start_round(&mut self, round: u32) {
+        self.rounds.push(RoundRecord {
+            round,
+            entries: vec![],
+        });
+    }
+
+    /// Records an entry in the current round, starting the first round if needed.
+    pub fn record(&mut self, description: &str, amount: Money) {
+        if self.rounds.is_empty() {
+            self.start_round(1);
+        }
+        if let Some(round) = self.rounds.last_mut() {
+            round.entries.push(LedgerEntry {
+                description: String::from(description),
+                amount,
+            });
+        }
+    }
+
+    /// The last `n` rounds that had any bets, oldest first.
+    pub fn last(&self, n: usize) -> Vec<&RoundRecord> {
+        let played = self
+            .rounds
+            .iter()
+            .filter(|r| !r.entries.is_empty())
+            .collect::<Vec<&RoundRecord>>();
+        played[played.len().saturating_sub(n)..].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ledger::Ledger;
+    use crate::money::Money;
+
+    #[test]
+    fn test_ledger_history() {
+        let mut ledger = Ledger::default();
+        for round in 1..=3 {
+            ledger.start_round(round);
+            ledger.record("Bet", Money::from_units(-10));
+            ledger.record("Win", Money::from_units(20 * (round as i64 % 2)));
+        }
+        // A round the player left before betting
+        ledger.start_round(4);
+
+        let last = ledger.last(2);
+        assert_eq!(last.len(), 2);
+        assert_eq!(last[0].round, 2);
+        assert_eq!(last[0].net(), Money::from_units(-10));
+        assert_eq!(last[1].net(), Money::from_units(10));
+        assert_eq!(ledger.last(10).len(), 3);
+    }
+}
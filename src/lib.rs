@@ -0,0 +1,46 @@
+pub mod advisor;
+pub mod betting;
+pub mod bonus;
+pub mod builder;
+pub mod chart;
+pub mod config;
+pub mod counting;
+pub mod custom_chart;
+pub mod dealer;
+pub mod deviations;
+pub mod drills;
+pub mod engine;
+pub mod error;
+pub mod events;
+pub mod experiment;
+pub mod fast;
+pub mod ffi;
+pub mod game;
+pub mod indices;
+pub mod input;
+pub mod insurance;
+pub mod ledger;
+pub mod mistakes;
+pub mod money;
+pub mod optimizer;
+pub mod outcomes;
+pub mod perfect;
+pub mod poker;
+pub mod protocol;
+pub mod rules;
+pub mod server;
+pub mod shuffle;
+pub mod side_bets;
+pub mod simulation;
+pub mod solver;
+pub mod spread;
+pub mod stats;
+pub mod sweep;
+pub mod table;
+pub mod team;
+pub mod training;
+pub mod types;
+pub mod variants;
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+pub mod wonging;
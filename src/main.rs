@@ -1,183 +1,1169 @@
-use crate::types::{Hand, Shoe, TWENTY_ONE};
-use clap::Parser;
-use std::io::stdin;
-use std::thread;
+use analysis::{Report, SummaryFormat};
+use blackjack::advisor::EdgeModel;
+use blackjack::betting::{BetRamp, BetSystem};
+use blackjack::bonus::Bonus21;
+use blackjack::config::GameConfig;
+use blackjack::counting::{CountingMethod, DeckEstimation};
+use blackjack::custom_chart::{ChartFormat, CustomChart};
+use blackjack::drills::{CountDrill, StrategyDrill};
+use blackjack::error::Error;
+use blackjack::experiment::SimulationFile;
+use blackjack::input::{PlayerKind, Strategy};
+use blackjack::money::Money;
+use blackjack::optimizer::Evolution;
+use blackjack::outcomes::StartingHand;
+use blackjack::rules::Rules;
+use blackjack::server;
+use blackjack::shuffle::ShuffleMethod;
+use blackjack::spread::{SpreadLimits, SpreadObjective};
+use blackjack::sweep::SweepRule;
+use blackjack::table::SeatPosition;
+use blackjack::team::Team;
+use blackjack::types::{DoubleRule, Payout, Player, Value};
+use blackjack::variants::Variant;
+use blackjack::wonging::Wonging;
+use clap::{Args, Parser, Subcommand};
+use render::{Console, OutputFormat};
+use std::path::PathBuf;
 use std::time::Duration;
 
-mod types;
+mod analysis;
+mod cli;
+mod render;
 
 /// BlackJack card game
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct BlackJack {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
     /// The number of decks in the shoe
     #[clap(short, long, default_value_t = 6)]
     deck_count: u32,
 
-    /// The minimum number of cards required to play another round
+    /// The number of cards placed behind the cut card, the shoe is reshuffled after the round in which it comes out
     #[clap(short, long, default_value_t = 52)]
-    reshuffle_limit: u32,
+    cut_card: u32,
+
+    /// The fraction of the shoe dealt before the cut card comes out (e.g. 0.75), instead of --cut-card
+    #[clap(long, conflicts_with = "cut-card")]
+    penetration: Option<f32>,
 
     /// Delay (in milliseconds) between moves to make it easier to follow the game, 0 means no delay.
     #[clap(long, default_value_t = 1000)]
     delay: u32,
-}
 
-struct GameConfig {
-    sleep_duration: Duration,
-    reshuffle_limit: u32,
-}
+    /// How the game is shown: plain, colored or json (one event per line, without delays)
+    #[clap(long, default_value_t = OutputFormat::Plain)]
+    output: OutputFormat,
 
-fn main() {
-    let args: BlackJack = BlackJack::parse();
+    /// The dealer hits on soft 17 (H17) instead of standing on all 17s (S17)
+    #[clap(long)]
+    hit_soft_17: bool,
 
-    let shoe = Shoe::new(args.deck_count).expect("Failed to create shoe");
-    let shoe = shoe.shuffle();
+    /// The dealer does not peek for blackjack when showing a ten or an Ace
+    #[clap(long)]
+    no_peek: bool,
 
-    play_shoe(
-        shoe,
-        &GameConfig {
-            reshuffle_limit: args.reshuffle_limit,
-            sleep_duration: Duration::from_millis(args.delay as u64),
-        },
-    );
+    /// Only the original bets are lost to a dealer blackjack found after the player acted,
+    /// doubles and splits are returned (as in European no hole card games)
+    #[clap(long)]
+    original_bets_only: bool,
+
+    /// The payout ratio for a blackjack, e.g. 3:2 or 6:5
+    #[clap(long, default_value_t = Payout::THREE_TO_TWO)]
+    blackjack_payout: Payout,
+
+    /// The maximum number of times a hand can be split
+    #[clap(long, default_value_t = 3)]
+    max_splits: u32,
+
+    /// Allow splitting aces again when a split ace receives another ace
+    #[clap(long)]
+    resplit_aces: bool,
+
+    /// Allow playing split aces normally instead of receiving only one card each
+    #[clap(long)]
+    hit_split_aces: bool,
+
+    /// The hand totals the player may double down on: any, 9-11 or 10-11
+    #[clap(long, default_value_t = DoubleRule::Any)]
+    double_on: DoubleRule,
+
+    /// Do not allow doubling down on a hand that was split
+    #[clap(long)]
+    no_double_after_split: bool,
+
+    /// Do not allow surrendering a hand, where the variant would
+    #[clap(long)]
+    no_surrender: bool,
+
+    /// A hand with this many cards that hasn't busted wins automatically (e.g. 5 for five-card Charlie)
+    #[clap(long)]
+    charlie: Option<usize>,
+
+    /// The number of hands to play each round by default (1-4)
+    #[clap(long, default_value_t = 1)]
+    hands: usize,
+
+    /// The blackjack variant to play: standard, free-bet, spanish21 or pontoon
+    #[clap(long, default_value_t = Variant::Standard)]
+    variant: Variant,
+
+    /// The bonus 21s paid at the table (five-card, 6-7-8, 7-7-7), defaults to the variant's bonuses
+    #[clap(long = "bonus-21", use_value_delimiter = true)]
+    bonuses: Vec<Bonus21>,
+
+    /// Offer the 21+3 side bet on the first hand and the dealer upcard each round
+    #[clap(long)]
+    twenty_one_plus_three: bool,
+
+    /// Offer the Bust It side bet on the dealer busting each round
+    #[clap(long)]
+    bust_it: bool,
+
+    /// The smallest bet allowed on a hand
+    #[clap(long, default_value_t = Money::from_units(1))]
+    table_min: Money,
+
+    /// The largest bet allowed on a hand
+    #[clap(long)]
+    table_max: Option<Money>,
+
+    /// All bets must be a multiple of this chip increment
+    #[clap(long, default_value_t = Money::from_units(1))]
+    bet_unit: Money,
+
+    /// Use a continuous shuffling machine, the discards are shuffled back into the shoe after every round
+    #[clap(long)]
+    csm: bool,
+
+    /// How the shoe is shuffled: uniform (a perfectly random order) or riffle (an imperfect hand shuffle)
+    #[clap(long, default_value_t = ShuffleMethod::Uniform)]
+    shuffle: ShuffleMethod,
+
+    /// The card counting system behind the counts shown: hi-lo, ko, hi-opt-1, hi-opt-2, omega-2, zen
+    /// or custom:<file> for a count with its tags in a TOML file
+    #[clap(long, alias = "count-system", default_value_t = CountingMethod::HiLo)]
+    count: CountingMethod,
+
+    /// Side count the aces for the counts that leave them out (hi-opt-1, hi-opt-2 and omega-2),
+    /// adjusting the true count bets are sized on for the aces left in the shoe
+    #[clap(long)]
+    ace_side_count: bool,
+
+    /// How closely the decks left are estimated to work out the true count: exact, quarter-decks,
+    /// half-decks or decks (whole decks, rounded down)
+    #[clap(long, default_value_t = DeckEstimation::HalfDecks)]
+    deck_estimation: DeckEstimation,
+
+    /// Size the main bets automatically with a betting system, whoever plays the hands: flat,
+    /// ramp (the --bet-ramp, or the counter bot's), kelly or a fraction of it like kelly:0.5 (on
+    /// the --edge-model of the --advisor if it is on), martingale, paroli or 1-3-2-6
+    #[clap(long)]
+    bet_system: Option<BetSystem>,
+
+    /// The base bet of the betting system, defaults to the table minimum
+    #[clap(long)]
+    base_bet: Option<Money>,
+
+    /// A bet spread on the true count in base bets, e.g. 2:4,3:8 bets 4 units from TC 2 and 8 from TC 3
+    #[clap(long, allow_hyphen_values = true)]
+    bet_ramp: Option<BetRamp>,
+
+    /// Place the bets from --bet-ramp automatically instead of only suggesting them
+    #[clap(long, requires = "bet-ramp", conflicts_with = "bet-system")]
+    auto_ramp: bool,
+
+    /// Offer to tip the dealer after a round with a winning hand
+    #[clap(long)]
+    tips: bool,
+
+    /// End the session once the net winnings reach this amount
+    #[clap(long)]
+    stop_win: Option<Money>,
+
+    /// End the session once the net losses reach this amount
+    #[clap(long)]
+    stop_loss: Option<Money>,
+
+    /// Suggest a Kelly criterion bet from the true count before each round
+    #[clap(long)]
+    advisor: bool,
+
+    /// The player edge assumed by the advisor, off the top and per true count (e.g. -0.5%,0.5%)
+    #[clap(long, default_value_t = EdgeModel::DEFAULT, allow_hyphen_values = true)]
+    edge_model: EdgeModel,
+
+    /// Practice mode, undo takes back the last hit, double or split of the round at the next decision
+    #[clap(long)]
+    practice: bool,
+
+    /// Show the basic strategy move (or the move of --chart) next to every prompt, and the Hi-Lo
+    /// index play when the count says otherwise, without having to follow them
+    #[clap(long)]
+    hints: bool,
+
+    /// Compare every move to basic strategy and report the accuracy, the expected cost of the
+    /// mistakes and the most common ones at the end of every shoe
+    #[clap(long)]
+    track_mistakes: bool,
+
+    /// The amount of money the player starts with
+    #[clap(short, long, default_value_t = Money::from_units(1000))]
+    buy_in_amount: Money,
+
+    /// The names of the players taking turns at the table, e.g. Ann,Bob, up to 7 each buying in
+    /// for --buy-in-amount. The dealer plays once a round against all of them
+    #[clap(long, use_value_delimiter = true)]
+    players: Vec<String>,
+
+    /// Where each player sits, in the order of --players: first-base, third-base or a spot from
+    /// 1 at first base to 7 at third base. The cards are dealt and played from first base on
+    #[clap(long, use_value_delimiter = true)]
+    seats: Vec<SeatPosition>,
+
+    /// Who plays: human, or a bot to watch, e.g. bot:basic (also bot:never-bust, bot:mimic-dealer,
+    /// the card counter bot:hi-lo, bot:chart playing --chart or bot:perfect playing the cards
+    /// left), which the simulations then play by default
+    #[clap(long, default_value_t = PlayerKind::Human)]
+    player: PlayerKind,
+
+    /// A strategy chart for the chart bot to play, a .csv or .toml file with a row for each hand
+    /// and the moves against the upcards from 2 to A (H, S, D, Ds, P, Rh, Rs or Rp)
+    #[clap(long)]
+    chart: Option<PathBuf>,
+
+    /// Answers the prompts of the game from this file instead of stdin, a line per answer (blank
+    /// lines answer with enter, lines starting with # are skipped), leaving the table when it runs out
+    #[clap(long)]
+    script: Option<PathBuf>,
+
+    /// Seeds the shuffles of the game, so the same cards are dealt every time, e.g. to replay a
+    /// --script
+    #[clap(long)]
+    shuffle_seed: Option<u64>,
 }
+/// Tools that play the rules without a person at the table, instead of a game in the terminal,
+/// and drills for the player to practice on.
+#[derive(Subcommand)]
+enum Command {
+    /// Estimates the house edge of the rules by simulating basic strategy
+    HouseEdge {
+        /// The number of rounds to simulate
+        #[clap(long, default_value_t = 1_000_000)]
+        rounds: u64,
 
-fn play_shoe(shoe: Shoe, conf: &GameConfig) {
-    let mut shoe = shoe;
-    loop {
-        println!("============ ROUND BEGIN ============");
-        shoe = play_round(shoe, conf);
-        thread::sleep(conf.sleep_duration);
-        println!("============ ROUND END   ============ \n");
-
-        println!(
-            "Counts (running/true) {}/{:.1}\n",
-            shoe.running_count, shoe.true_count
-        );
-
-        if shoe.num_cards() < conf.reshuffle_limit {
-            println!("Shoe over");
-            return;
-        }
-    }
+        /// Saves the simulation to this checkpoint file every so often, and carries on from it
+        /// if it is already there, to resume a long simulation that was interrupted. The rounds
+        /// are then dealt in parts seeded one after another instead of saving the random state,
+        /// so the result differs from that of a run without --resume with the same --seed
+        #[clap(long)]
+        resume: Option<PathBuf>,
+
+        /// Keeps simulating, instead of a number of rounds, until the 95% confidence interval of
+        /// the house edge is this narrow either side, e.g. 0.001 for ±0.1%
+        #[clap(long, conflicts_with_all = &["rounds", "resume", "trace"])]
+        target_precision: Option<f64>,
+
+        /// Writes every round to this file as a line of JSON, with the counts, the bet, the
+        /// cards, the decisions and the result
+        #[clap(long, conflicts_with = "resume")]
+        trace: Option<PathBuf>,
+
+        /// Plays the rounds on the fast engine, many times faster but only flat betting the
+        /// standard game and without the true counts
+        #[clap(long, conflicts_with_all = &["resume", "trace", "target-precision"])]
+        fast: bool,
+
+        /// Also work out the certainty equivalent of the game with this bankroll
+        #[clap(long)]
+        bankroll: Option<Money>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Estimates the risk of losing the bankroll before reaching a target with basic strategy and
+    /// the bet ramp (--bet-ramp) or flat table minimum bets
+    RiskOfRuin {
+        /// The bankroll each session starts with
+        #[clap(long, default_value_t = Money::from_units(1000))]
+        bankroll: Money,
+
+        /// The bankroll a session is won at, defaults to doubling the bankroll
+        #[clap(long)]
+        target: Option<Money>,
+
+        /// The number of sessions to simulate
+        #[clap(long, default_value_t = 1000)]
+        sessions: u64,
+
+        /// The most rounds played in a session before giving up on it
+        #[clap(long, default_value_t = 1_000_000)]
+        max_rounds: u64,
+
+        /// Also estimate the risk with the analytic formula from the simulated mean and variance
+        #[clap(long)]
+        analytic: bool,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Compares playing strategies side by side over the same shoes
+    Simulate {
+        /// A TOML file describing the simulation, its settings take precedence over the command
+        /// line
+        #[clap(long)]
+        config: Option<PathBuf>,
+
+        /// The strategies to compare: basic, never-bust, mimic-dealer, hi-lo, chart (the --chart) or perfect,
+        /// defaults to the bot of --player or basic
+        #[clap(long, use_value_delimiter = true)]
+        strategies: Vec<Strategy>,
+
+        /// The number of rounds to simulate for each strategy
+        #[clap(long, default_value_t = 1_000_000)]
+        rounds: u64,
+
+        /// Also work out the certainty equivalent of each strategy with this bankroll
+        #[clap(long)]
+        bankroll: Option<Money>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Estimates the house edge of basic strategy under every combination of the swept rules
+    /// and prints them side by side, to show what each rule is worth
+    Sweep {
+        /// The rules switched between their usual values: h17 (S17 or H17), das (allowed or
+        /// not), payout (3:2 or 6:5) and decks (the deck counts of --decks)
+        #[clap(
+            long,
+            use_value_delimiter = true,
+            default_value = "h17,das,payout,decks"
+        )]
+        rules: Vec<SweepRule>,
+
+        /// The deck counts swept
+        #[clap(long, use_value_delimiter = true, default_value = "1,2,4,6,8")]
+        decks: Vec<u32>,
+
+        /// The number of rounds to simulate for each combination of the rules
+        #[clap(long, default_value_t = 200_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Estimates the house edge of basic strategy with the cut card at each penetration, and how
+    /// often the count is high, betting the bet ramp (--bet-ramp) if given
+    Penetration {
+        /// The shares of the shoe dealt before the shuffle
+        #[clap(
+            long,
+            use_value_delimiter = true,
+            default_value = "0.5,0.6,0.7,0.75,0.8,0.85,0.9"
+        )]
+        penetrations: Vec<f32>,
+
+        /// The number of rounds to simulate at each penetration
+        #[clap(long, default_value_t = 500_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Simulates a team of spotters flat betting at tables of their own, who call in a big
+    /// player when the count at their table is high, betting the bet ramp (--bet-ramp) if given
+    Team {
+        /// The number of spotters, each at a table of their own
+        #[clap(long, default_value_t = 4)]
+        spotters: usize,
+
+        /// The flat bet of the spotters, defaults to the table minimum
+        #[clap(long)]
+        spotter_bet: Option<Money>,
+
+        /// The true count the big player is called in at
+        #[clap(long, default_value_t = 2.0, allow_hyphen_values = true)]
+        entry: f32,
+
+        /// The true count the big player leaves the table below, they leave at the shuffle too
+        #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        exit: f32,
+
+        /// The big player's bet, the base bet of the bet ramp if there is one
+        #[clap(long, default_value_t = Money::from_units(100))]
+        big_bet: Money,
+
+        /// The bankroll the team starts with
+        #[clap(long, default_value_t = Money::from_units(10_000))]
+        bankroll: Money,
+
+        /// The number of rounds to simulate at each table
+        #[clap(long, default_value_t = 100_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Simulates back counting (wonging): watching a table without a bet and only sitting in while
+    /// the true count is high, betting the bet ramp (--bet-ramp) if given
+    Wong {
+        /// The true count the player sits in at
+        #[clap(long, default_value_t = 1.0, allow_hyphen_values = true)]
+        entry: f32,
+
+        /// The true count the player leaves the table below, they leave at the shuffle too
+        #[clap(long, default_value_t = 0.0, allow_hyphen_values = true)]
+        exit: f32,
+
+        /// The player's bet, the base bet of the bet ramp if there is one, defaults to the base bet
+        #[clap(long)]
+        bet: Option<Money>,
+
+        /// The strategy the hands are played with, defaults to the bot of --player or basic
+        #[clap(long)]
+        strategy: Option<Strategy>,
+
+        /// The number of rounds to watch
+        #[clap(long, default_value_t = 100_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Pits the bots against each other over the same shoes for many sessions and ranks them by
+    /// their final bankroll, to check a change to a strategy didn't make it worse
+    Tournament {
+        /// The bots playing: basic, never-bust, mimic-dealer, hi-lo, chart (the --chart) or perfect
+        #[clap(
+            long,
+            use_value_delimiter = true,
+            default_value = "basic,never-bust,mimic-dealer,hi-lo"
+        )]
+        bots: Vec<Strategy>,
+
+        /// The number of sessions every bot plays
+        #[clap(long, default_value_t = 100)]
+        sessions: u64,
+
+        /// The number of rounds of a session
+        #[clap(long, default_value_t = 1_000)]
+        rounds: u64,
+
+        /// The bankroll every bot starts a session with
+        #[clap(long, default_value_t = Money::from_units(1000))]
+        bankroll: Money,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Experimental: learns a strategy by playing against the engine with Q-learning, prints it
+    /// as a strategy chart and compares it with basic strategy
+    Train {
+        /// The number of rounds to learn over
+        #[clap(long, default_value_t = 2_000_000)]
+        rounds: u64,
+
+        /// The share of the moves made at random to explore, from 0 to 1
+        #[clap(long, default_value_t = 0.1)]
+        epsilon: f64,
+
+        /// The number of rounds the learned strategy and basic strategy are compared over
+        #[clap(long, default_value_t = 1_000_000)]
+        eval_rounds: u64,
+
+        /// Saves the learned strategy as a chart to a .csv or .toml file, to play with --chart
+        #[clap(long)]
+        export: Option<PathBuf>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Evolves a strategy chart for the rules with a genetic algorithm, from the --chart or from
+    /// basic strategy, rating the charts by simulation, for rules no published chart covers
+    Optimize {
+        /// The charts of every generation
+        #[clap(long, default_value_t = 16)]
+        population: usize,
+
+        /// The number of generations to evolve
+        #[clap(long, default_value_t = 20)]
+        generations: u64,
+
+        /// The number of rounds every chart is played for to rate it
+        #[clap(long, default_value_t = 50_000)]
+        rounds: u64,
+
+        /// The share of the cells of a new chart changed to a random move
+        #[clap(long, default_value_t = 0.02)]
+        mutation_rate: f64,
+
+        /// The best charts carried over to the next generation unchanged
+        #[clap(long, default_value_t = 2)]
+        elite: usize,
+
+        /// The number of rounds the starting and the evolved chart are compared over
+        #[clap(long, default_value_t = 1_000_000)]
+        eval_rounds: u64,
+
+        /// Saves the evolved chart to a .csv or .toml file, to play with --chart
+        #[clap(long)]
+        export: Option<PathBuf>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Works out the index numbers of the Illustrious 18, the Fab 4 and insurance for the
+    /// counting system (--count) and the rules, and checks them by simulation
+    Indices {
+        /// The number of rounds the counter is simulated for with each set of indices
+        #[clap(long, default_value_t = 1_000_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Compares the counting systems by simulating the counter bot (bot:hi-lo) with each of them
+    /// over the same shoes, with their betting correlations
+    CountSystems {
+        /// The counting systems to compare
+        #[clap(
+            long,
+            use_value_delimiter = true,
+            default_value = "hi-lo,ko,hi-opt-1,hi-opt-2,omega-2,zen"
+        )]
+        systems: Vec<CountingMethod>,
+
+        /// The number of rounds to simulate with each system
+        #[clap(long, default_value_t = 500_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Shows when insurance is worth taking, by the true count and the share of tens left, from
+    /// a simulation of basic strategy
+    Insurance {
+        /// The number of rounds to simulate
+        #[clap(long, default_value_t = 1_000_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Works out the best play of every hand against every upcard for the rules and prints the
+    /// strategy chart
+    GenerateStrategy {
+        /// Also print the expected result of the best play, in initial bets
+        #[clap(long)]
+        ev: bool,
+
+        /// How the chart is printed: text or json
+        #[clap(long, default_value_t = SummaryFormat::Text)]
+        format: SummaryFormat,
+    },
+    /// Prints the strategy chart of the --chart, or basic strategy for the rules, as a table for
+    /// printing or sharing
+    ExportChart {
+        /// How the chart is laid out: ascii, markdown or html (a page with the cells colored)
+        #[clap(long, default_value_t = ChartFormat::Ascii)]
+        format: ChartFormat,
+
+        /// Writes the chart to this file instead of printing it
+        #[clap(long)]
+        output: Option<PathBuf>,
+    },
+    /// Simulates a strategy and prints how every starting hand did against every upcard, the
+    /// wins, pushes, losses, busts, doubles and splits behind the strategy chart
+    Outcomes {
+        /// The number of rounds to simulate
+        #[clap(long, default_value_t = 1_000_000)]
+        rounds: u64,
+
+        /// The strategy to play: basic, never-bust, mimic-dealer, hi-lo, chart (the --chart) or perfect,
+        /// defaults to the bot of --player or basic
+        #[clap(long)]
+        strategy: Option<Strategy>,
+
+        /// Only print this starting hand, as a hard total or two cards, e.g. 16, A,7 or 8,8, with
+        /// all its outcomes against each upcard
+        #[clap(long)]
+        hand: Option<StartingHand>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Prints the chance of the dealer reaching each total, a blackjack or a bust behind every
+    /// upcard, exact for an infinite shoe or simulated
+    DealerTable {
+        /// Deal this many dealer hands from shuffled shoes instead of working the table out
+        /// exactly
+        #[clap(long)]
+        simulate: Option<u64>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Works out the exact expected result of every move on a hand against the upcard, from the
+    /// cards left in the shoe
+    Solve {
+        /// The ranks of the player's cards, e.g. 10,6 or A,7
+        #[clap(long, use_value_delimiter = true, required = true)]
+        hand: Vec<Value>,
+
+        /// The rank of the dealer's upcard
+        #[clap(long)]
+        upcard: Value,
+
+        /// The ranks of the other cards seen since the shuffle, which are no longer in the shoe
+        #[clap(long, use_value_delimiter = true)]
+        seen: Vec<Value>,
+
+        /// How the result is printed: text or json
+        #[clap(long, default_value_t = SummaryFormat::Text)]
+        format: SummaryFormat,
+    },
+    /// Searches for the bet ramp on the true count that does best for the rules, from a
+    /// simulation of flat bets with basic strategy
+    OptimizeSpread {
+        /// The largest bet of the ramp in base bets (--base-bet), the table maximum may lower it
+        #[clap(long, default_value_t = 8)]
+        max_spread: u64,
+
+        /// The bankroll the bets are sized for
+        #[clap(long, default_value_t = Money::from_units(10_000))]
+        bankroll: Money,
+
+        /// What the ramps are judged by: score (the win rate relative to the variance) or growth
+        /// (of the bankroll with the base bet)
+        #[clap(long, default_value_t = SpreadObjective::Score)]
+        objective: SpreadObjective,
+
+        /// Pass over ramps with a higher risk of losing the bankroll, e.g. 0.05 for 5%
+        #[clap(long)]
+        max_risk: Option<f64>,
+
+        /// The number of rounds to simulate
+        #[clap(long, default_value_t = 1_000_000)]
+        rounds: u64,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    /// Practice drills for the player at the terminal
+    Drill {
+        #[clap(subcommand)]
+        drill: DrillCommand,
+    },
+    /// Hosts a table the players join over the network, every player sitting down with the
+    /// --buy-in-amount. The clients send and receive a JSON object per line
+    Serve {
+        /// The TCP port to listen on
+        #[clap(long, default_value_t = 7777)]
+        port: u16,
+    },
 }
 
-fn play_round(shoe: Shoe, conf: &GameConfig) -> Shoe {
-    let mut shoe = shoe.clone();
+/// The drills to practice counting and playing on.
+#[derive(Subcommand)]
+enum DrillCommand {
+    /// Flashes cards one at a time and asks for the running count of the --count after the last
+    /// one, a round at a time
+    Count {
+        /// The cards shown every round
+        #[clap(long, default_value_t = 20)]
+        cards: u32,
 
-    let mut dealer_hand = Hand::from_card(shoe.take_card());
-    let mut player_hand = Hand::from_card(shoe.take_card());
-    let dealer_face_down = shoe.take_card();
-    player_hand.add_card(shoe.take_card());
+        /// How long every card is shown, in milliseconds
+        #[clap(long, default_value_t = 1000)]
+        pace: u64,
 
-    println!("Dealer: {}", dealer_hand);
-    thread::sleep(conf.sleep_duration);
+        /// The rounds of the session
+        #[clap(long, default_value_t = 5)]
+        rounds: u32,
 
-    let (player_hand, shoe) = player_turn(player_hand, shoe);
+        /// Keeps every session in this file, a line of JSON each, and compares the session with
+        /// the ones before it
+        #[clap(long)]
+        history: Option<PathBuf>,
 
-    dealer_hand.add_card(dealer_face_down);
-    println!("Dealer hand: {}", dealer_hand);
+        /// Seeds the shuffles, so the same cards are shown every time
+        #[clap(long)]
+        seed: Option<u64>,
+    },
+    /// Asks for the move of two card hands against an upcard and grades the answers by the
+    /// --chart, or basic strategy for the rules, asking the hands missed again at the end
+    Strategy {
+        /// The hands asked
+        #[clap(long, default_value_t = 20)]
+        questions: u32,
 
-    // Check winnings
-    if player_hand.is_blackjack() {
-        if dealer_hand.is_blackjack() {
-            println!("Push! You get your money back");
-        } else {
-            println!("BlackJack wins 3:2");
-        }
-        return shoe;
-    }
+        /// Keeps every session in this file, a line of JSON each, to compare the sessions and
+        /// ask more of the hands missed before
+        #[clap(long)]
+        history: Option<PathBuf>,
 
-    let (dealer_hand, shoe) = dealer_turn(dealer_hand, shoe, conf);
+        /// Seeds the hands asked, so the same ones come up every time
+        #[clap(long)]
+        seed: Option<u64>,
+    },
+}
 
-    let player_value = player_hand.calc_value();
-    let dealer_value = dealer_hand.calc_value();
+/// How a simulation is seeded and summarized, shared by the subcommands.
+#[derive(Args)]
+struct ReportArgs {
+    /// Seeds the shuffles to make the result reproducible, a random seed is picked otherwise
+    #[clap(long)]
+    seed: Option<u64>,
 
-    thread::sleep(conf.sleep_duration);
+    /// How the summary is printed: text or json (a single document with the settings and results)
+    #[clap(long, default_value_t = SummaryFormat::Text)]
+    format: SummaryFormat,
 
-    if player_value > TWENTY_ONE {
-        println!("Player bust :(");
-        return shoe;
-    }
+    /// Also print how often the rounds were bet at each true count, with the EV at each count
+    /// (always in the json summary)
+    #[clap(long)]
+    true_counts: bool,
 
-    if dealer_value > TWENTY_ONE {
-        println!("Dealer bust! winnings 1:1");
-        return shoe;
-    }
+    /// Do not show the progress of the simulation
+    #[clap(short, long)]
+    quiet: bool,
+}
 
-    if player_value == dealer_value {
-        println!("Push! You get your money back");
-        return shoe;
+impl ReportArgs {
+    fn report(&self) -> Report {
+        Report {
+            seed: self.seed.unwrap_or_else(rand::random),
+            format: self.format,
+            true_counts: self.true_counts,
+            quiet: self.quiet,
+            bankroll: None,
+        }
     }
 
-    if dealer_value > player_value {
-        println!("Dealer wins, better luck next time!");
-        return shoe;
-    } else {
-        println!("Congratulations! winnings 1:1");
-        return shoe;
+    /// The report with the seed and output settings of a simulation file taking precedence.
+    fn with_file(&self, file: &SimulationFile) -> Result<Report, Error> {
+        let format = match &file.output.format {
+            Some(format) => format.parse().map_err(Error::Config)?,
+            None => self.format,
+        };
+        Ok(Report {
+            seed: file.seed.or(self.seed).unwrap_or_else(rand::random),
+            format,
+            true_counts: file.output.true_counts.unwrap_or(self.true_counts),
+            quiet: self.quiet,
+            bankroll: None,
+        })
     }
 }
 
-fn player_turn(hand: Hand, shoe: Shoe) -> (Hand, Shoe) {
-    let mut hand = hand;
-    let mut shoe = shoe;
-
-    loop {
-        println!("Hand: {}", hand);
+fn main() {
+    let args: BlackJack = BlackJack::parse();
 
-        if hand.calc_value() >= TWENTY_ONE {
-            if hand.is_blackjack() {
-                println!("BlackJack!");
-            }
+    let rules = Rules {
+        deck_count: args.deck_count,
+        cut_card: args.cut_card,
+        penetration: args.penetration,
+        csm: args.csm,
+        hit_soft_17: args.hit_soft_17,
+        dealer_peek: !args.no_peek,
+        original_bets_only: args.original_bets_only,
+        blackjack_payout: args.blackjack_payout,
+        max_splits: args.max_splits,
+        resplit_aces: args.resplit_aces,
+        hit_split_aces: args.hit_split_aces,
+        double_on: args.double_on,
+        double_after_split: !args.no_double_after_split,
+        surrender: !args.no_surrender,
+        charlie: args.charlie,
+        variant: args.variant,
+        bonuses: if args.bonuses.is_empty() {
+            args.variant.default_bonuses()
+        } else {
+            args.bonuses
+        },
+    };
 
-            // Player is bust or at exactly 21!
-            return (hand, shoe);
+    let chart = match args.chart.as_deref().map(CustomChart::load).transpose() {
+        Ok(chart) => chart,
+        Err(e) => {
+            eprintln!("The game could not be set up: {}", e);
+            std::process::exit(1);
         }
+    };
 
-        let mut choice = String::new();
-        loop {
-            choice = String::new();
-            println!("Move? [h/s]");
-            stdin()
-                .read_line(&mut choice)
-                .ok()
-                .expect("Failed to read choice");
-
-            choice = String::from(
-                choice
-                    .strip_suffix('\n')
-                    .expect("Choice did not end with newline?"),
-            );
+    let conf = GameConfig {
+        rules,
+        hands: args.hands,
+        twenty_one_plus_three: args.twenty_one_plus_three,
+        bust_it: args.bust_it,
+        table_min: args.table_min,
+        table_max: args.table_max,
+        bet_unit: args.bet_unit,
+        shuffle: args.shuffle,
+        counting: args.count,
+        ace_side_count: args.ace_side_count,
+        deck_estimation: args.deck_estimation,
+        bet_ramp: args.bet_ramp,
+        auto_ramp: args.auto_ramp,
+        chart,
+        tips: args.tips,
+        stop_win: args.stop_win,
+        stop_loss: args.stop_loss,
+        advisor: args.advisor.then_some(args.edge_model),
+        base_bet: args.base_bet.unwrap_or(args.table_min).max(args.table_min),
+        bet_system: args.bet_system,
+        sleep_duration: Duration::from_millis(args.delay as u64),
+        practice: args.practice,
+        hints: args.hints,
+        track_mistakes: args.track_mistakes,
+    };
+
+    if let Err(e) = conf.validate() {
+        eprintln!("The game could not be set up: {}", e);
+        std::process::exit(1);
+    }
+
+    let command = match args.command {
+        Some(Command::Drill { drill }) => {
+            let result = cli::console(&conf, args.output, args.script.as_deref())
+                .and_then(|out| drill_session(&out, &conf, drill));
+            if let Err(e) = result {
+                eprintln!("The drill had to stop: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Serve { port }) => {
+            println!("Hosting a table on port {}", port);
+            if let Err(e) = server::serve(conf, args.buy_in_amount, port) {
+                eprintln!("The server had to stop: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        command => command,
+    };
 
-            match choice.as_str() {
-                "h" => {
-                    let card = shoe.take_card();
-                    hand.add_card(card);
-                    break;
-                }
-                "s" => return (hand, shoe),
-                c => println!("Invalid choice '{}', please try again", c),
+    if let Some(command) = command {
+        let bot = args.player.bot().unwrap_or(Strategy::Basic);
+        let result = match command {
+            Command::HouseEdge {
+                rounds,
+                resume,
+                target_precision,
+                trace,
+                fast,
+                bankroll,
+                report,
+            } => analysis::house_edge(
+                conf,
+                rounds,
+                resume.as_deref(),
+                trace.as_deref(),
+                target_precision,
+                fast,
+                &Report {
+                    bankroll,
+                    ..report.report()
+                },
+            ),
+            Command::RiskOfRuin {
+                bankroll,
+                target,
+                sessions,
+                max_rounds,
+                analytic,
+                report,
+            } => analysis::ruin(
+                conf,
+                bankroll,
+                target.unwrap_or(bankroll * 2),
+                sessions,
+                max_rounds,
+                analytic,
+                &Report {
+                    bankroll: Some(bankroll),
+                    ..report.report()
+                },
+            ),
+            Command::Simulate {
+                config: Some(path),
+                strategies,
+                rounds,
+                bankroll,
+                report,
+            } => SimulationFile::load(&path).and_then(|file| {
+                let mut conf = conf;
+                file.apply(&mut conf)?;
+                let report = Report {
+                    bankroll,
+                    ..report.with_file(&file)?
+                };
+                let strategies = match file.strategies {
+                    Some(strategies) => strategies,
+                    None if strategies.is_empty() => vec![bot],
+                    None => strategies,
+                };
+                analysis::compare(conf, &strategies, file.rounds.unwrap_or(rounds), &report)
+            }),
+            Command::Simulate {
+                config: None,
+                strategies,
+                rounds,
+                bankroll,
+                report,
+            } => {
+                let strategies = if strategies.is_empty() {
+                    vec![bot]
+                } else {
+                    strategies
+                };
+                let report = Report {
+                    bankroll,
+                    ..report.report()
+                };
+                analysis::compare(conf, &strategies, rounds, &report)
+            }
+            Command::Sweep {
+                rules,
+                decks,
+                rounds,
+                report,
+            } => analysis::rule_sweep(conf, &rules, &decks, rounds, &report.report()),
+            Command::Penetration {
+                penetrations,
+                rounds,
+                report,
+            } => analysis::penetration(conf, &penetrations, rounds, &report.report()),
+            Command::Team {
+                spotters,
+                spotter_bet,
+                entry,
+                exit,
+                big_bet,
+                bankroll,
+                rounds,
+                report,
+            } => {
+                let team = Team {
+                    spotters,
+                    spotter_bet: spotter_bet.unwrap_or(conf.table_min),
+                    entry,
+                    exit,
+                    big_bet,
+                    ramp: conf.bet_ramp.clone(),
+                    bankroll,
+                };
+                analysis::team_play(conf, &team, rounds, &report.report())
+            }
+            Command::Wong {
+                entry,
+                exit,
+                bet,
+                strategy,
+                rounds,
+                report,
+            } => {
+                let wonging = Wonging {
+                    entry,
+                    exit,
+                    bet: bet.unwrap_or(conf.base_bet),
+                    ramp: conf.bet_ramp.clone(),
+                };
+                analysis::wonging(
+                    conf,
+                    &wonging,
+                    strategy.unwrap_or(bot),
+                    rounds,
+                    &report.report(),
+                )
+            }
+            Command::Tournament {
+                bots,
+                sessions,
+                rounds,
+                bankroll,
+                report,
+            } => analysis::tournament(conf, &bots, sessions, rounds, bankroll, &report.report()),
+            Command::Train {
+                rounds,
+                epsilon,
+                eval_rounds,
+                export,
+                report,
+            } => analysis::train(
+                conf,
+                rounds,
+                epsilon,
+                eval_rounds,
+                export.as_deref(),
+                &report.report(),
+            ),
+            Command::Optimize {
+                population,
+                generations,
+                rounds,
+                mutation_rate,
+                elite,
+                eval_rounds,
+                export,
+                report,
+            } => {
+                let evolution = Evolution {
+                    population,
+                    generations,
+                    rounds,
+                    mutation_rate,
+                    elite,
+                };
+                analysis::optimize(
+                    conf,
+                    &evolution,
+                    eval_rounds,
+                    export.as_deref(),
+                    &report.report(),
+                )
+            }
+            Command::Indices { rounds, report } => {
+                analysis::indices(conf, rounds, &report.report())
+            }
+            Command::CountSystems {
+                systems,
+                rounds,
+                report,
+            } => analysis::count_systems(conf, &systems, rounds, &report.report()),
+            Command::Insurance { rounds, report } => {
+                analysis::insurance(conf, rounds, &report.report())
+            }
+            Command::GenerateStrategy { ev, format } => {
+                analysis::strategy_chart(&conf.rules, ev, format)
+            }
+            Command::Drill { .. } | Command::Serve { .. } => {
+                unreachable!("the drills and the server are run before the simulations")
+            }
+            Command::ExportChart { format, output } => {
+                analysis::export_chart(&conf, format, output.as_deref())
             }
+            Command::Outcomes {
+                rounds,
+                strategy,
+                hand,
+                report,
+            } => analysis::hand_outcomes(
+                conf,
+                strategy.unwrap_or(bot),
+                rounds,
+                hand,
+                &report.report(),
+            ),
+            Command::DealerTable { simulate, report } => {
+                analysis::dealer_outcomes(&conf, simulate, &report.report())
+            }
+            Command::Solve {
+                hand,
+                upcard,
+                seen,
+                format,
+            } => analysis::solve_hand(&conf.rules, &hand, &upcard, &seen, format),
+            Command::OptimizeSpread {
+                max_spread,
+                bankroll,
+                objective,
+                max_risk,
+                rounds,
+                report,
+            } => {
+                let limits = SpreadLimits {
+                    max_spread,
+                    base_bet: conf.base_bet,
+                    bankroll,
+                    max_risk,
+                };
+                let report = Report {
+                    bankroll: Some(bankroll),
+                    ..report.report()
+                };
+                analysis::optimize_spread(conf, rounds, objective, limits, &report)
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("The simulation had to stop: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-            println!("Hand: {}", hand);
+    let players: Vec<Player> = match args.players.as_slice() {
+        [] => vec![Player::new(args.buy_in_amount)],
+        names => names
+            .iter()
+            .map(|name| Player::new(args.buy_in_amount).with_name(name))
+            .collect(),
+    };
+    let seats = match args.seats.as_slice() {
+        [] => SeatPosition::all().take(players.len()).collect(),
+        seats if seats.len() == players.len() => seats.to_vec(),
+        seats => {
+            eprintln!(
+                "The game could not be set up: {} seats for {} players",
+                seats.len(),
+                players.len()
+            );
+            std::process::exit(1);
         }
+    };
+    if let Err(e) = cli::run(
+        conf,
+        seats.into_iter().zip(players).collect(),
+        args.player,
+        args.output,
+        args.script.as_deref(),
+        args.shuffle_seed,
+    ) {
+        eprintln!("The game had to stop: {}", e);
+        std::process::exit(1);
     }
 }
 
-fn dealer_turn(hand: Hand, shoe: Shoe, conf: &GameConfig) -> (Hand, Shoe) {
-    let mut hand = hand;
-    let mut shoe = shoe;
-
-    loop {
-        if hand.calc_value() >= 17 {
-            return (hand, shoe);
+/// Runs the drill at the terminal.
+fn drill_session(out: &Console, conf: &GameConfig, drill: DrillCommand) -> Result<(), Error> {
+    match drill {
+        DrillCommand::Count {
+            cards,
+            pace,
+            rounds,
+            history,
+            seed,
+        } => {
+            let drill = CountDrill {
+                cards,
+                pace: Duration::from_millis(pace),
+                rounds,
+            };
+            cli::count_drill(out, conf, &drill, history.as_deref(), seed)
+        }
+        DrillCommand::Strategy {
+            questions,
+            history,
+            seed,
+        } => {
+            let drill = StrategyDrill { questions };
+            cli::strategy_drill(out, conf, &drill, history.as_deref(), seed)
         }
+    }
+}
 
-        thread::sleep(conf.sleep_duration);
+#[cfg(test)]
+mod tests {
+    use crate::BlackJack;
+    use clap::CommandFactory;
 
-        hand.add_card(shoe.take_card());
-        println!("Dealer hit {}", hand);
+    #[test]
+    fn test_arguments() {
+        BlackJack::command().debug_assert();
     }
 }
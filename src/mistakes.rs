@@ -0,0 +1,172 @@
+use crate::engine::GameEngine;
+use crate::input::BasicStrategy;
+use crate::rules::HandOptions;
+use crate::solver::{solve, Composition};
+use crate::types::{Move, Value};
+use std::collections::HashMap;
+
+/// A kind of mistake: the hand, the upcard, the move made and the basic strategy move.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MistakeKind {
+    /// The hand as it was played, e.g. "hard 16", "soft 18" or "8,8".
+    pub hand: String,
+    pub upcard: String,
+    pub played: Move,
+    pub advised: Move,
+}
+
+/// How often a kind of mistake was made and what it cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MistakeCount {
+    pub times: u64,
+    /// The expected cost in initial bets of the hands played.
+    pub cost: f64,
+}
+
+/// Compares every move the player makes to basic strategy, with the cost of each mistake worked
+/// out from the expected results of the moves off a full shoe.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MistakeTracker {
+    pub decisions: u64,
+    pub mistakes: u64,
+    /// The expected cost of all mistakes in initial bets, leaving out those that can't be worked
+    /// out, in the variants and with a Charlie.
+    pub cost: f64,
+    pub kinds: HashMap<MistakeKind, MistakeCount>,
+}
+
+impl MistakeTracker {
+    /// Records the move `played` on the hand at `hand`, returning the basic strategy move if it
+    /// was a mistake.
+    pub fn record(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+        played: Move,
+    ) -> Option<Move> {
+        self.decisions += 1;
+        let advised = BasicStrategy::decide(engine, hand, options);
+        if played == advised {
+            return None;
+        }
+        self.mistakes += 1;
+        let cost = Self::cost(engine, hand, played, advised).unwrap_or(0.0);
+        self.cost += cost;
+        let cards = &engine.player.hands[hand].hand;
+        let label = if cards.can_split() {
+            match &cards.cards[0].value {
+                Value::Ace => "A,A".to_string(),
+                value => format!("{},{}", value.value(), value.value()),
+            }
+        } else if cards.is_soft() {
+            format!("soft {}", cards.calc_value())
+        } else {
+            format!("hard {}", cards.calc_value())
+        };
+        let upcard = match engine.dealer_hand.cards.first().map(|card| &card.value) {
+            Some(Value::Ace) => "A".to_string(),
+            Some(value) => value.value().to_string(),
+            None => "?".to_string(),
+        };
+        let kind = MistakeKind {
+            hand: label,
+            upcard,
+            played,
+            advised,
+        };
+        let count = self.kinds.entry(kind).or_default();
+        count.times += 1;
+        count.cost += cost;
+        Some(advised)
+    }
+
+    /// How much worse `played` is than `advised` in initial bets, off a full shoe without the
+    /// hand and the upcard.
+    fn cost(engine: &GameEngine, hand: usize, played: Move, advised: Move) -> Option<f64> {
+        let rules = &engine.conf.rules;
+        let cards = &engine.player.hands[hand].hand;
+        let upcard = &engine.dealer_hand.cards.first()?.value;
+        let mut left = Composition::full(rules.deck_count, rules.variant).ok()?;
+        for card in &cards.cards {
+            left.remove(&card.value).ok()?;
+        }
+        left.remove(upcard).ok()?;
+        let evs = solve(rules, &left, cards, upcard).ok()?;
+        Some((evs.of(advised)? - evs.of(played)?).max(0.0))
+    }
+
+    /// The share of the moves that were right, all of them before any were made.
+    pub fn accuracy(&self) -> f64 {
+        if self.decisions == 0 {
+            return 1.0;
+        }
+        1.0 - self.mistakes as f64 / self.decisions as f64
+    }
+
+    /// The `n` mistakes made most often, the costlier first between those made as often.
+    pub fn most_common(&self, n: usize) -> Vec<(&MistakeKind, &MistakeCount)> {
+        let mut kinds = self.kinds.iter().collect::<Vec<_>>();
+        kinds.sort_by(|(_, a), (_, b)| b.times.cmp(&a.times).then(b.cost.total_cmp(&a.cost)));
+        kinds.truncate(n);
+        kinds
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::engine::tests::dealt;
+    use crate::mistakes::MistakeTracker;
+    use crate::types::{Move, Value};
+
+    #[test]
+    fn test_mistake_tracker() {
+        let mut tracker = MistakeTracker::default();
+        assert_eq!(tracker.accuracy(), 1.0);
+        let mut play = |cards: &[Value], upcard, played| {
+            let engine = dealt(GameConfig::default(), cards, upcard);
+            let options = engine.conf.rules.hand_options(&engine.player, 0);
+            tracker.record(&engine, 0, &options, played)
+        };
+        assert_eq!(
+            play(&[Value::Six, Value::Five], Value::Six, Move::Double),
+            None
+        );
+        // Standing on 12 against a 2 is a small mistake, hitting 20 a large one
+        assert_eq!(
+            play(&[Value::Ten, Value::Two], Value::Two, Move::Stand),
+            Some(Move::Hit)
+        );
+        assert_eq!(
+            play(&[Value::Seven, Value::Five], Value::Two, Move::Stand),
+            Some(Move::Hit)
+        );
+        assert_eq!(
+            play(&[Value::Ten, Value::King], Value::Six, Move::Hit),
+            Some(Move::Stand)
+        );
+
+        assert_eq!(tracker.decisions, 4);
+        assert_eq!(tracker.mistakes, 3);
+        assert_eq!(tracker.accuracy(), 0.25);
+        let common = tracker.most_common(5);
+        assert_eq!(common.len(), 2);
+        let (kind, count) = common[0];
+        assert_eq!(kind.hand, "hard 12");
+        assert_eq!(kind.upcard, "2");
+        assert_eq!(count.times, 2);
+        assert!(count.cost > 0.0 && count.cost < 0.1);
+        let (kind, count) = common[1];
+        assert_eq!(kind.hand, "hard 20");
+        assert!(count.cost > 0.5);
+        assert!((tracker.cost - common.iter().map(|(_, c)| c.cost).sum::<f64>()).abs() < 1e-9);
+
+        tracker.reset();
+        assert_eq!(tracker.decisions, 0);
+    }
+}
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+const CENTS_PER_UNIT: i64 = 100;
+
+/// An amount of money stored as a whole number of cents, so bets and payouts add up exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub const fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    pub const fn from_units(units: i64) -> Self {
+        Money(units * CENTS_PER_UNIT)
+    }
+
+    /// The amount nearest to a fractional number of units, e.g. 12.5 for 12.50.
+    pub fn from_units_f64(units: f64) -> Self {
+        Money((units * CENTS_PER_UNIT as f64).round() as i64)
+    }
+
+    /// The whole units of the amount, any cents are dropped.
+    pub fn units(&self) -> i64 {
+        self.0 / CENTS_PER_UNIT
+    }
+
+    /// The amount in units, with the cents as a fraction.
+    pub fn units_f64(&self) -> f64 {
+        self.0 as f64 / CENTS_PER_UNIT as f64
+    }
+
+    /// Half the amount, rounded down to the cent.
+    pub fn half(&self) -> Self {
+        Money(self.0.div_euclid(2))
+    }
+
+    /// Whether the amount can be made up exactly of `unit`s, a zero unit allows any amount.
+    pub fn is_multiple_of(&self, unit: Money) -> bool {
+        unit.0 == 0 || self.0 % unit.0 == 0
+    }
+
+    /// Scales the amount by `numerator / denominator`, rounding down to the cent. Saturates like
+    /// a multiplication.
+    pub fn scale(&self, numerator: u32, denominator: u32) -> Self {
+        Money(
+            self.0
+                .saturating_mul(numerator as i64)
+                .div_euclid(denominator as i64),
+        )
+    }
+
+    /// A fraction of the amount, e.g. 0.02 for a 2% bet, rounded down to the cent.
+    pub fn fraction(&self, fraction: f64) -> Self {
+        Money((self.0 as f64 * fraction).floor() as i64)
+    }
+
+    /// The largest multiple of `unit` that doesn't exceed the amount.
+    pub fn round_down_to(&self, unit: Money) -> Self {
+        if unit.0 == 0 {
+            return *self;
+        }
+        Money(self.0.div_euclid(unit.0) * unit.0)
+    }
+}
+
+/// Serialized as a number of units, e.g. 12.5 for 12.50.
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.units_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Money::from_units_f64)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl Mul<u64> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: u64) -> Money {
+        Money(
+            self.0
+                .saturating_mul(i64::try_from(rhs).unwrap_or(i64::MAX)),
+        )
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, |acc, m| acc + m)
+    }
+}
+
+/// Whole amounts are shown without cents, e.g. "25" and "-7.50".
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.0 < 0 {
+            "-"
+        } else if f.sign_plus() {
+            "+"
+        } else {
+            ""
+        };
+        let cents = self.0.unsigned_abs();
+        let units = cents / CENTS_PER_UNIT as u64;
+        let rest = cents % CENTS_PER_UNIT as u64;
+        if rest == 0 {
+            write!(f, "{}{}", sign, units)
+        } else {
+            write!(f, "{}{}.{:02}", sign, units, rest)
+        }
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expected an amount such as '25' or '7.50', got '{}'", s);
+        let (units, cents) = match s.split_once('.') {
+            Some((units, cents)) if (1..=2).contains(&cents.len()) => (units, cents),
+            Some(_) => return Err(invalid()),
+            None => (s, "0"),
+        };
+        if !units.chars().all(|c| c.is_ascii_digit()) || !cents.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let units = units.parse::<i64>().map_err(|_| invalid())?;
+        let mut cents_value = cents.parse::<i64>().map_err(|_| invalid())?;
+        if cents.len() == 1 {
+            cents_value *= 10;
+        }
+        units
+            .checked_mul(CENTS_PER_UNIT)
+            .and_then(|cents| cents.checked_add(cents_value))
+            .map(Money)
+            .ok_or_else(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+
+    #[test]
+    fn test_money_parse_and_display() {
+        assert_eq!("25".parse::<Money>(), Ok(Money::from_units(25)));
+        assert_eq!("7.5".parse::<Money>(), Ok(Money::from_cents(750)));
+        assert_eq!("0.05".parse::<Money>(), Ok(Money::from_cents(5)));
+        assert!("-5".parse::<Money>().is_err());
+        assert!("1.234".parse::<Money>().is_err());
+        assert!("abc".parse::<Money>().is_err());
+        assert!("99999999999999999".parse::<Money>().is_err());
+
+        assert_eq!(Money::from_units(25).to_string(), "25");
+        assert_eq!(Money::from_cents(-750).to_string(), "-7.50");
+        assert_eq!(format!("{:+}", Money::from_cents(1505)), "+15.05");
+    }
+
+    #[test]
+    fn test_money_arithmetic() {
+        let bet = Money::from_units(5);
+        assert_eq!(bet.scale(3, 2), Money::from_cents(750));
+        assert_eq!(Money::from_cents(5).scale(3, 2), Money::from_cents(7));
+        assert_eq!(Money::from_cents(15).half(), Money::from_cents(7));
+        assert_eq!(
+            Money::from_cents(i64::MAX / 2).scale(3, 1),
+            Money::from_cents(i64::MAX)
+        );
+        assert_eq!(bet * 3 - bet, Money::from_units(10));
+        assert!(Money::from_units(25).is_multiple_of(Money::from_units(5)));
+        assert!(!Money::from_cents(2550).is_multiple_of(Money::from_units(5)));
+        assert_eq!(Money::from_units(200).fraction(0.025), Money::from_units(5));
+        assert_eq!(
+            Money::from_cents(2750).round_down_to(Money::from_units(5)),
+            Money::from_units(25)
+        );
+    }
+}
@@ -0,0 +1,270 @@
+use crate::chart::ChartMove;
+use crate::config::GameConfig;
+use crate::custom_chart::CustomChart;
+use crate::error::Error;
+use crate::input::Strategy;
+use crate::outcomes::StartingHand;
+use crate::simulation::{simulate, PartProgress, Progress};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// The moves a cell of a hand that can't be split is mutated to.
+const MOVES: [ChartMove; 6] = [
+    ChartMove::Hit,
+    ChartMove::Stand,
+    ChartMove::DoubleOrHit,
+    ChartMove::DoubleOrStand,
+    ChartMove::SurrenderOrHit,
+    ChartMove::SurrenderOrStand,
+];
+
+/// The moves a cell of a pair is mutated to.
+const PAIR_MOVES: [ChartMove; 8] = [
+    ChartMove::Hit,
+    ChartMove::Stand,
+    ChartMove::DoubleOrHit,
+    ChartMove::DoubleOrStand,
+    ChartMove::SurrenderOrHit,
+    ChartMove::SurrenderOrStand,
+    ChartMove::Split,
+    ChartMove::SurrenderOrSplit,
+];
+
+/// How strategy charts are evolved: a population of charts is played with the chart bot every
+/// generation, the best are kept and the rest replaced by children of the better charts, each
+/// cell taken from either parent by its row and changed at random now and then.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evolution {
+    /// The charts of every generation.
+    pub population: usize,
+    pub generations: u64,
+    /// The rounds every chart is played for to rate it.
+    pub rounds: u64,
+    /// The share of the cells of a child changed to a random move.
+    pub mutation_rate: f64,
+    /// The best charts carried over to the next generation unchanged.
+    pub elite: usize,
+}
+
+impl Default for Evolution {
+    fn default() -> Self {
+        Self {
+            population: 16,
+            generations: 20,
+            rounds: 50_000,
+            mutation_rate: 0.02,
+            elite: 2,
+        }
+    }
+}
+
+impl Evolution {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.generations == 0 {
+            return Err(Error::Config(
+                "the charts need at least one generation to evolve".to_string(),
+            ));
+        }
+        if self.population < 2 {
+            return Err(Error::Config(format!(
+                "the population needs at least 2 charts, got {}",
+                self.population
+            )));
+        }
+        if self.elite == 0 || self.elite >= self.population {
+            return Err(Error::Config(format!(
+                "the elite is from 1 chart to one less than the population of {}, got {}",
+                self.population, self.elite
+            )));
+        }
+        if !(0.0..=1.0).contains(&self.mutation_rate) {
+            return Err(Error::Config(format!(
+                "the mutation rate is a share from 0 to 1, got {}",
+                self.mutation_rate
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How the charts of a generation did, in player edge.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Generation {
+    pub best: f64,
+    pub mean: f64,
+}
+
+/// The best chart of the last generation and how every generation did.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evolved {
+    pub chart: CustomChart,
+    /// The player edge of the chart in the last generation.
+    pub edge: f64,
+    pub generations: Vec<Generation>,
+}
+
+/// Evolves `start`, e.g. basic strategy for the rules, into a chart playing better under the
+/// rules of `conf`, for rules no published chart covers.
+///
+/// All charts of a generation are played over the same shoes, dealt from `seed` plus the number
+/// of the generation, so they are compared on the same cards while no chart is fitted to a
+/// single set of shoes. The rows of `start` are the hands evolved, the chart bot plays the rest
+/// by basic strategy.
+pub fn evolve(
+    conf: &GameConfig,
+    evolution: &Evolution,
+    start: &CustomChart,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Evolved, Error> {
+    evolution.validate()?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut population = vec![start.clone()];
+    while population.len() < evolution.population {
+        let mut chart = start.clone();
+        mutate(&mut chart, evolution.mutation_rate, &mut rng);
+        population.push(chart);
+    }
+
+    let total = evolution.rounds * evolution.population as u64 * evolution.generations;
+    let mut generations = vec![];
+    let mut best = (start.clone(), f64::NEG_INFINITY);
+    for generation in 0..evolution.generations {
+        let mut rated = vec![];
+        for (i, chart) in population.into_iter().enumerate() {
+            let conf = GameConfig {
+                chart: Some(chart.clone()),
+                ..conf.clone()
+            };
+            let before = evolution.rounds * (generation * evolution.population as u64 + i as u64);
+            let mut part = PartProgress {
+                progress: &mut *progress,
+                before,
+                total,
+            };
+            let result = simulate(
+                conf,
+                &mut Strategy::Chart,
+                evolution.rounds,
+                Some(seed.wrapping_add(generation)),
+                &mut part,
+            )?;
+            rated.push((chart, result.player_edge()));
+        }
+        rated.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        let edges = rated.iter().map(|(_, edge)| edge).sum::<f64>();
+        generations.push(Generation {
+            best: rated[0].1,
+            mean: edges / rated.len() as f64,
+        });
+        best = rated[0].clone();
+
+        population = rated
+            .iter()
+            .take(evolution.elite)
+            .map(|(chart, _)| chart.clone())
+            .collect();
+        while population.len() < evolution.population {
+            let mother = select(&rated, &mut rng);
+            let father = select(&rated, &mut rng);
+            let mut child = crossover(mother, father, &mut rng);
+            mutate(&mut child, evolution.mutation_rate, &mut rng);
+            population.push(child);
+        }
+    }
+    let (mut chart, edge) = best;
+    chart.name = format!("evolved {}", start.name);
+    Ok(Evolved {
+        chart,
+        edge,
+        generations,
+    })
+}
+
+/// The better of two charts picked at random from the rated charts.
+fn select<'a>(rated: &'a [(CustomChart, f64)], rng: &mut StdRng) -> &'a CustomChart {
+    let a = rng.gen_range(0..rated.len());
+    let b = rng.gen_range(0..rated.len());
+    // The charts are sorted from the best
+    &rated[a.min(b)].0
+}
+
+/// A child of two charts, taking every row from either of them.
+fn crossover(mother: &CustomChart, father: &CustomChart, rng: &mut StdRng) -> CustomChart {
+    let mut child = mother.clone();
+    for (hand, cells) in child.rows.iter_mut() {
+        if let Some(other) = father.rows.get(hand).filter(|_| rng.gen_bool(0.5)) {
+            *cells = *other;
+        }
+    }
+    child
+}
+
+/// Changes every cell of the chart to a random move with a chance of `rate`, pairs to any move
+/// and the other hands to any but splitting.
+fn mutate(chart: &mut CustomChart, rate: f64, rng: &mut StdRng) {
+    for (hand, cells) in chart.rows.iter_mut() {
+        let moves: &[ChartMove] = match hand {
+            StartingHand::Pair(_) => &PAIR_MOVES,
+            _ => &MOVES,
+        };
+        for cell in cells.iter_mut() {
+            if rng.gen_bool(rate) {
+                *cell = moves[rng.gen_range(0..moves.len())];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chart::{generate_chart, ChartMove};
+    use crate::config::GameConfig;
+    use crate::custom_chart::CustomChart;
+    use crate::optimizer::{evolve, mutate, Evolution};
+    use crate::outcomes::StartingHand;
+    use crate::simulation::NoProgress;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_evolve() {
+        let conf = GameConfig::default();
+        let basic = CustomChart::from_chart("basic", &generate_chart(&conf.rules).unwrap());
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut chart = basic.clone();
+        mutate(&mut chart, 0.0, &mut rng);
+        assert_eq!(chart, basic);
+        mutate(&mut chart, 1.0, &mut rng);
+        assert_ne!(chart, basic);
+        assert!(chart.rows.iter().all(|(hand, cells)| {
+            matches!(hand, StartingHand::Pair(_))
+                || !cells
+                    .iter()
+                    .any(|c| matches!(c, ChartMove::Split | ChartMove::SurrenderOrSplit))
+        }));
+
+        let evolution = Evolution {
+            population: 4,
+            generations: 3,
+            rounds: 2_000,
+            mutation_rate: 0.05,
+            elite: 1,
+        };
+        let evolved = evolve(&conf, &evolution, &basic, 5, &mut NoProgress).unwrap();
+        assert_eq!(evolved.generations.len(), 3);
+        assert_eq!(evolved.edge, evolved.generations[2].best);
+        assert!(evolved
+            .generations
+            .iter()
+            .all(|generation| generation.best >= generation.mean));
+        assert_eq!(evolved.chart.name, "evolved basic");
+        assert!(evolved.chart.rows.keys().eq(basic.rows.keys()));
+
+        let elite = Evolution {
+            elite: 4,
+            ..evolution.clone()
+        };
+        assert!(evolve(&conf, &elite, &basic, 5, &mut NoProgress).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use crate::chart::{upcard_column, RANKS};
+use crate::config::GameConfig;
+use crate::error::Error;
+use crate::events::{GameEvent, GameObserver, HandResult, Seat};
+use crate::input::PlayerInput;
+use crate::money::Money;
+use crate::simulation::{play_rounds, Progress, Simulator, SIMULATION_BANKROLL};
+use crate::types::{Hand, Outcome, Value};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// A player's first two cards, grouped as in a strategy chart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StartingHand {
+    /// Two different cards without an ace, by their total.
+    Hard(u32),
+    /// An ace with another card, by the value of the other card. An ace with a ten is a
+    /// blackjack.
+    Soft(u32),
+    /// Two cards of the same value, by that value with aces as 11.
+    Pair(u32),
+}
+
+impl StartingHand {
+    pub fn from_cards(first: &Value, second: &Value) -> Self {
+        let (first, second) = (first.value(), second.value());
+        if first == second {
+            StartingHand::Pair(first)
+        } else if first == 11 || second == 11 {
+            StartingHand::Soft(first.min(second))
+        } else {
+            StartingHand::Hard(first + second)
+        }
+    }
+}
+
+/// Written as in a strategy chart, e.g. "16", "A,7" or "8,8".
+impl Display for StartingHand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartingHand::Hard(total) => write!(f, "{}", total),
+            StartingHand::Soft(card) => write!(f, "A,{}", card),
+            StartingHand::Pair(11) => write!(f, "A,A"),
+            StartingHand::Pair(card) => write!(f, "{},{}", card, card),
+        }
+    }
+}
+
+impl FromStr for StartingHand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            format!(
+                "expected a hard total from 5 to 19 or two cards, e.g. A,7 or 8,8, got '{}'",
+                s
+            )
+        };
+        match s.split_once(',') {
+            Some((first, second)) => {
+                let first = first.parse::<Value>().map_err(|_| error())?;
+                let second = second.parse::<Value>().map_err(|_| error())?;
+                Ok(StartingHand::from_cards(&first, &second))
+            }
+            None => match s.trim().parse::<u32>() {
+                Ok(total @ 5..=19) => Ok(StartingHand::Hard(total)),
+                _ => Err(error()),
+            },
+        }
+    }
+}
+
+/// How the hands played from one starting hand against one upcard ended.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct HandOutcomes {
+    /// The number of times the starting hand was dealt against the upcard.
+    pub dealt: u64,
+    /// The hands played from them, more than were dealt when pairs are split.
+    pub hands: u64,
+    pub blackjacks: u64,
+    /// Every other hand that was paid, including dealer busts, Charlies and bonus 21s.
+    pub wins: u64,
+    pub pushes: u64,
+    /// The hands that stood and lost to the dealer.
+    pub losses: u64,
+    pub busts: u64,
+    pub surrenders: u64,
+    pub doubles: u64,
+    /// The doubled hands that won.
+    pub double_wins: u64,
+    /// The starting hands that were split.
+    pub splits: u64,
+    pub initial_bets: Money,
+    /// The net result of all hands played from the starting hands.
+    pub net: Money,
+}
+
+impl HandOutcomes {
+    fn add(&mut self, result: &HandResult) {
+        self.hands += 1;
+        self.net += result.winnings;
+        let won = result.winnings > Money::ZERO;
+        match result.outcome {
+            Outcome::Blackjack | Outcome::EvenMoney => self.blackjacks += 1,
+            Outcome::Push | Outcome::DealerPush => self.pushes += 1,
+            Outcome::Bust => self.busts += 1,
+            Outcome::Surrender => self.surrenders += 1,
+            _ if won => self.wins += 1,
+            _ => self.losses += 1,
+        }
+        if result.doubled {
+            self.doubles += 1;
+            self.double_wins += won as u64;
+        }
+    }
+
+    /// The share of the hands played that `count` are.
+    pub fn share(&self, count: u64) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        count as f64 / self.hands as f64
+    }
+
+    /// The player's expected result of being dealt the hand, as a fraction of the initial bet.
+    pub fn ev(&self) -> f64 {
+        if self.initial_bets == Money::ZERO {
+            return 0.0;
+        }
+        self.net.units_f64() / self.initial_bets.units_f64()
+    }
+}
+
+/// The outcomes of every starting hand dealt in a simulation against every upcard.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutcomeTable {
+    pub rounds: u64,
+    /// The outcomes of the starting hand against the upcards from 2 to ace.
+    pub hands: BTreeMap<StartingHand, [HandOutcomes; RANKS]>,
+}
+
+/// Remembers the bet and the first two cards of every hand dealt, to file the results of the
+/// round under them.
+struct OutcomeRecorder {
+    table: Rc<RefCell<OutcomeTable>>,
+    dealt: Vec<(Money, Hand)>,
+}
+
+impl GameObserver for OutcomeRecorder {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::BetPlaced { hand, amount } => {
+                if *hand == 0 {
+                    self.dealt.clear();
+                }
+                self.dealt.push((*amount, Hand::default()));
+            }
+            GameEvent::CardDealt {
+                seat: Seat::Hand(hand),
+                card: Some(card),
+            } => {
+                // Only the first two cards, later cards are hits or dealt to split hands
+                if let Some((_, cards)) = self.dealt.get_mut(*hand) {
+                    if cards.cards.len() < 2 {
+                        cards.add_card(card.clone());
+                    }
+                }
+            }
+            GameEvent::RoundSettled { dealer, hands } => {
+                let Some(upcard) = dealer.cards.first() else {
+                    return;
+                };
+                let mut table = self.table.borrow_mut();
+                for (origin, (bet, cards)) in self.dealt.iter().enumerate() {
+                    let [first, second] = &cards.cards[..] else {
+                        continue;
+                    };
+                    let starting = StartingHand::from_cards(&first.value, &second.value);
+                    let outcomes =
+                        &mut table.hands.entry(starting).or_default()[upcard_column(&upcard.value)];
+                    outcomes.dealt += 1;
+                    outcomes.initial_bets += *bet;
+                    let results = hands.iter().filter(|result| result.origin == origin);
+                    outcomes.splits += results.clone().any(|result| result.split) as u64;
+                    for result in results {
+                        outcomes.add(result);
+                    }
+                }
+                self.dealt.clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Plays `rounds` rounds with `strategy` as [simulate] does and collects how the hands played
+/// from every starting hand ended against every upcard, to show why a chart plays a hand the
+/// way it does.
+///
+/// [simulate]: crate::simulation::simulate
+pub fn simulate_outcomes(
+    conf: GameConfig,
+    strategy: &mut dyn PlayerInput,
+    rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<OutcomeTable, Error> {
+    let mut simulator = Simulator::new(conf, SIMULATION_BANKROLL, seed)?;
+    let table = Rc::new(RefCell::new(OutcomeTable::default()));
+    simulator.engine.subscribe(Box::new(OutcomeRecorder {
+        table: table.clone(),
+        dealt: vec![],
+    }));
+    let result = play_rounds(simulator, strategy, rounds, progress)?;
+    let mut table = table.take();
+    table.rounds = result.rounds;
+    Ok(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::input::Strategy;
+    use crate::outcomes::{simulate_outcomes, StartingHand};
+    use crate::simulation::NoProgress;
+    use crate::types::Value;
+
+    #[test]
+    fn test_starting_hand() {
+        let hand = |first, second| StartingHand::from_cards(&first, &second);
+        assert_eq!(hand(Value::Ten, Value::Six), StartingHand::Hard(16));
+        assert_eq!(hand(Value::Seven, Value::Ace), StartingHand::Soft(7));
+        assert_eq!(hand(Value::King, Value::Queen), StartingHand::Pair(10));
+        assert_eq!(hand(Value::Ace, Value::Ace).to_string(), "A,A");
+        for text in ["16", "A,7", "8,8", "A,A", "A,10"] {
+            assert_eq!(text.parse::<StartingHand>().unwrap().to_string(), text);
+        }
+        assert_eq!("K,6".parse(), Ok(StartingHand::Hard(16)));
+        assert!("21".parse::<StartingHand>().is_err());
+        assert!("A,B".parse::<StartingHand>().is_err());
+    }
+
+    #[test]
+    fn test_simulate_outcomes() {
+        let table = simulate_outcomes(
+            GameConfig::default(),
+            &mut Strategy::Basic,
+            20_000,
+            Some(4),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(table.rounds, 20_000);
+        let dealt = table
+            .hands
+            .values()
+            .flatten()
+            .map(|outcomes| outcomes.dealt)
+            .sum::<u64>();
+        assert_eq!(dealt, 20_000);
+
+        // Basic strategy always splits aces and 8s, and doubles 11 against a 6
+        let column = 4;
+        let aces = &table.hands[&StartingHand::Pair(11)][column];
+        assert_eq!(aces.splits, aces.dealt);
+        assert!(aces.hands >= 2 * aces.dealt);
+        let eleven = &table.hands[&StartingHand::Hard(11)][column];
+        assert_eq!(eleven.doubles, eleven.hands);
+        assert!(eleven.ev() > 0.3);
+        // A blackjack never loses
+        let blackjacks = &table.hands[&StartingHand::Soft(10)][column];
+        assert_eq!(blackjacks.blackjacks, blackjacks.hands);
+        let stiff = &table.hands[&StartingHand::Hard(16)][column];
+        assert_eq!(
+            stiff.blackjacks
+                + stiff.wins
+                + stiff.pushes
+                + stiff.losses
+                + stiff.busts
+                + stiff.surrenders,
+            stiff.hands
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use crate::engine::GameEngine;
+use crate::input::BasicStrategy;
+use crate::rules::HandOptions;
+use crate::solver::{solve, Composition};
+use crate::types::{Move, Value};
+
+/// A bot playing every hand perfectly for the cards left in the shoe: it works out the exact
+/// expected result of every move from the composition of the cards it hasn't seen, rather than
+/// from a count, and makes the best one. Insurance is taken when a third of those cards or more
+/// are tens. Its result bounds what any strategy playing the hands can get out of flat bets.
+///
+/// Every decision is worked out afresh, so it is slow to simulate. Where the expected results
+/// can't be worked out, in the variants and with a Charlie, it plays basic strategy.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PerfectPlay;
+
+impl PerfectPlay {
+    /// The cards the player hasn't seen: the shoe and the hole card.
+    pub fn unseen(engine: &GameEngine) -> Composition {
+        let mut cards = Composition::from_shoe(&engine.shoe);
+        if let Some(card) = engine.hole_card() {
+            cards.add(&card.value);
+        }
+        cards
+    }
+
+    /// The allowed move with the highest expected result for the unseen cards.
+    pub fn decide(engine: &GameEngine, hand: usize, options: &HandOptions) -> Move {
+        let cards = &engine.player.hands[hand].hand;
+        let best = engine.dealer_hand.cards.first().and_then(|upcard| {
+            let evs = solve(
+                &engine.conf.rules,
+                &Self::unseen(engine),
+                cards,
+                &upcard.value,
+            );
+            let evs = evs.ok()?;
+            options
+                .moves
+                .iter()
+                .filter_map(|action| evs.of(*action).map(|ev| (*action, ev)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+        });
+        match best {
+            Some((action, _)) => action,
+            None => BasicStrategy::decide(engine, hand, options),
+        }
+    }
+
+    /// Whether insurance, paying 2 to 1 on a ten, is worth taking on the unseen cards.
+    pub fn insures(engine: &GameEngine) -> bool {
+        let cards = Self::unseen(engine);
+        3 * cards.count(&Value::Ten) >= cards.total() && cards.total() > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::engine::tests::dealt;
+    use crate::input::Strategy;
+    use crate::perfect::PerfectPlay;
+    use crate::simulation::{simulate, NoProgress};
+    use crate::types::{Move, Value};
+
+    #[test]
+    fn test_perfect_play() {
+        let mut engine = dealt(GameConfig::default(), &[Value::Ten, Value::Six], Value::Ten);
+        let options = engine.conf.rules.hand_options(&engine.player, 0);
+        // 16 against a ten is surrendered off a full shoe
+        assert_eq!(PerfectPlay::decide(&engine, 0, &options), Move::Surrender);
+        assert!(!PerfectPlay::insures(&engine));
+        // With only sixes left the dealer busts every time, so the 16 is stood on
+        let shoe = engine.shoe.clone();
+        engine.shoe.cards.retain(|card| card.value() == Value::Six);
+        assert_eq!(PerfectPlay::decide(&engine, 0, &options), Move::Stand);
+        assert!(!PerfectPlay::insures(&engine));
+        engine.shoe = shoe;
+        engine.shoe.cards.retain(|card| card.value().value() == 10);
+        assert!(PerfectPlay::insures(&engine));
+
+        let perfect = simulate(
+            GameConfig::default(),
+            &mut Strategy::Perfect,
+            100,
+            Some(2),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(perfect.rounds, 100);
+    }
+}
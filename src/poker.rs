@@ -0,0 +1,149 @@
+use crate::types::{Card, Payout, Value};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The poker hands a three card hand can make, as rated by the 21+3 side bet.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ThreeCardHand {
+    SuitedTrips,
+    StraightFlush,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    Nothing,
+}
+
+impl ThreeCardHand {
+    pub fn evaluate(cards: &[Card; 3]) -> Self {
+        let flush = cards.iter().all(|c| c.suit == cards[0].suit);
+        let trips = cards.iter().all(|c| c.value == cards[0].value);
+
+        let mut ranks = cards.iter().map(|c| rank(&c.value)).collect::<Vec<u32>>();
+        ranks.sort_unstable();
+        // The ace plays both high (Q-K-A) and low (A-2-3)
+        let straight =
+            (ranks[1] == ranks[0] + 1 && ranks[2] == ranks[1] + 1) || ranks == [2, 3, 14];
+
+        match (trips, straight, flush) {
+            (true, _, true) => ThreeCardHand::SuitedTrips,
+            (true, _, false) => ThreeCardHand::ThreeOfAKind,
+            (false, true, true) => ThreeCardHand::StraightFlush,
+            (false, true, false) => ThreeCardHand::Straight,
+            (false, false, true) => ThreeCardHand::Flush,
+            (false, false, false) => ThreeCardHand::Nothing,
+        }
+    }
+
+    /// The 21+3 payout for the hand, `None` if the side bet loses.
+    pub fn payout(&self) -> Option<Payout> {
+        let numerator = match self {
+            ThreeCardHand::SuitedTrips => 100,
+            ThreeCardHand::StraightFlush => 40,
+            ThreeCardHand::ThreeOfAKind => 30,
+            ThreeCardHand::Straight => 10,
+            ThreeCardHand::Flush => 5,
+            ThreeCardHand::Nothing => return None,
+        };
+
+        Some(Payout {
+            numerator,
+            denominator: 1,
+        })
+    }
+}
+
+impl Display for ThreeCardHand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ThreeCardHand::SuitedTrips => "Suited three of a kind",
+                ThreeCardHand::StraightFlush => "Straight flush",
+                ThreeCardHand::ThreeOfAKind => "Three of a kind",
+                ThreeCardHand::Straight => "Straight",
+                ThreeCardHand::Flush => "Flush",
+                ThreeCardHand::Nothing => "Nothing",
+            }
+        )
+    }
+}
+
+/// The poker rank of a card, where picture cards are ranked individually and the ace is high.
+fn rank(value: &Value) -> u32 {
+    match value {
+        Value::Jack => 11,
+        Value::Queen => 12,
+        Value::King => 13,
+        Value::Ace => 14,
+        v => v.value(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::poker::ThreeCardHand;
+    use crate::types::{Card, Suit, Value};
+
+    fn card(value: Value, suit: Suit) -> Card {
+        Card { suit, value }
+    }
+
+    #[test]
+    fn test_evaluate_three_card_hands() {
+        let cases = [
+            (
+                [
+                    card(Value::Seven, Suit::Hearts),
+                    card(Value::Seven, Suit::Hearts),
+                    card(Value::Seven, Suit::Hearts),
+                ],
+                ThreeCardHand::SuitedTrips,
+            ),
+            (
+                [
+                    card(Value::Seven, Suit::Hearts),
+                    card(Value::Seven, Suit::Clubs),
+                    card(Value::Seven, Suit::Spades),
+                ],
+                ThreeCardHand::ThreeOfAKind,
+            ),
+            (
+                [
+                    card(Value::Queen, Suit::Clubs),
+                    card(Value::Ace, Suit::Clubs),
+                    card(Value::King, Suit::Clubs),
+                ],
+                ThreeCardHand::StraightFlush,
+            ),
+            (
+                [
+                    card(Value::Two, Suit::Clubs),
+                    card(Value::Ace, Suit::Hearts),
+                    card(Value::Three, Suit::Clubs),
+                ],
+                ThreeCardHand::Straight,
+            ),
+            (
+                [
+                    card(Value::King, Suit::Clubs),
+                    card(Value::Ace, Suit::Hearts),
+                    card(Value::Two, Suit::Clubs),
+                ],
+                ThreeCardHand::Nothing,
+            ),
+            (
+                [
+                    card(Value::Two, Suit::Diamonds),
+                    card(Value::Nine, Suit::Diamonds),
+                    card(Value::Jack, Suit::Diamonds),
+                ],
+                ThreeCardHand::Flush,
+            ),
+        ];
+
+        for (cards, expected) in cases {
+            assert_eq!(ThreeCardHand::evaluate(&cards), expected);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::engine::{GameEngine, GameState};
+use crate::events::GameEvent;
+use crate::game::Action;
+use crate::money::Money;
+use crate::side_bets::SideBet;
+use crate::table::SeatPosition;
+use crate::types::{Hand, Move, PlayerHand};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+// The messages of a game served over the network, a JSON object per line in either direction,
+// e.g. {"type":"Join","name":"Ann"} and {"type":"Move","action":"Hit"} from a client.
+
+/// A message from a client to the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    /// Sits down at the spot, or at the first free one, between rounds.
+    Join {
+        name: String,
+        #[serde(default)]
+        position: Option<SeatPosition>,
+    },
+    /// Places the main bet for another hand.
+    Bet {
+        amount: Money,
+    },
+    SideBet {
+        side_bet: SideBet,
+        amount: Money,
+    },
+    /// Done betting, the cards are dealt once every player at the table is.
+    Deal,
+    EvenMoney {
+        hand: usize,
+    },
+    Insure {
+        amount: Money,
+    },
+    Move {
+        action: Move,
+    },
+    Buy {
+        amount: Money,
+    },
+    Rescue {
+        rescue: bool,
+    },
+    Tip {
+        amount: Money,
+    },
+    /// Gets up from the table, at the end of the round if one is being played.
+    Leave,
+}
+
+impl ClientMessage {
+    /// The decision the message makes in the game, `None` for joining, leaving and being done
+    /// betting, which the server handles itself.
+    pub fn action(&self) -> Option<Action> {
+        match *self {
+            ClientMessage::Bet { amount } => Some(Action::Bet(amount)),
+            ClientMessage::SideBet { side_bet, amount } => Some(Action::SideBet(side_bet, amount)),
+            ClientMessage::EvenMoney { hand } => Some(Action::EvenMoney(hand)),
+            ClientMessage::Insure { amount } => Some(Action::Insure(amount)),
+            ClientMessage::Move { action } => Some(Action::Move(action)),
+            ClientMessage::Buy { amount } => Some(Action::Buy(amount)),
+            ClientMessage::Rescue { rescue } => Some(Action::Rescue(rescue)),
+            ClientMessage::Tip { amount } => Some(Action::Tip(amount)),
+            ClientMessage::Join { .. } | ClientMessage::Deal | ClientMessage::Leave => None,
+        }
+    }
+}
+
+impl FromStr for ClientMessage {
+    type Err = String;
+
+    /// Parses a line sent by a client.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(|e| format!("invalid message: {}", e))
+    }
+}
+
+/// A message from the server to one client or to everyone at the table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    /// The client sat down at `seat`, with a bankroll of `bankroll`.
+    Welcome {
+        seat: usize,
+        position: SeatPosition,
+        bankroll: Money,
+    },
+    /// Something happened at the table.
+    Event { event: GameEvent },
+    /// The table as it stands after the last action.
+    Table(TableState),
+    /// The game waits for a decision of the client.
+    YourTurn { state: GameState, moves: Vec<Move> },
+    /// The player got up from the table, with what was left of the bankroll.
+    Left {
+        seat: usize,
+        name: String,
+        bankroll: Money,
+    },
+    /// The client's message was refused, the game is as it was.
+    Rejected { reason: String },
+}
+
+/// What everyone at the table can see. The shoe and the hole card stay with the server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TableState {
+    pub state: GameState,
+    /// The seat whose decision the game waits for, `None` while betting.
+    pub in_play: Option<usize>,
+    pub dealer: Hand,
+    /// Every player at the table, from first base on.
+    pub seats: Vec<SeatState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeatState {
+    pub seat: usize,
+    pub position: SeatPosition,
+    pub name: String,
+    pub bankroll: Money,
+    pub hands: Vec<PlayerHand>,
+}
+
+impl TableState {
+    /// The table of `engine`, leaving out the seats without a named player.
+    pub fn new(engine: &GameEngine) -> Self {
+        let in_play = match engine.state() {
+            GameState::Insurance
+            | GameState::PlayerTurn(_)
+            | GameState::DoubleRescue(_)
+            | GameState::RoundOver => Some(engine.seat()),
+            _ => None,
+        };
+        let seats = (0..engine.seats())
+            .filter_map(|seat| {
+                let player = engine.player_at(seat);
+                Some(SeatState {
+                    seat,
+                    position: engine.position(seat),
+                    name: player.name.clone()?,
+                    bankroll: player.money,
+                    hands: player.hands.clone(),
+                })
+            })
+            .collect();
+        Self {
+            state: engine.state(),
+            in_play,
+            dealer: engine.dealer_hand.clone(),
+            seats,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::Action;
+    use crate::money::Money;
+    use crate::protocol::{ClientMessage, ServerMessage};
+    use crate::table::SeatPosition;
+    use crate::types::Move;
+
+    #[test]
+    fn test_messages() {
+        let join: ClientMessage = r#"{"type":"Join","name":"Ann","position":6}"#.parse().unwrap();
+        assert_eq!(
+            join,
+            ClientMessage::Join {
+                name: "Ann".to_string(),
+                position: Some(SeatPosition::THIRD_BASE)
+            }
+        );
+        assert_eq!(join.action(), None);
+        let past_third_base = r#"{"type":"Join","name":"x","position":99}"#;
+        assert!(past_third_base.parse::<ClientMessage>().is_err());
+
+        let hit: ClientMessage = r#"{"type":"Move","action":"Hit"}"#.parse().unwrap();
+        assert_eq!(hit.action(), Some(Action::Move(Move::Hit)));
+        let bet: ClientMessage = r#"{"type":"Bet","amount":12.5}"#.parse().unwrap();
+        assert_eq!(bet.action(), Some(Action::Bet(Money::from_cents(1250))));
+        assert!(r#"{"type":"Cheat"}"#.parse::<ClientMessage>().is_err());
+        assert!("hit".parse::<ClientMessage>().is_err());
+
+        let rejected = ServerMessage::Rejected {
+            reason: "it isn't your turn".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_string(&rejected).unwrap(),
+            r#"{"type":"Rejected","reason":"it isn't your turn"}"#
+        );
+    }
+}
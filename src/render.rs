@@ -0,0 +1,521 @@
+use blackjack::config::GameConfig;
+use blackjack::error::Error;
+use blackjack::events::{FinishReason, GameEvent, GameObserver, HandResult, Seat};
+use blackjack::money::Money;
+use blackjack::table::SeatPosition;
+use blackjack::types::{Card, ChipStack, Hand, Move, Outcome, Payout, Player, Suit};
+use blackjack::variants::Variant;
+use serde_json::json;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::io::{stdin, stdout, Write};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+/// What a player is called at a table with several players, with where they sit.
+pub fn seat_label(name: Option<&str>, position: SeatPosition) -> String {
+    match name {
+        Some(name) => format!("{} ({})", name, position),
+        None => format!("Player at {}", position),
+    }
+}
+
+/// What a line of text from the CLI itself is for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextKind {
+    /// Marks the start or end of a round or a summary.
+    Heading,
+    /// A question answered on stdin or by the script.
+    Prompt,
+    Info,
+    /// Input that was rejected and has to be given again.
+    Warning,
+    /// Shown for a moment in place of the text flashed before it, cleared by an empty text.
+    Flash,
+}
+
+/// Shows the game to the player, both the events of the engine and the CLI's own text.
+pub trait OutputRenderer {
+    fn event(&mut self, event: &GameEvent);
+
+    fn text(&mut self, text: &str, kind: TextKind);
+}
+
+/// The output formats that can be selected from the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Plain,
+    Colored,
+    Json,
+}
+
+impl OutputFormat {
+    pub fn renderer(&self, conf: &GameConfig) -> Box<dyn OutputRenderer> {
+        match self {
+            OutputFormat::Plain => Box::new(TextRenderer::new(conf, false)),
+            OutputFormat::Colored => Box::new(TextRenderer::new(conf, true)),
+            OutputFormat::Json => Box::new(JsonRenderer),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OutputFormat::Plain => "plain",
+                OutputFormat::Colored => "colored",
+                OutputFormat::Json => "json",
+            }
+        )
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(OutputFormat::Plain),
+            "colored" => Ok(OutputFormat::Colored),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "expected one of 'plain', 'colored' or 'json', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// A renderer shared between the engine, which feeds it events, and the CLI writing its prompts
+/// and reading the answers to them.
+#[derive(Clone)]
+pub struct Console {
+    renderer: Rc<RefCell<Box<dyn OutputRenderer>>>,
+    /// The answers still to be given from a script, read from stdin when there is none.
+    script: Option<Rc<RefCell<VecDeque<String>>>>,
+}
+
+impl Console {
+    pub fn new(renderer: Box<dyn OutputRenderer>, script: Option<VecDeque<String>>) -> Self {
+        Self {
+            renderer: Rc::new(RefCell::new(renderer)),
+            script: script.map(|lines| Rc::new(RefCell::new(lines))),
+        }
+    }
+
+    pub fn heading(&self, text: &str) {
+        self.renderer.borrow_mut().text(text, TextKind::Heading);
+    }
+
+    pub fn prompt(&self, text: &str) {
+        self.renderer.borrow_mut().text(text, TextKind::Prompt);
+    }
+
+    pub fn info(&self, text: &str) {
+        self.renderer.borrow_mut().text(text, TextKind::Info);
+    }
+
+    pub fn warn(&self, text: &str) {
+        self.renderer.borrow_mut().text(text, TextKind::Warning);
+    }
+
+    /// Flashes `text` for `pace`, or clears the last one for an empty text. A script doesn't
+    /// wait to see it.
+    pub fn flash(&self, text: &str, pace: Duration) {
+        self.renderer.borrow_mut().text(text, TextKind::Flash);
+        if self.script.is_none() {
+            thread::sleep(pace);
+        }
+    }
+
+    /// Reads the answer to the last prompt, the next line of the script, shown as if it had been
+    /// typed, or of stdin. Fails once the input is closed or the script has run out.
+    pub fn read_line(&self) -> Result<String, Error> {
+        if let Some(script) = &self.script {
+            let answer = script.borrow_mut().pop_front().ok_or(Error::InputClosed)?;
+            self.info(&format!("> {}", answer));
+            return Ok(answer);
+        }
+        let mut input = String::new();
+        match stdin().read_line(&mut input) {
+            Ok(0) => Err(Error::InputClosed),
+            Ok(_) => Ok(String::from(input.trim_end_matches(['\n', '\r']))),
+            Err(e) => Err(Error::Input(e.to_string())),
+        }
+    }
+}
+
+impl GameObserver for Console {
+    fn on_event(&mut self, event: &GameEvent) {
+        self.renderer.borrow_mut().event(event);
+    }
+}
+
+/// The ANSI colors used by the colored output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    Red,
+    Green,
+    Yellow,
+    Cyan,
+    Bold,
+}
+
+impl Color {
+    fn code(&self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Green => "32",
+            Color::Yellow => "33",
+            Color::Cyan => "36",
+            Color::Bold => "1",
+        }
+    }
+}
+
+/// Renders the game as text on stdout, pausing between moves so it can be followed.
+/// With colors on, red suits, results and prompts are highlighted with ANSI escape codes.
+pub struct TextRenderer {
+    colored: bool,
+    variant: Variant,
+    blackjack_payout: Payout,
+    sleep_duration: Duration,
+    /// The dealer's cards in view.
+    dealer: Hand,
+    /// The number of cards dealt to the dealer this round, face up or down.
+    dealer_cards: usize,
+    /// The hand last shown to the player.
+    current_hand: Option<usize>,
+}
+
+impl TextRenderer {
+    pub fn new(conf: &GameConfig, colored: bool) -> Self {
+        Self {
+            colored,
+            variant: conf.rules.variant,
+            blackjack_payout: conf.rules.blackjack_payout(),
+            sleep_duration: conf.sleep_duration,
+            dealer: Hand::default(),
+            dealer_cards: 0,
+            current_hand: None,
+        }
+    }
+
+    fn pause(&self) {
+        thread::sleep(self.sleep_duration);
+    }
+
+    fn paint(&self, text: &str, color: Color) -> String {
+        if self.colored {
+            format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+        } else {
+            String::from(text)
+        }
+    }
+
+    fn card(&self, card: &Card) -> String {
+        match card.suit {
+            Suit::Hearts | Suit::Diamonds => self.paint(&card.to_string(), Color::Red),
+            Suit::Spades | Suit::Clubs => card.to_string(),
+        }
+    }
+
+    fn hand(&self, hand: &Hand) -> String {
+        let cards = hand
+            .cards
+            .iter()
+            .map(|c| self.card(c))
+            .collect::<Vec<String>>();
+        format!("{} ({})", cards.join(" "), hand.calc_value())
+    }
+
+    fn show_hand(&mut self, hand: usize, hands: usize, cards: &Hand) {
+        if hands > 1 && self.current_hand != Some(hand) {
+            println!("Playing hand {}", hand + 1);
+        }
+        self.current_hand = Some(hand);
+        println!("Hand: {}", self.hand(cards));
+    }
+
+    fn show_result(&self, dealer: &Hand, result: &HandResult) {
+        let natural = self.variant.terminology().natural;
+        let message = match result.outcome {
+            Outcome::Blackjack => format!("{} wins {}", natural, self.blackjack_payout),
+            Outcome::Win => String::from("Congratulations! winnings 1:1"),
+            Outcome::DealerBust => String::from("Dealer bust! winnings 1:1"),
+            Outcome::Push => String::from("Push! You get your money back"),
+            Outcome::Loss => String::from("Dealer wins, better luck next time!"),
+            Outcome::Bust => String::from("Player bust :("),
+            Outcome::Surrender => String::from("Surrendered, half the bet is returned"),
+            Outcome::EvenMoney => String::from("Even money! winnings 1:1"),
+            Outcome::Charlie => String::from("Charlie! winnings 1:1"),
+            Outcome::DealerPush => format!("Dealer {}, push!", dealer.calc_value()),
+            Outcome::Bonus(payout) => format!("21 bonus! winnings {}", payout),
+            Outcome::FiveCardTrick => String::from("Five card trick! winnings 2:1"),
+            Outcome::OriginalBetLoss => format!(
+                "Dealer {}, only the original bet is lost ({} returned)",
+                natural, result.payout
+            ),
+        };
+
+        let winnings = result.winnings;
+        let text = if winnings >= Money::from_units(1) {
+            format!(
+                "{} ({:+}, {})",
+                message,
+                winnings,
+                ChipStack::from_amount(winnings)
+            )
+        } else {
+            format!("{} ({:+})", message, winnings)
+        };
+        let color = if winnings > Money::ZERO {
+            Color::Green
+        } else if winnings < Money::ZERO {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        println!("{}", self.paint(&text, color));
+    }
+}
+
+impl OutputRenderer for TextRenderer {
+    fn event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::Shuffled { continuous: true } => {
+                println!("Continuous shuffling machine loaded\n")
+            }
+            GameEvent::Shuffled { continuous: false } => {
+                println!("Shoe shuffled, one card burned\n")
+            }
+            GameEvent::DiscardsShuffled => {
+                println!("The shoe ran out, the discards are shuffled to finish the round")
+            }
+            GameEvent::SeatInPlay { name, position, .. } => {
+                self.current_hand = None;
+                let label = seat_label(name.as_deref(), *position);
+                self.text(&format!("---- {} ----", label), TextKind::Heading);
+            }
+            // Bets are confirmed as they are placed
+            GameEvent::BetPlaced { .. } | GameEvent::SideBetPlaced { .. } => {}
+            GameEvent::CardDealt {
+                seat: Seat::Dealer,
+                card,
+            } => {
+                self.dealer_cards += 1;
+                match card {
+                    Some(card) if self.dealer_cards == 1 => {
+                        self.dealer = Hand::from_card(card.clone());
+                        println!("Dealer: {}", self.hand(&self.dealer));
+                        self.pause();
+                    }
+                    None if self.dealer_cards == 1 => {
+                        println!("Dealer: ?? ??");
+                        self.pause();
+                    }
+                    // Only cards drawn after the hole card is revealed are shown as hits
+                    Some(card) if self.dealer.cards.len() > 1 => {
+                        self.dealer.add_card(card.clone());
+                        println!("Dealer hit {}", self.hand(&self.dealer));
+                        self.pause();
+                    }
+                    _ => {}
+                }
+            }
+            GameEvent::CardDealt { .. } => {}
+            GameEvent::TwentyOnePlusThreeSettled {
+                poker_hand,
+                bet,
+                payout,
+            } => match payout {
+                Some(payout) => println!(
+                    "{}",
+                    self.paint(
+                        &format!(
+                            "21+3: {} pays {} ({:+})",
+                            poker_hand,
+                            payout,
+                            payout.apply(*bet)
+                        ),
+                        Color::Green
+                    )
+                ),
+                None => println!(
+                    "{}",
+                    self.paint(&format!("21+3 lost (-{})", bet), Color::Red)
+                ),
+            },
+            GameEvent::DealerPeeked { blackjack } => {
+                let natural = self.variant.terminology().natural;
+                if *blackjack {
+                    println!("Dealer peeks... Dealer has {}!", natural);
+                } else {
+                    println!("Dealer peeks... No {}", natural);
+                }
+                self.pause();
+            }
+            GameEvent::PlayerTurn { hand, hands, cards } => self.show_hand(*hand, *hands, cards),
+            GameEvent::PlayerAction {
+                action: Move::Double,
+                cards,
+                ..
+            } if self.variant.double_ends_hand() => println!("Doubled down: {}", self.hand(cards)),
+            GameEvent::PlayerAction {
+                action: Move::Surrender,
+                ..
+            } => println!("Surrendered"),
+            GameEvent::PlayerAction { .. } => {}
+            GameEvent::MoveUndone { hand, hands, cards } => {
+                println!("Move undone");
+                self.show_hand(*hand, *hands, cards);
+            }
+            GameEvent::DoubleRescued { .. } => println!("Rescued, the original bet is surrendered"),
+            GameEvent::HandFinished {
+                hand,
+                hands,
+                cards,
+                reason,
+            } => {
+                self.show_hand(*hand, *hands, cards);
+                match reason {
+                    FinishReason::Blackjack => {
+                        println!("{}!", self.variant.terminology().natural)
+                    }
+                    FinishReason::FiveCardTrick => println!("Five card trick!"),
+                    FinishReason::Charlie => println!("Charlie!"),
+                    _ => {}
+                }
+            }
+            GameEvent::DealerReveal { hand } => {
+                self.dealer = hand.clone();
+                println!("Dealer hand: {}", self.hand(hand));
+            }
+            GameEvent::InsuranceSettled { bet, won: true } => println!(
+                "{}",
+                self.paint(
+                    &format!(
+                        "Insurance pays {} ({:+})",
+                        Player::INSURANCE_PAYOUT,
+                        Player::INSURANCE_PAYOUT.apply(*bet)
+                    ),
+                    Color::Green
+                )
+            ),
+            GameEvent::InsuranceSettled { bet, won: false } => {
+                println!(
+                    "{}",
+                    self.paint(&format!("Insurance lost (-{})", bet), Color::Red)
+                )
+            }
+            GameEvent::BustItSettled {
+                bet,
+                dealer_cards,
+                payout,
+            } => match (payout, dealer_cards) {
+                (Some(payout), Some(cards)) => println!(
+                    "{}",
+                    self.paint(
+                        &format!(
+                            "Bust It: dealer bust with {} cards, pays {} ({:+})",
+                            cards,
+                            payout,
+                            payout.apply(*bet)
+                        ),
+                        Color::Green
+                    )
+                ),
+                _ => println!(
+                    "{}",
+                    self.paint(&format!("Bust It lost (-{})", bet), Color::Red)
+                ),
+            },
+            GameEvent::RoundSettled { dealer, hands } => {
+                self.pause();
+                for (i, result) in hands.iter().enumerate() {
+                    if hands.len() > 1 {
+                        print!("Hand {} ({}): ", i + 1, self.hand(&result.hand));
+                    }
+                    self.show_result(dealer, result);
+                }
+            }
+            GameEvent::DealerTipped { amount } => {
+                println!("Thank you! Tipped the dealer {}", amount)
+            }
+            GameEvent::RoundFinished {
+                bankroll,
+                running_count,
+                true_count,
+            } => {
+                self.dealer = Hand::default();
+                self.dealer_cards = 0;
+                self.current_hand = None;
+
+                self.pause();
+                self.text(
+                    "============ ROUND END   ============ \n",
+                    TextKind::Heading,
+                );
+                if let Some(bankroll) = bankroll {
+                    println!("Bankroll: {}", bankroll);
+                }
+                println!(
+                    "Counts (running/true) {}/{:.1}\n",
+                    running_count, true_count
+                );
+            }
+        }
+    }
+
+    fn text(&mut self, text: &str, kind: TextKind) {
+        let text = match kind {
+            TextKind::Heading => self.paint(text, Color::Bold),
+            TextKind::Prompt => self.paint(text, Color::Cyan),
+            TextKind::Info => String::from(text),
+            TextKind::Warning => self.paint(text, Color::Yellow),
+            // Written over the line in place, without moving on to the next one
+            TextKind::Flash if text.is_empty() => {
+                print!("\r        \r");
+                let _ = stdout().flush();
+                return;
+            }
+            TextKind::Flash => {
+                print!("\r    {:<4}", text);
+                let _ = stdout().flush();
+                return;
+            }
+        };
+        println!("{}", text);
+    }
+}
+
+/// Writes every event and line of text as a JSON object on its own line, for other programs to
+/// read. There are no pauses between moves.
+pub struct JsonRenderer;
+
+impl OutputRenderer for JsonRenderer {
+    fn event(&mut self, event: &GameEvent) {
+        match serde_json::to_string(event) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("Failed to serialize event: {}", e),
+        }
+    }
+
+    fn text(&mut self, text: &str, kind: TextKind) {
+        let kind = match kind {
+            TextKind::Heading => "heading",
+            TextKind::Prompt => "prompt",
+            TextKind::Info => "info",
+            TextKind::Warning => "warning",
+            TextKind::Flash if text.is_empty() => return,
+            TextKind::Flash => "flash",
+        };
+        println!("{}", json!({ "text": text.trim(), "kind": kind }));
+    }
+}
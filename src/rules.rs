@@ -0,0 +1,269 @@
+use crate::bonus::Bonus21;
+use crate::error::Error;
+use crate::money::Money;
+use crate::types::{Deck, DoubleRule, Hand, Move, Outcome, Payout, Player, PlayerHand};
+use crate::variants::Variant;
+use serde::Serialize;
+
+/// The dealer stands on 17 or more, except a soft 17 when hitting soft 17.
+const DEALER_STAND_VALUE: u32 = 17;
+
+/// The rules of play at the table, from the shoe to the payouts.
+///
+/// The defaults are a six deck S17 game paying 3:2, with the dealer peeking for blackjack.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rules {
+    pub deck_count: u32,
+    /// The number of cards placed behind the cut card.
+    pub cut_card: u32,
+    /// The fraction of the shoe dealt before the cut card comes out, overrides `cut_card`.
+    pub penetration: Option<f32>,
+    /// A continuous shuffling machine returns the discards to the shoe after every round.
+    pub csm: bool,
+    pub hit_soft_17: bool,
+    pub dealer_peek: bool,
+    /// Only the original bets are lost to a dealer blackjack found after the player acted.
+    pub original_bets_only: bool,
+    pub blackjack_payout: Payout,
+    pub max_splits: u32,
+    pub resplit_aces: bool,
+    pub hit_split_aces: bool,
+    pub double_on: DoubleRule,
+    pub double_after_split: bool,
+    /// Late surrender, where the variant allows it.
+    pub surrender: bool,
+    /// A hand with this many cards that hasn't busted wins automatically.
+    pub charlie: Option<usize>,
+    pub variant: Variant,
+    pub bonuses: Vec<Bonus21>,
+}
+
+/// What the player may do with a hand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HandOptions {
+    pub moves: Vec<Move>,
+    /// Doubling is paid for by the house.
+    pub free_double: bool,
+    /// Splitting is paid for by the house.
+    pub free_split: bool,
+}
+
+impl Rules {
+    /// Checks that the rules can be played together, e.g. that the cut card fits in the shoe.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.deck_count == 0 {
+            return Err(Error::Config(
+                "the shoe needs at least one deck".to_string(),
+            ));
+        }
+        if let Some(penetration) = self.penetration {
+            if !(0.0..=1.0).contains(&penetration) {
+                return Err(Error::Config(
+                    "the penetration must be between 0 and 1".to_string(),
+                ));
+            }
+        }
+        let shoe_size = Deck::new(self.variant)?.cards.len() as u32 * self.deck_count;
+        if !self.csm && self.cut_card_position(shoe_size) > shoe_size {
+            return Err(Error::Config(format!(
+                "the cut card can't be placed behind {} cards in a shoe of {}",
+                self.cut_card, shoe_size
+            )));
+        }
+        if self.charlie.is_some_and(|cards| cards < 3) {
+            return Err(Error::Config(
+                "a Charlie needs at least 3 cards".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The payout for a natural, the variant's payout takes precedence over the table's.
+    pub fn blackjack_payout(&self) -> Payout {
+        self.variant
+            .natural_payout()
+            .unwrap_or(self.blackjack_payout)
+    }
+
+    /// The number of cards to place behind the cut card in a freshly shuffled shoe of `num_cards`.
+    pub fn cut_card_position(&self, num_cards: u32) -> u32 {
+        match self.penetration {
+            Some(penetration) => (num_cards as f32 * (1.0 - penetration)) as u32,
+            None => self.cut_card,
+        }
+    }
+
+    /// Whether the dealer has to take another card.
+    pub fn dealer_hits(&self, hand: &Hand) -> bool {
+        let value = hand.calc_value();
+        value < DEALER_STAND_VALUE
+            || (self.hit_soft_17 && value == DEALER_STAND_VALUE && hand.is_soft())
+    }
+
+    /// Resolves a finished hand against the dealer under the table rules.
+    pub fn resolve(&self, hand: &PlayerHand, dealer: &Hand) -> Outcome {
+        match Outcome::resolve(hand, dealer, self.charlie, self.variant, &self.bonuses) {
+            Outcome::Loss if self.original_bets_only && dealer.is_blackjack() => {
+                Outcome::OriginalBetLoss
+            }
+            outcome => outcome,
+        }
+    }
+
+    /// The moves available for the player's hand at `index`, none if the hand can't be played on.
+    pub fn hand_options(&self, player: &Player, index: usize) -> HandOptions {
+        let hand = &player.hands[index];
+        let first_move = hand.hand.cards.len() == 2;
+        // Split aces normally only receive a single card each
+        let locked = hand.is_split_aces() && !self.hit_split_aces;
+        let free_double = self.variant.is_free_double(hand);
+        let free_split = self.variant.is_free_split(hand);
+        let can_double = if self.variant.double_ends_hand() {
+            first_move
+                && !locked
+                && (self.double_after_split || !hand.split)
+                && (free_double
+                    || (self.double_on.allows(hand.hand.calc_value())
+                        && player.can_afford(hand.stake())))
+        } else {
+            hand.hand.cards.len() < 4 && player.can_afford(Money::from_cents(1))
+        };
+        let can_split = hand.hand.can_split()
+            && player.splits(hand.origin) < self.max_splits
+            && (!hand.is_split_aces() || self.resplit_aces)
+            && (free_split || player.can_afford(hand.stake()));
+        let can_stand = hand.hand.calc_value() >= self.variant.min_stand_value();
+        let can_surrender =
+            self.surrender && self.variant.allows_surrender() && hand.can_surrender();
+
+        let mut moves = vec![];
+        if locked && !can_split {
+            return HandOptions {
+                moves,
+                free_double: false,
+                free_split: false,
+            };
+        }
+
+        if !locked {
+            moves.push(Move::Hit);
+        }
+        if can_stand {
+            moves.push(Move::Stand);
+        }
+        if can_double {
+            moves.push(Move::Double);
+        }
+        if can_split {
+            moves.push(Move::Split);
+        }
+        if can_surrender {
+            moves.push(Move::Surrender);
+        }
+
+        HandOptions {
+            moves,
+            free_double: can_double && free_double,
+            free_split: can_split && free_split,
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            deck_count: 6,
+            cut_card: 52,
+            penetration: None,
+            csm: false,
+            hit_soft_17: false,
+            dealer_peek: true,
+            original_bets_only: false,
+            blackjack_payout: Payout::THREE_TO_TWO,
+            max_splits: 3,
+            resplit_aces: false,
+            hit_split_aces: false,
+            double_on: DoubleRule::Any,
+            double_after_split: true,
+            surrender: true,
+            charlie: None,
+            variant: Variant::Standard,
+            bonuses: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+    use crate::rules::Rules;
+    use crate::types::{Card, Hand, Move, Player, PlayerHand, Suit, Value};
+
+    fn hand(values: &[Value]) -> Hand {
+        let mut hand = Hand::default();
+        for value in values {
+            hand.add_card(Card {
+                suit: Suit::Hearts,
+                value: value.clone(),
+            });
+        }
+        hand
+    }
+
+    #[test]
+    fn test_dealer_hits_soft_17() {
+        let mut rules = Rules::default();
+        let soft_17 = hand(&[Value::Ace, Value::Six]);
+        let hard_17 = hand(&[Value::Ten, Value::Seven]);
+        assert!(!rules.dealer_hits(&soft_17));
+        assert!(rules.dealer_hits(&hand(&[Value::Ten, Value::Six])));
+
+        rules.hit_soft_17 = true;
+        assert!(rules.dealer_hits(&soft_17));
+        assert!(!rules.dealer_hits(&hard_17));
+    }
+
+    #[test]
+    fn test_hand_options() {
+        let conf = Rules::default();
+        let mut player = Player::new(Money::from_units(100));
+        player.hands.push(PlayerHand::new(
+            hand(&[Value::Eight, Value::Eight]),
+            Money::from_units(10),
+        ));
+        assert_eq!(
+            conf.hand_options(&player, 0).moves,
+            vec![
+                Move::Hit,
+                Move::Stand,
+                Move::Double,
+                Move::Split,
+                Move::Surrender
+            ]
+        );
+
+        // Split aces only receive one card
+        let mut aces = PlayerHand::new(hand(&[Value::Ace, Value::King]), Money::from_units(10));
+        aces.split = true;
+        player.hands[0] = aces;
+        assert!(conf.hand_options(&player, 0).moves.is_empty());
+
+        let conf = Rules {
+            double_after_split: false,
+            surrender: false,
+            ..Rules::default()
+        };
+        player.hands[0] = PlayerHand::new(hand(&[Value::Ten, Value::Six]), Money::from_units(10));
+        assert_eq!(
+            conf.hand_options(&player, 0).moves,
+            vec![Move::Hit, Move::Stand, Move::Double]
+        );
+        let mut split = PlayerHand::new(hand(&[Value::Eight, Value::Three]), Money::from_units(10));
+        split.split = true;
+        player.hands[0] = split;
+        assert_eq!(
+            conf.hand_options(&player, 0).moves,
+            vec![Move::Hit, Move::Stand]
+        );
+    }
+}
@@ -0,0 +1,555 @@
+use crate::config::{GameConfig, MAX_SEATS};
+use crate::engine::{GameEngine, GameState};
+use crate::error::Error;
+use crate::game::{Action, Game};
+use crate::money::Money;
+use crate::protocol::{ClientMessage, ServerMessage, TableState};
+use crate::table::SeatPosition;
+use crate::types::{Move, Player, Shoe};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::thread;
+use std::time::Duration;
+
+/// A connection to the server, numbered in the order the clients connected.
+pub type ClientId = usize;
+
+/// Who a message from the server is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recipient {
+    Client(ClientId),
+    Everyone,
+}
+
+/// The messages to send after handling a message from a client.
+pub type Outbox = Vec<(Recipient, ServerMessage)>;
+
+/// The client playing at a seat.
+struct Sitting {
+    client: ClientId,
+    /// Done betting, waiting for the others to deal.
+    ready: bool,
+    /// Left during the round, the hands are played out and the seat vacated after it.
+    leaving: bool,
+}
+
+/// A table shared by the clients of a server, which owns the shoe and plays every action so no
+/// client can deal itself a card or act for another.
+///
+/// The table has a seat at every spot, empty until a client sits down at it between rounds. The
+/// cards are dealt once every player at the table is done betting, and the hands of the players
+/// who left during a round are stood on.
+pub struct Table {
+    game: Game,
+    buy_in: Money,
+    sitting: Vec<Option<Sitting>>,
+}
+
+impl Table {
+    /// A table playing by `conf`, dealing from `shoe` in its current order, where every player
+    /// sits down with a bankroll of `buy_in`.
+    pub fn new(conf: GameConfig, shoe: Shoe, buy_in: Money) -> Result<Self, Error> {
+        let spots = SeatPosition::all()
+            .map(|position| (position, Player::new(Money::ZERO)))
+            .collect();
+        let engine = GameEngine::seated_at(conf, shoe, spots, vec![])?;
+        Ok(Self {
+            game: Game::with_engine(engine),
+            buy_in,
+            sitting: (0..MAX_SEATS).map(|_| None).collect(),
+        })
+    }
+
+    /// Shuffles the shoe before the first round.
+    pub fn shuffle(&mut self) {
+        self.game.shuffle();
+    }
+
+    /// What everyone at the table can see.
+    pub fn view(&self) -> TableState {
+        TableState::new(self.game.engine())
+    }
+
+    /// Handles a message from `client`, returning the messages to send in reply.
+    pub fn handle(&mut self, client: ClientId, message: ClientMessage) -> Outbox {
+        let mut outbox = vec![];
+        let result = match message {
+            ClientMessage::Join { name, position } => {
+                self.join(client, &name, position, &mut outbox)
+            }
+            ClientMessage::Deal => self.ready(client, &mut outbox),
+            ClientMessage::Leave => self.leave(client, &mut outbox),
+            message => match message.action() {
+                Some(action) => self.act(client, action, &mut outbox),
+                None => unreachable!("every other message is an action"),
+            },
+        };
+        if let Err(reason) = result {
+            outbox.push((
+                Recipient::Client(client),
+                ServerMessage::Rejected { reason },
+            ));
+        }
+        self.play_out(&mut outbox);
+        self.vacate(&mut outbox);
+        self.announce(&mut outbox);
+        outbox
+    }
+
+    /// Gets the player of a client that disconnected up from the table.
+    pub fn disconnect(&mut self, client: ClientId) -> Outbox {
+        match self.seat_of(client) {
+            Some(_) => self.handle(client, ClientMessage::Leave),
+            None => vec![],
+        }
+    }
+
+    /// The seat `client` plays at.
+    fn seat_of(&self, client: ClientId) -> Option<usize> {
+        self.sitting.iter().position(|sitting| {
+            matches!(sitting, Some(sitting) if sitting.client == client && !sitting.leaving)
+        })
+    }
+
+    fn join(
+        &mut self,
+        client: ClientId,
+        name: &str,
+        position: Option<SeatPosition>,
+        outbox: &mut Outbox,
+    ) -> Result<(), String> {
+        if self.seat_of(client).is_some() {
+            return Err("you already sit at the table".to_string());
+        }
+        if self.game.state() != GameState::Betting {
+            return Err("sit down once the round is over".to_string());
+        }
+        let seat = match position {
+            Some(position) if self.sitting[position.number() - 1].is_some() => {
+                return Err(format!("someone already sits at {}", position))
+            }
+            Some(position) => position.number() - 1,
+            None => self
+                .sitting
+                .iter()
+                .position(Option::is_none)
+                .ok_or_else(|| "the table is full".to_string())?,
+        };
+        let player = Player::new(self.buy_in).with_name(name);
+        self.game
+            .seat_player(seat, player)
+            .map_err(|e| e.to_string())?;
+        self.sitting[seat] = Some(Sitting {
+            client,
+            ready: false,
+            leaving: false,
+        });
+        outbox.push((
+            Recipient::Client(client),
+            ServerMessage::Welcome {
+                seat,
+                position: self.game.engine().position(seat),
+                bankroll: self.buy_in,
+            },
+        ));
+        Ok(())
+    }
+
+    fn leave(&mut self, client: ClientId, outbox: &mut Outbox) -> Result<(), String> {
+        let seat = self
+            .seat_of(client)
+            .ok_or_else(|| "you don't sit at the table".to_string())?;
+        if let Some(sitting) = &mut self.sitting[seat] {
+            sitting.leaving = true;
+        }
+        // The others may only have been waiting for the player leaving to deal
+        if self.game.state() == GameState::Betting {
+            self.vacate(outbox);
+            return self.deal_when_ready(outbox);
+        }
+        Ok(())
+    }
+
+    fn ready(&mut self, client: ClientId, outbox: &mut Outbox) -> Result<(), String> {
+        let seat = self
+            .seat_of(client)
+            .ok_or_else(|| "you don't sit at the table".to_string())?;
+        if self.game.state() != GameState::Betting {
+            return Err(Error::WrongState(self.game.state()).to_string());
+        }
+        if let Some(sitting) = &mut self.sitting[seat] {
+            sitting.ready = true;
+        }
+        self.deal_when_ready(outbox)
+    }
+
+    /// Deals the cards if every player at the table is done betting.
+    fn deal_when_ready(&mut self, outbox: &mut Outbox) -> Result<(), String> {
+        let mut players = self.sitting.iter().flatten().peekable();
+        if players.peek().is_none() || !players.all(|sitting| sitting.ready) {
+            return Ok(());
+        }
+        for sitting in self.sitting.iter_mut().flatten() {
+            sitting.ready = false;
+        }
+        if self.game.engine().seats_in_round().is_empty() {
+            return Err("nobody placed a bet".to_string());
+        }
+        self.play(Action::Deal, outbox)
+    }
+
+    fn act(&mut self, client: ClientId, action: Action, outbox: &mut Outbox) -> Result<(), String> {
+        let seat = self
+            .seat_of(client)
+            .ok_or_else(|| "you don't sit at the table".to_string())?;
+        // The players bet at any time before the deal, afterwards they decide in seat order
+        if self.game.state() == GameState::Betting {
+            if self.game.engine().seat() != seat {
+                self.play(Action::TakeSeat(seat), outbox)?;
+            }
+        } else if self.game.engine().seat() != seat {
+            return Err("it isn't your turn".to_string());
+        }
+        self.play(action, outbox)
+    }
+
+    /// Plays `action`, sending the events to everyone. A round the shoe runs out of cards for is
+    /// called off.
+    fn play(&mut self, action: Action, outbox: &mut Outbox) -> Result<(), String> {
+        let events = match self.game.play(action) {
+            Ok(events) => events,
+            Err(Error::OutOfCards) => self.game.void_round(),
+            Err(e) => return Err(e.to_string()),
+        };
+        outbox.extend(
+            events
+                .into_iter()
+                .map(|event| (Recipient::Everyone, ServerMessage::Event { event })),
+        );
+        Ok(())
+    }
+
+    /// Decides for the players who left during the round, standing on their hands.
+    fn play_out(&mut self, outbox: &mut Outbox) {
+        loop {
+            let seat = self.game.engine().seat();
+            if matches!(&self.sitting[seat], Some(sitting) if !sitting.leaving) {
+                return;
+            }
+            let action = match self.game.state() {
+                GameState::Insurance => Action::Insure(Money::ZERO),
+                GameState::PlayerTurn(_) => Action::Move(Move::Stand),
+                GameState::DoubleRescue(_) => Action::Rescue(false),
+                GameState::RoundOver => Action::Tip(Money::ZERO),
+                _ => return,
+            };
+            if self.play(action, outbox).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Gets the players who left up from the table once the round is over.
+    fn vacate(&mut self, outbox: &mut Outbox) {
+        if self.game.state() != GameState::Betting {
+            return;
+        }
+        for seat in 0..self.sitting.len() {
+            if !matches!(&self.sitting[seat], Some(sitting) if sitting.leaving) {
+                continue;
+            }
+            if let Ok(player) = self.game.seat_player(seat, Player::new(Money::ZERO)) {
+                self.sitting[seat] = None;
+                outbox.push((
+                    Recipient::Everyone,
+                    ServerMessage::Left {
+                        seat,
+                        name: player.name.unwrap_or_default(),
+                        bankroll: player.money,
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Shows everyone the table and asks the player in play for a decision.
+    fn announce(&self, outbox: &mut Outbox) {
+        let view = self.view();
+        let in_play = view
+            .in_play
+            .and_then(|seat| self.sitting[seat].as_ref())
+            .map(|sitting| sitting.client);
+        outbox.push((Recipient::Everyone, ServerMessage::Table(view)));
+        if let Some(client) = in_play {
+            outbox.push((
+                Recipient::Client(client),
+                ServerMessage::YourTurn {
+                    state: self.game.state(),
+                    moves: self.game.options().map(|o| o.moves).unwrap_or_default(),
+                },
+            ));
+        }
+    }
+}
+
+/// The longest line a client may send, a longer one closes the connection.
+const MAX_LINE: u64 = 4096;
+/// The messages waiting to be written to a client, one falling further behind is dropped.
+const MAX_PENDING: usize = 256;
+/// How long a write may block before the client is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the connections tell the game.
+enum Incoming {
+    Connected(ClientId, Connection),
+    Line(ClientId, String),
+    Disconnected(ClientId),
+}
+
+/// A client's connection, written to on a thread of its own so a client that stops reading
+/// can't hold up the table.
+struct Connection {
+    lines: SyncSender<String>,
+    stream: TcpStream,
+}
+
+/// Hosts a table playing by `conf` on `port` until the process is stopped. The clients connect
+/// over TCP and send and receive the messages of [`crate::protocol`], a JSON object per line.
+///
+/// Every connection is read and written on threads of its own, while the game is played on the
+/// calling thread one message at a time.
+pub fn serve(conf: GameConfig, buy_in: Money, port: u16) -> Result<(), Error> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|e| Error::Config(format!("cannot listen on port {}: {}", port, e)))?;
+    let shoe = Shoe::new(conf.rules.deck_count, conf.rules.variant)?;
+    let mut table = Table::new(conf, shoe, buy_in)?;
+    table.shuffle();
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || accept(listener, sender));
+    let mut connections = HashMap::new();
+    for incoming in receiver {
+        let outbox = match incoming {
+            Incoming::Connected(client, connection) => {
+                connections.insert(client, connection);
+                vec![(
+                    Recipient::Client(client),
+                    ServerMessage::Table(table.view()),
+                )]
+            }
+            Incoming::Line(client, line) => match line.parse() {
+                Ok(message) => table.handle(client, message),
+                Err(reason) => vec![(
+                    Recipient::Client(client),
+                    ServerMessage::Rejected { reason },
+                )],
+            },
+            Incoming::Disconnected(client) => {
+                connections.remove(&client);
+                table.disconnect(client)
+            }
+        };
+        deliver(&mut connections, outbox);
+    }
+    Ok(())
+}
+
+/// Accepts the clients, reading the lines each one sends and writing the ones sent to it on
+/// threads of its own.
+fn accept(listener: TcpListener, sender: Sender<Incoming>) {
+    let streams = listener.incoming().filter_map(Result::ok);
+    for (client, stream) in streams.enumerate() {
+        let (reader, writer) = match (stream.try_clone(), stream.try_clone()) {
+            (Ok(reader), Ok(writer)) => (reader, writer),
+            _ => continue,
+        };
+        if writer.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+            continue;
+        }
+        let (lines, pending) = mpsc::sync_channel(MAX_PENDING);
+        thread::spawn(move || write_lines(writer, pending));
+        let connection = Connection { lines, stream };
+        if sender
+            .send(Incoming::Connected(client, connection))
+            .is_err()
+        {
+            return;
+        }
+        let sender = sender.clone();
+        thread::spawn(move || read_lines(client, reader, sender));
+    }
+}
+
+/// Passes the lines the client sends on to the game until the connection closes or the client
+/// sends a line longer than [`MAX_LINE`].
+fn read_lines(client: ClientId, stream: TcpStream, sender: Sender<Incoming>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        match reader.by_ref().take(MAX_LINE).read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(read) if read as u64 == MAX_LINE && !line.ends_with('\n') => break,
+            Ok(_) if line.trim().is_empty() => continue,
+            Ok(_) => {
+                let line = line.trim_end().to_string();
+                if sender.send(Incoming::Line(client, line)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+    let _ = reader.get_ref().shutdown(Shutdown::Both);
+    let _ = sender.send(Incoming::Disconnected(client));
+}
+
+/// Writes the lines for the client until the connection closes or a write times out.
+fn write_lines(mut stream: TcpStream, pending: Receiver<String>) {
+    for line in pending {
+        if writeln!(stream, "{}", line).is_err() {
+            let _ = stream.shutdown(Shutdown::Both);
+            return;
+        }
+    }
+}
+
+/// Queues the messages for the clients they are for. A client too far behind to take another
+/// message is disconnected, and gets up from the table once its reader notices.
+fn deliver(connections: &mut HashMap<ClientId, Connection>, outbox: Outbox) {
+    for (recipient, message) in outbox {
+        let line = match serde_json::to_string(&message) {
+            Ok(line) => line,
+            Err(_) => continue,
+        };
+        connections.retain(|&client, connection| {
+            if recipient != Recipient::Everyone && recipient != Recipient::Client(client) {
+                return true;
+            }
+            match connection.lines.try_send(line.clone()) {
+                Ok(()) => true,
+                Err(_) => {
+                    let _ = connection.stream.shutdown(Shutdown::Both);
+                    false
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::engine::GameState;
+    use crate::money::Money;
+    use crate::protocol::{ClientMessage, ServerMessage};
+    use crate::server::{Outbox, Recipient, Table};
+    use crate::table::SeatPosition;
+    use crate::types::{Card, Move, PackedCard, Shoe, Suit, Value};
+
+    /// A table whose shoe deals `values` in order.
+    fn table(values: &[Value]) -> Table {
+        let conf = GameConfig::default();
+        let mut shoe = Shoe::new(1, conf.rules.variant).unwrap();
+        shoe.cards = values
+            .iter()
+            .rev()
+            .map(|value| Card {
+                suit: Suit::Hearts,
+                value: value.clone(),
+            })
+            .map(PackedCard::from)
+            .collect();
+        Table::new(conf, shoe, Money::from_units(100)).unwrap()
+    }
+
+    fn join(name: &str) -> ClientMessage {
+        ClientMessage::Join {
+            name: name.to_string(),
+            position: None,
+        }
+    }
+
+    fn rejected(outbox: &Outbox) -> Option<&str> {
+        outbox.iter().find_map(|(_, message)| match message {
+            ServerMessage::Rejected { reason } => Some(reason.as_str()),
+            _ => None,
+        })
+    }
+
+    fn your_turn(outbox: &Outbox) -> Option<Recipient> {
+        outbox
+            .iter()
+            .find(|(_, message)| matches!(message, ServerMessage::YourTurn { .. }))
+            .map(|(recipient, _)| *recipient)
+    }
+
+    #[test]
+    fn test_shared_table() {
+        // Ann and Bob on 10+6 and 10+7 against a dealer 9+7, who busts on a ten
+        let mut table = table(&[
+            Value::Ten,
+            Value::Ten,
+            Value::Nine,
+            Value::Six,
+            Value::Seven,
+            Value::Seven,
+            Value::Ten,
+        ]);
+        let ann = ClientMessage::Join {
+            name: "Ann".to_string(),
+            position: Some(SeatPosition::FIRST_BASE),
+        };
+        let outbox = table.handle(0, ann);
+        assert!(matches!(
+            outbox[0],
+            (Recipient::Client(0), ServerMessage::Welcome { seat: 0, .. })
+        ));
+        assert_eq!(rejected(&table.handle(1, join("Bob"))), None);
+        assert_eq!(table.view().seats.len(), 2);
+        assert_eq!(
+            table.view().seats[1].position,
+            SeatPosition::spot(2).unwrap()
+        );
+
+        let bet = ClientMessage::Bet {
+            amount: Money::from_units(10),
+        };
+        table.handle(1, bet.clone());
+        table.handle(0, bet);
+        assert!(rejected(&table.handle(0, ClientMessage::Deal)).is_none());
+        assert_eq!(table.view().state, GameState::Betting, "Bob isn't done yet");
+
+        let outbox = table.handle(1, ClientMessage::Deal);
+        assert_eq!(table.view().state, GameState::PlayerTurn(0));
+        assert_eq!(your_turn(&outbox), Some(Recipient::Client(0)));
+        assert!(rejected(&table.handle(2, join("Cy"))).is_some());
+
+        let hit = ClientMessage::Move { action: Move::Hit };
+        let outbox = table.handle(1, hit);
+        assert_eq!(rejected(&outbox), Some("it isn't your turn"));
+        assert_eq!(table.view().seats[1].hands[0].hand.cards.len(), 2);
+
+        let outbox = table.handle(
+            0,
+            ClientMessage::Move {
+                action: Move::Stand,
+            },
+        );
+        assert_eq!(your_turn(&outbox), Some(Recipient::Client(1)));
+
+        // Bob's hand is stood on after leaving, the seat is vacated once the round is over
+        let outbox = table.handle(1, ClientMessage::Leave);
+        assert_eq!(table.view().state, GameState::Betting);
+        let left = outbox.iter().find_map(|(_, message)| match message {
+            ServerMessage::Left { name, bankroll, .. } => Some((name.as_str(), *bankroll)),
+            _ => None,
+        });
+        assert_eq!(left, Some(("Bob", Money::from_units(110))));
+        let view = table.view();
+        assert_eq!(view.seats.len(), 1);
+        assert_eq!(view.seats[0].bankroll, Money::from_units(110));
+        assert!(rejected(&table.handle(1, ClientMessage::Deal)).is_some());
+    }
+}
@@ -0,0 +1,244 @@
+use crate::types::{Card, PackedCard};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Puts the cards of a shoe in a new order. The last card is the top of the shoe, dealt first.
+pub trait Shuffler {
+    fn shuffle(&mut self, cards: &mut [PackedCard]);
+}
+
+/// A uniformly random shuffle, every order of the cards is equally likely.
+pub struct FisherYates {
+    rng: StdRng,
+}
+
+impl FisherYates {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// A shuffler that gives the same orders every run, for reproducible simulations.
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for FisherYates {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shuffler for FisherYates {
+    fn shuffle(&mut self, cards: &mut [PackedCard]) {
+        cards.shuffle(&mut self.rng);
+    }
+}
+
+/// Emulates a dealer riffling the cards by hand (the Gilbert-Shannon-Reeds model).
+///
+/// The shoe is cut roughly in half and the halves are interleaved, each card falling from a half
+/// with a chance in proportion to its size. A few riffles leave runs of cards in their old order,
+/// as in real casino shuffles, which is what shuffle tracking exploits.
+pub struct RiffleShuffle {
+    pub riffles: u32,
+    rng: StdRng,
+}
+
+impl RiffleShuffle {
+    /// The number of riffles in a typical casino shuffle.
+    pub const CASINO_RIFFLES: u32 = 4;
+
+    pub fn new(riffles: u32) -> Self {
+        Self {
+            riffles,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    pub fn seeded(riffles: u32, seed: u64) -> Self {
+        Self {
+            riffles,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn riffle(&mut self, cards: &mut [PackedCard]) {
+        // The cut follows a binomial distribution around the middle of the shoe
+        let cut = (0..cards.len()).filter(|_| self.rng.gen_bool(0.5)).count();
+        let (left, right) = cards.split_at(cut);
+        let (mut left, mut right) = (left.to_vec(), right.to_vec());
+
+        // Cards fall from the bottom of either half, building the shoe from the bottom up
+        let mut riffled = Vec::with_capacity(cards.len());
+        while !left.is_empty() || !right.is_empty() {
+            let from_left = self.rng.gen_range(0..left.len() + right.len()) < left.len();
+            let half = if from_left { &mut left } else { &mut right };
+            riffled.push(half.remove(0));
+        }
+        cards.clone_from_slice(&riffled);
+    }
+}
+
+impl Default for RiffleShuffle {
+    fn default() -> Self {
+        Self::new(RiffleShuffle::CASINO_RIFFLES)
+    }
+}
+
+impl Shuffler for RiffleShuffle {
+    fn shuffle(&mut self, cards: &mut [PackedCard]) {
+        for _ in 0..self.riffles {
+            self.riffle(cards);
+        }
+    }
+}
+
+/// Stacks the shoe so `cards` are dealt first in the given order, for deterministic tests.
+///
+/// The stacked cards are taken from the shoe, the rest keep their order below them. Cards that
+/// aren't in the shoe are left out of the stack.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FixedOrder {
+    pub cards: Vec<Card>,
+}
+
+impl FixedOrder {
+    pub fn new(cards: Vec<Card>) -> Self {
+        Self { cards }
+    }
+}
+
+impl Shuffler for FixedOrder {
+    fn shuffle(&mut self, cards: &mut [PackedCard]) {
+        let mut top = cards.len();
+        for card in self.cards.iter().map(PackedCard::from) {
+            if let Some(position) = cards[..top].iter().rposition(|c| *c == card) {
+                // Shift the cards in between down to keep their order
+                cards[position..top].rotate_left(1);
+                top -= 1;
+            }
+        }
+    }
+}
+
+/// The shuffles that can be selected from the command line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShuffleMethod {
+    Uniform,
+    Riffle,
+}
+
+impl ShuffleMethod {
+    pub fn shuffler(&self) -> Box<dyn Shuffler> {
+        match self {
+            ShuffleMethod::Uniform => Box::<FisherYates>::default(),
+            ShuffleMethod::Riffle => Box::<RiffleShuffle>::default(),
+        }
+    }
+
+    /// The shuffler giving the same orders for the same seed.
+    pub fn seeded(&self, seed: u64) -> Box<dyn Shuffler> {
+        match self {
+            ShuffleMethod::Uniform => Box::new(FisherYates::seeded(seed)),
+            ShuffleMethod::Riffle => {
+                Box::new(RiffleShuffle::seeded(RiffleShuffle::CASINO_RIFFLES, seed))
+            }
+        }
+    }
+}
+
+impl Display for ShuffleMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ShuffleMethod::Uniform => "uniform",
+                ShuffleMethod::Riffle => "riffle",
+            }
+        )
+    }
+}
+
+impl FromStr for ShuffleMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(ShuffleMethod::Uniform),
+            "riffle" => Ok(ShuffleMethod::Riffle),
+            _ => Err(format!("expected 'uniform' or 'riffle', got '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shuffle::{FisherYates, FixedOrder, RiffleShuffle, Shuffler};
+    use crate::types::{Card, Deck, PackedCard, Suit, Value};
+    use crate::variants::Variant;
+
+    fn deck() -> Vec<PackedCard> {
+        Deck::new(Variant::Standard)
+            .unwrap()
+            .cards
+            .into_iter()
+            .map(PackedCard::from)
+            .collect()
+    }
+
+    fn sorted(mut cards: Vec<PackedCard>) -> Vec<PackedCard> {
+        cards.sort();
+        cards
+    }
+
+    #[test]
+    fn test_shuffles_keep_the_cards() {
+        let shufflers: Vec<Box<dyn Shuffler>> = vec![
+            Box::new(FisherYates::seeded(7)),
+            Box::new(RiffleShuffle::seeded(4, 7)),
+        ];
+        for mut shuffler in shufflers {
+            let mut cards = deck();
+            shuffler.shuffle(&mut cards);
+            assert_ne!(cards, deck());
+            assert_eq!(sorted(cards), sorted(deck()));
+        }
+    }
+
+    #[test]
+    fn test_seeded_shuffles_repeat() {
+        let mut first = deck();
+        let mut second = deck();
+        RiffleShuffle::seeded(3, 42).shuffle(&mut first);
+        RiffleShuffle::seeded(3, 42).shuffle(&mut second);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_fixed_order() {
+        let card = |value| Card {
+            suit: Suit::Spades,
+            value,
+        };
+        let stack = vec![card(Value::Ace), card(Value::King), card(Value::Two)];
+        let mut cards = deck();
+        FixedOrder::new(stack.clone()).shuffle(&mut cards);
+
+        let dealt = cards
+            .iter()
+            .rev()
+            .take(3)
+            .map(|c| Card::from(*c))
+            .collect::<Vec<Card>>();
+        assert_eq!(dealt, stack);
+        assert_eq!(sorted(cards), sorted(deck()));
+    }
+}
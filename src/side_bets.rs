@@ -0,0 +1,57 @@
+use crate::types::Payout;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The optional side bets placed next to the main bets.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SideBet {
+    /// A poker hand from the player's first two cards and the dealer upcard.
+    TwentyOnePlusThree,
+    /// The dealer busting, paying more the more cards the dealer busts with.
+    BustIt,
+}
+
+impl Display for SideBet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SideBet::TwentyOnePlusThree => "21+3",
+                SideBet::BustIt => "Bust It",
+            }
+        )
+    }
+}
+
+/// The Bust It payout when the dealer busts with `num_cards` cards, `None` if the dealer
+/// didn't bust.
+pub fn bust_it_payout(num_cards: usize) -> Option<Payout> {
+    let numerator = match num_cards {
+        0..=2 => return None,
+        3 => 1,
+        4 => 2,
+        5 => 9,
+        6 => 50,
+        7 => 100,
+        _ => 250,
+    };
+
+    Some(Payout {
+        numerator,
+        denominator: 1,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::side_bets::bust_it_payout;
+
+    #[test]
+    fn test_bust_it_payouts() {
+        assert_eq!(bust_it_payout(2), None);
+        assert_eq!(bust_it_payout(3).unwrap().numerator, 1);
+        assert_eq!(bust_it_payout(6).unwrap().numerator, 50);
+        assert_eq!(bust_it_payout(11).unwrap().numerator, 250);
+    }
+}
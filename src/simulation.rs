@@ -0,0 +1,1359 @@
+use crate::config::GameConfig;
+use crate::counting::CountingMethod;
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::events::{GameEvent, GameObserver, HandResult, Seat};
+use crate::input::{PlayerInput, Strategy};
+use crate::money::Money;
+use crate::shuffle::Shuffler;
+use crate::types::{Card, Hand, Move, Outcome, PackedCard, Player, Shoe};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+
+/// The bankroll of a simulated player, large enough to never go broke on table minimum bets.
+pub(crate) const SIMULATION_BANKROLL: Money = Money::from_units(1_000_000_000);
+
+/// The rounds played between updates of the progress of a simulation.
+const PROGRESS_INTERVAL: u64 = 10_000;
+
+/// The rounds in a batch of the batch means the confidence intervals are estimated by, long
+/// enough to span many shoes so the batches are close to independent of each other.
+pub(crate) const BATCH_ROUNDS: u64 = 1_000;
+
+/// The fewest batches a confidence interval is estimated from.
+const MIN_BATCHES: u64 = 20;
+
+/// The standard normal quantile of a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// The rounds played between checkpoints of a resumable simulation.
+const CHECKPOINT_ROUNDS: u64 = 1_000_000;
+
+/// Hears how far a long simulation has come, e.g. to show a progress bar.
+pub trait Progress {
+    /// Called every so often with the rounds (or sessions) done so far out of `total`, and once
+    /// more when all are done.
+    fn update(&mut self, done: u64, total: u64);
+}
+
+/// Ignores the progress, for simulations that are run without anyone watching.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn update(&mut self, _done: u64, _total: u64) {}
+}
+
+/// Reports the progress of one part of a simulation as progress through the whole of it.
+pub(crate) struct PartProgress<'a> {
+    pub(crate) progress: &'a mut dyn Progress,
+    /// The rounds done in the parts before this one.
+    pub(crate) before: u64,
+    pub(crate) total: u64,
+}
+
+impl Progress for PartProgress<'_> {
+    fn update(&mut self, done: u64, _total: u64) {
+        self.progress.update(self.before + done, self.total);
+    }
+}
+
+/// Where the player's result comes from, by the way the hands were played.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct EdgeBreakdown {
+    /// Hands that were neither naturals, doubled, split nor surrendered.
+    pub base: Money,
+    pub blackjacks: Money,
+    pub doubles: Money,
+    /// Every hand played after a split, including doubles after splitting.
+    pub splits: Money,
+    pub surrenders: Money,
+}
+
+impl EdgeBreakdown {
+    fn add(&mut self, result: &HandResult) {
+        let part = if result.split {
+            &mut self.splits
+        } else if result.doubled {
+            &mut self.doubles
+        } else {
+            match result.outcome {
+                Outcome::Blackjack | Outcome::EvenMoney => &mut self.blackjacks,
+                Outcome::Surrender => &mut self.surrenders,
+                _ => &mut self.base,
+            }
+        };
+        *part += result.winnings;
+    }
+}
+
+/// The rounds bet at one true count.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CountResult {
+    pub rounds: u64,
+    pub initial_bets: Money,
+    /// The sum of the round results in units.
+    pub result_sum: f64,
+    /// The sum of the squared round results.
+    pub result_squares: f64,
+}
+
+impl CountResult {
+    /// The player's expected result at the count, as a fraction of the initial bet.
+    pub fn player_edge(&self) -> f64 {
+        if self.initial_bets == Money::ZERO {
+            return 0.0;
+        }
+        self.result_sum / self.initial_bets.units_f64()
+    }
+
+    /// The mean squared result of a round per squared average bet, i.e. of a round of a single
+    /// unit when the bets were flat.
+    pub fn squared_per_bet(&self) -> f64 {
+        if self.initial_bets == Money::ZERO {
+            return 0.0;
+        }
+        let bet = self.initial_bets.units_f64() / self.rounds as f64;
+        self.result_squares / self.rounds as f64 / (bet * bet)
+    }
+}
+
+/// Sums over the batches of consecutive rounds of a simulation, to estimate how precise its
+/// results are by batch means. Unlike the rounds dealt from the same shoe, the batches are close
+/// to independent of each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BatchMeans {
+    pub batches: u64,
+    /// The sum of the expected result of a round of each batch, and of its square.
+    pub mean_sum: f64,
+    pub mean_squares: f64,
+    /// The sum of the player's result per initial bet of each batch, and of its square.
+    pub edge_sum: f64,
+    pub edge_squares: f64,
+}
+
+impl BatchMeans {
+    pub(crate) fn add_batch(&mut self, mean: f64, edge: f64) {
+        self.batches += 1;
+        self.mean_sum += mean;
+        self.mean_squares += mean * mean;
+        self.edge_sum += edge;
+        self.edge_squares += edge * edge;
+    }
+
+    fn add(&mut self, other: &BatchMeans) {
+        self.batches += other.batches;
+        self.mean_sum += other.mean_sum;
+        self.mean_squares += other.mean_squares;
+        self.edge_sum += other.edge_sum;
+        self.edge_squares += other.edge_squares;
+    }
+
+    /// The half width of the 95% confidence interval of the mean of the batches, `None` with
+    /// too few batches to tell.
+    fn margin(&self, sum: f64, squares: f64) -> Option<f64> {
+        if self.batches < MIN_BATCHES {
+            return None;
+        }
+        let batches = self.batches as f64;
+        let variance = ((squares - sum * sum / batches) / (batches - 1.0)).max(0.0);
+        Some(Z_95 * (variance / batches).sqrt())
+    }
+}
+
+/// The totals of a simulation, the result is the player's, so the house edge is its opposite.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub rounds: u64,
+    /// The hands settled, including the hands split off.
+    pub hands: u64,
+    /// The total of the bets placed before the deal.
+    pub initial_bets: Money,
+    /// The player's net result over all hands.
+    pub net: Money,
+    /// The sum of the round results in units, including insurance and side bets.
+    pub result_sum: f64,
+    /// The sum of the squared round results, for the variance.
+    pub result_squares: f64,
+    pub breakdown: EdgeBreakdown,
+    /// The rounds by the true count they were bet at, rounded down to a whole count.
+    pub true_counts: BTreeMap<i32, CountResult>,
+    /// The batches of rounds played in full.
+    pub batches: BatchMeans,
+}
+
+impl SimulationResult {
+    /// Adds the totals of another simulation to these.
+    pub fn add(&mut self, other: &SimulationResult) {
+        self.rounds += other.rounds;
+        self.hands += other.hands;
+        self.initial_bets += other.initial_bets;
+        self.net += other.net;
+        self.result_sum += other.result_sum;
+        self.result_squares += other.result_squares;
+        let breakdown = &mut self.breakdown;
+        breakdown.base += other.breakdown.base;
+        breakdown.blackjacks += other.breakdown.blackjacks;
+        breakdown.doubles += other.breakdown.doubles;
+        breakdown.splits += other.breakdown.splits;
+        breakdown.surrenders += other.breakdown.surrenders;
+        self.batches.add(&other.batches);
+        for (true_count, other) in &other.true_counts {
+            let count = self.true_counts.entry(*true_count).or_default();
+            count.rounds += other.rounds;
+            count.initial_bets += other.initial_bets;
+            count.result_sum += other.result_sum;
+            count.result_squares += other.result_squares;
+        }
+    }
+
+    /// The player's result as a fraction of the initial bets, negative when the house wins.
+    pub fn player_edge(&self) -> f64 {
+        self.per_initial_bet(self.net)
+    }
+
+    pub fn house_edge(&self) -> f64 {
+        -self.player_edge()
+    }
+
+    /// The expected result of a round in units.
+    pub fn mean(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.result_sum / self.rounds as f64
+    }
+
+    /// The variance of a round's result in units squared, a single hand is dealt every round.
+    pub fn variance(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.result_squares / self.rounds as f64 - self.mean().powi(2)
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// N0, the number of rounds after which the expected result equals one standard deviation,
+    /// i.e. the long run starts to outweigh the luck. `None` without an edge either way.
+    pub fn n0(&self) -> Option<f64> {
+        let mean = self.mean();
+        (mean != 0.0).then(|| self.variance() / (mean * mean))
+    }
+
+    /// The half width of the 95% confidence interval of the expected result of a round in units,
+    /// `None` if too few rounds were played to tell.
+    pub fn mean_margin(&self) -> Option<f64> {
+        let batches = &self.batches;
+        batches.margin(batches.mean_sum, batches.mean_squares)
+    }
+
+    /// The half width of the 95% confidence interval of the house edge, `None` if too few rounds
+    /// were played to tell.
+    pub fn house_edge_margin(&self) -> Option<f64> {
+        let batches = &self.batches;
+        batches.margin(batches.edge_sum, batches.edge_squares)
+    }
+
+    /// The desirability index, 1000 times the expected result of a round per standard
+    /// deviation, i.e. how good the game is regardless of the bet size.
+    pub fn desirability_index(&self) -> f64 {
+        let std_dev = self.std_dev();
+        if std_dev == 0.0 {
+            return 0.0;
+        }
+        1000.0 * self.mean() / std_dev
+    }
+
+    /// SCORE, the expected win per 100 rounds of a 10,000 unit bankroll with the bets scaled to
+    /// the optimal Kelly bet, which comes down to the square of the desirability index. `None`
+    /// without an edge for the player.
+    pub fn score(&self) -> Option<f64> {
+        (self.mean() > 0.0).then(|| self.desirability_index().powi(2))
+    }
+
+    /// The certainty equivalent of a round with `bankroll`, the sure result in units worth as much
+    /// to a Kelly bettor as the risky one, i.e. the expected result less the cost of its variance.
+    pub fn certainty_equivalent(&self, bankroll: Money) -> f64 {
+        self.mean() - self.variance() / (2.0 * bankroll.units_f64())
+    }
+
+    /// The share of the rounds bet at a true count of `true_count` or more.
+    pub fn share_from_count(&self, true_count: i32) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        let rounds = self
+            .true_counts
+            .range(true_count..)
+            .map(|(_, count)| count.rounds)
+            .sum::<u64>();
+        rounds as f64 / self.rounds as f64
+    }
+
+    /// An amount as a fraction of the initial bets, e.g. one part of the breakdown.
+    pub fn per_initial_bet(&self, amount: Money) -> f64 {
+        if self.initial_bets == Money::ZERO {
+            return 0.0;
+        }
+        amount.units_f64() / self.initial_bets.units_f64()
+    }
+}
+
+/// Adds up the settled hands as the rounds are played.
+struct Tally(Rc<RefCell<SimulationResult>>);
+
+impl GameObserver for Tally {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::RoundSettled { hands, .. } = event {
+            let mut result = self.0.borrow_mut();
+            for hand in hands {
+                result.hands += 1;
+                result.net += hand.winnings;
+                result.breakdown.add(hand);
+            }
+        }
+    }
+}
+
+/// A move made in a simulated round, with the cards of the hand it was made on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TracedDecision {
+    pub hand: usize,
+    pub cards: Hand,
+    pub action: Move,
+}
+
+/// A round of a simulation as written to its trace, to check how a strategy played it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TracedRound {
+    pub round: u64,
+    /// The counts the round was bet at.
+    pub running_count: i32,
+    pub true_count: f32,
+    pub bet: Money,
+    pub upcard: Option<Card>,
+    pub decisions: Vec<TracedDecision>,
+    pub dealer: Hand,
+    pub hands: Vec<HandResult>,
+    /// The change in the bankroll, including insurance and side bets.
+    pub result: Money,
+}
+
+/// Collects the cards and decisions of the round being played for the trace.
+struct Tracer {
+    round: Rc<RefCell<TracedRound>>,
+    /// The cards of each hand as they were at the last decision on it.
+    cards: BTreeMap<usize, Hand>,
+}
+
+impl GameObserver for Tracer {
+    fn on_event(&mut self, event: &GameEvent) {
+        let mut round = self.round.borrow_mut();
+        match event {
+            GameEvent::CardDealt {
+                seat: Seat::Dealer,
+                card: Some(card),
+            } if round.upcard.is_none() => round.upcard = Some(card.clone()),
+            GameEvent::PlayerTurn { hand, cards, .. } => {
+                self.cards.insert(*hand, cards.clone());
+            }
+            GameEvent::PlayerAction {
+                hand,
+                action,
+                cards,
+            } => {
+                let before = self.cards.insert(*hand, cards.clone());
+                round.decisions.push(TracedDecision {
+                    hand: *hand,
+                    cards: before.unwrap_or_default(),
+                    action: *action,
+                });
+            }
+            GameEvent::RoundSettled { dealer, hands } => {
+                round.dealer = dealer.clone();
+                round.hands = hands.clone();
+                self.cards.clear();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sorts the cards before shuffling, so every shoe only depends on the seed and not on how the
+/// cards of the previous shoe were played.
+struct Reproducible(Box<dyn Shuffler>);
+
+impl Shuffler for Reproducible {
+    fn shuffle(&mut self, cards: &mut [PackedCard]) {
+        cards.sort();
+        self.0.shuffle(cards);
+    }
+}
+
+/// An engine played by bots, adding up the results round by round.
+pub(crate) struct Simulator<'a> {
+    pub(crate) engine: GameEngine,
+    tally: Rc<RefCell<SimulationResult>>,
+    /// The round being traced and where the trace is written to, one JSON line per round.
+    trace: Option<(Rc<RefCell<TracedRound>>, &'a mut dyn Write)>,
+    /// The totals of the batch being played.
+    batch: SimulationResult,
+}
+
+impl<'a> Simulator<'a> {
+    pub(crate) fn new(conf: GameConfig, bankroll: Money, seed: Option<u64>) -> Result<Self, Error> {
+        conf.validate()?;
+        let tally = Rc::new(RefCell::new(SimulationResult::default()));
+        let shoe = Shoe::new(conf.rules.deck_count, conf.rules.variant)?;
+        let shuffler = seed.map(|seed| Reproducible(conf.shuffle.seeded(seed)));
+        let observer = Box::new(Tally(tally.clone()));
+        let mut engine = GameEngine::with_shoe(conf, shoe, Player::new(bankroll), vec![observer]);
+        if let Some(shuffler) = shuffler {
+            engine.set_shuffler(Box::new(shuffler));
+        }
+        engine.shuffle();
+        Ok(Self {
+            engine,
+            tally,
+            trace: None,
+            batch: SimulationResult::default(),
+        })
+    }
+
+    /// Writes every round played from now on to `out`.
+    fn trace(&mut self, out: &'a mut dyn Write) {
+        let round = Rc::new(RefCell::new(TracedRound::default()));
+        self.engine.subscribe(Box::new(Tracer {
+            round: round.clone(),
+            cards: BTreeMap::new(),
+        }));
+        self.trace = Some((round, out));
+    }
+
+    /// The bet of the next round, from the betting system if there is one, or else from the bet
+    /// ramp on the true count if there is one.
+    fn next_bet(&self, strategy: &mut dyn PlayerInput) -> Result<Money, Error> {
+        let conf = &self.engine.conf;
+        if let Some(system) = &self.engine.player.bet_strategy {
+            let bet = system.next_bet(&self.engine.bet_situation());
+            return conf
+                .fit_bet(bet, self.engine.player.money)
+                .ok_or(Error::InvalidBet(conf.table_min));
+        }
+        match &conf.bet_ramp {
+            Some(ramp) => {
+                let bet = ramp.bet(self.engine.counter.true_count, conf.base_bet);
+                conf.fit_bet(bet, self.engine.player.money)
+                    .ok_or(Error::InvalidBet(conf.table_min))
+            }
+            None => strategy.bet(&self.engine, 1, 1),
+        }
+    }
+
+    /// Plays a round of a single hand, returning the change in the bankroll, or `None` if the
+    /// shoe ran out of cards and the round was void.
+    fn play_round(&mut self, strategy: &mut dyn PlayerInput) -> Result<Option<Money>, Error> {
+        let bet = self.next_bet(strategy)?;
+        self.play_bets(strategy, &[bet])
+    }
+
+    /// Plays a round of a hand for each of the bets, as [Simulator::play_round] does.
+    pub(crate) fn play_bets(
+        &mut self,
+        strategy: &mut dyn PlayerInput,
+        bets: &[Money],
+    ) -> Result<Option<Money>, Error> {
+        let bet = bets.iter().copied().sum::<Money>();
+        let engine = &mut self.engine;
+        let bankroll = engine.player.money;
+        let (running_count, true_count) = (engine.counter.running_count, engine.counter.true_count);
+        let net = self.tally.borrow().net;
+        if let Some((round, _)) = &self.trace {
+            *round.borrow_mut() = TracedRound::default();
+        }
+        for bet in bets {
+            engine.place_bet(*bet)?;
+        }
+        let result = match engine.play_round(strategy) {
+            Ok(()) => engine.player.money - bankroll,
+            Err(Error::OutOfCards) => {
+                engine.void_round();
+                engine.shuffle();
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        if engine.shoe.cut_card_reached && !engine.conf.rules.csm {
+            engine.shuffle();
+        }
+
+        let mut tally = self.tally.borrow_mut();
+        tally.rounds += 1;
+        if let Some((round, out)) = &mut self.trace {
+            let mut round = round.borrow_mut();
+            round.round = tally.rounds;
+            round.running_count = running_count;
+            round.true_count = true_count;
+            round.bet = bet;
+            round.result = result;
+            serde_json::to_writer(&mut *out, &*round)
+                .map_err(|e| e.to_string())
+                .and_then(|()| writeln!(out).map_err(|e| e.to_string()))
+                .map_err(|e| Error::Config(format!("cannot write the trace: {}", e)))?;
+        }
+        let count = tally
+            .true_counts
+            .entry(true_count.floor() as i32)
+            .or_default();
+        count.rounds += 1;
+        count.initial_bets += bet;
+        count.result_sum += result.units_f64();
+        count.result_squares += result.units_f64().powi(2);
+        tally.initial_bets += bet;
+        tally.result_sum += result.units_f64();
+        tally.result_squares += result.units_f64().powi(2);
+
+        let batch = &mut self.batch;
+        batch.rounds += 1;
+        batch.initial_bets += bet;
+        batch.net += tally.net - net;
+        batch.result_sum += result.units_f64();
+        if batch.rounds == BATCH_ROUNDS {
+            tally.batches.add_batch(batch.mean(), batch.player_edge());
+            *batch = SimulationResult::default();
+        }
+        Ok(Some(result))
+    }
+
+    pub(crate) fn result(&self) -> SimulationResult {
+        self.tally.borrow().clone()
+    }
+}
+
+/// Plays `rounds` rounds of a single hand with `strategy` making every decision, betting the
+/// bet ramp of `conf` if there is one.
+///
+/// The shoe is reshuffled when the cut card comes out, rounds the shoe runs out of cards for are
+/// void and not counted. With a `seed` the same shoes are dealt every time, and so the results of
+/// a strategy are reproducible.
+pub fn simulate(
+    conf: GameConfig,
+    strategy: &mut dyn PlayerInput,
+    rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<SimulationResult, Error> {
+    let simulator = Simulator::new(conf, SIMULATION_BANKROLL, seed)?;
+    play_rounds(simulator, strategy, rounds, progress)
+}
+
+/// Simulates as [simulate] does, writing every round played to `trace` as a line of JSON with
+/// the counts and bet, the cards, the decisions made and the result.
+pub fn simulate_traced(
+    conf: GameConfig,
+    strategy: &mut dyn PlayerInput,
+    rounds: u64,
+    seed: Option<u64>,
+    trace: &mut dyn Write,
+    progress: &mut dyn Progress,
+) -> Result<SimulationResult, Error> {
+    let mut simulator = Simulator::new(conf, SIMULATION_BANKROLL, seed)?;
+    simulator.trace(trace);
+    play_rounds(simulator, strategy, rounds, progress)
+}
+
+/// Plays rounds as [simulate] does until the 95% confidence interval of the house edge reaches
+/// `precision` either side, e.g. 0.001 for ±0.1%.
+pub fn simulate_to_precision(
+    conf: GameConfig,
+    strategy: &mut dyn PlayerInput,
+    precision: f64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<SimulationResult, Error> {
+    if precision.is_nan() || precision <= 0.0 {
+        return Err(Error::Config(
+            "the target precision must be above 0".to_string(),
+        ));
+    }
+    let mut simulator = Simulator::new(conf, SIMULATION_BANKROLL, seed)?;
+    let mut played = 0;
+    loop {
+        if simulator.play_round(strategy)?.is_none() {
+            continue;
+        }
+        played += 1;
+        if played % BATCH_ROUNDS != 0 {
+            continue;
+        }
+        let margin = simulator.tally.borrow().house_edge_margin();
+        match margin {
+            Some(margin) if margin <= precision => break,
+            // The interval narrows with the square root of the rounds
+            Some(margin) if played % PROGRESS_INTERVAL == 0 => {
+                let total = (played as f64 * (margin / precision).powi(2)).ceil() as u64;
+                progress.update(played, total.max(played));
+            }
+            _ => {}
+        }
+    }
+    progress.update(played, played);
+    Ok(simulator.result())
+}
+
+pub(crate) fn play_rounds(
+    mut simulator: Simulator,
+    strategy: &mut dyn PlayerInput,
+    rounds: u64,
+    progress: &mut dyn Progress,
+) -> Result<SimulationResult, Error> {
+    let mut played = 0;
+    while played < rounds {
+        if simulator.play_round(strategy)?.is_some() {
+            played += 1;
+            if played % PROGRESS_INTERVAL == 0 {
+                progress.update(played, rounds);
+            }
+        }
+    }
+    progress.update(played, rounds);
+    Ok(simulator.result())
+}
+
+/// How far a long simulation has come, saved every so often so it can be resumed after it was
+/// interrupted.
+///
+/// The rounds are played in parts dealing their own shoes, seeded with the seed plus the number
+/// of the part, so the rest of the simulation only depends on the parts played and not on the
+/// state the shuffles were in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The settings of the simulation, a checkpoint only resumes the same simulation.
+    pub settings: Value,
+    pub seed: u64,
+    pub rounds: u64,
+    /// The rounds played in each part, between saving the checkpoint.
+    pub part_rounds: u64,
+    /// The parts played so far.
+    pub parts: u64,
+    /// The totals of the parts played so far.
+    pub result: SimulationResult,
+}
+
+impl Checkpoint {
+    /// The start of a simulation of `rounds` rounds with the table set up as `conf`.
+    pub fn new(conf: &GameConfig, seed: u64, rounds: u64) -> Self {
+        Self {
+            settings: Checkpoint::settings(conf),
+            seed,
+            rounds,
+            part_rounds: CHECKPOINT_ROUNDS,
+            parts: 0,
+            result: SimulationResult::default(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("cannot read {}: {}", path.display(), e)))?;
+        serde_json::from_str(&text)
+            .map_err(|e| Error::Config(format!("{} is not a checkpoint: {}", path.display(), e)))
+    }
+
+    /// Writes the checkpoint next to the file and moves it over, so an interruption while
+    /// saving leaves the previous checkpoint as it was.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let cannot_write =
+            |e: std::io::Error| Error::Config(format!("cannot write {}: {}", path.display(), e));
+        let text = serde_json::to_string(self).map_err(|e| Error::Config(e.to_string()))?;
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        std::fs::write(&partial, text).map_err(cannot_write)?;
+        std::fs::rename(&partial, path).map_err(cannot_write)
+    }
+
+    /// Whether the checkpoint is of a simulation of `rounds` rounds with the table set up as
+    /// `conf`.
+    pub fn matches(&self, conf: &GameConfig, rounds: u64) -> bool {
+        self.matches_settings(conf) && self.rounds == rounds
+    }
+
+    /// Whether the checkpoint is of a simulation with the table set up as `conf`, however many
+    /// rounds it was of.
+    pub fn matches_settings(&self, conf: &GameConfig) -> bool {
+        self.settings == Checkpoint::settings(conf)
+    }
+
+    /// The settings of `conf` the result of a simulation depends on.
+    fn settings(conf: &GameConfig) -> Value {
+        let settings = json!({
+            "rules": conf.rules,
+            "hands": conf.hands,
+            "twenty_one_plus_three": conf.twenty_one_plus_three,
+            "bust_it": conf.bust_it,
+            "table_min": conf.table_min,
+            "table_max": conf.table_max,
+            "bet_unit": conf.bet_unit,
+            "shuffle": conf.shuffle.to_string(),
+            "counting": conf.counting.to_string(),
+            "ace_side_count": conf.ace_side_count,
+            "deck_estimation": conf.deck_estimation.to_string(),
+            "base_bet": conf.base_bet,
+            "bet_system": conf.bet_system.map(|system| system.to_string()),
+            "bet_ramp": conf.bet_ramp.as_ref().map(|ramp| ramp.to_string()),
+            "auto_ramp": conf.auto_ramp,
+            "chart": conf.chart.as_ref().map(|chart| chart.path.display().to_string()),
+            "tips": conf.tips,
+        });
+        // Read back as a saved checkpoint would be, so the numbers compare equal after loading
+        serde_json::from_str(&settings.to_string()).unwrap_or(settings)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.result.rounds >= self.rounds
+    }
+}
+
+/// Plays the rest of the simulation of the checkpoint as [simulate] does, one part at a time,
+/// passing the checkpoint to `save` after each part.
+///
+/// The result of a simulation that was resumed is the same as that of one that wasn't, but not
+/// the same as that of [simulate] with the same seed, which deals all rounds from one seed.
+pub fn simulate_resumable(
+    conf: GameConfig,
+    strategy: &mut dyn PlayerInput,
+    checkpoint: &mut Checkpoint,
+    save: &mut dyn FnMut(&Checkpoint) -> Result<(), Error>,
+    progress: &mut dyn Progress,
+) -> Result<SimulationResult, Error> {
+    if !checkpoint.matches_settings(&conf) {
+        return Err(Error::Config(
+            "the checkpoint is of a simulation with other settings".to_string(),
+        ));
+    }
+    let part_rounds = checkpoint.part_rounds.max(1);
+    while !checkpoint.is_done() {
+        let done = checkpoint.result.rounds;
+        let rounds = part_rounds.min(checkpoint.rounds - done);
+        let mut part = PartProgress {
+            progress: &mut *progress,
+            before: done,
+            total: checkpoint.rounds,
+        };
+        let seed = checkpoint.seed.wrapping_add(checkpoint.parts);
+        let result = simulate(conf.clone(), strategy, rounds, Some(seed), &mut part)?;
+        checkpoint.result.add(&result);
+        checkpoint.parts += 1;
+        save(checkpoint)?;
+    }
+    Ok(checkpoint.result.clone())
+}
+
+/// How often a bankroll was lost before it reached the target, over many sessions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuinResult {
+    pub sessions: u64,
+    /// The sessions that ended with less than the table minimum left.
+    pub ruined: u64,
+    pub reached_target: u64,
+    /// The totals over all rounds of all sessions.
+    pub totals: SimulationResult,
+}
+
+impl RuinResult {
+    /// The fraction of the sessions that were ruined.
+    pub fn risk_of_ruin(&self) -> f64 {
+        if self.sessions == 0 {
+            return 0.0;
+        }
+        self.ruined as f64 / self.sessions as f64
+    }
+
+    /// The risk of ruin by the diffusion approximation from the simulated mean and variance of
+    /// a round, of losing `bankroll` before winning enough to reach `target`.
+    pub fn analytic_risk_of_ruin(&self, bankroll: Money, target: Money) -> f64 {
+        let (mean, variance) = (self.totals.mean(), self.totals.variance());
+        let (bankroll, target) = (bankroll.units_f64(), target.units_f64());
+        if variance <= 0.0 {
+            return if mean < 0.0 { 1.0 } else { 0.0 };
+        }
+        let drift = 2.0 * mean / variance;
+        if drift.abs() < 1e-12 {
+            return 1.0 - bankroll / target;
+        }
+        let survive = |amount: f64| (-drift * amount).exp();
+        (survive(bankroll) - survive(target)) / (1.0 - survive(target))
+    }
+}
+
+/// Plays `sessions` sessions starting from `bankroll`, each until the player can't afford the
+/// table minimum, reaches `target` or has played `max_rounds` rounds.
+///
+/// Each session deals its own shoes, from `seed` plus the session number if a seed is given.
+#[allow(clippy::too_many_arguments)]
+pub fn risk_of_ruin(
+    conf: &GameConfig,
+    strategy: &mut dyn PlayerInput,
+    bankroll: Money,
+    target: Money,
+    sessions: u64,
+    max_rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<RuinResult, Error> {
+    if sessions == 0 {
+        return Err(Error::Config(
+            "the risk of ruin needs at least one session".to_string(),
+        ));
+    }
+    let mut ruin = RuinResult::default();
+    let mut totals = SimulationResult::default();
+    for session in 0..sessions {
+        let session_seed = seed.map(|seed| seed.wrapping_add(session));
+        let mut simulator = Simulator::new(conf.clone(), bankroll, session_seed)?;
+        let mut played = 0;
+        loop {
+            let money = simulator.engine.player.money;
+            if conf.fit_bet(conf.table_min, money).is_none() {
+                ruin.ruined += 1;
+                break;
+            }
+            if money >= target {
+                ruin.reached_target += 1;
+                break;
+            }
+            if played == max_rounds {
+                break;
+            }
+            if simulator.play_round(strategy)?.is_some() {
+                played += 1;
+            }
+        }
+        totals.add(&simulator.result());
+        ruin.sessions += 1;
+        progress.update(ruin.sessions, sessions);
+    }
+    ruin.totals = totals;
+    Ok(ruin)
+}
+
+/// Simulates each of the strategies over the same shoes, from a single seed, to compare them.
+pub fn compare_strategies(
+    conf: &GameConfig,
+    strategies: &[Strategy],
+    rounds: u64,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(Strategy, SimulationResult)>, Error> {
+    let total = rounds * strategies.len() as u64;
+    strategies
+        .iter()
+        .enumerate()
+        .map(|(i, strategy)| {
+            let mut player = *strategy;
+            let mut part = PartProgress {
+                progress: &mut *progress,
+                before: rounds * i as u64,
+                total,
+            };
+            let result = simulate(conf.clone(), &mut player, rounds, Some(seed), &mut part)?;
+            Ok((*strategy, result))
+        })
+        .collect()
+}
+
+/// Simulates the counter bot with each of the counting systems over the same shoes, from a single
+/// seed, to compare how much they are worth.
+pub fn compare_counts(
+    conf: &GameConfig,
+    systems: &[CountingMethod],
+    rounds: u64,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(CountingMethod, SimulationResult)>, Error> {
+    let total = rounds * systems.len() as u64;
+    systems
+        .iter()
+        .enumerate()
+        .map(|(i, system)| {
+            let conf = GameConfig {
+                counting: system.clone(),
+                ..conf.clone()
+            };
+            let mut part = PartProgress {
+                progress: &mut *progress,
+                before: rounds * i as u64,
+                total,
+            };
+            let result = simulate(conf, &mut Strategy::HiLo, rounds, Some(seed), &mut part)?;
+            Ok((system.clone(), result))
+        })
+        .collect()
+}
+
+/// How a bot did over the sessions of a tournament.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standing {
+    pub strategy: Strategy,
+    pub sessions: u64,
+    pub rounds: u64,
+    /// The bankrolls the sessions ended with, added up.
+    pub final_bankrolls: Money,
+    pub best: Money,
+    pub worst: Money,
+    /// The sessions it ended with the most money of all bots, every bot of a tie wins.
+    pub wins: u64,
+    /// The sessions it couldn't afford the table minimum any more.
+    pub busts: u64,
+}
+
+impl Standing {
+    /// The average bankroll at the end of a session.
+    pub fn mean_bankroll(&self) -> Money {
+        if self.sessions == 0 {
+            return Money::ZERO;
+        }
+        Money::from_units_f64(self.final_bankrolls.units_f64() / self.sessions as f64)
+    }
+}
+
+/// Plays `sessions` sessions of `rounds` rounds from `bankroll` with each of the strategies over
+/// the same shoes, the shoes of a session are dealt from `seed` plus its number. A bot that can't
+/// afford the table minimum sits out the rest of the session. The standings are ranked by the
+/// average final bankroll, the best first.
+#[allow(clippy::too_many_arguments)]
+pub fn tournament(
+    conf: &GameConfig,
+    strategies: &[Strategy],
+    sessions: u64,
+    rounds: u64,
+    bankroll: Money,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Vec<Standing>, Error> {
+    let mut standings = strategies
+        .iter()
+        .map(|strategy| Standing {
+            strategy: *strategy,
+            sessions: 0,
+            rounds: 0,
+            final_bankrolls: Money::ZERO,
+            best: bankroll,
+            worst: bankroll,
+            wins: 0,
+            busts: 0,
+        })
+        .collect::<Vec<_>>();
+    for session in 0..sessions {
+        let session_seed = seed.wrapping_add(session);
+        let mut finals = vec![];
+        for standing in standings.iter_mut() {
+            let mut simulator = Simulator::new(conf.clone(), bankroll, Some(session_seed))?;
+            let mut strategy = standing.strategy;
+            let mut played = 0;
+            while played < rounds {
+                let money = simulator.engine.player.money;
+                if conf.fit_bet(conf.table_min, money).is_none() {
+                    standing.busts += 1;
+                    break;
+                }
+                if simulator.play_round(&mut strategy)?.is_some() {
+                    played += 1;
+                }
+            }
+            let money = simulator.engine.player.money;
+            standing.sessions += 1;
+            standing.rounds += played;
+            standing.final_bankrolls += money;
+            if session == 0 {
+                (standing.best, standing.worst) = (money, money);
+            }
+            standing.best = standing.best.max(money);
+            standing.worst = standing.worst.min(money);
+            finals.push(money);
+        }
+        let top = finals.iter().copied().max().unwrap_or(Money::ZERO);
+        for (standing, money) in standings.iter_mut().zip(finals) {
+            standing.wins += (money == top) as u64;
+        }
+        progress.update(session + 1, sessions);
+    }
+    standings.sort_by_key(|standing| std::cmp::Reverse(standing.final_bankrolls));
+    Ok(standings)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::betting::BetSystem;
+    use crate::config::GameConfig;
+    use crate::counting::CountingMethod;
+    use crate::error::Error;
+    use crate::input::{BasicStrategy, Strategy};
+    use crate::money::Money;
+    use crate::simulation::{
+        compare_counts, compare_strategies, risk_of_ruin, simulate, simulate_resumable,
+        simulate_to_precision, simulate_traced, tournament, Checkpoint, NoProgress, Progress,
+        SimulationResult,
+    };
+
+    #[test]
+    fn test_simulate() {
+        let result = simulate(
+            GameConfig::default(),
+            &mut BasicStrategy,
+            2000,
+            Some(1),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(result.rounds, 2000);
+        assert!(result.hands >= 2000);
+        let breakdown = result.breakdown;
+        assert_eq!(
+            breakdown.base
+                + breakdown.blackjacks
+                + breakdown.doubles
+                + breakdown.splits
+                + breakdown.surrenders,
+            result.net
+        );
+        assert!(breakdown.blackjacks > Money::ZERO);
+        assert!(breakdown.surrenders < Money::ZERO);
+
+        // Basic strategy has no edge, so no SCORE
+        assert!(result.desirability_index() < 0.0);
+        assert_eq!(result.score(), None);
+        let bankroll = Money::from_units(1000);
+        assert!(result.certainty_equivalent(bankroll) < result.mean());
+
+        let counts = &result.true_counts;
+        assert_eq!(counts.values().map(|c| c.rounds).sum::<u64>(), 2000);
+        assert!(counts[&0].rounds > counts[&3].rounds);
+        assert_eq!(result.share_from_count(i32::MIN), 1.0);
+        assert!(result.share_from_count(1) > result.share_from_count(3));
+        let net = counts.values().map(|c| c.result_sum).sum::<f64>();
+        assert!((net - result.result_sum).abs() < 1e-6);
+
+        // The same seed deals the same shoes
+        let again = simulate(
+            GameConfig::default(),
+            &mut BasicStrategy,
+            2000,
+            Some(1),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(again, result);
+    }
+
+    /// Remembers every progress update.
+    #[derive(Default)]
+    struct Updates(Vec<(u64, u64)>);
+
+    impl Progress for Updates {
+        fn update(&mut self, done: u64, total: u64) {
+            self.0.push((done, total));
+        }
+    }
+
+    #[test]
+    fn test_compare_strategies() {
+        let strategies = [Strategy::Basic, Strategy::NeverBust];
+        let mut updates = Updates::default();
+        let results =
+            compare_strategies(&GameConfig::default(), &strategies, 2000, 3, &mut updates).unwrap();
+        // The progress is through the rounds of both strategies
+        assert_eq!(updates.0, vec![(2000, 4000), (4000, 4000)]);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, Strategy::Basic);
+        for (_, result) in &results {
+            assert_eq!(result.rounds, 2000);
+            assert!(result.variance() > 0.5);
+        }
+        // Never busting never doubles or splits
+        assert_eq!(results[1].1.breakdown.doubles, Money::ZERO);
+        assert_eq!(results[1].1.breakdown.splits, Money::ZERO);
+
+        let systems = [CountingMethod::HiLo, CountingMethod::OmegaII];
+        let results =
+            compare_counts(&GameConfig::default(), &systems, 2000, 3, &mut NoProgress).unwrap();
+        assert_eq!(results[1].0, CountingMethod::OmegaII);
+        // The counter bot spreads its bets with either count
+        for (_, result) in &results {
+            assert_eq!(result.rounds, 2000);
+            assert!(result.initial_bets > Money::from_units(2000));
+        }
+    }
+
+    #[test]
+    fn test_bet_system() {
+        let simulate_with = |strategy: Strategy, bet_system| {
+            let conf = GameConfig {
+                bet_system,
+                ..GameConfig::default()
+            };
+            simulate(conf, &mut { strategy }, 10_000, Some(6), &mut NoProgress).unwrap()
+        };
+        // The counter bot flat bets with the flat betting system, and basic strategy ramps its
+        // bets on the count with the ramp
+        let flat = simulate_with(Strategy::HiLo, Some(BetSystem::Flat));
+        assert_eq!(flat.initial_bets, Money::from_units(flat.rounds as i64));
+        let counted = simulate_with(Strategy::HiLo, None);
+        assert!(counted.initial_bets > flat.initial_bets);
+        let ramped = simulate_with(Strategy::Basic, Some(BetSystem::Ramp));
+        assert!(ramped.initial_bets > flat.initial_bets);
+        let martingale = simulate_with(Strategy::Basic, Some(BetSystem::Martingale));
+        assert!(martingale.initial_bets > flat.initial_bets);
+    }
+
+    #[test]
+    fn test_tournament() {
+        let strategies = [Strategy::NeverBust, Strategy::Basic, Strategy::MimicDealer];
+        let bankroll = Money::from_units(100);
+        let tournament = |seed| {
+            tournament(
+                &GameConfig::default(),
+                &strategies,
+                40,
+                500,
+                bankroll,
+                seed,
+                &mut NoProgress,
+            )
+            .unwrap()
+        };
+        let standings = tournament(8);
+        assert_eq!(standings.len(), 3);
+        // Basic strategy loses the least
+        assert_eq!(standings[0].strategy, Strategy::Basic);
+        assert!(standings[0].mean_bankroll() > standings[1].mean_bankroll());
+        for standing in &standings {
+            assert_eq!(standing.sessions, 40);
+            assert!(standing.worst <= standing.mean_bankroll());
+            assert!(standing.mean_bankroll() <= standing.best);
+            assert!(standing.rounds <= 40 * 500);
+        }
+        assert!(standings.iter().map(|standing| standing.wins).sum::<u64>() >= 40);
+        assert_eq!(tournament(8), standings);
+    }
+
+    #[test]
+    fn test_simulate_traced() {
+        let mut trace = vec![];
+        let result = simulate_traced(
+            GameConfig::default(),
+            &mut BasicStrategy,
+            300,
+            Some(5),
+            &mut trace,
+            &mut NoProgress,
+        )
+        .unwrap();
+        let untraced = simulate(
+            GameConfig::default(),
+            &mut BasicStrategy,
+            300,
+            Some(5),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(result, untraced);
+
+        let rounds = String::from_utf8(trace)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(rounds.len(), 300);
+        assert_eq!(rounds[299]["round"], 300);
+        let net = rounds
+            .iter()
+            .map(|round| round["result"].as_f64().unwrap())
+            .sum::<f64>();
+        assert!((net - result.result_sum).abs() < 1e-6);
+        for round in &rounds {
+            assert!(round["upcard"].is_object());
+            assert!(!round["hands"].as_array().unwrap().is_empty());
+            for decision in round["decisions"].as_array().unwrap() {
+                assert!(decision["cards"]["cards"].as_array().unwrap().len() >= 2);
+            }
+        }
+        assert!(rounds
+            .iter()
+            .any(|round| round["decisions"][0]["action"] == "Hit"));
+    }
+
+    #[test]
+    fn test_confidence_intervals() {
+        let conf = GameConfig::default();
+        let result = simulate(
+            conf.clone(),
+            &mut BasicStrategy,
+            2000,
+            Some(9),
+            &mut NoProgress,
+        )
+        .unwrap();
+        // Too few batches to tell
+        assert_eq!(result.batches.batches, 2);
+        assert_eq!(result.house_edge_margin(), None);
+
+        let result = simulate_to_precision(
+            conf.clone(),
+            &mut BasicStrategy,
+            0.05,
+            Some(9),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(result.rounds % 1000, 0);
+        let margin = result.house_edge_margin().unwrap();
+        assert!(margin <= 0.05);
+        assert!(result.mean_margin().unwrap() > 0.0);
+        // Batches of equal rounds, so the mean of the batch means is the mean
+        let batches = &result.batches;
+        assert!((batches.mean_sum / batches.batches as f64 - result.mean()).abs() < 1e-9);
+
+        assert!(
+            simulate_to_precision(conf, &mut BasicStrategy, 0.0, Some(9), &mut NoProgress).is_err()
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let result = SimulationResult {
+            rounds: 100,
+            result_sum: 2.0,
+            result_squares: 133.0,
+            ..Default::default()
+        };
+        let variance: f64 = 1.33 - 0.02 * 0.02;
+        assert!((result.desirability_index() - 20.0 / variance.sqrt()).abs() < 1e-9);
+        // The win rate of a 10,000 unit bankroll at the Kelly bet, per 100 rounds
+        let kelly_bet = 10_000.0 * 0.02 / variance;
+        assert!((result.score().unwrap() - 100.0 * kelly_bet * 0.02).abs() < 1e-6);
+        let ce = result.certainty_equivalent(Money::from_units(10_000));
+        assert!((ce - (0.02 - variance / 20_000.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_simulate_resumable() {
+        let conf = GameConfig::default();
+        let mut checkpoint = Checkpoint::new(&conf, 3, 2000);
+        checkpoint.part_rounds = 500;
+        let whole = simulate_resumable(
+            conf.clone(),
+            &mut BasicStrategy,
+            &mut checkpoint.clone(),
+            &mut |_| Ok(()),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(whole.rounds, 2000);
+
+        // Interrupted after the second part, then resumed from the saved checkpoint
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.json", std::process::id()));
+        let interrupted = simulate_resumable(
+            conf.clone(),
+            &mut BasicStrategy,
+            &mut checkpoint,
+            &mut |checkpoint| {
+                checkpoint.save(&path)?;
+                match checkpoint.parts {
+                    2 => Err(Error::InputClosed),
+                    _ => Ok(()),
+                }
+            },
+            &mut NoProgress,
+        );
+        assert_eq!(interrupted, Err(Error::InputClosed));
+        let mut saved = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved, checkpoint);
+        assert_eq!(saved.result.rounds, 1000);
+        let resumed = simulate_resumable(
+            conf.clone(),
+            &mut BasicStrategy,
+            &mut saved,
+            &mut |_| Ok(()),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(resumed, whole);
+        assert!(saved.is_done());
+
+        // Only the same simulation is resumed
+        assert!(saved.matches(&conf, 2000));
+        assert!(!saved.matches(&conf, 3000));
+        let mut other = conf;
+        other.rules.hit_soft_17 = true;
+        assert!(!saved.matches(&other, 2000));
+        assert!(simulate_resumable(
+            other,
+            &mut BasicStrategy,
+            &mut saved,
+            &mut |_| Ok(()),
+            &mut NoProgress
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_risk_of_ruin() {
+        let bankroll = Money::from_units(20);
+        let target = Money::from_units(40);
+        let conf = GameConfig::default();
+        let mut strategy = Strategy::NeverBust;
+        let ruin = risk_of_ruin(
+            &conf,
+            &mut strategy,
+            bankroll,
+            target,
+            50,
+            100_000,
+            Some(1),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(ruin.sessions, 50);
+        assert_eq!(ruin.ruined + ruin.reached_target, 50);
+        assert!(ruin.risk_of_ruin() > 0.5);
+        let analytic = ruin.analytic_risk_of_ruin(bankroll, target);
+        assert!((0.5..1.0).contains(&analytic));
+
+        // A session that can't be played to the end is neither ruined nor successful
+        let ruin = risk_of_ruin(
+            &conf,
+            &mut strategy,
+            bankroll,
+            target,
+            5,
+            1,
+            Some(1),
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(ruin.ruined + ruin.reached_target, 0);
+        assert_eq!(ruin.totals.rounds, 5);
+
+        let none = risk_of_ruin(
+            &conf,
+            &mut strategy,
+            bankroll,
+            target,
+            0,
+            100,
+            Some(1),
+            &mut NoProgress,
+        );
+        assert!(matches!(none, Err(Error::Config(_))));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::chart::{hand_value, DEALER_RESULTS, RANKS};
+use crate::error::Error;
+use crate::rules::Rules;
+use crate::types::{Hand, Move, Shoe, Value, TWENTY_ONE};
+use crate::variants::Variant;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The rank of a card by its lowest value, aces at 1 and every ten valued card at 10.
+pub fn rank(value: &Value) -> usize {
+    match value {
+        Value::Ace => 1,
+        value => value.value() as usize,
+    }
+}
+
+/// The cards left to draw by rank, as the player would know them from the cards seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Composition {
+    /// The cards of each rank, indexed by [`rank`].
+    counts: [u16; RANKS + 1],
+}
+
+impl Composition {
+    /// The cards of a full shoe of `decks` decks of the variant.
+    pub fn full(decks: u32, variant: Variant) -> Result<Self, Error> {
+        Ok(Self::from_shoe(&Shoe::new(decks, variant)?))
+    }
+
+    /// The cards still in the shoe, ignoring the discards.
+    pub fn from_shoe(shoe: &Shoe) -> Self {
+        let mut counts = [0; RANKS + 1];
+        for (value, count) in shoe.remaining_by_rank() {
+            counts[rank(&value)] += count as u16;
+        }
+        Self { counts }
+    }
+
+    /// The cards of the rank of `value` left, every ten valued card counts as a ten.
+    pub fn count(&self, value: &Value) -> u32 {
+        self.counts[rank(value)] as u32
+    }
+
+    pub fn total(&self) -> u32 {
+        self.counts.iter().map(|count| *count as u32).sum()
+    }
+
+    /// Takes out a card that was seen, failing if there is none of its rank left.
+    pub fn remove(&mut self, value: &Value) -> Result<(), Error> {
+        let count = &mut self.counts[rank(value)];
+        if *count == 0 {
+            return Err(Error::Config(format!(
+                "there is no {} left to remove",
+                value
+            )));
+        }
+        *count -= 1;
+        Ok(())
+    }
+
+    /// Puts a card back, e.g. the hole card the player hasn't seen.
+    pub fn add(&mut self, value: &Value) {
+        self.counts[rank(value)] += 1;
+    }
+
+    fn without(&self, rank: usize) -> Self {
+        let mut composition = *self;
+        composition.counts[rank] -= 1;
+        composition
+    }
+
+    /// The chance the next card is of `rank`.
+    fn chance(&self, rank: usize) -> f64 {
+        self.counts[rank] as f64 / self.total() as f64
+    }
+
+    /// The ranks that can be drawn with the chance of each.
+    fn draws(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        let total = self.total() as f64;
+        (1..=RANKS)
+            .filter(|rank| self.counts[*rank] > 0)
+            .map(move |rank| (rank, self.counts[rank] as f64 / total))
+    }
+}
+
+/// The exact expected result of every move on a hand in initial bets, `None` where the move isn't
+/// allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DecisionEv {
+    pub stand: f64,
+    pub hit: f64,
+    pub double: Option<f64>,
+    pub split: Option<f64>,
+    pub surrender: Option<f64>,
+}
+
+impl DecisionEv {
+    /// The move with the highest expected result, standing on a tie with hitting.
+    pub fn best(&self) -> (Move, f64) {
+        [
+            (Move::Hit, Some(self.hit)),
+            (Move::Double, self.double),
+            (Move::Split, self.split),
+            (Move::Surrender, self.surrender),
+        ]
+        .into_iter()
+        .filter_map(|(action, ev)| ev.map(|ev| (action, ev)))
+        .fold((Move::Stand, self.stand), |best, (action, ev)| {
+            if ev > best.1 {
+                (action, ev)
+            } else {
+                best
+            }
+        })
+    }
+
+    /// The expected result of a move, `None` if it isn't allowed.
+    pub fn of(&self, action: Move) -> Option<f64> {
+        match action {
+            Move::Stand => Some(self.stand),
+            Move::Hit => Some(self.hit),
+            Move::Double => self.double,
+            Move::Split => self.split,
+            Move::Surrender => self.surrender,
+        }
+    }
+}
+
+/// The dealer's final totals from 17 to 21, then a bust.
+type DealerResults = [f64; DEALER_RESULTS];
+
+/// Works out the expected results against one upcard, removing every card drawn from the
+/// composition it is drawn from.
+struct Solver<'a> {
+    rules: &'a Rules,
+    upcard: usize,
+    /// The dealer's final totals from a hand and the cards left.
+    dealer: HashMap<(Composition, usize, bool), DealerResults>,
+    /// The dealer's final totals behind the upcard given no blackjack, with the chance of one.
+    outcomes: HashMap<Composition, (DealerResults, f64)>,
+    hits: HashMap<(Composition, usize, bool), f64>,
+}
+
+impl<'a> Solver<'a> {
+    fn new(rules: &'a Rules, upcard: usize) -> Self {
+        Self {
+            rules,
+            upcard,
+            dealer: HashMap::new(),
+            outcomes: HashMap::new(),
+            hits: HashMap::new(),
+        }
+    }
+
+    /// The card that makes a blackjack with the upcard.
+    fn blackjack_card(&self) -> Option<usize> {
+        match self.upcard {
+            1 => Some(10),
+            10 => Some(1),
+            _ => None,
+        }
+    }
+
+    fn dealer_results(&mut self, cards: Composition, hard: usize, ace: bool) -> DealerResults {
+        let value = hand_value(hard, ace);
+        let mut results = [0.0; DEALER_RESULTS];
+        if value > TWENTY_ONE as usize {
+            results[DEALER_RESULTS - 1] = 1.0;
+            return results;
+        }
+        let soft = value != hard;
+        if !(value < 17 || (self.rules.hit_soft_17 && value == 17 && soft)) {
+            results[value - 17] = 1.0;
+            return results;
+        }
+        if let Some(results) = self.dealer.get(&(cards, hard, ace)) {
+            return *results;
+        }
+        for (card, chance) in cards.draws() {
+            let next = self.dealer_results(cards.without(card), hard + card, ace || card == 1);
+            for (total, result) in results.iter_mut().zip(next) {
+                *total += chance * result;
+            }
+        }
+        self.dealer.insert((cards, hard, ace), results);
+        results
+    }
+
+    /// The dealer's final totals given there is no blackjack, and the chance there is one.
+    fn outcomes(&mut self, cards: Composition) -> (DealerResults, f64) {
+        if let Some(outcomes) = self.outcomes.get(&cards) {
+            return *outcomes;
+        }
+        let blackjack_card = self.blackjack_card();
+        let blackjack = blackjack_card.map_or(0.0, |card| cards.chance(card));
+        let mut results = [0.0; DEALER_RESULTS];
+        for (hole, chance) in cards.draws() {
+            if Some(hole) == blackjack_card {
+                continue;
+            }
+            let chance = chance / (1.0 - blackjack);
+            let upcard = self.upcard;
+            let next =
+                self.dealer_results(cards.without(hole), upcard + hole, upcard == 1 || hole == 1);
+            for (total, result) in results.iter_mut().zip(next) {
+                *total += chance * result;
+            }
+        }
+        self.outcomes.insert(cards, (results, blackjack));
+        (results, blackjack)
+    }
+
+    /// The expected result of standing on `value`, given the dealer has no blackjack.
+    fn stand(&mut self, cards: Composition, value: usize) -> f64 {
+        if value > TWENTY_ONE as usize {
+            return -1.0;
+        }
+        let (dealer, _) = self.outcomes(cards);
+        let mut ev = dealer[DEALER_RESULTS - 1];
+        for (total, chance) in (17..).zip(&dealer[..DEALER_RESULTS - 1]) {
+            if value > total {
+                ev += chance;
+            } else if value < total {
+                ev -= chance;
+            }
+        }
+        ev
+    }
+
+    fn best_hit_or_stand(&mut self, cards: Composition, hard: usize, ace: bool) -> f64 {
+        if hard > TWENTY_ONE as usize {
+            return -1.0;
+        }
+        if let Some(ev) = self.hits.get(&(cards, hard, ace)) {
+            return *ev;
+        }
+        let ev = self
+            .stand(cards, hand_value(hard, ace))
+            .max(self.hit(cards, hard, ace));
+        self.hits.insert((cards, hard, ace), ev);
+        ev
+    }
+
+    fn hit(&mut self, cards: Composition, hard: usize, ace: bool) -> f64 {
+        cards
+            .draws()
+            .map(|(card, chance)| {
+                chance * self.best_hit_or_stand(cards.without(card), hard + card, ace || card == 1)
+            })
+            .sum()
+    }
+
+    fn double(&mut self, cards: Composition, hard: usize, ace: bool) -> f64 {
+        cards
+            .draws()
+            .map(|(card, chance)| {
+                let value = hand_value(hard + card, ace || card == 1);
+                2.0 * chance * self.stand(cards.without(card), value)
+            })
+            .sum()
+    }
+
+    fn can_double(&self, hard: usize, ace: bool) -> bool {
+        self.rules.double_on.allows(hand_value(hard, ace) as u32)
+    }
+
+    /// The expected result of one of the hands after splitting a pair of `card`s, which isn't
+    /// split again. The other hand is taken not to draw from the cards first.
+    fn split_hand(&mut self, cards: Composition, card: usize) -> f64 {
+        cards
+            .draws()
+            .map(|(drawn, chance)| {
+                let (rest, hard, ace) =
+                    (cards.without(drawn), card + drawn, card == 1 || drawn == 1);
+                let ev = if card == 1 && !self.rules.hit_split_aces {
+                    self.stand(rest, hand_value(hard, ace))
+                } else if self.rules.double_after_split && self.can_double(hard, ace) {
+                    self.best_hit_or_stand(rest, hard, ace)
+                        .max(self.double(rest, hard, ace))
+                } else {
+                    self.best_hit_or_stand(rest, hard, ace)
+                };
+                chance * ev
+            })
+            .sum()
+    }
+
+    /// The expected result of a move overall, from its result without a dealer blackjack. Unless
+    /// the dealer peeks, the `stake` is lost to a blackjack found after the player acted.
+    fn overall(&mut self, cards: Composition, ev: f64, stake: f64) -> f64 {
+        if self.rules.dealer_peek {
+            return ev;
+        }
+        let (_, blackjack) = self.outcomes(cards);
+        let stake = if self.rules.original_bets_only {
+            1.0
+        } else {
+            stake
+        };
+        (1.0 - blackjack) * ev - blackjack * stake
+    }
+}
+
+/// Works out the exact expected result of every move on the player's hand against the upcard,
+/// with `cards` the cards the player hasn't seen, i.e. without the hand and the upcard.
+///
+/// Every card the player and the dealer draw is removed from the cards left, so the advice
+/// depends on the full composition rather than a count. Given the dealer peeked, the hole card
+/// isn't the one that makes a blackjack, which is only applied to the dealer's cards. A pair is
+/// split once, each hand drawing from the cards left after the split.
+pub fn solve(
+    rules: &Rules,
+    cards: &Composition,
+    hand: &Hand,
+    upcard: &Value,
+) -> Result<DecisionEv, Error> {
+    if rules.variant != Variant::Standard || rules.charlie.is_some() {
+        return Err(Error::Config(
+            "exact expected results can only be worked out for standard blackjack without a Charlie"
+                .to_string(),
+        ));
+    }
+    if hand.cards.is_empty() || cards.total() == 0 {
+        return Err(Error::Config(
+            "a hand and cards left to draw are needed".to_string(),
+        ));
+    }
+    let cards = *cards;
+    let hard = hand.hard_value() as usize;
+    let ace = hand.cards.iter().any(|card| card.value == Value::Ace);
+    let first_move = hand.cards.len() == 2;
+    let mut solver = Solver::new(rules, rank(upcard));
+
+    let stand = solver.stand(cards, hand.calc_value() as usize);
+    let stand = solver.overall(cards, stand, 1.0);
+    let hit = solver.hit(cards, hard, ace);
+    let hit = solver.overall(cards, hit, 1.0);
+    let double = (first_move && solver.can_double(hard, ace)).then(|| {
+        let double = solver.double(cards, hard, ace);
+        solver.overall(cards, double, 2.0)
+    });
+    let split = (hand.is_pair() && rules.max_splits > 0).then(|| {
+        let split = 2.0 * solver.split_hand(cards, rank(&hand.cards[0].value));
+        solver.overall(cards, split, 2.0)
+    });
+    // A surrendered hand is settled before the hole card is looked at, so it loses half the bet
+    // even to a blackjack the dealer didn't peek for
+    let surrender = (first_move && rules.surrender).then_some(-0.5);
+    Ok(DecisionEv {
+        stand,
+        hit,
+        double,
+        split,
+        surrender,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::Rules;
+    use crate::solver::{solve, Composition};
+    use crate::types::{Card, Hand, Move, Suit, Value};
+    use crate::variants::Variant;
+
+    /// The hand of `values` and the cards of `decks` decks left once it and the upcard are seen.
+    fn deal(decks: u32, values: &[Value], upcard: &Value) -> (Hand, Composition) {
+        let mut cards = Composition::full(decks, Variant::Standard).unwrap();
+        let mut hand = Hand::default();
+        for value in values {
+            cards.remove(value).unwrap();
+            hand.add_card(Card {
+                suit: Suit::Hearts,
+                value: value.clone(),
+            });
+        }
+        cards.remove(upcard).unwrap();
+        (hand, cards)
+    }
+
+    #[test]
+    fn test_composition() {
+        let mut cards = Composition::full(1, Variant::Standard).unwrap();
+        assert_eq!(cards.total(), 52);
+        assert_eq!(cards.count(&Value::King), 16);
+        for _ in 0..4 {
+            cards.remove(&Value::Ace).unwrap();
+        }
+        assert!(cards.remove(&Value::Ace).is_err());
+        cards.add(&Value::Ace);
+        assert_eq!(cards.count(&Value::Ace), 1);
+    }
+
+    #[test]
+    fn test_solve() {
+        let rules = Rules::default();
+        let (hand, cards) = deal(6, &[Value::Ten, Value::Six], &Value::Ten);
+        let ev = solve(&rules, &cards, &hand, &Value::Ten).unwrap();
+        assert_eq!(ev.best().0, Move::Surrender);
+        assert_eq!(ev.surrender, Some(-0.5));
+        assert!(ev.hit > ev.stand);
+        assert_eq!(ev.split, None);
+
+        let (hand, cards) = deal(6, &[Value::Six, Value::Five], &Value::Six);
+        let ev = solve(&rules, &cards, &hand, &Value::Six).unwrap();
+        assert_eq!(ev.best().0, Move::Double);
+        assert!(ev.double.unwrap() > 0.6);
+
+        let (hand, cards) = deal(6, &[Value::Eight, Value::Eight], &Value::Seven);
+        let ev = solve(&rules, &cards, &hand, &Value::Seven).unwrap();
+        assert_eq!(ev.best().0, Move::Split);
+
+        // Standing on 16 against a 6 loses 15.4% in an infinite shoe, close to six decks
+        let (hand, cards) = deal(6, &[Value::Ten, Value::Six], &Value::Six);
+        let ev = solve(&rules, &cards, &hand, &Value::Six).unwrap();
+        assert!((ev.stand + 0.154).abs() < 0.01);
+
+        // Once the tens are gone, 12 against a 2 can't bust on the next card
+        let (hand, mut cards) = deal(1, &[Value::Ten, Value::Two], &Value::Two);
+        while cards.count(&Value::Ten) > 0 {
+            cards.remove(&Value::Ten).unwrap();
+        }
+        let ev = solve(&rules, &cards, &hand, &Value::Two).unwrap();
+        assert_eq!(ev.best().0, Move::Hit);
+        assert_eq!(ev.hit, ev.best().1);
+    }
+
+    #[test]
+    fn test_solve_surrender_without_peek() {
+        let rules = Rules {
+            dealer_peek: false,
+            ..Rules::default()
+        };
+        let (hand, cards) = deal(6, &[Value::Ten, Value::Six], &Value::Ace);
+        let ev = solve(&rules, &cards, &hand, &Value::Ace).unwrap();
+        assert_eq!(ev.surrender, Some(-0.5));
+        assert_eq!(ev.best().0, Move::Surrender);
+    }
+}
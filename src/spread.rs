@@ -0,0 +1,340 @@
+use crate::betting::BetRamp;
+use crate::error::Error;
+use crate::money::Money;
+use crate::simulation::CountResult;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The least share of the rounds a true count is judged on, rarer counts at either end are pooled
+/// with their neighbours so a handful of lucky rounds doesn't decide the top of the ramp.
+const MIN_COUNT_SHARE: f64 = 0.005;
+
+/// The penalties on the variance tried by the search, from 10^-6 to 10 in steps of 10^0.05.
+const PENALTY_STEPS: i32 = 140;
+
+/// What a bet ramp is judged by.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpreadObjective {
+    /// The win rate per 100 rounds of a 10,000 unit bankroll with the ramp scaled to the optimal
+    /// Kelly bet, i.e. how good the ramp is regardless of the bankroll.
+    Score,
+    /// The expected growth of the log of the bankroll per round with the base bet as given.
+    Growth,
+}
+
+impl Display for SpreadObjective {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SpreadObjective::Score => "score",
+                SpreadObjective::Growth => "growth",
+            }
+        )
+    }
+}
+
+impl FromStr for SpreadObjective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "score" => Ok(SpreadObjective::Score),
+            "growth" => Ok(SpreadObjective::Growth),
+            _ => Err(format!("expected 'score' or 'growth', got '{}'", s)),
+        }
+    }
+}
+
+/// The bounds the bet ramps are searched within.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpreadLimits {
+    /// The largest bet in base bets, the smallest is a single base bet.
+    pub max_spread: u64,
+    pub base_bet: Money,
+    pub bankroll: Money,
+    /// The highest risk of losing the whole bankroll a ramp may have, as a fraction.
+    pub max_risk: Option<f64>,
+}
+
+/// How a bet ramp is expected to do, from the results of flat bets at each true count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RampPerformance {
+    pub ramp: BetRamp,
+    /// The expected result of a round in units.
+    pub mean: f64,
+    /// The variance of a round's result in units squared.
+    pub variance: f64,
+    pub score: f64,
+    /// The expected growth of the log of the bankroll per round.
+    pub growth: f64,
+    /// The risk of losing the bankroll playing on forever, by the diffusion approximation.
+    pub risk_of_ruin: f64,
+}
+
+impl RampPerformance {
+    fn value(&self, objective: SpreadObjective) -> f64 {
+        match objective {
+            SpreadObjective::Score => self.score,
+            SpreadObjective::Growth => self.growth,
+        }
+    }
+}
+
+/// The rounds at one or more neighbouring true counts, per unit bet.
+struct Bucket {
+    true_count: i32,
+    share: f64,
+    mean: f64,
+    squared: f64,
+}
+
+impl Bucket {
+    /// What betting `units` at the count adds to the mean less `penalty` times the squares.
+    fn value(&self, units: u64, penalty: f64) -> f64 {
+        let units = units as f64;
+        self.share * (units * self.mean - penalty * units * units * self.squared)
+    }
+}
+
+/// Pools the rare counts at either end until every bucket has its minimum share of the rounds.
+/// The top bucket stands for its count and above, the bottom one for its count and below.
+fn buckets(counts: &BTreeMap<i32, CountResult>) -> Vec<Bucket> {
+    let rounds = counts.values().map(|count| count.rounds).sum::<u64>() as f64;
+    let min_rounds = (rounds * MIN_COUNT_SHARE).ceil() as u64;
+    let mut pooled: Vec<(i32, CountResult)> = vec![];
+    let mut pool: Option<(i32, CountResult)> = None;
+    for (true_count, count) in counts.iter().rev() {
+        let (_, total) = pool.get_or_insert((*true_count, CountResult::default()));
+        total.rounds += count.rounds;
+        total.initial_bets += count.initial_bets;
+        total.result_sum += count.result_sum;
+        total.result_squares += count.result_squares;
+        if total.rounds >= min_rounds {
+            pooled.extend(pool.take().map(|(_, total)| (*true_count, total)));
+        }
+    }
+    if let Some((_, rest)) = pool {
+        match pooled.last_mut() {
+            Some((_, bottom)) => {
+                bottom.rounds += rest.rounds;
+                bottom.initial_bets += rest.initial_bets;
+                bottom.result_sum += rest.result_sum;
+                bottom.result_squares += rest.result_squares;
+            }
+            None => pooled.push((counts.keys().next().copied().unwrap_or(0), rest)),
+        }
+    }
+
+    pooled
+        .into_iter()
+        .rev()
+        .map(|(true_count, count)| Bucket {
+            true_count,
+            share: count.rounds as f64 / rounds,
+            mean: count.player_edge(),
+            squared: count.squared_per_bet(),
+        })
+        .collect()
+}
+
+/// The units to bet at each bucket for the most mean less `penalty` times the squares, never
+/// betting less at a higher count.
+fn best_units(buckets: &[Bucket], max_spread: u64, penalty: f64) -> Vec<u64> {
+    let spread = max_spread as usize;
+    // The best value of the buckets so far with the last one at each number of units
+    let mut best = vec![0.0; spread];
+    let mut choices: Vec<Vec<usize>> = Vec::with_capacity(buckets.len());
+    for bucket in buckets {
+        let mut next = vec![0.0; spread];
+        let mut from = vec![0; spread];
+        let (mut top, mut top_at) = (f64::NEG_INFINITY, 0);
+        for units in 0..spread {
+            if best[units] > top {
+                top = best[units];
+                top_at = units;
+            }
+            next[units] = top + bucket.value(units as u64 + 1, penalty);
+            from[units] = top_at;
+        }
+        best = next;
+        choices.push(from);
+    }
+
+    let mut at = (0..spread)
+        .max_by(|a, b| best[*a].total_cmp(&best[*b]))
+        .unwrap_or(0);
+    let mut units = vec![1; buckets.len()];
+    for (bucket, from) in choices.iter().enumerate().rev() {
+        units[bucket] = at as u64 + 1;
+        at = from[at];
+    }
+    units
+}
+
+fn performance(buckets: &[Bucket], units: &[u64], limits: &SpreadLimits) -> RampPerformance {
+    let base = limits.base_bet.units_f64();
+    let bankroll = limits.bankroll.units_f64();
+    let (mut mean, mut squares) = (0.0, 0.0);
+    for (bucket, units) in buckets.iter().zip(units) {
+        let bet = *units as f64 * base;
+        mean += bucket.share * bet * bucket.mean;
+        squares += bucket.share * bet * bet * bucket.squared;
+    }
+    let variance = squares - mean * mean;
+    let risk_of_ruin = if mean <= 0.0 {
+        1.0
+    } else {
+        (-2.0 * mean * bankroll / variance).exp()
+    };
+
+    let mut steps = vec![];
+    let mut previous = 1;
+    for (bucket, units) in buckets.iter().zip(units) {
+        if *units != previous {
+            steps.push((bucket.true_count as f32, *units));
+            previous = *units;
+        }
+    }
+    RampPerformance {
+        ramp: BetRamp { steps },
+        mean,
+        variance,
+        score: if mean > 0.0 {
+            1e6 * mean * mean / variance
+        } else {
+            0.0
+        },
+        growth: mean / bankroll - squares / (2.0 * bankroll * bankroll),
+        risk_of_ruin,
+    }
+}
+
+/// Searches the bet ramps of one to `max_spread` base bets, rising with the true count, for the
+/// best by `objective` from the results of flat bets at each true count.
+///
+/// The ramps considered each make the best trade of the mean against the variance for some
+/// penalty on the variance, which includes the ramp with the most growth for the bankroll. Ramps
+/// with a higher risk of ruin than `max_risk` are passed over.
+pub fn optimal_ramp(
+    counts: &BTreeMap<i32, CountResult>,
+    objective: SpreadObjective,
+    limits: &SpreadLimits,
+) -> Result<RampPerformance, Error> {
+    if limits.max_spread == 0 {
+        return Err(Error::Config(
+            "the bet spread must be at least 1".to_string(),
+        ));
+    }
+    if limits.base_bet <= Money::ZERO || limits.bankroll <= Money::ZERO {
+        return Err(Error::Config(
+            "the base bet and the bankroll must be positive".to_string(),
+        ));
+    }
+    let buckets = buckets(counts);
+    if buckets.is_empty() {
+        return Err(Error::Config(
+            "there are no rounds to judge the bet ramps by".to_string(),
+        ));
+    }
+
+    let growth_penalty = limits.base_bet.units_f64() / (2.0 * limits.bankroll.units_f64());
+    let penalties = (0..=PENALTY_STEPS)
+        .rev()
+        .map(|step| 10f64.powf(step as f64 / 20.0 - 6.0))
+        .chain([growth_penalty]);
+    let flat = vec![1; buckets.len()];
+    let candidates = std::iter::once(flat)
+        .chain(penalties.map(|penalty| best_units(&buckets, limits.max_spread, penalty)));
+
+    let mut best: Option<RampPerformance> = None;
+    for units in candidates {
+        let candidate = performance(&buckets, &units, limits);
+        if limits
+            .max_risk
+            .is_some_and(|risk| candidate.risk_of_ruin > risk)
+        {
+            continue;
+        }
+        if best
+            .as_ref()
+            .is_none_or(|best| candidate.value(objective) > best.value(objective))
+        {
+            best = Some(candidate);
+        }
+    }
+    best.ok_or_else(|| {
+        Error::Config(format!(
+            "no bet ramp keeps the risk of ruin below {}%",
+            limits.max_risk.unwrap_or(0.0) * 100.0
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+    use crate::simulation::CountResult;
+    use crate::spread::{optimal_ramp, SpreadLimits, SpreadObjective};
+    use std::collections::BTreeMap;
+
+    /// Flat single unit bets at true counts -1 to 3, each worth 0.5% more than the last.
+    fn counts() -> BTreeMap<i32, CountResult> {
+        (-1..=3)
+            .map(|true_count| {
+                let rounds = 100_000;
+                let edge = -0.005 + 0.005 * true_count as f64;
+                let count = CountResult {
+                    rounds,
+                    initial_bets: Money::from_units(rounds as i64),
+                    result_sum: edge * rounds as f64,
+                    result_squares: 1.3 * rounds as f64,
+                };
+                (true_count, count)
+            })
+            .collect()
+    }
+
+    const LIMITS: SpreadLimits = SpreadLimits {
+        max_spread: 8,
+        base_bet: Money::from_units(10),
+        bankroll: Money::from_units(10_000),
+        max_risk: None,
+    };
+
+    #[test]
+    fn test_optimal_ramp() {
+        let best = optimal_ramp(&counts(), SpreadObjective::Score, &LIMITS).unwrap();
+        let ramp = &best.ramp;
+        assert_eq!(ramp.units(0.0), 1);
+        assert_eq!(ramp.units(1.0), 1);
+        assert!(ramp.units(2.0) > 1);
+        assert_eq!(ramp.units(3.0), 8);
+        assert!(best.mean > 0.0 && best.score > 0.0);
+
+        // A small bankroll can't afford to spread as much for the most growth
+        let small = SpreadLimits {
+            bankroll: Money::from_units(5000),
+            ..LIMITS
+        };
+        let growth = optimal_ramp(&counts(), SpreadObjective::Growth, &small).unwrap();
+        assert!(growth.ramp.units(3.0) < 8);
+        assert!(growth.ramp.units(3.0) > 1);
+
+        // Every ramp of a losing game risks the whole bankroll
+        let mut losing = counts();
+        losing.remove(&3);
+        losing.remove(&2);
+        let safe = SpreadLimits {
+            max_risk: Some(0.1),
+            ..LIMITS
+        };
+        assert!(optimal_ramp(&losing, SpreadObjective::Score, &safe).is_err());
+        let flat = optimal_ramp(&losing, SpreadObjective::Score, &LIMITS).unwrap();
+        assert!(flat.ramp.steps.is_empty());
+    }
+}
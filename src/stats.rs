@@ -0,0 +1,99 @@
+use crate::money::Money;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// Profit and loss over a session at the table, updated every time a round is settled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub starting_bankroll: Money,
+    /// The total amount bought in for, the starting bankroll plus all rebuys.
+    pub bought_in: Money,
+    pub bankroll: Money,
+    /// The best result of the session so far.
+    pub peak: Money,
+    /// The largest fall from a peak during the session.
+    pub max_drawdown: Money,
+    /// The total tipped to the dealer, already taken out of the bankroll.
+    pub tips: Money,
+    pub rounds: u32,
+}
+
+impl SessionStats {
+    pub fn new(starting_bankroll: Money) -> Self {
+        Self {
+            starting_bankroll,
+            bought_in: starting_bankroll,
+            bankroll: starting_bankroll,
+            peak: Money::ZERO,
+            max_drawdown: Money::ZERO,
+            tips: Money::ZERO,
+            rounds: 0,
+        }
+    }
+
+    /// The net result of the session, rebuys don't count as winnings.
+    pub fn profit(&self) -> Money {
+        self.bankroll - self.bought_in
+    }
+
+    /// How far the result currently is below the peak.
+    pub fn drawdown(&self) -> Money {
+        self.peak - self.profit()
+    }
+
+    /// Records the bankroll after a settled round.
+    pub fn record_round(&mut self, bankroll: Money) {
+        self.rounds += 1;
+        self.bankroll = bankroll;
+        self.peak = self.peak.max(self.profit());
+        self.max_drawdown = self.max_drawdown.max(self.drawdown());
+    }
+
+    pub fn rebuy(&mut self, amount: Money) {
+        self.bought_in += amount;
+        self.bankroll += amount;
+    }
+}
+
+impl Display for SessionStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Rounds played:     {}", self.rounds)?;
+        writeln!(f, "Starting bankroll: {}", self.starting_bankroll)?;
+        if self.bought_in != self.starting_bankroll {
+            writeln!(f, "Total bought in:   {}", self.bought_in)?;
+        }
+        writeln!(f, "Bankroll:          {}", self.bankroll)?;
+        writeln!(f, "Net result:        {:+}", self.profit())?;
+        if self.tips > Money::ZERO {
+            writeln!(f, "Tips:              {}", self.tips)?;
+        }
+        writeln!(f, "Peak:              {:+}", self.peak)?;
+        write!(f, "Max drawdown:      {}", self.max_drawdown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::money::Money;
+    use crate::stats::SessionStats;
+
+    #[test]
+    fn test_session_stats() {
+        let mut stats = SessionStats::new(Money::from_units(100));
+        stats.record_round(Money::from_units(130));
+        stats.record_round(Money::from_units(90));
+        stats.record_round(Money::from_units(110));
+        assert_eq!(stats.rounds, 3);
+        assert_eq!(stats.profit(), Money::from_units(10));
+        assert_eq!(stats.peak, Money::from_units(30));
+        assert_eq!(stats.drawdown(), Money::from_units(20));
+        assert_eq!(stats.max_drawdown, Money::from_units(40));
+
+        // Rebuys are not counted as winnings
+        stats.record_round(Money::ZERO);
+        stats.rebuy(Money::from_units(100));
+        assert_eq!(stats.bankroll, Money::from_units(100));
+        assert_eq!(stats.profit(), Money::from_units(-100));
+        assert_eq!(stats.max_drawdown, Money::from_units(130));
+    }
+}
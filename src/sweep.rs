@@ -0,0 +1,271 @@
+use crate::config::GameConfig;
+use crate::error::Error;
+use crate::input::BasicStrategy;
+use crate::rules::Rules;
+use crate::simulation::{simulate, PartProgress, Progress, SimulationResult};
+use crate::types::{Deck, Payout};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A rule a sweep switches between the values it usually has at a table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SweepRule {
+    /// The dealer stands on soft 17 (S17) or hits it (H17).
+    HitSoft17,
+    /// Doubling after a split is allowed (DAS) or not (NDAS).
+    DoubleAfterSplit,
+    /// A blackjack pays 3:2 or 6:5.
+    Payout,
+    /// The decks in the shoe, from the deck counts of the sweep.
+    Decks,
+}
+
+impl Display for SweepRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SweepRule::HitSoft17 => "h17",
+                SweepRule::DoubleAfterSplit => "das",
+                SweepRule::Payout => "payout",
+                SweepRule::Decks => "decks",
+            }
+        )
+    }
+}
+
+impl FromStr for SweepRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "h17" => Ok(SweepRule::HitSoft17),
+            "das" => Ok(SweepRule::DoubleAfterSplit),
+            "payout" => Ok(SweepRule::Payout),
+            "decks" => Ok(SweepRule::Decks),
+            _ => Err(format!(
+                "expected one of 'h17', 'das', 'payout' or 'decks', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+impl SweepRule {
+    /// The rules in the order they are varied in, the deck counts last.
+    pub const ALL: [SweepRule; 4] = [
+        SweepRule::HitSoft17,
+        SweepRule::DoubleAfterSplit,
+        SweepRule::Payout,
+        SweepRule::Decks,
+    ];
+
+    /// The rules with this rule set to each of its values in turn.
+    fn vary(&self, rules: &Rules, decks: &[u32]) -> Result<Vec<Rules>, Error> {
+        let with = |change: &dyn Fn(&mut Rules)| {
+            let mut rules = rules.clone();
+            change(&mut rules);
+            rules
+        };
+        Ok(match self {
+            SweepRule::HitSoft17 => vec![
+                with(&|rules| rules.hit_soft_17 = false),
+                with(&|rules| rules.hit_soft_17 = true),
+            ],
+            SweepRule::DoubleAfterSplit => vec![
+                with(&|rules| rules.double_after_split = true),
+                with(&|rules| rules.double_after_split = false),
+            ],
+            SweepRule::Payout => vec![
+                with(&|rules| rules.blackjack_payout = Payout::THREE_TO_TWO),
+                with(&|rules| rules.blackjack_payout = Payout::SIX_TO_FIVE),
+            ],
+            SweepRule::Decks => {
+                // The cut card is kept at the same share of the shoe, as it wouldn't fit in
+                // the smaller shoes otherwise
+                let shoe_size = rules.deck_count * Deck::new(rules.variant)?.cards.len() as u32;
+                let penetration = rules
+                    .penetration
+                    .unwrap_or(1.0 - rules.cut_card_position(shoe_size) as f32 / shoe_size as f32);
+                decks
+                    .iter()
+                    .map(|&deck_count| {
+                        with(&|rules| {
+                            rules.deck_count = deck_count;
+                            rules.penetration = Some(penetration);
+                        })
+                    })
+                    .collect()
+            }
+        })
+    }
+
+    /// The short name of the value the rule has in `rules`, e.g. "H17" or "6:5".
+    pub fn label(&self, rules: &Rules) -> String {
+        match self {
+            SweepRule::HitSoft17 if rules.hit_soft_17 => "H17".to_string(),
+            SweepRule::HitSoft17 => "S17".to_string(),
+            SweepRule::DoubleAfterSplit if rules.double_after_split => "DAS".to_string(),
+            SweepRule::DoubleAfterSplit => "NDAS".to_string(),
+            SweepRule::Payout => rules.blackjack_payout.to_string(),
+            SweepRule::Decks if rules.deck_count == 1 => "1 deck".to_string(),
+            SweepRule::Decks => format!("{} decks", rules.deck_count),
+        }
+    }
+}
+
+/// Every combination of the values of the swept rules, with the other rules as in `base`.
+///
+/// The rules are varied in the order of [SweepRule::ALL], so the combinations that only differ
+/// in the deck count come one after another.
+pub fn variations(base: &Rules, swept: &[SweepRule], decks: &[u32]) -> Result<Vec<Rules>, Error> {
+    let mut variations = vec![base.clone()];
+    for rule in SweepRule::ALL.iter().filter(|rule| swept.contains(rule)) {
+        variations = variations
+            .iter()
+            .map(|rules| rule.vary(rules, decks))
+            .collect::<Result<Vec<_>, _>>()?
+            .concat();
+    }
+    Ok(variations)
+}
+
+/// The result of basic strategy under one combination of the swept rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepPoint {
+    pub rules: Rules,
+    pub result: SimulationResult,
+}
+
+/// Simulates `rounds` rounds of basic strategy under every combination of the swept rules, all
+/// from the same seed so the differences are down to the rules rather than the shoes.
+pub fn sweep(
+    conf: &GameConfig,
+    swept: &[SweepRule],
+    decks: &[u32],
+    rounds: u64,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Vec<SweepPoint>, Error> {
+    let variations = variations(&conf.rules, swept, decks)?;
+    simulate_each(conf, variations, rounds, seed, progress)
+}
+
+/// Simulates `rounds` rounds of basic strategy with the cut card placed at each of the
+/// `penetrations`, the share of the shoe dealt before the shuffle, all from the same seed. Betting
+/// the bet ramp, how the edge and the true counts change show what deeper penetration is worth to
+/// a counter.
+pub fn penetration_sweep(
+    conf: &GameConfig,
+    penetrations: &[f32],
+    rounds: u64,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Vec<SweepPoint>, Error> {
+    let variations = penetrations
+        .iter()
+        .map(|&penetration| Rules {
+            penetration: Some(penetration),
+            ..conf.rules.clone()
+        })
+        .collect();
+    simulate_each(conf, variations, rounds, seed, progress)
+}
+
+/// Simulates basic strategy under each of the rules in turn.
+fn simulate_each(
+    conf: &GameConfig,
+    variations: Vec<Rules>,
+    rounds: u64,
+    seed: u64,
+    progress: &mut dyn Progress,
+) -> Result<Vec<SweepPoint>, Error> {
+    let total = rounds * variations.len() as u64;
+    variations
+        .into_iter()
+        .enumerate()
+        .map(|(i, rules)| {
+            let conf = GameConfig {
+                rules: rules.clone(),
+                ..conf.clone()
+            };
+            let mut part = PartProgress {
+                progress: &mut *progress,
+                before: rounds * i as u64,
+                total,
+            };
+            let result = simulate(conf, &mut BasicStrategy, rounds, Some(seed), &mut part)?;
+            Ok(SweepPoint { rules, result })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::rules::Rules;
+    use crate::simulation::NoProgress;
+    use crate::sweep::{penetration_sweep, sweep, variations, SweepRule};
+    use crate::types::Payout;
+
+    #[test]
+    fn test_variations() {
+        let rules = variations(&Rules::default(), &SweepRule::ALL, &[1, 2, 6]).unwrap();
+        assert_eq!(rules.len(), 2 * 2 * 2 * 3);
+        assert_eq!(rules[1].deck_count, 2);
+        for rules in &rules {
+            rules.validate().unwrap();
+        }
+        let single_deck = rules.iter().find(|rules| rules.deck_count == 1).unwrap();
+        assert_eq!(SweepRule::Decks.label(single_deck), "1 deck");
+        // The 52 cards behind the cut card of six decks are a sixth of the shoe
+        let penetration = single_deck.penetration.unwrap();
+        assert!((penetration - 5.0 / 6.0).abs() < 1e-6);
+        assert_eq!(SweepRule::HitSoft17.label(&rules[0]), "S17");
+        assert_eq!(SweepRule::Payout.label(&rules[3]), "6:5");
+        assert_eq!(SweepRule::DoubleAfterSplit.label(&rules[6]), "NDAS");
+
+        // A rule swept twice is varied once, and nothing swept leaves the rules as they are
+        let twice = [SweepRule::Decks, SweepRule::HitSoft17, SweepRule::HitSoft17];
+        let rules = variations(&Rules::default(), &twice, &[1, 8]).unwrap();
+        assert_eq!(rules.len(), 4);
+        assert_eq!(SweepRule::Decks.label(&rules[1]), "8 decks");
+        assert_eq!(
+            variations(&Rules::default(), &[], &[]).unwrap(),
+            vec![Rules::default()]
+        );
+        assert_eq!("das".parse(), Ok(SweepRule::DoubleAfterSplit));
+        assert!("rsa".parse::<SweepRule>().is_err());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let points = sweep(
+            &GameConfig::default(),
+            &[SweepRule::Payout],
+            &[],
+            2000,
+            11,
+            &mut NoProgress,
+        )
+        .unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].rules.blackjack_payout, Payout::SIX_TO_FIVE);
+        // The same shoes, only the blackjacks are paid less
+        assert!(points[1].result.house_edge() > points[0].result.house_edge());
+        assert_eq!(points[0].result.rounds, 2000);
+    }
+
+    #[test]
+    fn test_penetration_sweep() {
+        let conf = GameConfig::default();
+        let points = penetration_sweep(&conf, &[0.5, 0.9], 5000, 3, &mut NoProgress).unwrap();
+        assert_eq!(points[1].rules.penetration, Some(0.9));
+        // Dealing deeper into the shoe brings the high counts
+        assert!(points[1].result.share_from_count(2) > points[0].result.share_from_count(2));
+        assert!(penetration_sweep(&conf, &[1.5], 5000, 3, &mut NoProgress).is_err());
+    }
+}
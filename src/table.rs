@@ -0,0 +1,105 @@
+use crate::config::MAX_SEATS;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A spot at the table, from first base on the dealer's left to third base on the dealer's right.
+///
+/// The cards are dealt and the hands played from first base on, so the further towards third
+/// base a player sits, the more cards of the other players are seen before deciding.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(try_from = "usize")]
+pub struct SeatPosition(usize);
+
+impl SeatPosition {
+    pub const FIRST_BASE: SeatPosition = SeatPosition(0);
+    pub const THIRD_BASE: SeatPosition = SeatPosition(MAX_SEATS - 1);
+
+    /// The spot numbered `number` from 1 at first base, `None` past third base.
+    pub fn spot(number: usize) -> Option<Self> {
+        (1..=MAX_SEATS)
+            .contains(&number)
+            .then(|| SeatPosition(number - 1))
+    }
+
+    /// The number of the spot, from 1 at first base.
+    pub fn number(&self) -> usize {
+        self.0 + 1
+    }
+
+    /// Every spot in the order the cards are dealt.
+    pub fn all() -> impl Iterator<Item = SeatPosition> {
+        (0..MAX_SEATS).map(SeatPosition)
+    }
+}
+
+/// Reads back the index from first base a position is saved as, e.g. sent by a client.
+impl TryFrom<usize> for SeatPosition {
+    type Error = String;
+
+    fn try_from(index: usize) -> Result<Self, Self::Error> {
+        SeatPosition::spot(index + 1).ok_or(format!(
+            "expected a spot from 0 at first base to {} at third base, got {}",
+            MAX_SEATS - 1,
+            index
+        ))
+    }
+}
+
+impl Display for SeatPosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SeatPosition::FIRST_BASE => write!(f, "first base"),
+            SeatPosition::THIRD_BASE => write!(f, "third base"),
+            SeatPosition(spot) => write!(f, "spot {}", spot + 1),
+        }
+    }
+}
+
+impl FromStr for SeatPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "first-base" | "first" => Ok(SeatPosition::FIRST_BASE),
+            "third-base" | "third" | "anchor" => Ok(SeatPosition::THIRD_BASE),
+            _ => s.parse().ok().and_then(SeatPosition::spot).ok_or(format!(
+                "expected one of 'first-base', 'third-base' or a spot from 1 to {}, got '{}'",
+                MAX_SEATS, s
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::MAX_SEATS;
+    use crate::table::SeatPosition;
+
+    #[test]
+    fn test_seat_positions() {
+        assert_eq!(
+            "first-base".parse::<SeatPosition>(),
+            Ok(SeatPosition::FIRST_BASE)
+        );
+        assert_eq!("anchor".parse(), Ok(SeatPosition::THIRD_BASE));
+        assert_eq!("7".parse(), Ok(SeatPosition::THIRD_BASE));
+        assert_eq!("3".parse::<SeatPosition>().unwrap().to_string(), "spot 3");
+        assert!("0".parse::<SeatPosition>().is_err());
+        assert!("8".parse::<SeatPosition>().is_err());
+
+        let spots: Vec<SeatPosition> = SeatPosition::all().collect();
+        assert_eq!(spots.len(), MAX_SEATS);
+        assert_eq!(spots[0].to_string(), "first base");
+        assert_eq!(spots[MAX_SEATS - 1].to_string(), "third base");
+        assert!(SeatPosition::FIRST_BASE < SeatPosition::spot(2).unwrap());
+
+        let saved = serde_json::to_string(&SeatPosition::THIRD_BASE).unwrap();
+        let restored: SeatPosition = serde_json::from_str(&saved).unwrap();
+        assert_eq!(restored, SeatPosition::THIRD_BASE);
+        assert!(serde_json::from_str::<SeatPosition>(&MAX_SEATS.to_string()).is_err());
+        assert!(serde_json::from_str::<SeatPosition>("99").is_err());
+    }
+}
@@ -0,0 +1,314 @@
+use crate::betting::BetRamp;
+use crate::config::GameConfig;
+use crate::error::Error;
+use crate::events::{GameEvent, GameObserver};
+use crate::input::BasicStrategy;
+use crate::money::Money;
+use crate::simulation::{Progress, Simulator};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The bankroll of the simulated tables, the team's own bankroll is followed separately.
+const TABLE_BANKROLL: Money = Money::from_units(1_000_000_000);
+
+/// The rounds played at every table between updates of the progress.
+const PROGRESS_INTERVAL: u64 = 1_000;
+
+/// How a team plays: each spotter flat bets at a table of their own and counts, calling the big
+/// player in when the count at their table gets high enough.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Team {
+    /// The spotters, one at each table.
+    pub spotters: usize,
+    pub spotter_bet: Money,
+    /// The true count the big player is called in at.
+    pub entry: f32,
+    /// The true count the big player leaves the table below, e.g. after the shuffle.
+    pub exit: f32,
+    /// The big player's bet, in units of the bet ramp if there is one.
+    pub big_bet: Money,
+    pub ramp: Option<BetRamp>,
+    /// The bankroll the team starts with.
+    pub bankroll: Money,
+}
+
+impl Team {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.spotters == 0 {
+            return Err(Error::Config(
+                "the team needs at least one spotter".to_string(),
+            ));
+        }
+        if self.exit > self.entry {
+            return Err(Error::Config(format!(
+                "the big player can't leave below TC {} when entering at TC {}",
+                self.exit, self.entry
+            )));
+        }
+        Ok(())
+    }
+
+    /// The big player's bet at the true count.
+    fn big_bet(&self, true_count: f32) -> Money {
+        match &self.ramp {
+            Some(ramp) => ramp.bet(true_count, self.big_bet),
+            None => self.big_bet,
+        }
+    }
+}
+
+/// The bets placed by one role of the team and how they did.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TeamPart {
+    /// The rounds played, one bet each.
+    pub rounds: u64,
+    pub initial_bets: Money,
+    pub net: Money,
+}
+
+impl TeamPart {
+    pub(crate) fn add(&mut self, bet: Money, net: Money) {
+        self.rounds += 1;
+        self.initial_bets += bet;
+        self.net += net;
+    }
+
+    /// The result as a fraction of the initial bets, negative when the house wins.
+    pub fn player_edge(&self) -> f64 {
+        if self.initial_bets == Money::ZERO {
+            return 0.0;
+        }
+        self.net.units_f64() / self.initial_bets.units_f64()
+    }
+}
+
+/// The result of the team over all tables, a round is a round at every table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TeamResult {
+    pub rounds: u64,
+    pub spotters: TeamPart,
+    pub big_player: TeamPart,
+    /// The sum of the team's results of a round in units, and of their squares.
+    pub result_sum: f64,
+    pub result_squares: f64,
+    pub final_bankroll: Money,
+    pub lowest_bankroll: Money,
+    /// The largest fall of the bankroll from a high to a later low.
+    pub max_drawdown: Money,
+}
+
+impl TeamResult {
+    pub fn net(&self) -> Money {
+        self.spotters.net + self.big_player.net
+    }
+
+    /// The expected result of a round of the team in units.
+    pub fn mean(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.result_sum / self.rounds as f64
+    }
+
+    /// The variance of the result of a round of the team in units squared.
+    pub fn variance(&self) -> f64 {
+        if self.rounds == 0 {
+            return 0.0;
+        }
+        self.result_squares / self.rounds as f64 - self.mean().powi(2)
+    }
+
+    /// The share of the rounds at the tables the big player bet in.
+    pub fn big_player_share(&self) -> f64 {
+        if self.spotters.rounds == 0 {
+            return 0.0;
+        }
+        self.big_player.rounds as f64 / self.spotters.rounds as f64
+    }
+
+    /// The risk of losing `bankroll` playing on forever, by the diffusion approximation from
+    /// the mean and variance of a round.
+    pub fn risk_of_ruin(&self, bankroll: Money) -> f64 {
+        let (mean, variance) = (self.mean(), self.variance());
+        if mean <= 0.0 {
+            return 1.0;
+        }
+        (-2.0 * mean * bankroll.units_f64() / variance).exp()
+    }
+}
+
+/// The winnings of the last round at a table by the hand they were bet on, and whether the
+/// shoe was shuffled since.
+#[derive(Default)]
+pub(crate) struct Seats {
+    winnings: Vec<Money>,
+    pub(crate) shuffled: bool,
+}
+
+impl Seats {
+    /// The winnings of the seat the bet at `index` was placed in.
+    pub(crate) fn seat(&self, index: usize) -> Money {
+        self.winnings.get(index).copied().unwrap_or(Money::ZERO)
+    }
+}
+
+/// Follows the rounds at a table for the team.
+pub(crate) struct SeatObserver(pub(crate) Rc<RefCell<Seats>>);
+
+impl GameObserver for SeatObserver {
+    fn on_event(&mut self, event: &GameEvent) {
+        let mut seats = self.0.borrow_mut();
+        match event {
+            GameEvent::Shuffled { .. } => seats.shuffled = true,
+            GameEvent::RoundSettled { hands, .. } => {
+                seats.winnings.clear();
+                for hand in hands {
+                    if seats.winnings.len() <= hand.origin {
+                        seats.winnings.resize(hand.origin + 1, Money::ZERO);
+                    }
+                    seats.winnings[hand.origin] += hand.winnings;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A spotter's table, with the big player's seat.
+struct Table {
+    simulator: Simulator<'static>,
+    seats: Rc<RefCell<Seats>>,
+}
+
+/// Plays `rounds` rounds of basic strategy at each of the team's tables, with the big player
+/// joining the first table whose true count reaches the entry count while not at a table, and
+/// staying there until it falls below the exit count or the shoe is shuffled.
+///
+/// Each table deals its own shoes, from `seed` plus the number of the table if a seed is given.
+pub fn simulate_team(
+    conf: &GameConfig,
+    team: &Team,
+    rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<TeamResult, Error> {
+    team.validate()?;
+    let mut tables = (0..team.spotters)
+        .map(|i| {
+            let seed = seed.map(|seed| seed.wrapping_add(i as u64));
+            let mut simulator = Simulator::new(conf.clone(), TABLE_BANKROLL, seed)?;
+            let seats = Rc::new(RefCell::new(Seats::default()));
+            simulator
+                .engine
+                .subscribe(Box::new(SeatObserver(seats.clone())));
+            Ok(Table { simulator, seats })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut result = TeamResult {
+        final_bankroll: team.bankroll,
+        lowest_bankroll: team.bankroll,
+        ..Default::default()
+    };
+    let mut high = team.bankroll;
+    let mut big_player = None;
+    while result.rounds < rounds {
+        let mut round = Money::ZERO;
+        for (i, table) in tables.iter_mut().enumerate() {
+            let engine = &table.simulator.engine;
+            let true_count = engine.counter.true_count;
+            let shuffled = std::mem::take(&mut table.seats.borrow_mut().shuffled);
+            if big_player == Some(i) && (shuffled || true_count < team.exit) {
+                big_player = None;
+            }
+            if big_player.is_none() && true_count >= team.entry {
+                big_player = Some(i);
+            }
+            let mut bets: Vec<Money> = conf
+                .fit_bet(team.spotter_bet, TABLE_BANKROLL)
+                .into_iter()
+                .collect();
+            if big_player == Some(i) {
+                bets.extend(conf.fit_bet(team.big_bet(true_count), TABLE_BANKROLL));
+            }
+            if table
+                .simulator
+                .play_bets(&mut BasicStrategy, &bets)?
+                .is_none()
+            {
+                continue;
+            }
+            let seats = table.seats.borrow();
+            result.spotters.add(bets[0], seats.seat(0));
+            round += seats.seat(0);
+            if let Some(bet) = bets.get(1) {
+                result.big_player.add(*bet, seats.seat(1));
+                round += seats.seat(1);
+            }
+        }
+        result.rounds += 1;
+        result.result_sum += round.units_f64();
+        result.result_squares += round.units_f64().powi(2);
+        result.final_bankroll += round;
+        high = high.max(result.final_bankroll);
+        result.lowest_bankroll = result.lowest_bankroll.min(result.final_bankroll);
+        result.max_drawdown = result.max_drawdown.max(high - result.final_bankroll);
+        if result.rounds.is_multiple_of(PROGRESS_INTERVAL) {
+            progress.update(result.rounds, rounds);
+        }
+    }
+    progress.update(result.rounds, rounds);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::money::Money;
+    use crate::simulation::NoProgress;
+    use crate::team::{simulate_team, Team};
+
+    fn team() -> Team {
+        Team {
+            spotters: 3,
+            spotter_bet: Money::from_units(5),
+            entry: 2.0,
+            exit: 0.0,
+            big_bet: Money::from_units(100),
+            ramp: None,
+            bankroll: Money::from_units(20_000),
+        }
+    }
+
+    #[test]
+    fn test_simulate_team() {
+        let conf = GameConfig::default();
+        let result = simulate_team(&conf, &team(), 1000, Some(4), &mut NoProgress).unwrap();
+        assert_eq!(result.rounds, 1000);
+        // Every table plays every round, barring a void round
+        assert!(result.spotters.rounds > 2990);
+        assert_eq!(
+            result.spotters.initial_bets,
+            Money::from_units(5) * result.spotters.rounds
+        );
+        // The big player only bets at high counts, at one table at a time
+        assert!(result.big_player.rounds > 0);
+        assert!(result.big_player_share() < 1.0 / 3.0);
+        assert_eq!(
+            result.big_player.initial_bets,
+            Money::from_units(100) * result.big_player.rounds
+        );
+        assert_eq!(result.final_bankroll - team().bankroll, result.net());
+        assert!((result.result_sum - result.net().units_f64()).abs() < 1e-6);
+        assert!(result.lowest_bankroll <= team().bankroll);
+        assert!(result.max_drawdown >= team().bankroll - result.lowest_bankroll);
+
+        let again = simulate_team(&conf, &team(), 1000, Some(4), &mut NoProgress).unwrap();
+        assert_eq!(again, result);
+
+        let no_spotters = Team {
+            spotters: 0,
+            ..team()
+        };
+        assert!(simulate_team(&conf, &no_spotters, 10, None, &mut NoProgress).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use crate::chart::{ChartCell, ChartMove, ChartRow, StrategyChart, RANKS};
+use crate::config::GameConfig;
+use crate::engine::GameEngine;
+use crate::error::Error;
+use crate::events::{GameEvent, GameObserver};
+use crate::input::{hand_situation, BasicStrategy, PlayerInput};
+use crate::money::Money;
+use crate::outcomes::StartingHand;
+use crate::rules::{HandOptions, Rules};
+use crate::side_bets::SideBet;
+use crate::simulation::{play_rounds, Progress, SimulationResult, Simulator, SIMULATION_BANKROLL};
+use crate::types::{Move, Value};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The moves a value is learned for, in the order of [ActionValues].
+const MOVES: [Move; 5] = [
+    Move::Hit,
+    Move::Stand,
+    Move::Double,
+    Move::Split,
+    Move::Surrender,
+];
+
+/// The situation a move is learned for: the hand as in a strategy chart, by its total once it
+/// has more than two cards, and the upcard from 2 to 11.
+pub type State = (StartingHand, u32);
+
+/// The learned value of a move in initial bets and how often it was updated.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActionValue {
+    pub value: f64,
+    pub visits: u64,
+}
+
+/// The values of the moves of [MOVES] in one state.
+pub type ActionValues = [ActionValue; 5];
+
+/// The value of every move in every state that came up, learned by tabular Q-learning.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QTable {
+    pub values: HashMap<State, ActionValues>,
+}
+
+impl QTable {
+    /// The state of the hand at `hand`, a pair while it can be split.
+    pub fn state(engine: &GameEngine, hand: usize) -> State {
+        let (value, soft, pair, upcard) = hand_situation(engine, hand);
+        let hand = match pair {
+            Some(card) => StartingHand::Pair(card),
+            None if soft => StartingHand::Soft(value - Value::ACE_HIGH_VAL),
+            None => StartingHand::Hard(value),
+        };
+        (hand, upcard)
+    }
+
+    /// The allowed move with the highest learned value, `None` if none of them was tried.
+    pub fn best(&self, state: &State, allowed: impl Fn(Move) -> bool) -> Option<(Move, f64)> {
+        let values = self.values.get(state)?;
+        MOVES
+            .iter()
+            .zip(values)
+            .filter(|(action, value)| allowed(**action) && value.visits > 0)
+            .map(|(action, value)| (*action, value.value))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Moves the value of the move towards `target`, by the average of all targets so far.
+    fn update(&mut self, state: State, action: Move, target: f64) {
+        let index = MOVES.iter().position(|m| *m == action).unwrap_or(0);
+        let value = &mut self.values.entry(state).or_default()[index];
+        value.visits += 1;
+        value.value += (target - value.value) / value.visits as f64;
+    }
+
+    /// The learned policy as a strategy chart of the two card hands, with the learned value of
+    /// the move in each cell. Cells that never came up are filled in with basic strategy.
+    pub fn chart(&self, rules: &Rules) -> StrategyChart {
+        let row = |hand: StartingHand| {
+            let (value, soft, pair) = match hand {
+                StartingHand::Hard(total) => (total, false, None),
+                StartingHand::Soft(card) => (card + Value::ACE_HIGH_VAL, true, None),
+                StartingHand::Pair(Value::ACE_HIGH_VAL) => (12, true, Some(Value::ACE_HIGH_VAL)),
+                StartingHand::Pair(card) => (card * 2, false, Some(card)),
+            };
+            let cells = (2..=Value::ACE_HIGH_VAL)
+                .map(|upcard| self.cell(rules, (hand, upcard), value, soft, pair))
+                .collect();
+            ChartRow {
+                label: hand.to_string(),
+                cells,
+            }
+        };
+        StrategyChart {
+            hard: (5..=19)
+                .map(|total| row(StartingHand::Hard(total)))
+                .collect(),
+            soft: (2..=9).map(|card| row(StartingHand::Soft(card))).collect(),
+            pairs: (2..=Value::ACE_HIGH_VAL)
+                .map(|card| row(StartingHand::Pair(card)))
+                .collect(),
+        }
+    }
+
+    /// The chart move of the best learned move, with hitting or standing, whichever is worth
+    /// more, where doubling or surrendering isn't allowed.
+    fn cell(
+        &self,
+        rules: &Rules,
+        state: State,
+        value: u32,
+        soft: bool,
+        pair: Option<u32>,
+    ) -> ChartCell {
+        let Some((best, ev)) = self.best(&state, |_| true) else {
+            let upcard = state.1;
+            let basic = BasicStrategy::choose(rules, value, soft, pair, upcard, |_| true);
+            let play = match basic {
+                Some(Move::Stand) => ChartMove::Stand,
+                Some(Move::Double) => ChartMove::DoubleOrHit,
+                Some(Move::Split) => ChartMove::Split,
+                Some(Move::Surrender) => ChartMove::SurrenderOrHit,
+                _ => ChartMove::Hit,
+            };
+            return ChartCell { play, ev: 0.0 };
+        };
+        let stands = self
+            .best(&state, |m| m == Move::Hit || m == Move::Stand)
+            .is_some_and(|(action, _)| action == Move::Stand);
+        let play = match (best, stands) {
+            (Move::Hit, _) => ChartMove::Hit,
+            (Move::Stand, _) => ChartMove::Stand,
+            (Move::Double, false) => ChartMove::DoubleOrHit,
+            (Move::Double, true) => ChartMove::DoubleOrStand,
+            (Move::Split, _) => ChartMove::Split,
+            (Move::Surrender, false) => ChartMove::SurrenderOrHit,
+            (Move::Surrender, true) => ChartMove::SurrenderOrStand,
+        };
+        ChartCell { play, ev }
+    }
+}
+
+/// A move made during the round, with the moves that were allowed instead.
+struct Step {
+    state: State,
+    action: Move,
+    allowed: Vec<Move>,
+}
+
+/// The moves made on every hand of the round in progress, and the table they are learned into.
+#[derive(Default)]
+struct Learning {
+    table: QTable,
+    /// The moves of every hand of the round by the index of the hand, the split moves apart as
+    /// they lead to two hands.
+    hands: Vec<Vec<Step>>,
+    splits: Vec<(usize, Step)>,
+    bet: Money,
+}
+
+impl Learning {
+    /// Learns from the results of the hands of the round, `None` for a void round. A move is
+    /// learned towards the best value of the next state of the hand, the last move of a hand
+    /// towards its result and a split towards the results of both hands.
+    fn settle(&mut self, winnings: &[Money]) {
+        let bet = self.bet.units_f64().max(f64::EPSILON);
+        let reward = |index: usize| winnings.get(index).map_or(0.0, |w| w.units_f64() / bet);
+        let hands = std::mem::take(&mut self.hands);
+        for (index, steps) in hands.iter().enumerate() {
+            for (i, step) in steps.iter().enumerate() {
+                let target = match steps.get(i + 1) {
+                    Some(next) => self
+                        .table
+                        .best(&next.state, |m| next.allowed.contains(&m))
+                        .map_or(0.0, |(_, value)| value),
+                    None => reward(index),
+                };
+                self.table.update(step.state, step.action, target);
+            }
+        }
+        for (index, step) in std::mem::take(&mut self.splits) {
+            let target = reward(index) + reward(index + 1);
+            self.table.update(step.state, step.action, target);
+        }
+    }
+}
+
+/// Learns from the results of every round.
+struct Teacher(Rc<RefCell<Learning>>);
+
+impl GameObserver for Teacher {
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::RoundSettled { hands, .. } = event {
+            let winnings = hands.iter().map(|hand| hand.winnings).collect::<Vec<_>>();
+            self.0.borrow_mut().settle(&winnings);
+        }
+    }
+}
+
+/// A player learning by trial and error: it tries a random move `epsilon` of the time and the
+/// move it has learned to be the best otherwise, flat betting the table minimum and never
+/// insuring.
+struct Learner {
+    learning: Rc<RefCell<Learning>>,
+    epsilon: f64,
+    rng: StdRng,
+}
+
+impl PlayerInput for Learner {
+    fn num_hands(&mut self, engine: &GameEngine) -> Result<Option<usize>, Error> {
+        BasicStrategy.num_hands(engine)
+    }
+
+    fn bet(&mut self, engine: &GameEngine, hand: usize, num_hands: usize) -> Result<Money, Error> {
+        let bet = BasicStrategy.bet(engine, hand, num_hands)?;
+        let mut learning = self.learning.borrow_mut();
+        // A void round is never settled, its moves are dropped
+        learning.hands.clear();
+        learning.splits.clear();
+        learning.bet = bet;
+        Ok(bet)
+    }
+
+    fn side_bet(&mut self, engine: &GameEngine, side_bet: SideBet) -> Result<Money, Error> {
+        BasicStrategy.side_bet(engine, side_bet)
+    }
+
+    fn even_money(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.even_money(engine, hand)
+    }
+
+    fn insurance(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        BasicStrategy.insurance(engine)
+    }
+
+    fn action(
+        &mut self,
+        engine: &GameEngine,
+        hand: usize,
+        options: &HandOptions,
+    ) -> Result<Move, Error> {
+        let state = QTable::state(engine, hand);
+        let mut learning = self.learning.borrow_mut();
+        let action = match learning.table.best(&state, |m| options.moves.contains(&m)) {
+            Some((best, _)) if !self.rng.gen_bool(self.epsilon) => best,
+            _ => options.moves[self.rng.gen_range(0..options.moves.len())],
+        };
+        let step = Step {
+            state,
+            action,
+            allowed: options.moves.clone(),
+        };
+        let hands = &mut learning.hands;
+        if hands.len() <= hand {
+            hands.resize_with(hand + 1, Vec::new);
+        }
+        if action == Move::Split {
+            // The new hand is played right after this one, moving the later hands along
+            hands.insert(hand + 1, vec![]);
+            for (index, _) in learning.splits.iter_mut() {
+                if *index > hand {
+                    *index += 1;
+                }
+            }
+            learning.splits.push((hand, step));
+        } else {
+            hands[hand].push(step);
+        }
+        Ok(action)
+    }
+
+    fn buy_amount(&mut self, engine: &GameEngine, hand: usize) -> Result<Money, Error> {
+        BasicStrategy.buy_amount(engine, hand)
+    }
+
+    fn rescue(&mut self, engine: &GameEngine, hand: usize) -> Result<bool, Error> {
+        BasicStrategy.rescue(engine, hand)
+    }
+
+    fn tip(&mut self, engine: &GameEngine) -> Result<Money, Error> {
+        BasicStrategy.tip(engine)
+    }
+
+    fn rebuy(&mut self, engine: &GameEngine) -> Result<Option<Money>, Error> {
+        BasicStrategy.rebuy(engine)
+    }
+}
+
+/// Plays `rounds` rounds of flat bets of the table minimum, learning the value of every move in
+/// every state from the results with tabular Q-learning, trying a random move `epsilon` of the
+/// time to explore. Returns the learned values and the results of the rounds played while
+/// learning, random moves included.
+pub fn train(
+    conf: &GameConfig,
+    rounds: u64,
+    epsilon: f64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<(QTable, SimulationResult), Error> {
+    if !(0.0..=1.0).contains(&epsilon) {
+        return Err(Error::Config(format!(
+            "the exploration rate is a share from 0 to 1, got {}",
+            epsilon
+        )));
+    }
+    let conf = GameConfig {
+        bet_ramp: None,
+        ..conf.clone()
+    };
+    let mut simulator = Simulator::new(conf, SIMULATION_BANKROLL, seed)?;
+    let learning = Rc::new(RefCell::new(Learning::default()));
+    simulator
+        .engine
+        .subscribe(Box::new(Teacher(learning.clone())));
+    let mut learner = Learner {
+        learning: learning.clone(),
+        epsilon,
+        rng: match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        },
+    };
+    let result = play_rounds(simulator, &mut learner, rounds, progress)?;
+    drop(learner);
+    let table = Rc::try_unwrap(learning)
+        .map(|learning| learning.into_inner().table)
+        .unwrap_or_else(|learning| learning.borrow().table.clone());
+    Ok((table, result))
+}
+
+/// The share of the cells of `chart` that are played as in `reference`.
+pub fn agreement(chart: &StrategyChart, reference: &StrategyChart) -> f64 {
+    let rows = |chart: &StrategyChart| {
+        chart
+            .hard
+            .iter()
+            .chain(&chart.soft)
+            .chain(&chart.pairs)
+            .map(|row| {
+                (
+                    row.label.clone(),
+                    row.cells.iter().map(|c| c.play).collect(),
+                )
+            })
+            .collect::<HashMap<String, Vec<ChartMove>>>()
+    };
+    let reference = rows(reference);
+    let (mut same, mut total) = (0, 0);
+    for (label, plays) in rows(chart) {
+        let Some(other) = reference.get(&label) else {
+            continue;
+        };
+        total += RANKS;
+        same += plays.iter().zip(other).filter(|(a, b)| a == b).count();
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    same as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::chart::generate_chart;
+    use crate::config::GameConfig;
+    use crate::outcomes::StartingHand;
+    use crate::rules::Rules;
+    use crate::simulation::NoProgress;
+    use crate::training::{agreement, train};
+    use crate::types::Move;
+
+    #[test]
+    fn test_train() {
+        let conf = GameConfig::default();
+        let (table, result) = train(&conf, 200_000, 0.2, Some(9), &mut NoProgress).unwrap();
+        assert_eq!(result.rounds, 200_000);
+        // The clear cut moves are learned quickly
+        let best = |hand, upcard| table.best(&(hand, upcard), |_| true).unwrap().0;
+        assert_eq!(best(StartingHand::Hard(20), 6), Move::Stand);
+        assert_eq!(best(StartingHand::Hard(6), 10), Move::Hit);
+        assert_eq!(best(StartingHand::Pair(11), 6), Move::Split);
+        let chart = table.chart(&conf.rules);
+        let basic = generate_chart(&Rules::default()).unwrap();
+        assert!(agreement(&chart, &basic) > 0.6);
+        assert_eq!(agreement(&basic, &basic), 1.0);
+
+        assert!(train(&conf, 10, 1.5, None, &mut NoProgress).is_err());
+    }
+}
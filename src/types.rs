@@ -1,45 +1,59 @@
-use rand::prelude::*;
+use crate::betting::BetStrategy;
+use crate::bonus::{self, Bonus21};
+use crate::error::Error;
+use crate::ledger::Ledger;
+use crate::money::Money;
+use crate::shuffle::Shuffler;
+use crate::stats::SessionStats;
+use crate::variants::{self, Variant};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
-
-#[derive(Debug)]
-pub enum Error {
-    InvalidCardVal(u32),
-}
+use std::str::FromStr;
 
 pub const TWENTY_ONE: u32 = 21;
 pub const DECK_SIZE: usize = 52;
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Hand {
     pub cards: Vec<Card>,
 }
 
 impl Hand {
     pub fn calc_value(&self) -> u32 {
-        let aces = self
-            .cards
-            .iter()
-            .filter(|c| c.value == Value::Ace)
-            .collect::<Vec<&Card>>()
-            .len() as u32;
+        let soft_value = self.soft_value();
+        if soft_value <= TWENTY_ONE {
+            soft_value
+        } else {
+            self.hard_value()
+        }
+    }
 
-        let val_without_aces = self
-            .cards
+    /// The value with every ace counted as 1.
+    pub fn hard_value(&self) -> u32 {
+        self.cards
             .iter()
-            .filter(|c| c.value != Value::Ace)
-            .map(|c| c.value.value())
-            .sum();
-
-        let mut val = val_without_aces;
-        for n in 0..=aces {
-            let num_aces = aces - n;
-            val = val_without_aces + (num_aces * Value::ACE_HIGH_VAL) + (n * Value::ACE_LOW_VAL);
+            .map(|c| match c.value {
+                Value::Ace => Value::ACE_LOW_VAL,
+                _ => c.value.value(),
+            })
+            .sum()
+    }
 
-            if val <= TWENTY_ONE {
-                return val;
-            }
+    /// The value with one ace counted as 11, the hard value if there is no ace.
+    /// Only one ace can ever count as 11, two would make 22.
+    pub fn soft_value(&self) -> u32 {
+        let hard_value = self.hard_value();
+        if self.cards.iter().any(|c| c.value == Value::Ace) {
+            hard_value + Value::ACE_HIGH_VAL - Value::ACE_LOW_VAL
+        } else {
+            hard_value
         }
-        return val;
+    }
+
+    /// A hand is soft when it contains an ace that is currently counted as 11.
+    pub fn is_soft(&self) -> bool {
+        self.calc_value() != self.hard_value()
     }
 
     pub fn from_card(card: Card) -> Self {
@@ -54,6 +68,49 @@ impl Hand {
         let num_aces = self.cards.iter().filter(|c| c.value == Value::Ace).count();
         num_aces == 1 && self.cards.len() == 2 && self.calc_value() == TWENTY_ONE
     }
+
+    /// Whether the hand has reached `num_cards` cards without busting.
+    pub fn is_charlie(&self, num_cards: usize) -> bool {
+        self.cards.len() >= num_cards && self.calc_value() <= TWENTY_ONE
+    }
+
+    /// Whether the hand consists of exactly two cards of the same rank.
+    pub fn is_pair(&self) -> bool {
+        self.cards.len() == 2 && self.cards[0].value == self.cards[1].value
+    }
+
+    /// Whether the hand can be split into two hands, the table rules may still forbid it.
+    pub fn can_split(&self) -> bool {
+        self.is_pair()
+    }
+
+    /// Splits a pair into two hands of one card each, the first keeps the first card.
+    pub fn split(self) -> Result<(Hand, Hand), Error> {
+        if !self.can_split() {
+            return Err(Error::IllegalMove(Move::Split));
+        }
+        let [first, second]: [Card; 2] = self.cards.try_into().expect("A pair has two cards");
+        Ok((Hand::from_card(first), Hand::from_card(second)))
+    }
+
+    /// The suit shared by every card in the hand, `None` if the suits are mixed.
+    pub fn suit(&self) -> Option<Suit> {
+        let suit = self.cards.first()?.suit;
+        self.cards.iter().all(|c| c.suit == suit).then_some(suit)
+    }
+
+    /// Whether the hand consists of exactly the given card values, in any order.
+    pub fn is_composed_of(&self, values: &[u32]) -> bool {
+        let mut own = self
+            .cards
+            .iter()
+            .map(|c| c.value.value())
+            .collect::<Vec<u32>>();
+        let mut values = values.to_vec();
+        own.sort_unstable();
+        values.sort_unstable();
+        own == values
+    }
 }
 
 impl Display for Hand {
@@ -71,64 +128,659 @@ impl Display for Hand {
     }
 }
 
-#[derive(Clone)]
+/// A decision the player can make on a hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Move {
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+}
+
+/// A hand played by the player together with the bet placed on it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerHand {
+    pub hand: Hand,
+    pub bet: Money,
+    /// The part of `bet` placed before the cards were dealt, the rest was added by doubling.
+    /// Hands split off another hand have no original bet as the whole bet is an additional wager.
+    pub original_bet: Money,
+    /// The part of the stake wagered by the house through free doubles and splits,
+    /// it is paid out on a win but never lost.
+    pub free_bet: Money,
+    /// Hands created by splitting a pair can never be a blackjack.
+    pub split: bool,
+    pub doubled: bool,
+    pub surrendered: bool,
+    /// A blackjack paid out 1:1 before the dealer checked for blackjack.
+    pub even_money: bool,
+    /// The index of the dealt hand this hand originates from, shared by all hands split from it.
+    pub origin: usize,
+}
+
+impl PlayerHand {
+    pub fn new(hand: Hand, bet: Money) -> Self {
+        Self {
+            hand,
+            bet,
+            original_bet: bet,
+            free_bet: Money::ZERO,
+            split: false,
+            doubled: false,
+            surrendered: false,
+            even_money: false,
+            origin: 0,
+        }
+    }
+
+    pub fn is_blackjack(&self) -> bool {
+        !self.split && self.hand.is_blackjack()
+    }
+
+    pub fn is_bust(&self) -> bool {
+        self.hand.calc_value() > TWENTY_ONE
+    }
+
+    /// Whether the hand is already decided before the dealer plays.
+    pub fn is_finished(&self) -> bool {
+        self.is_bust() || self.is_blackjack() || self.surrendered
+    }
+
+    pub fn is_split_aces(&self) -> bool {
+        self.split && self.hand.cards.first().map(|c| &c.value) == Some(&Value::Ace)
+    }
+
+    /// Surrender is only allowed on the first two cards of the original hand.
+    pub fn can_surrender(&self) -> bool {
+        !self.split && self.hand.cards.len() == 2
+    }
+
+    /// The total amount the hand plays for, including free bets.
+    pub fn stake(&self) -> Money {
+        self.bet + self.free_bet
+    }
+
+    /// The wagers added after the deal by doubling and splitting.
+    pub fn additional_bet(&self) -> Money {
+        self.bet - self.original_bet
+    }
+
+    /// Doubles the stake, the hand then receives exactly one more card.
+    pub fn double(&mut self, card: Card) {
+        self.bet += self.stake();
+        self.doubled = true;
+        self.hand.add_card(card);
+    }
+
+    /// Raises the stake by `amount` and takes another card without ending the hand, as in Pontoon.
+    pub fn buy(&mut self, card: Card, amount: Money) {
+        self.bet += amount;
+        self.hand.add_card(card);
+    }
+
+    /// Doubles the stake with a free bet from the house.
+    pub fn free_double(&mut self, card: Card) {
+        self.free_bet += self.stake();
+        self.doubled = true;
+        self.hand.add_card(card);
+    }
+
+    /// Splits the pair into two hands with one card each, both carrying the original bet.
+    pub fn split(&mut self) -> PlayerHand {
+        let (first, second) = std::mem::take(&mut self.hand)
+            .split()
+            .expect("Only a pair can be split");
+        self.hand = first;
+        self.split = true;
+        PlayerHand {
+            original_bet: Money::ZERO,
+            free_bet: self.free_bet,
+            split: true,
+            origin: self.origin,
+            ..PlayerHand::new(second, self.bet)
+        }
+    }
+}
+
+/// Serialized without the betting system, which keeps its own progression.
+#[derive(Serialize, Deserialize)]
+pub struct Player {
+    /// The name the player is called by at a table with several players.
+    #[serde(default)]
+    pub name: Option<String>,
+    pub money: Money,
+    pub stats: SessionStats,
+    /// Every bet and payout of the session.
+    pub ledger: Ledger,
+    /// Sizes the main bets automatically instead of asking the player, if a betting system is used.
+    #[serde(skip)]
+    pub bet_strategy: Option<Box<dyn BetStrategy>>,
+    pub hands: Vec<PlayerHand>,
+    /// The insurance side bet against a dealer blackjack, 0 if no insurance was taken.
+    pub insurance: Money,
+    /// The 21+3 side bet on the first hand and the dealer upcard, 0 if not placed.
+    pub twenty_one_plus_three: Money,
+    /// The Bust It side bet on the dealer busting, 0 if not placed.
+    pub bust_it: Money,
+}
+
+impl Player {
+    /// Insurance pays 2:1 when the dealer has blackjack.
+    pub const INSURANCE_PAYOUT: Payout = Payout::TWO_TO_ONE;
+
+    pub fn new(money: Money) -> Self {
+        Self {
+            name: None,
+            money,
+            stats: SessionStats::new(money),
+            ledger: Ledger::default(),
+            bet_strategy: None,
+            hands: vec![],
+            insurance: Money::ZERO,
+            twenty_one_plus_three: Money::ZERO,
+            bust_it: Money::ZERO,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// A copy of the player's seat and session, without the betting system.
+    pub fn detached(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            money: self.money,
+            stats: self.stats.clone(),
+            ledger: self.ledger.clone(),
+            bet_strategy: None,
+            hands: self.hands.clone(),
+            insurance: self.insurance,
+            twenty_one_plus_three: self.twenty_one_plus_three,
+            bust_it: self.bust_it,
+        }
+    }
+
+    /// Bets are taken from the bankroll as they are placed, so only the remaining money counts.
+    pub fn can_afford(&self, extra_bet: Money) -> bool {
+        extra_bet <= self.money
+    }
+
+    /// Takes a newly placed bet from the bankroll and records it in the ledger.
+    pub fn wager(&mut self, description: &str, amount: Money) {
+        if amount == Money::ZERO {
+            return;
+        }
+        self.money -= amount;
+        self.ledger.record(description, -amount);
+    }
+
+    /// Pays a settled bet back to the bankroll, `amount` includes the returned bet.
+    /// Lost bets are credited with nothing so the ledger shows how they were settled.
+    pub fn credit(&mut self, description: &str, amount: Money) {
+        self.money += amount;
+        self.ledger.record(description, amount);
+    }
+
+    /// Tips the dealer, the tip is gone for good but counted in the session stats.
+    pub fn tip(&mut self, amount: Money) {
+        self.money -= amount;
+        self.stats.tips += amount;
+        self.ledger.record("Dealer tip", -amount);
+    }
+
+    /// Buys in for more money after going broke.
+    pub fn rebuy(&mut self, amount: Money) {
+        self.money += amount;
+        self.stats.rebuy(amount);
+    }
+
+    /// Removes all hands and bets before a new round.
+    pub fn clear_bets(&mut self) {
+        self.hands.clear();
+        self.insurance = Money::ZERO;
+        self.twenty_one_plus_three = Money::ZERO;
+        self.bust_it = Money::ZERO;
+    }
+
+    /// The number of times the dealt hand `origin` has been split.
+    pub fn splits(&self, origin: usize) -> u32 {
+        let hands = self.hands.iter().filter(|h| h.origin == origin).count() as u32;
+        hands.saturating_sub(1)
+    }
+
+    /// The largest insurance bet allowed, half the bet on all hands that aren't a blackjack.
+    pub fn max_insurance(&self) -> Money {
+        self.hands
+            .iter()
+            .filter(|h| !h.is_blackjack())
+            .map(|h| h.bet)
+            .sum::<Money>()
+            .half()
+    }
+}
+
+/// The result of a single player hand against the dealer.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    Blackjack,
+    Win,
+    DealerBust,
+    Push,
+    Loss,
+    Bust,
+    Surrender,
+    EvenMoney,
+    Charlie,
+    /// The dealer reached a total that pushes all live hands, e.g. 22 in Free Bet.
+    DealerPush,
+    /// A 21 paying a bonus, e.g. a five card 21 in Spanish 21.
+    Bonus(Payout),
+    /// Five cards without busting in Pontoon, paying 2:1.
+    FiveCardTrick,
+    /// Lost to an unpeeked dealer blackjack where only original bets are lost,
+    /// the wagers added by doubling and splitting are returned.
+    OriginalBetLoss,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Blackjack => write!(f, "blackjack"),
+            Outcome::Win => write!(f, "win"),
+            Outcome::DealerBust => write!(f, "dealer bust"),
+            Outcome::Push => write!(f, "push"),
+            Outcome::Loss => write!(f, "loss"),
+            Outcome::Bust => write!(f, "bust"),
+            Outcome::Surrender => write!(f, "surrender"),
+            Outcome::EvenMoney => write!(f, "even money"),
+            Outcome::Charlie => write!(f, "charlie"),
+            Outcome::DealerPush => write!(f, "dealer push"),
+            Outcome::Bonus(payout) => write!(f, "bonus {}", payout),
+            Outcome::FiveCardTrick => write!(f, "five card trick"),
+            Outcome::OriginalBetLoss => write!(f, "original bet loss"),
+        }
+    }
+}
+
+impl Outcome {
+    /// `charlie` is the number of cards that automatically wins the hand, if that rule is in play,
+    /// and `bonuses` are the bonus 21s offered at the table.
+    pub fn resolve(
+        player: &PlayerHand,
+        dealer: &Hand,
+        charlie: Option<usize>,
+        variant: Variant,
+        bonuses: &[Bonus21],
+    ) -> Self {
+        if variant == Variant::Pontoon {
+            return variants::resolve_pontoon(player, dealer);
+        }
+
+        if player.surrendered {
+            return Outcome::Surrender;
+        }
+
+        if player.even_money {
+            return Outcome::EvenMoney;
+        }
+
+        if player.is_blackjack() {
+            if dealer.is_blackjack() && !variant.player_21_always_wins() {
+                return Outcome::Push;
+            }
+            return Outcome::Blackjack;
+        }
+
+        let player_value = player.hand.calc_value();
+        let dealer_value = dealer.calc_value();
+
+        if player_value > TWENTY_ONE {
+            Outcome::Bust
+        } else if dealer.is_blackjack() {
+            // A dealer blackjack beats any hand that isn't a blackjack, even a 21
+            Outcome::Loss
+        } else if charlie.is_some_and(|n| player.hand.is_charlie(n)) {
+            Outcome::Charlie
+        } else if let Some(payout) = bonus::bonus_payout(player, bonuses) {
+            // A bonus 21 is paid regardless of what the dealer makes
+            Outcome::Bonus(payout)
+        } else if variant.player_21_always_wins() && player_value == TWENTY_ONE {
+            Outcome::Win
+        } else if variant.dealer_push_value() == Some(dealer_value) {
+            Outcome::DealerPush
+        } else if dealer_value > TWENTY_ONE {
+            Outcome::DealerBust
+        } else if player_value == dealer_value {
+            Outcome::Push
+        } else if dealer_value > player_value {
+            Outcome::Loss
+        } else {
+            Outcome::Win
+        }
+    }
+
+    /// The amount paid back for the hand, the returned bet plus the net winnings.
+    pub fn total_return(&self, hand: &PlayerHand, blackjack_payout: &Payout) -> Money {
+        hand.bet + self.net_winnings(hand, blackjack_payout)
+    }
+
+    /// The amount won (positive) or lost (negative) by the hand, free bets are only ever won.
+    pub fn net_winnings(&self, hand: &PlayerHand, blackjack_payout: &Payout) -> Money {
+        let bet = hand.bet;
+        let stake = hand.stake();
+        match self {
+            Outcome::Blackjack => blackjack_payout.apply(stake),
+            Outcome::Bonus(payout) => payout.apply(stake),
+            Outcome::FiveCardTrick => Payout::TWO_TO_ONE.apply(stake),
+            Outcome::Win | Outcome::DealerBust | Outcome::EvenMoney | Outcome::Charlie => stake,
+            Outcome::Push | Outcome::DealerPush => Money::ZERO,
+            Outcome::Loss | Outcome::Bust => -bet,
+            Outcome::Surrender => -bet.half(),
+            Outcome::OriginalBetLoss => -hand.original_bet,
+        }
+    }
+}
+
+/// A payout ratio such as 3:2, the winnings for a bet are `bet * numerator / denominator`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Payout {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl Payout {
+    pub const THREE_TO_TWO: Payout = Payout {
+        numerator: 3,
+        denominator: 2,
+    };
+    pub const SIX_TO_FIVE: Payout = Payout {
+        numerator: 6,
+        denominator: 5,
+    };
+    pub const TWO_TO_ONE: Payout = Payout {
+        numerator: 2,
+        denominator: 1,
+    };
+    pub const THREE_TO_ONE: Payout = Payout {
+        numerator: 3,
+        denominator: 1,
+    };
+
+    /// The winnings for `bet`, rounded down to the cent.
+    pub fn apply(&self, bet: Money) -> Money {
+        bet.scale(self.numerator, self.denominator)
+    }
+}
+
+impl Display for Payout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.numerator, self.denominator)
+    }
+}
+
+impl FromStr for Payout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (numerator, denominator) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected a ratio like 3:2, got '{}'", s))?;
+        let numerator = numerator
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid numerator '{}': {}", numerator, e))?;
+        let denominator = denominator
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid denominator '{}': {}", denominator, e))?;
+
+        if denominator == 0 {
+            return Err(String::from("the denominator cannot be 0"));
+        }
+
+        Ok(Payout {
+            numerator,
+            denominator,
+        })
+    }
+}
+
+/// A casino chip, named by its usual colour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Chip {
+    White,
+    Red,
+    Green,
+    Black,
+    Purple,
+    Orange,
+}
+
+impl Chip {
+    /// All chips from the highest to the lowest denomination.
+    pub const ALL: [Chip; 6] = [
+        Chip::Orange,
+        Chip::Purple,
+        Chip::Black,
+        Chip::Green,
+        Chip::Red,
+        Chip::White,
+    ];
+
+    pub fn value(&self) -> u64 {
+        match self {
+            Chip::White => 1,
+            Chip::Red => 5,
+            Chip::Green => 25,
+            Chip::Black => 100,
+            Chip::Purple => 500,
+            Chip::Orange => 1000,
+        }
+    }
+}
+
+/// An amount broken down into as few chips as possible, highest denominations first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChipStack {
+    pub chips: Vec<(Chip, u64)>,
+}
+
+impl ChipStack {
+    /// Only whole units are made up of chips, any cents are left out.
+    pub fn from_amount(amount: Money) -> Self {
+        let mut remaining = amount.units().max(0) as u64;
+        let mut chips = vec![];
+        for chip in Chip::ALL {
+            let count = remaining / chip.value();
+            if count > 0 {
+                chips.push((chip, count));
+                remaining -= count * chip.value();
+            }
+        }
+
+        Self { chips }
+    }
+}
+
+impl Display for ChipStack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.chips
+                .iter()
+                .map(|(chip, count)| format!("{}x{}", count, chip.value()))
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
+
+/// Which hand totals the player is allowed to double down on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum DoubleRule {
+    Any,
+    NineToEleven,
+    TenToEleven,
+}
+
+impl DoubleRule {
+    pub fn allows(&self, hand_value: u32) -> bool {
+        match self {
+            DoubleRule::Any => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&hand_value),
+            DoubleRule::TenToEleven => (10..=11).contains(&hand_value),
+        }
+    }
+}
+
+impl Display for DoubleRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DoubleRule::Any => "any",
+                DoubleRule::NineToEleven => "9-11",
+                DoubleRule::TenToEleven => "10-11",
+            }
+        )
+    }
+}
+
+impl FromStr for DoubleRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "any" => Ok(DoubleRule::Any),
+            "9-11" => Ok(DoubleRule::NineToEleven),
+            "10-11" => Ok(DoubleRule::TenToEleven),
+            _ => Err(format!(
+                "expected one of 'any', '9-11' or '10-11', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Shoe {
-    pub cards: Vec<Card>,
-    pub running_count: i32,
-    pub true_count: f32,
+    /// The cards left to deal, packed as a shoe can hold many decks. The last card is on top.
+    pub cards: Vec<PackedCard>,
+    /// The number of cards behind the cut card.
+    pub cut_card: u32,
+    /// Set once the cut card comes out, the shoe is reshuffled after the current round.
+    pub cut_card_reached: bool,
+    /// The discard tray holding burned cards and the cards of finished rounds.
+    pub discards: Vec<PackedCard>,
 }
 
 impl Shoe {
-    pub fn new(num_decks: u32) -> Result<Self, Error> {
+    pub fn new(num_decks: u32, variant: Variant) -> Result<Self, Error> {
         let deck_cards = (0..num_decks)
-            .into_iter()
-            .map(|d| match Deck::new() {
+            .map(|_| match Deck::new(variant) {
                 Ok(d) => Ok(d.cards),
                 Err(e) => Err(e),
             })
             .collect::<Result<Vec<Vec<Card>>, Error>>()?
             .into_iter()
             .flatten()
-            .collect::<Vec<Card>>();
+            .map(PackedCard::from)
+            .collect::<Vec<PackedCard>>();
 
         Ok(Shoe {
             cards: deck_cards,
-            running_count: 0,
-            true_count: 0f32,
+            cut_card: 0,
+            cut_card_reached: false,
+            discards: vec![],
         })
     }
 
-    pub fn take_card(&mut self) -> Card {
-        let card = self.cards.pop().expect("Out of cards!");
-        let card_val = card.value.value();
-        let count_change = if card_val <= 6 {
-            1
-        } else if card_val >= 10 {
-            -1
-        } else {
-            0
-        };
+    /// Takes the top card of the shoe.
+    pub fn take_card(&mut self) -> Result<Card, Error> {
+        let card = self.cards.pop().ok_or(Error::OutOfCards)?;
+        if self.num_cards() <= self.cut_card {
+            self.cut_card_reached = true;
+        }
+
+        Ok(card.into())
+    }
+
+    /// Places the cut card with `cards_behind` cards left behind it.
+    pub fn place_cut_card(&mut self, cards_behind: u32) {
+        self.cut_card = cards_behind;
+        self.cut_card_reached = self.num_cards() <= cards_behind;
+    }
 
-        self.running_count += count_change;
-        let remaining_decks = (self.cards.len() / DECK_SIZE) as f32;
-        self.true_count = self.running_count as f32 / remaining_decks;
+    /// Discards the top card face down, so it is never seen or counted.
+    pub fn burn_card(&mut self) {
+        if let Some(card) = self.cards.pop() {
+            self.discards.push(card);
+        }
+    }
+
+    /// The fraction of the shoe that has been dealt (or burned) and moved to the discard tray.
+    pub fn penetration(&self) -> f32 {
+        let total = self.cards.len() + self.discards.len();
+        if total == 0 {
+            return 0.0;
+        }
+        self.discards.len() as f32 / total as f32
+    }
+
+    pub fn discard(&mut self, cards: Vec<Card>) {
+        self.discards
+            .extend(cards.into_iter().map(PackedCard::from));
+    }
 
-        card
+    /// Returns the discards to the shoe and shuffles them in with the cards left, like a
+    /// continuous shuffling machine.
+    pub fn return_discards(&mut self, shuffler: &mut dyn Shuffler) {
+        self.cards.append(&mut self.discards);
+        shuffler.shuffle(&mut self.cards);
     }
 
     pub fn num_cards(&self) -> u32 {
         self.cards.len() as u32
     }
 
-    pub fn shuffle(self) -> Self {
-        let mut rng = thread_rng();
+    /// The number of cards of every rank left to deal, including the ranks that have run out.
+    pub fn remaining_by_rank(&self) -> BTreeMap<Value, u32> {
+        let mut remaining = Value::ALL
+            .iter()
+            .map(|value| (value.clone(), 0))
+            .collect::<BTreeMap<Value, u32>>();
+        for card in self.cards.iter() {
+            *remaining.entry(card.value()).or_default() += 1;
+        }
+        remaining
+    }
+
+    /// The next `num_cards` cards that will be dealt, in order, without taking them.
+    pub fn preview(&self, num_cards: usize) -> Vec<Card> {
+        self.cards
+            .iter()
+            .rev()
+            .take(num_cards)
+            .map(|card| Card::from(*card))
+            .collect()
+    }
+
+    /// Gathers the discards back into the shoe and shuffles all cards with `shuffler`.
+    pub fn shuffle(self, shuffler: &mut dyn Shuffler) -> Self {
         let mut new_cards = self.cards.clone();
-        new_cards.shuffle(&mut rng);
+        new_cards.extend(self.discards);
+        shuffler.shuffle(&mut new_cards);
 
         Self {
             cards: new_cards,
-            running_count: 0,
-            true_count: 0f32,
+            cut_card: self.cut_card,
+            cut_card_reached: false,
+            discards: vec![],
         }
     }
 }
@@ -139,42 +791,107 @@ pub struct Deck {
 }
 
 impl Deck {
-    pub fn new() -> Result<Self, Error> {
+    pub fn new(variant: Variant) -> Result<Self, Error> {
         let all_values = (2u32..=14)
-            .into_iter()
-            .map(|v| Value::try_from(v))
+            .map(Value::try_from)
+            .filter(|v| !(variant.removes_tens() && matches!(v, Ok(Value::Ten))))
             .collect::<Result<Vec<Value>, Error>>()?;
 
         let all_suites = vec![Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
         let cards = all_suites
             .into_iter()
-            .map(|suit| {
+            .flat_map(|suit| {
                 all_values
                     .clone()
                     .into_iter()
                     .map(|value| Card { suit, value })
                     .collect::<Vec<Card>>()
             })
-            .flatten()
             .collect::<Vec<Card>>();
 
         Ok(Deck { cards })
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Card {
     pub suit: Suit,
     pub value: Value,
 }
 
+/// A card packed into a single byte, the rank in the low four bits and the suit above them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8")]
+pub struct PackedCard(u8);
+
+impl PackedCard {
+    const RANK_BITS: u8 = 4;
+    const SUITS: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+    const VALUES: [Value; 13] = Value::ALL;
+
+    pub fn suit(self) -> Suit {
+        Self::SUITS[(self.0 >> Self::RANK_BITS) as usize]
+    }
+
+    pub fn value(self) -> Value {
+        Self::VALUES[(self.0 & ((1 << Self::RANK_BITS) - 1)) as usize].clone()
+    }
+
+    /// The packed byte of the card.
+    pub fn to_byte(self) -> u8 {
+        self.0
+    }
+
+    /// Unpacks a byte from [`PackedCard::to_byte`], `None` if it isn't a card.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        let in_range = (byte >> Self::RANK_BITS) < Self::SUITS.len() as u8
+            && (byte & ((1 << Self::RANK_BITS) - 1)) < Self::VALUES.len() as u8;
+        in_range.then_some(PackedCard(byte))
+    }
+}
+
+impl TryFrom<u8> for PackedCard {
+    type Error = String;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        PackedCard::from_byte(byte).ok_or_else(|| format!("{} isn't a packed card", byte))
+    }
+}
+
+impl From<&Card> for PackedCard {
+    fn from(card: &Card) -> Self {
+        PackedCard((card.suit as u8) << Self::RANK_BITS | card.value.clone() as u8)
+    }
+}
+
+impl From<Card> for PackedCard {
+    fn from(card: Card) -> Self {
+        PackedCard::from(&card)
+    }
+}
+
+impl From<PackedCard> for Card {
+    fn from(card: PackedCard) -> Self {
+        Card {
+            suit: card.suit(),
+            value: card.value(),
+        }
+    }
+}
+
+impl Display for PackedCard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Card::from(*self))
+    }
+}
+
 impl Display for Card {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}{}", self.value, self.suit)
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Suit {
     Spades,
     Hearts,
@@ -197,7 +914,7 @@ impl Display for Suit {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Value {
     Two,
     Three,
@@ -217,6 +934,23 @@ pub enum Value {
 impl Value {
     pub const ACE_HIGH_VAL: u32 = 11;
     pub const ACE_LOW_VAL: u32 = 1;
+
+    /// Every rank, from two to ace.
+    pub const ALL: [Value; 13] = [
+        Value::Two,
+        Value::Three,
+        Value::Four,
+        Value::Five,
+        Value::Six,
+        Value::Seven,
+        Value::Eight,
+        Value::Nine,
+        Value::Ten,
+        Value::Jack,
+        Value::Queen,
+        Value::King,
+        Value::Ace,
+    ];
 }
 
 impl TryFrom<u32> for Value {
@@ -267,6 +1001,19 @@ impl Display for Value {
     }
 }
 
+/// Parses a rank as printed on the card, e.g. "7", "10", "K" or "A", in any case.
+impl FromStr for Value {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Value::ALL
+            .iter()
+            .find(|value| value.to_string().eq_ignore_ascii_case(s.trim()))
+            .cloned()
+            .ok_or_else(|| format!("expected a rank from 2 to 10, J, Q, K or A, got '{}'", s))
+    }
+}
+
 impl Value {
     pub fn value(&self) -> u32 {
         match self {
@@ -288,9 +1035,32 @@ impl Value {
 }
 
 #[cfg(test)]
-mod tests {
-    use crate::types::{Card, Suit, Value};
-    use crate::Hand;
+pub(crate) mod tests {
+    use crate::error::Error;
+    use crate::money::Money;
+    use crate::shuffle::FisherYates;
+    use crate::types::{
+        Card, Chip, ChipStack, Deck, Hand, Move, Outcome, PackedCard, Payout, Player, PlayerHand,
+        Shoe, Suit, Value,
+    };
+    use crate::variants::Variant;
+
+    /// A hand of `values` in `suit`, for a bet of 10.
+    pub(crate) fn suited_hand(values: &[Value], suit: Suit) -> PlayerHand {
+        let mut hand = Hand::default();
+        for value in values {
+            hand.add_card(Card {
+                suit,
+                value: value.clone(),
+            });
+        }
+        PlayerHand::new(hand, Money::from_units(10))
+    }
+
+    /// A hand of `values` in spades, for a bet of 10.
+    pub(crate) fn hand(values: &[Value]) -> PlayerHand {
+        suited_hand(values, Suit::Spades)
+    }
 
     #[test]
     fn test_hand_add_card() {
@@ -369,4 +1139,416 @@ mod tests {
 
         assert_eq!(hand.calc_value(), 21);
     }
+
+    #[test]
+    fn test_soft_and_hard_values() {
+        let hand = |values: &[Value]| Hand {
+            cards: values
+                .iter()
+                .map(|value| Card {
+                    suit: Suit::Hearts,
+                    value: value.clone(),
+                })
+                .collect(),
+        };
+
+        let soft_17 = hand(&[Value::Ace, Value::Six]);
+        assert!(soft_17.is_soft());
+        assert_eq!(soft_17.hard_value(), 7);
+        assert_eq!(soft_17.soft_value(), 17);
+
+        let hard_17 = hand(&[Value::Ten, Value::Six, Value::Ace]);
+        assert!(!hard_17.is_soft());
+        assert_eq!(hard_17.hard_value(), 17);
+        assert_eq!(hard_17.soft_value(), 27);
+        assert_eq!(hard_17.calc_value(), 17);
+
+        let two_aces = hand(&[Value::Ace, Value::Ace]);
+        assert!(two_aces.is_soft());
+        assert_eq!(two_aces.calc_value(), 12);
+        assert!(!hand(&[Value::Ten, Value::Seven]).is_soft());
+    }
+
+    #[test]
+    fn test_player_hand_split() {
+        let mut hand = Hand::from_card(Card {
+            suit: Suit::Spades,
+            value: Value::Eight,
+        });
+        hand.add_card(Card {
+            suit: Suit::Hearts,
+            value: Value::Eight,
+        });
+        assert!(hand.is_pair());
+        assert!(hand.can_split());
+        let (first, second) = hand.clone().split().unwrap();
+        assert_eq!(first.cards, vec![hand.cards[0].clone()]);
+        assert_eq!(second.cards, vec![hand.cards[1].clone()]);
+        let mut three_cards = hand.clone();
+        three_cards.add_card(hand.cards[0].clone());
+        assert_eq!(three_cards.split(), Err(Error::IllegalMove(Move::Split)));
+
+        let mut player_hand = PlayerHand::new(hand, Money::from_units(10));
+        let new_hand = player_hand.split();
+
+        assert_eq!(player_hand.hand.cards.len(), 1);
+        assert_eq!(new_hand.hand.cards.len(), 1);
+        assert_eq!(new_hand.bet, Money::from_units(10));
+        assert!(player_hand.split && new_hand.split);
+        assert_eq!(new_hand.origin, player_hand.origin);
+    }
+
+    #[test]
+    fn test_player_splits() {
+        let card = Card {
+            suit: Suit::Spades,
+            value: Value::Ace,
+        };
+        let mut hand = Hand::from_card(card.clone());
+        hand.add_card(card.clone());
+
+        let mut player = Player::new(Money::from_units(100));
+        let mut first = PlayerHand::new(hand, Money::from_units(10));
+        first.origin = 1;
+        let mut second = first.split();
+        second.hand.add_card(card);
+        let third = second.split();
+        player.hands = vec![first, second, third];
+
+        assert_eq!(player.splits(0), 0);
+        assert_eq!(player.splits(1), 2);
+        assert!(player.hands.iter().all(|h| h.is_split_aces()));
+    }
+
+    #[test]
+    fn test_split_hand_is_not_blackjack() {
+        let mut hand = Hand::from_card(Card {
+            suit: Suit::Spades,
+            value: Value::Ace,
+        });
+        hand.add_card(Card {
+            suit: Suit::Hearts,
+            value: Value::King,
+        });
+        let mut player_hand = PlayerHand::new(hand, Money::from_units(10));
+        assert!(player_hand.is_blackjack());
+
+        player_hand.split = true;
+        assert!(!player_hand.is_blackjack());
+
+        let mut dealer = Hand::from_card(Card {
+            suit: Suit::Clubs,
+            value: Value::King,
+        });
+        dealer.add_card(Card {
+            suit: Suit::Diamonds,
+            value: Value::Queen,
+        });
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, None, Variant::Standard, &[]),
+            Outcome::Win
+        );
+    }
+
+    #[test]
+    fn test_player_hand_double() {
+        let mut hand = Hand::from_card(Card {
+            suit: Suit::Spades,
+            value: Value::Five,
+        });
+        hand.add_card(Card {
+            suit: Suit::Hearts,
+            value: Value::Six,
+        });
+
+        let mut player_hand = PlayerHand::new(hand, Money::from_units(10));
+        player_hand.double(Card {
+            suit: Suit::Clubs,
+            value: Value::Queen,
+        });
+
+        assert!(player_hand.doubled);
+        assert_eq!(player_hand.bet, Money::from_units(20));
+        assert_eq!(player_hand.hand.calc_value(), 21);
+        assert_eq!(
+            Outcome::Win.net_winnings(&player_hand, &Payout::THREE_TO_TWO),
+            Money::from_units(20)
+        );
+    }
+
+    #[test]
+    fn test_original_bet_loss() {
+        let mut hand = Hand::from_card(Card {
+            suit: Suit::Spades,
+            value: Value::Eight,
+        });
+        hand.add_card(Card {
+            suit: Suit::Hearts,
+            value: Value::Eight,
+        });
+
+        let mut player_hand = PlayerHand::new(hand, Money::from_units(10));
+        let mut split_hand = player_hand.split();
+        split_hand.double(Card {
+            suit: Suit::Clubs,
+            value: Value::Three,
+        });
+        assert_eq!(player_hand.additional_bet(), Money::from_units(0));
+        assert_eq!(split_hand.additional_bet(), Money::from_units(20));
+
+        let payout = Payout::THREE_TO_TWO;
+        assert_eq!(
+            Outcome::OriginalBetLoss.net_winnings(&player_hand, &payout),
+            Money::from_units(-10)
+        );
+        assert_eq!(
+            Outcome::OriginalBetLoss.net_winnings(&split_hand, &payout),
+            Money::from_units(0)
+        );
+        assert_eq!(
+            Outcome::Loss.net_winnings(&split_hand, &payout),
+            Money::from_units(-20)
+        );
+    }
+
+    #[test]
+    fn test_surrender_against_blackjack() {
+        let mut hand = Hand::from_card(Card {
+            suit: Suit::Spades,
+            value: Value::Ten,
+        });
+        hand.add_card(Card {
+            suit: Suit::Hearts,
+            value: Value::Six,
+        });
+        let mut player_hand = PlayerHand::new(hand, Money::from_units(10));
+        player_hand.surrendered = true;
+
+        let mut dealer = Hand::from_card(Card {
+            suit: Suit::Clubs,
+            value: Value::Ace,
+        });
+        dealer.add_card(Card {
+            suit: Suit::Diamonds,
+            value: Value::King,
+        });
+        let outcome = Outcome::resolve(&player_hand, &dealer, None, Variant::Standard, &[]);
+        assert_eq!(outcome, Outcome::Surrender);
+        assert_eq!(
+            outcome.net_winnings(&player_hand, &Payout::THREE_TO_TWO),
+            Money::from_units(-5)
+        );
+    }
+
+    #[test]
+    fn test_bankroll_settlement() {
+        let mut player = Player::new(Money::from_units(100));
+        player.wager("Bet", Money::from_units(10));
+        player
+            .hands
+            .push(PlayerHand::new(Hand::default(), Money::from_units(10)));
+        assert_eq!(player.money, Money::from_units(90));
+        assert!(player.can_afford(Money::from_units(90)));
+        assert!(!player.can_afford(Money::from_units(91)));
+
+        let payout = Payout::THREE_TO_TWO;
+        let hand = &player.hands[0];
+        assert_eq!(
+            Outcome::Blackjack.total_return(hand, &payout),
+            Money::from_units(25)
+        );
+        assert_eq!(
+            Outcome::Push.total_return(hand, &payout),
+            Money::from_units(10)
+        );
+        assert_eq!(
+            Outcome::Surrender.total_return(hand, &payout),
+            Money::from_units(5)
+        );
+        assert_eq!(
+            Outcome::Loss.total_return(hand, &payout),
+            Money::from_units(0)
+        );
+
+        player.credit("Win", Outcome::Win.total_return(&player.hands[0], &payout));
+        assert_eq!(player.money, Money::from_units(110));
+
+        player.tip(Money::from_units(2));
+        assert_eq!(player.money, Money::from_units(108));
+        assert_eq!(player.stats.tips, Money::from_units(2));
+
+        player.stats.record_round(player.money);
+        player.rebuy(Money::from_units(50));
+        assert_eq!(player.stats.bought_in, Money::from_units(150));
+        assert_eq!(player.stats.profit(), Money::from_units(8));
+    }
+
+    #[test]
+    fn test_payout_parse() {
+        let payout = "6:5".parse::<Payout>().unwrap();
+        assert_eq!(payout.to_string(), "6:5");
+        let hand = PlayerHand::new(Hand::default(), Money::from_units(10));
+        assert_eq!(
+            Outcome::Blackjack.net_winnings(&hand, &payout),
+            Money::from_units(12)
+        );
+
+        assert!("6-5".parse::<Payout>().is_err());
+        assert!("3:0".parse::<Payout>().is_err());
+    }
+
+    #[test]
+    fn test_five_card_charlie() {
+        let two = Card {
+            suit: Suit::Hearts,
+            value: Value::Two,
+        };
+        let mut hand = Hand::from_card(two.clone());
+        for _ in 0..4 {
+            hand.add_card(two.clone());
+        }
+        assert!(hand.is_charlie(5));
+        assert!(!hand.is_charlie(6));
+
+        let mut dealer = Hand::from_card(Card {
+            suit: Suit::Clubs,
+            value: Value::King,
+        });
+        dealer.add_card(Card {
+            suit: Suit::Diamonds,
+            value: Value::Nine,
+        });
+
+        let player_hand = PlayerHand::new(hand, Money::from_units(10));
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, Some(5), Variant::Standard, &[]),
+            Outcome::Charlie
+        );
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, None, Variant::Standard, &[]),
+            Outcome::Loss
+        );
+    }
+
+    #[test]
+    fn test_free_bet_winnings() {
+        let mut hand = Hand::from_card(Card {
+            suit: Suit::Spades,
+            value: Value::Five,
+        });
+        hand.add_card(Card {
+            suit: Suit::Hearts,
+            value: Value::Five,
+        });
+        let mut player_hand = PlayerHand::new(hand, Money::from_units(10));
+        player_hand.free_double(Card {
+            suit: Suit::Clubs,
+            value: Value::Two,
+        });
+        assert_eq!(player_hand.stake(), Money::from_units(20));
+
+        let payout = Payout::THREE_TO_TWO;
+        assert_eq!(
+            Outcome::Win.net_winnings(&player_hand, &payout),
+            Money::from_units(20)
+        );
+        assert_eq!(
+            Outcome::Loss.net_winnings(&player_hand, &payout),
+            Money::from_units(-10)
+        );
+
+        let mut dealer = Hand::from_card(Card {
+            suit: Suit::Clubs,
+            value: Value::King,
+        });
+        dealer.add_card(Card {
+            suit: Suit::Diamonds,
+            value: Value::Six,
+        });
+        dealer.add_card(Card {
+            suit: Suit::Diamonds,
+            value: Value::Six,
+        });
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, None, Variant::FreeBet, &[]),
+            Outcome::DealerPush
+        );
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, None, Variant::Standard, &[]),
+            Outcome::DealerBust
+        );
+    }
+
+    #[test]
+    fn test_chip_stack() {
+        let stack = ChipStack::from_amount(Money::from_units(1285));
+        assert_eq!(
+            stack.chips,
+            vec![
+                (Chip::Orange, 1),
+                (Chip::Black, 2),
+                (Chip::Green, 3),
+                (Chip::Red, 2)
+            ]
+        );
+        assert_eq!(stack.to_string(), "1x1000 2x100 3x25 2x5");
+        assert!(ChipStack::from_amount(Money::from_units(0))
+            .chips
+            .is_empty());
+    }
+
+    #[test]
+    fn test_packed_card() {
+        let deck = Deck::new(Variant::Standard).unwrap();
+        for card in deck.cards.iter() {
+            let packed = PackedCard::from(card);
+            assert_eq!(Card::from(packed), *card);
+            assert_eq!(PackedCard::from_byte(packed.to_byte()), Some(packed));
+        }
+        assert_eq!(std::mem::size_of::<PackedCard>(), 1);
+        assert_eq!(PackedCard::from_byte(0x0f), None);
+        assert_eq!(PackedCard::from_byte(0x40), None);
+    }
+
+    #[test]
+    fn test_shoe_return_discards() {
+        let mut shoe = Shoe::new(1, Variant::Standard).unwrap();
+        let mut hand = Hand::from_card(shoe.take_card().unwrap());
+        hand.add_card(shoe.take_card().unwrap());
+        shoe.burn_card();
+        assert_eq!(shoe.num_cards(), 49);
+
+        shoe.discard(hand.cards);
+        assert_eq!(shoe.discards.len(), 3);
+        assert_eq!(shoe.penetration(), 3.0 / 52.0);
+
+        let mut seeded = shoe.clone();
+        shoe.return_discards(&mut FisherYates::seeded(3));
+        assert_eq!(shoe.num_cards(), 52);
+        assert!(shoe.discards.is_empty());
+
+        // The same shuffle returns the cards to the same places
+        seeded.return_discards(&mut FisherYates::seeded(3));
+        assert_eq!(seeded.cards, shoe.cards);
+    }
+
+    #[test]
+    fn test_shoe_inspection() {
+        let mut shoe = Shoe::new(2, Variant::Standard).unwrap();
+        let next = shoe.preview(2);
+        assert_eq!(shoe.take_card().unwrap(), next[0]);
+        assert_eq!(shoe.take_card().unwrap(), next[1]);
+
+        let remaining = shoe.remaining_by_rank();
+        assert_eq!(remaining.len(), 13);
+        assert_eq!(remaining.values().sum::<u32>(), 102);
+        assert_eq!(
+            remaining[&next[0].value],
+            if next[0].value == next[1].value { 6 } else { 7 }
+        );
+
+        // Spanish 21 decks have no tens, but the rank is still counted
+        let spanish = Shoe::new(1, Variant::Spanish21).unwrap();
+        assert_eq!(spanish.remaining_by_rank()[&Value::Ten], 0);
+    }
 }
@@ -0,0 +1,280 @@
+use crate::bonus::Bonus21;
+use crate::types::{Hand, Outcome, Payout, PlayerHand, TWENTY_ONE};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The blackjack variant being played, layered on top of the base rules.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Variant {
+    Standard,
+    /// Free doubles on hard 9-11 and free splits on all pairs but tens,
+    /// paid for by the dealer pushing all live hands on a 22.
+    FreeBet,
+    /// Played without tens, a player 21 always wins and some 21s pay a bonus.
+    Spanish21,
+    /// British Pontoon, the dealer's cards are face down, the dealer wins ties
+    /// and a five card trick beats everything but a pontoon.
+    Pontoon,
+}
+
+/// The names of the player's moves, which differ between variants.
+pub struct Terminology {
+    pub hit: &'static str,
+    pub stand: &'static str,
+    pub double: &'static str,
+    pub natural: &'static str,
+}
+
+impl Variant {
+    /// The dealer total that pushes every hand that is still in play.
+    pub fn dealer_push_value(&self) -> Option<u32> {
+        match self {
+            Variant::FreeBet => Some(22),
+            _ => None,
+        }
+    }
+
+    /// Whether doubling the hand is free, i.e. matched by the house instead of the player.
+    pub fn is_free_double(&self, hand: &PlayerHand) -> bool {
+        match self {
+            // A two card total of 9-11 is always hard
+            Variant::FreeBet => {
+                hand.hand.cards.len() == 2 && (9..=11).contains(&hand.hand.calc_value())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether splitting the hand is free, i.e. the new hand is wagered by the house.
+    pub fn is_free_split(&self, hand: &PlayerHand) -> bool {
+        match self {
+            Variant::FreeBet => hand.hand.is_pair() && hand.hand.cards[0].value.value() != 10,
+            _ => false,
+        }
+    }
+
+    /// Whether the pip tens are removed from every deck, leaving 48 cards.
+    pub fn removes_tens(&self) -> bool {
+        *self == Variant::Spanish21
+    }
+
+    /// Whether a player 21 (and blackjack) beats a dealer 21.
+    pub fn player_21_always_wins(&self) -> bool {
+        *self == Variant::Spanish21
+    }
+
+    /// Whether the player may surrender the original bet after doubling.
+    pub fn allows_double_rescue(&self) -> bool {
+        *self == Variant::Spanish21
+    }
+
+    pub fn terminology(&self) -> Terminology {
+        match self {
+            Variant::Pontoon => Terminology {
+                hit: "twist",
+                stand: "stick",
+                double: "buy",
+                natural: "Pontoon",
+            },
+            _ => Terminology {
+                hit: "hit",
+                stand: "stand",
+                double: "double",
+                natural: "BlackJack",
+            },
+        }
+    }
+
+    /// The payout for a natural if the variant overrides the table's blackjack payout.
+    pub fn natural_payout(&self) -> Option<Payout> {
+        match self {
+            Variant::Pontoon => Some(Payout::TWO_TO_ONE),
+            _ => None,
+        }
+    }
+
+    /// Whether one of the dealer's cards is dealt face up.
+    pub fn dealer_shows_upcard(&self) -> bool {
+        *self != Variant::Pontoon
+    }
+
+    /// Whether insurance and even money are offered against a dealer Ace.
+    pub fn offers_insurance(&self) -> bool {
+        *self != Variant::Pontoon
+    }
+
+    pub fn allows_surrender(&self) -> bool {
+        *self != Variant::Pontoon
+    }
+
+    /// The lowest total the player is allowed to stand on.
+    pub fn min_stand_value(&self) -> u32 {
+        match self {
+            Variant::Pontoon => 15,
+            _ => 0,
+        }
+    }
+
+    /// Whether doubling deals a single card and ends the hand, in Pontoon the player may keep
+    /// buying cards (up to four) instead.
+    pub fn double_ends_hand(&self) -> bool {
+        *self != Variant::Pontoon
+    }
+
+    /// The bonus 21s offered when the table doesn't configure its own.
+    pub fn default_bonuses(&self) -> Vec<Bonus21> {
+        match self {
+            Variant::Spanish21 => Bonus21::ALL.to_vec(),
+            _ => vec![],
+        }
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Variant::Standard => "standard",
+                Variant::FreeBet => "free-bet",
+                Variant::Spanish21 => "spanish21",
+                Variant::Pontoon => "pontoon",
+            }
+        )
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Variant::Standard),
+            "free-bet" => Ok(Variant::FreeBet),
+            "spanish21" => Ok(Variant::Spanish21),
+            "pontoon" => Ok(Variant::Pontoon),
+            _ => Err(format!(
+                "expected one of 'standard', 'free-bet', 'spanish21' or 'pontoon', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// The number of cards that make up a five card trick in Pontoon.
+pub const FIVE_CARD_TRICK: usize = 5;
+
+/// Resolves a hand under Pontoon rules, where a pontoon beats a five card trick which beats
+/// every other hand, and the dealer wins all ties.
+pub fn resolve_pontoon(player: &PlayerHand, dealer: &Hand) -> Outcome {
+    if player.is_bust() {
+        return Outcome::Bust;
+    }
+
+    // The dealer's pontoon wins even against a player pontoon
+    if dealer.is_blackjack() {
+        return Outcome::Loss;
+    }
+
+    if player.is_blackjack() {
+        return Outcome::Blackjack;
+    }
+
+    if player.hand.is_charlie(FIVE_CARD_TRICK) {
+        return Outcome::FiveCardTrick;
+    }
+
+    let player_value = player.hand.calc_value();
+    let dealer_value = dealer.calc_value();
+    if dealer_value > TWENTY_ONE {
+        Outcome::DealerBust
+    } else if player_value > dealer_value {
+        Outcome::Win
+    } else {
+        Outcome::Loss
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::tests::hand;
+    use crate::types::{Deck, Outcome, Payout, Value};
+    use crate::variants::Variant;
+
+    #[test]
+    fn test_free_bet_doubles() {
+        let variant = Variant::FreeBet;
+        assert!(variant.is_free_double(&hand(&[Value::Five, Value::Six])));
+        assert!(variant.is_free_double(&hand(&[Value::Four, Value::Five])));
+        assert!(!variant.is_free_double(&hand(&[Value::Four, Value::Four])));
+        assert!(!variant.is_free_double(&hand(&[Value::Two, Value::Three, Value::Five])));
+        assert!(!Variant::Standard.is_free_double(&hand(&[Value::Five, Value::Six])));
+    }
+
+    #[test]
+    fn test_free_bet_splits() {
+        let variant = Variant::FreeBet;
+        assert!(variant.is_free_split(&hand(&[Value::Eight, Value::Eight])));
+        assert!(variant.is_free_split(&hand(&[Value::Ace, Value::Ace])));
+        assert!(!variant.is_free_split(&hand(&[Value::King, Value::King])));
+        assert!(!variant.is_free_split(&hand(&[Value::Eight, Value::Nine])));
+    }
+
+    #[test]
+    fn test_spanish21_deck() {
+        let deck = Deck::new(Variant::Spanish21).unwrap();
+        assert_eq!(deck.cards.len(), 48);
+        assert!(deck.cards.iter().all(|c| c.value != Value::Ten));
+    }
+
+    #[test]
+    fn test_spanish21_bonuses() {
+        let player_hand = hand(&[Value::Seven, Value::Eight, Value::Six]);
+        let dealer = hand(&[Value::King, Value::Ace]).hand;
+        let bonuses = Variant::Spanish21.default_bonuses();
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, None, Variant::Spanish21, &bonuses),
+            Outcome::Loss
+        );
+
+        let dealer = hand(&[Value::King, Value::Queen, Value::Ace]).hand;
+        assert_eq!(
+            Outcome::resolve(&player_hand, &dealer, None, Variant::Spanish21, &bonuses),
+            Outcome::Bonus(Payout::THREE_TO_ONE)
+        );
+        assert!(Variant::Standard.default_bonuses().is_empty());
+    }
+
+    #[test]
+    fn test_pontoon_resolution() {
+        let dealer = hand(&[Value::King, Value::Nine]).hand;
+        let five_card_trick = hand(&[
+            Value::Two,
+            Value::Two,
+            Value::Three,
+            Value::Four,
+            Value::Two,
+        ]);
+        assert_eq!(
+            super::resolve_pontoon(&five_card_trick, &dealer),
+            Outcome::FiveCardTrick
+        );
+
+        // The dealer wins ties
+        let nineteen = hand(&[Value::Queen, Value::Nine]);
+        assert_eq!(super::resolve_pontoon(&nineteen, &dealer), Outcome::Loss);
+
+        let dealer_pontoon = hand(&[Value::Ace, Value::Jack]).hand;
+        let pontoon = hand(&[Value::Ace, Value::King]);
+        assert_eq!(
+            super::resolve_pontoon(&pontoon, &dealer),
+            Outcome::Blackjack
+        );
+        assert_eq!(
+            super::resolve_pontoon(&pontoon, &dealer_pontoon),
+            Outcome::Loss
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use crate::builder::GameBuilder;
+use crate::error::Error;
+use crate::events::GameEvent;
+use crate::game::{Action, Game};
+use crate::money::Money;
+use crate::types::Move;
+use wasm_bindgen::prelude::*;
+
+/// A table driven from JavaScript, e.g. by a browser UI.
+///
+/// Amounts are passed as numbers of units. Every action returns the events it caused as a JSON
+/// array and a rejected action throws an error with the reason.
+#[wasm_bindgen]
+pub struct Table {
+    game: Game,
+}
+
+fn to_js(error: Error) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn events_json(events: Vec<GameEvent>) -> String {
+    serde_json::to_string(&events).expect("Events can always be serialized")
+}
+
+#[wasm_bindgen]
+impl Table {
+    /// Sits down at a freshly shuffled shoe, the other rules are the defaults.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        decks: u32,
+        penetration: f32,
+        hit_soft_17: bool,
+        buy_in: f64,
+    ) -> Result<Table, JsError> {
+        let game = GameBuilder::new()
+            .decks(decks)
+            .penetration(penetration)
+            .h17(hit_soft_17)
+            .buy_in(Money::from_units_f64(buy_in))
+            .build_game()
+            .map_err(to_js)?;
+        Ok(Table { game })
+    }
+
+    /// The table as JSON: the state, the dealer's cards, the hands, the bankroll, the available
+    /// moves and the count.
+    pub fn view(&self) -> String {
+        serde_json::to_string(&self.game.view()).expect("The table can always be serialized")
+    }
+
+    fn play(&mut self, action: Action) -> Result<String, JsError> {
+        self.game.play(action).map(events_json).map_err(to_js)
+    }
+
+    pub fn bet(&mut self, amount: f64) -> Result<String, JsError> {
+        self.play(Action::Bet(Money::from_units_f64(amount)))
+    }
+
+    pub fn deal(&mut self) -> Result<String, JsError> {
+        self.play(Action::Deal)
+    }
+
+    #[wasm_bindgen(js_name = evenMoney)]
+    pub fn even_money(&mut self, hand: usize) -> Result<String, JsError> {
+        self.play(Action::EvenMoney(hand))
+    }
+
+    pub fn insure(&mut self, amount: f64) -> Result<String, JsError> {
+        self.play(Action::Insure(Money::from_units_f64(amount)))
+    }
+
+    pub fn hit(&mut self) -> Result<String, JsError> {
+        self.play(Action::Move(Move::Hit))
+    }
+
+    pub fn stand(&mut self) -> Result<String, JsError> {
+        self.play(Action::Move(Move::Stand))
+    }
+
+    pub fn double(&mut self) -> Result<String, JsError> {
+        self.play(Action::Move(Move::Double))
+    }
+
+    pub fn split(&mut self) -> Result<String, JsError> {
+        self.play(Action::Move(Move::Split))
+    }
+
+    pub fn surrender(&mut self) -> Result<String, JsError> {
+        self.play(Action::Move(Move::Surrender))
+    }
+
+    pub fn buy(&mut self, amount: f64) -> Result<String, JsError> {
+        self.play(Action::Buy(Money::from_units_f64(amount)))
+    }
+
+    pub fn rescue(&mut self, rescue: bool) -> Result<String, JsError> {
+        self.play(Action::Rescue(rescue))
+    }
+
+    pub fn tip(&mut self, amount: f64) -> Result<String, JsError> {
+        self.play(Action::Tip(Money::from_units_f64(amount)))
+    }
+
+    /// Calls off a round the shoe ran out of cards for and reshuffles.
+    #[wasm_bindgen(js_name = voidRound)]
+    pub fn void_round(&mut self) -> String {
+        events_json(self.game.void_round())
+    }
+}
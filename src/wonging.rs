@@ -0,0 +1,194 @@
+use crate::betting::BetRamp;
+use crate::config::GameConfig;
+use crate::error::Error;
+use crate::input::PlayerInput;
+use crate::money::Money;
+use crate::simulation::{Progress, Simulator, SIMULATION_BANKROLL};
+use crate::team::{SeatObserver, Seats, TeamPart};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The rounds watched between updates of the progress.
+const PROGRESS_INTERVAL: u64 = 1_000;
+
+/// How a back counter plays: watching the table without a bet and only sitting in while the
+/// true count is high.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wonging {
+    /// The true count the player sits in at.
+    pub entry: f32,
+    /// The true count the player leaves the table below, they leave at the shuffle too.
+    pub exit: f32,
+    /// The player's bet, in units of the bet ramp if there is one.
+    pub bet: Money,
+    pub ramp: Option<BetRamp>,
+}
+
+impl Wonging {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.exit > self.entry {
+            return Err(Error::Config(format!(
+                "the player can't leave below TC {} when sitting in at TC {}",
+                self.exit, self.entry
+            )));
+        }
+        Ok(())
+    }
+
+    /// The player's bet at the true count.
+    fn bet(&self, true_count: f32) -> Money {
+        match &self.ramp {
+            Some(ramp) => ramp.bet(true_count, self.bet),
+            None => self.bet,
+        }
+    }
+}
+
+/// The rounds a back counter watched and the ones they bet in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WongResult {
+    /// The rounds watched, including the ones played.
+    pub observed: u64,
+    pub played: TeamPart,
+    /// The table minimum bet in every round at the seat of the other players, to compare with
+    /// playing through the whole shoe.
+    pub table: TeamPart,
+    /// The sum of the results of the rounds played in units, and of their squares.
+    pub result_sum: f64,
+    pub result_squares: f64,
+}
+
+impl WongResult {
+    /// The share of the rounds watched the player bet in.
+    pub fn played_share(&self) -> f64 {
+        if self.observed == 0 {
+            return 0.0;
+        }
+        self.played.rounds as f64 / self.observed as f64
+    }
+
+    /// The expected result of a round played in units.
+    pub fn mean(&self) -> f64 {
+        if self.played.rounds == 0 {
+            return 0.0;
+        }
+        self.result_sum / self.played.rounds as f64
+    }
+
+    /// The variance of the result of a round played in units squared.
+    pub fn variance(&self) -> f64 {
+        if self.played.rounds == 0 {
+            return 0.0;
+        }
+        self.result_squares / self.played.rounds as f64 - self.mean().powi(2)
+    }
+
+    /// The expected result of every 100 rounds watched in units, the rate the player wins at
+    /// at the tables.
+    pub fn win_per_100_observed(&self) -> f64 {
+        if self.observed == 0 {
+            return 0.0;
+        }
+        self.played.net.units_f64() / self.observed as f64 * 100.0
+    }
+}
+
+/// Watches `rounds` rounds at a table where the other players bet the table minimum, with the
+/// player sitting in with `strategy` once the true count reaches the entry count and leaving
+/// when it falls below the exit count or the shoe is shuffled. Every hand at the table is
+/// played by `strategy`.
+pub fn simulate_wonging(
+    conf: &GameConfig,
+    wonging: &Wonging,
+    strategy: &mut dyn PlayerInput,
+    rounds: u64,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<WongResult, Error> {
+    wonging.validate()?;
+    let mut simulator = Simulator::new(conf.clone(), SIMULATION_BANKROLL, seed)?;
+    let seats = Rc::new(RefCell::new(Seats::default()));
+    simulator
+        .engine
+        .subscribe(Box::new(SeatObserver(seats.clone())));
+    let mut result = WongResult::default();
+    let mut seated = false;
+    while result.observed < rounds {
+        let true_count = simulator.engine.counter.true_count;
+        let shuffled = std::mem::take(&mut seats.borrow_mut().shuffled);
+        if seated && (shuffled || true_count < wonging.exit) {
+            seated = false;
+        }
+        if !seated && true_count >= wonging.entry {
+            seated = true;
+        }
+        let mut bets = vec![conf.table_min];
+        if seated {
+            bets.extend(conf.fit_bet(wonging.bet(true_count), SIMULATION_BANKROLL));
+        }
+        if simulator.play_bets(strategy, &bets)?.is_none() {
+            continue;
+        }
+        let seats = seats.borrow();
+        result.observed += 1;
+        result.table.add(bets[0], seats.seat(0));
+        if let Some(bet) = bets.get(1) {
+            let net = seats.seat(1);
+            result.played.add(*bet, net);
+            result.result_sum += net.units_f64();
+            result.result_squares += net.units_f64().powi(2);
+        }
+        if result.observed.is_multiple_of(PROGRESS_INTERVAL) {
+            progress.update(result.observed, rounds);
+        }
+    }
+    progress.update(result.observed, rounds);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GameConfig;
+    use crate::input::Strategy;
+    use crate::money::Money;
+    use crate::simulation::NoProgress;
+    use crate::wonging::{simulate_wonging, Wonging};
+
+    #[test]
+    fn test_simulate_wonging() {
+        let conf = GameConfig::default();
+        let wonging = Wonging {
+            entry: 1.0,
+            exit: 0.0,
+            bet: Money::from_units(10),
+            ramp: None,
+        };
+        let simulate = |wonging: &Wonging| {
+            simulate_wonging(
+                &conf,
+                wonging,
+                &mut Strategy::Basic,
+                100_000,
+                Some(6),
+                &mut NoProgress,
+            )
+        };
+        let result = simulate(&wonging).unwrap();
+        assert_eq!(result.observed, 100_000);
+        assert_eq!(result.table.rounds, 100_000);
+        assert!(result.played.rounds > 0 && result.played_share() < 0.5);
+        assert_eq!(
+            result.played.initial_bets,
+            Money::from_units(10) * result.played.rounds
+        );
+        assert!((result.result_sum - result.played.net.units_f64()).abs() < 1e-6);
+        // Only playing the good counts beats playing every round
+        assert!(result.played.player_edge() > result.table.player_edge());
+
+        let backwards = Wonging {
+            exit: 2.0,
+            ..wonging
+        };
+        assert!(simulate(&backwards).is_err());
+    }
+}